@@ -0,0 +1,89 @@
+//! The addresses the server listens on, with the `bind` semantics of Redis.
+
+use std::net::{AddrParseError, SocketAddr};
+use std::str::FromStr;
+use std::{fmt, io};
+
+/// An address the server listens on.
+///
+/// Like Redis, an address prefixed by `-` is optional: the server still
+/// starts when it isn't available on the host, e.g. `-[::1]:3456` when IPv6
+/// is disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BindAddr {
+    pub addr: SocketAddr,
+    pub optional: bool,
+}
+
+impl BindAddr {
+    /// Parse a list of addresses separated by spaces, like the `bind`
+    /// setting of Redis.
+    pub fn parse_list(list: &str) -> Result<Vec<BindAddr>, AddrParseError> {
+        list.split_whitespace().map(str::parse).collect()
+    }
+
+    /// Tell if the error binding this address means the address isn't
+    /// available on the host, so it can be skipped when it's optional.
+    pub fn is_unavailable(err: &io::Error) -> bool {
+        err.kind() == io::ErrorKind::AddrNotAvailable
+            || matches!(
+                err.raw_os_error(),
+                Some(libc::EAFNOSUPPORT | libc::EPROTONOSUPPORT)
+            )
+    }
+}
+
+impl From<SocketAddr> for BindAddr {
+    fn from(addr: SocketAddr) -> Self {
+        BindAddr {
+            addr,
+            optional: false,
+        }
+    }
+}
+
+impl FromStr for BindAddr {
+    type Err = AddrParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_prefix('-') {
+            Some(addr) => Ok(BindAddr {
+                addr: addr.parse()?,
+                optional: true,
+            }),
+            None => Ok(BindAddr::from(s.parse::<SocketAddr>()?)),
+        }
+    }
+}
+
+impl fmt::Display for BindAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.optional {
+            f.write_str("-")?;
+        }
+        self.addr.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bind_list() {
+        let binds =
+            BindAddr::parse_list("127.0.0.1:3456  -[::1]:3456").unwrap();
+        assert_eq!(
+            binds,
+            vec![
+                BindAddr::from(SocketAddr::from(([127, 0, 0, 1], 3456))),
+                BindAddr {
+                    addr: "[::1]:3456".parse().unwrap(),
+                    optional: true,
+                },
+            ]
+        );
+        assert_eq!(binds[1].to_string(), "-[::1]:3456");
+        assert!(BindAddr::parse_list("127.0.0.1").is_err());
+    }
+}
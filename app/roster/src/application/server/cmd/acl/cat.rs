@@ -1,8 +1,13 @@
+use bytes::Bytes;
 use bytestring::ByteString;
 
-use super::super::parse::Parse;
+use super::super::categories::COMMAND_CATEGORIES;
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
 use crate::application::server::connection::WriteConnection;
 use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::acl::AclCategory;
 
 /// The command shows the available ACL categories if called without arguments.
 /// If a category name is given, the command shows all the Redis commands in the
@@ -11,7 +16,6 @@ use crate::application::server::context::Context;
 /// ACL CAT [category]
 #[derive(Debug, Default)]
 pub struct AclCat {
-    #[allow(dead_code)]
     category: Option<ByteString>,
 }
 
@@ -42,8 +46,14 @@ impl AclCat {
     /// ```text
     /// ACL CAT [category]
     /// ```
-    pub(crate) fn parse_frames(parse: &mut Parse) -> anyhow::Result<AclCat> {
-        let category = parse.next_string().ok();
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<AclCat, CommandError> {
+        let category = match parse.next_string() {
+            Ok(category) => Some(category),
+            Err(ParseError::EndOfStream) => None,
+            Err(err) => return Err(err.into()),
+        };
 
         Ok(AclCat::new(category))
     }
@@ -54,15 +64,35 @@ impl AclCat {
     /// to execute a received command.
     pub(crate) async fn apply(
         self,
-        _dst: &mut WriteConnection,
-        _ctx: Context,
+        dst: &mut WriteConnection,
+        ctx: Context,
     ) -> anyhow::Result<()> {
-        unimplemented!()
+        let Some(category) = self.category else {
+            let response = AclCategory::ALL
+                .iter()
+                .map(|category| {
+                    Frame::Bulk(Bytes::from_static(category.name().as_bytes()))
+                })
+                .collect();
+            dst.write_frame(&Frame::Array(response)).await?;
+            return Ok(());
+        };
 
-        /*
-        let response = Frame::Simple(ByteString::from_static("OK"));
-        dst.write_frame(&response).await?;
+        let category = category.parse::<AclCategory>().map_err(|_| {
+            CommandError::err(format!("Unknown category '{category}'"))
+        })?;
+
+        let builtin = COMMAND_CATEGORIES
+            .iter()
+            .filter(|(_, categories)| categories.contains(&category))
+            .map(|(name, _)| name.to_string());
+        let registered = ctx.supervisor.commands().names_in(category);
+        let response = builtin
+            .chain(registered)
+            .map(|name| Frame::Bulk(Bytes::from(name)))
+            .collect();
+
+        dst.write_frame(&Frame::Array(response)).await?;
         Ok(())
-        */
     }
 }
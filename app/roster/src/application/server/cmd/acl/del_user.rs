@@ -0,0 +1,51 @@
+use bytestring::ByteString;
+
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Delete the users and return how many existed, the `default` user can't
+/// be deleted.
+///
+/// ```text
+/// ACL DELUSER username [username ...]
+/// ```
+#[derive(Debug, Default)]
+pub struct AclDelUser {
+    usernames: Vec<ByteString>,
+}
+
+impl AclDelUser {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<AclDelUser, CommandError> {
+        let mut usernames = vec![parse.next_string()?];
+        loop {
+            match parse.next_string() {
+                Ok(username) => usernames.push(username),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(AclDelUser { usernames })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let usernames = self.usernames.iter().map(|name| &name[..]);
+        let deleted = ctx
+            .supervisor
+            .users()
+            .delete(&usernames.collect::<Vec<_>>())
+            .map_err(|err| CommandError::err(err.to_string()))?;
+
+        dst.write_frame(&Frame::Integer(deleted as i64)).await?;
+        Ok(())
+    }
+}
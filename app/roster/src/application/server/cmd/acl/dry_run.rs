@@ -0,0 +1,93 @@
+use bytes::Bytes;
+use bytestring::ByteString;
+
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::{Command, CommandError};
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::acl::Denied;
+
+/// Check if a user can run a command, without running it.
+///
+/// The reply is `OK`, or a bulk string telling why the command is denied.
+///
+/// ```text
+/// ACL DRYRUN username command [arg [arg ...]]
+/// ```
+#[derive(Debug, Default)]
+pub struct AclDryRun {
+    username: ByteString,
+    /// The command with its arguments.
+    command: Vec<Frame>,
+}
+
+impl AclDryRun {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<AclDryRun, CommandError> {
+        let username = parse.next_string()?;
+        let mut command = vec![Frame::Bulk(parse.next_bytes()?)];
+        loop {
+            match parse.next_bytes() {
+                Ok(arg) => command.push(Frame::Bulk(arg)),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(AclDryRun { username, command })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let Some(user) = ctx.supervisor.users().get(&self.username) else {
+            return Err(CommandError::err(format!(
+                "User '{}' not found",
+                self.username
+            ))
+            .into());
+        };
+
+        let cmd = Command::from_frame(
+            Frame::Array(self.command),
+            ctx.supervisor.commands(),
+        )?;
+        if let Command::Unknown(cmd) = &cmd {
+            return Err(CommandError::err(format!(
+                "Command '{}' not found",
+                cmd.name()
+            ))
+            .into());
+        }
+
+        let response = match cmd.check_acl(&user) {
+            Ok(()) => Frame::Simple(ByteString::from_static("OK")),
+            Err(denied) => {
+                let message = match denied {
+                    Denied::Command => format!(
+                        "User {} has no permissions to run the '{}' command",
+                        user.name(),
+                        cmd.acl_name()
+                    ),
+                    Denied::Key(key) => format!(
+                        "This user has no permissions to access the '{}' key",
+                        String::from_utf8_lossy(&key)
+                    ),
+                    Denied::Channel(channel) => format!(
+                        "This user has no permissions to access the '{}' \
+                         channel",
+                        String::from_utf8_lossy(&channel)
+                    ),
+                };
+                Frame::Bulk(Bytes::from(message))
+            }
+        };
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
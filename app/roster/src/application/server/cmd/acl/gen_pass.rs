@@ -0,0 +1,63 @@
+use std::fmt::Write as _;
+
+use bytes::Bytes;
+use rand::RngCore;
+
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Generate a random password, as a hexadecimal string of `bits` bits
+/// rounded to the next multiple of 4, 256 by default.
+///
+/// ```text
+/// ACL GENPASS [bits]
+/// ```
+#[derive(Debug)]
+pub struct AclGenPass {
+    bits: u64,
+}
+
+impl AclGenPass {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<AclGenPass, CommandError> {
+        let bits = match parse.next_i64() {
+            Ok(bits @ 1..=4096) => bits as u64,
+            Ok(_) => {
+                return Err(CommandError::err(
+                    "ACL GENPASS argument must be the number of bits for the \
+                     output password, a positive number up to 4096",
+                ));
+            }
+            Err(ParseError::EndOfStream) => 256,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(AclGenPass { bits })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        _ctx: Context,
+    ) -> anyhow::Result<()> {
+        let chars = self.bits.div_ceil(4) as usize;
+        let mut bytes = vec![0; chars.div_ceil(2)];
+        rand::thread_rng().fill_bytes(&mut bytes);
+
+        let mut password = bytes.iter().fold(
+            String::with_capacity(chars + 1),
+            |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            },
+        );
+        password.truncate(chars);
+
+        dst.write_frame(&Frame::Bulk(Bytes::from(password))).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use bytes::Bytes;
+use bytestring::ByteString;
+
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::acl::Selector;
+
+/// Return the rules of a user, or a null when the user doesn't exist.
+///
+/// ```text
+/// ACL GETUSER username
+/// ```
+#[derive(Debug, Default)]
+pub struct AclGetUser {
+    username: ByteString,
+}
+
+fn bulk(value: impl Into<Bytes>) -> Frame {
+    Frame::Bulk(value.into())
+}
+
+/// The command, key and channel rules of a selector.
+fn describe(selector: &Selector) -> Vec<(Frame, Frame)> {
+    vec![
+        (bulk("commands"), bulk(selector.describe_commands())),
+        (bulk("keys"), bulk(selector.describe_keys())),
+        (bulk("channels"), bulk(selector.describe_channels())),
+    ]
+}
+
+impl AclGetUser {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<AclGetUser, CommandError> {
+        let username = parse.next_string()?;
+        Ok(AclGetUser { username })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let Some(user) = ctx.supervisor.users().get(&self.username) else {
+            dst.write_frame(&Frame::Null).await?;
+            return Ok(());
+        };
+
+        let mut flags =
+            vec![bulk(if user.is_enabled() { "on" } else { "off" })];
+        if user.is_nopass() {
+            flags.push(bulk("nopass"));
+        }
+        let passwords = user
+            .passwords()
+            .iter()
+            .map(|hash| bulk(hash.clone()))
+            .collect();
+        let selectors = user
+            .selectors()
+            .iter()
+            .map(|selector| Frame::Map(describe(selector)))
+            .collect();
+
+        let mut response = vec![
+            (bulk("flags"), Frame::Array(flags)),
+            (bulk("passwords"), Frame::Array(passwords)),
+        ];
+        response.extend(describe(user.root()));
+        response.push((bulk("selectors"), Frame::Array(selectors)));
+
+        dst.write_frame(&Frame::Map(response)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use bytes::Bytes;
+
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Describe every user with its rules, like in an ACL file.
+///
+/// ```text
+/// ACL LIST
+/// ```
+#[derive(Debug, Default)]
+pub struct AclList {}
+
+impl AclList {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<AclList, CommandError> {
+        Ok(AclList {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let response = ctx
+            .supervisor
+            .users()
+            .list()
+            .iter()
+            .map(|user| Frame::Bulk(Bytes::from(user.describe())))
+            .collect();
+
+        dst.write_frame(&Frame::Array(response)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use bytestring::ByteString;
+
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Replace the users with the ones of the ACL file, the users are left
+/// unchanged when the file is invalid.
+///
+/// ```text
+/// ACL LOAD
+/// ```
+#[derive(Debug, Default)]
+pub struct AclLoad {}
+
+impl AclLoad {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<AclLoad, CommandError> {
+        Ok(AclLoad {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let Some(path) = ctx.supervisor.acl_file() else {
+            return Err(CommandError::err(
+                "This Roster instance is not configured to use an ACL file. \
+                 You may want to specify users via the ACL SETUSER command.",
+            )
+            .into());
+        };
+
+        let contents = std::fs::read_to_string(path).map_err(|err| {
+            CommandError::err(format!(
+                "Error loading ACLs, opening file '{}': {err}",
+                path.display()
+            ))
+        })?;
+
+        let commands = ctx.supervisor.commands();
+        ctx.supervisor
+            .users()
+            .load(&contents, &|name| commands.is_known(name))
+            .map_err(|err| {
+                CommandError::err(format!(
+                    "{}:{err}. WARNING: ACL errors detected, no change to the \
+                     previously active ACL rules was performed",
+                    path.display()
+                ))
+            })?;
+
+        dst.write_frame(&Frame::Simple(ByteString::from_static("OK")))
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use bytes::Bytes;
+
+use super::super::parse::{string_to_i64, Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::acl::LogEntry;
+
+/// Show the most recent commands denied by the ACL rules and failed
+/// authentications, 10 by default, or clear them with `RESET`.
+///
+/// ```text
+/// ACL LOG [count | RESET]
+/// ```
+#[derive(Debug)]
+pub enum AclLog {
+    Show(usize),
+    Reset,
+}
+
+fn bulk(value: impl Into<Bytes>) -> Frame {
+    Frame::Bulk(value.into())
+}
+
+fn entry_frame(entry: LogEntry) -> Frame {
+    let age = entry.age().as_secs_f64();
+    Frame::Map(vec![
+        (bulk("count"), Frame::Integer(entry.count as i64)),
+        (bulk("reason"), bulk(entry.reason.name())),
+        (bulk("context"), bulk(entry.context.name())),
+        (bulk("object"), bulk(entry.object)),
+        (bulk("username"), bulk(entry.username)),
+        (bulk("age-seconds"), Frame::Double(age)),
+        (bulk("client-info"), bulk(entry.client_info)),
+        (bulk("entry-id"), Frame::Integer(entry.entry_id as i64)),
+        (
+            bulk("timestamp-created"),
+            Frame::Integer(entry.created as i64),
+        ),
+        (
+            bulk("timestamp-last-updated"),
+            Frame::Integer(entry.updated as i64),
+        ),
+    ])
+}
+
+impl AclLog {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<AclLog, CommandError> {
+        let arg = match parse.next_bytes() {
+            Ok(arg) => arg,
+            Err(ParseError::EndOfStream) => return Ok(AclLog::Show(10)),
+            Err(err) => return Err(err.into()),
+        };
+
+        if arg.eq_ignore_ascii_case(b"reset") {
+            return Ok(AclLog::Reset);
+        }
+        match string_to_i64(&arg) {
+            Some(count) if count >= 0 => Ok(AclLog::Show(count as usize)),
+            Some(_) => Err(CommandError::err(
+                "value is out of range, must be positive",
+            )),
+            None => Err(CommandError::NotAnInteger),
+        }
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let log = ctx.supervisor.acl_log();
+        let response = match self {
+            AclLog::Show(count) => Frame::Array(
+                log.entries(count).into_iter().map(entry_frame).collect(),
+            ),
+            AclLog::Reset => {
+                log.reset();
+                Frame::Simple("OK".into())
+            }
+        };
+
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
@@ -1,33 +1,167 @@
-use super::parse::Parse;
+use super::parse::{Parse, ParseError};
 use super::CommandExecution;
-use crate::application::server::cmd::unknown::Unknown;
-use crate::application::server::cmd::{Command, SubcommandRegistry};
+use crate::application::server::cmd::{
+    Command, CommandError, SubcommandRegistry,
+};
 use crate::application::server::connection::WriteConnection;
 use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
 
 mod cat;
+mod del_user;
+mod dry_run;
+mod gen_pass;
+mod get_user;
+mod list;
+mod load;
+mod log;
+mod save;
+mod set_user;
+mod users;
+mod whoami;
 
 #[derive(Debug)]
 pub enum Acl {
+    Help,
     Cat(cat::AclCat),
+    DelUser(del_user::AclDelUser),
+    DryRun(dry_run::AclDryRun),
+    GenPass(gen_pass::AclGenPass),
+    GetUser(get_user::AclGetUser),
+    List(list::AclList),
+    Load(load::AclLoad),
+    Log(log::AclLog),
+    Save(save::AclSave),
+    SetUser(set_user::AclSetUser),
+    Users(users::AclUsers),
+    WhoAmI(whoami::AclWhoAmI),
 }
 
+const HELP_TEXT: &str = r#"ACL <subcommand> [<arg> [value] [opt] ...]. Subcommands are:
+CAT [<category>]
+    List all commands that belong to <category>, or all command categories
+    when no category is specified.
+DELUSER <username> [<username> ...]
+    Delete a list of users.
+DRYRUN <username> <command> [<arg> ...]
+    Returns whether the user can execute the given command without executing the command.
+GETUSER <username>
+    Get the user's details.
+GENPASS [<bits>]
+    Generate a secure 256-bit user password. The optional `bits` argument can
+    be used to specify a different size.
+LIST
+    Show users details in config file format.
+LOAD
+    Reload users from the ACL file.
+LOG [<count> | RESET]
+    Show the ACL log entries.
+SAVE
+    Save the current config to the ACL file.
+SETUSER <username> <property> [<property> ...]
+    Create or modify a user with the specified properties.
+USERS
+    List all the registered usernames.
+WHOAMI
+    Return the current connection username.
+HELP
+    Print this help.
+"#;
+
 impl SubcommandRegistry for Acl {
-    fn from_parse(mut parse: Parse) -> anyhow::Result<Command> {
-        let sub_command_name = parse.next_string()?.to_lowercase();
+    fn from_parse(parse: &mut Parse) -> Result<Command, CommandError> {
+        let sub_cmd = match parse.next_string() {
+            Ok(elt) => elt,
+            Err(ParseError::EndOfStream) => {
+                return Err(CommandError::WrongArity("acl".to_string()));
+            }
+            Err(err) => {
+                return Err(err.into());
+            }
+        };
+
+        Acl::parse_subcommand(&sub_cmd, parse).map_err(|err| {
+            err.for_command(&format!("acl|{}", sub_cmd.to_lowercase()))
+        })
+    }
+
+    async fn help(
+        dst: &mut WriteConnection,
+        _ctx: Context,
+    ) -> anyhow::Result<()> {
+        let response = Frame::Array(
+            HELP_TEXT
+                .split_terminator('\n')
+                .map(|x| Frame::Simple(x.into()))
+                .collect(),
+        );
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
 
-        let command = match &sub_command_name[..] {
-            "cat" => {
-                Command::Acl(Acl::Cat(cat::AclCat::parse_frames(&mut parse)?))
+impl Acl {
+    /// The name of the subcommand, in lower case.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Acl::Help => "help",
+            Acl::Cat(_) => "cat",
+            Acl::DelUser(_) => "deluser",
+            Acl::DryRun(_) => "dryrun",
+            Acl::GenPass(_) => "genpass",
+            Acl::GetUser(_) => "getuser",
+            Acl::List(_) => "list",
+            Acl::Load(_) => "load",
+            Acl::Log(_) => "log",
+            Acl::Save(_) => "save",
+            Acl::SetUser(_) => "setuser",
+            Acl::Users(_) => "users",
+            Acl::WhoAmI(_) => "whoami",
+        }
+    }
+
+    fn parse_subcommand(
+        sub_cmd: &str,
+        parse: &mut Parse,
+    ) -> Result<Command, CommandError> {
+        let command = match &sub_cmd.to_lowercase()[..] {
+            "cat" => Command::Acl(Acl::Cat(cat::AclCat::parse_frames(parse)?)),
+            "deluser" => Command::Acl(Acl::DelUser(
+                del_user::AclDelUser::parse_frames(parse)?,
+            )),
+            "dryrun" => Command::Acl(Acl::DryRun(
+                dry_run::AclDryRun::parse_frames(parse)?,
+            )),
+            "genpass" => Command::Acl(Acl::GenPass(
+                gen_pass::AclGenPass::parse_frames(parse)?,
+            )),
+            "getuser" => Command::Acl(Acl::GetUser(
+                get_user::AclGetUser::parse_frames(parse)?,
+            )),
+            "list" => {
+                Command::Acl(Acl::List(list::AclList::parse_frames(parse)?))
+            }
+            "load" => {
+                Command::Acl(Acl::Load(load::AclLoad::parse_frames(parse)?))
+            }
+            "log" => Command::Acl(Acl::Log(log::AclLog::parse_frames(parse)?)),
+            "save" => {
+                Command::Acl(Acl::Save(save::AclSave::parse_frames(parse)?))
+            }
+            "setuser" => Command::Acl(Acl::SetUser(
+                set_user::AclSetUser::parse_frames(parse)?,
+            )),
+            "users" => {
+                Command::Acl(Acl::Users(users::AclUsers::parse_frames(parse)?))
             }
+            "whoami" => Command::Acl(Acl::WhoAmI(
+                whoami::AclWhoAmI::parse_frames(parse)?,
+            )),
+            "help" => Command::Acl(Acl::Help),
             _ => {
-                // The command is not recognized and an Unknown command is
-                // returned.
-                //
-                // `return` is called here to skip the `finish()` call below. As
-                // the command is not recognized, there is most likely
-                // unconsumed fields remaining in the `Parse` instance.
-                return Ok(Command::Unknown(Unknown::new(sub_command_name)));
+                return Err(CommandError::err(format!(
+                    "unknown subcommand '{sub_cmd}'. Try ACL HELP."
+                )));
             }
         };
 
@@ -48,7 +182,19 @@ impl CommandExecution for Acl {
         ctx: Context,
     ) -> anyhow::Result<()> {
         match self {
+            Acl::Help => Acl::help(dst, ctx).await,
             Acl::Cat(cmd) => cmd.apply(dst, ctx).await,
+            Acl::DelUser(cmd) => cmd.apply(dst, ctx).await,
+            Acl::DryRun(cmd) => cmd.apply(dst, ctx).await,
+            Acl::GenPass(cmd) => cmd.apply(dst, ctx).await,
+            Acl::GetUser(cmd) => cmd.apply(dst, ctx).await,
+            Acl::List(cmd) => cmd.apply(dst, ctx).await,
+            Acl::Load(cmd) => cmd.apply(dst, ctx).await,
+            Acl::Log(cmd) => cmd.apply(dst, ctx).await,
+            Acl::Save(cmd) => cmd.apply(dst, ctx).await,
+            Acl::SetUser(cmd) => cmd.apply(dst, ctx).await,
+            Acl::Users(cmd) => cmd.apply(dst, ctx).await,
+            Acl::WhoAmI(cmd) => cmd.apply(dst, ctx).await,
         }
     }
 }
@@ -0,0 +1,53 @@
+use bytestring::ByteString;
+
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::infrastructure::fs::write_atomic;
+
+/// Write the users to the ACL file.
+///
+/// The users are written and synced to a temporary file renamed over the ACL
+/// file, so the ACL file is never left half written.
+///
+/// ```text
+/// ACL SAVE
+/// ```
+#[derive(Debug, Default)]
+pub struct AclSave {}
+
+impl AclSave {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<AclSave, CommandError> {
+        Ok(AclSave {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let Some(path) = ctx.supervisor.acl_file() else {
+            return Err(CommandError::err(
+                "This Roster instance is not configured to use an ACL file. \
+                 You may want to specify users via the ACL SETUSER command.",
+            )
+            .into());
+        };
+
+        let contents = ctx.supervisor.users().to_file();
+        write_atomic(path, contents.as_bytes()).map_err(|err| {
+            CommandError::err(format!(
+                "There was an error trying to save the ACLs to '{}': {err}",
+                path.display()
+            ))
+        })?;
+
+        dst.write_frame(&Frame::Simple(ByteString::from_static("OK")))
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use bytestring::ByteString;
+
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Create a user or modify its rules, see [crate::domain::acl::User] for the
+/// rules. The user is left unchanged when one of the rules is invalid.
+///
+/// ```text
+/// ACL SETUSER username [rule [rule ...]]
+/// ```
+#[derive(Debug, Default)]
+pub struct AclSetUser {
+    username: ByteString,
+    rules: Vec<ByteString>,
+}
+
+impl AclSetUser {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<AclSetUser, CommandError> {
+        let username = parse.next_string()?;
+        let mut rules = Vec::new();
+        loop {
+            match parse.next_string() {
+                Ok(rule) => rules.push(rule),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(AclSetUser { username, rules })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let commands = ctx.supervisor.commands();
+        let is_known = |name: &str| commands.is_known(name);
+        let rules = self.rules.iter().map(|rule| &rule[..]);
+
+        ctx.supervisor
+            .users()
+            .set_user(&self.username, &rules.collect::<Vec<_>>(), &is_known)
+            .map_err(|err| CommandError::err(err.to_string()))?;
+
+        dst.write_frame(&Frame::Simple(ByteString::from_static("OK")))
+            .await?;
+        Ok(())
+    }
+}
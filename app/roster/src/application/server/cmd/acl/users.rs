@@ -0,0 +1,40 @@
+use bytes::Bytes;
+
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// List the names of the users.
+///
+/// ```text
+/// ACL USERS
+/// ```
+#[derive(Debug, Default)]
+pub struct AclUsers {}
+
+impl AclUsers {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<AclUsers, CommandError> {
+        Ok(AclUsers {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let response = ctx
+            .supervisor
+            .users()
+            .list()
+            .iter()
+            .map(|user| Frame::Bulk(Bytes::from(user.name().to_string())))
+            .collect();
+
+        dst.write_frame(&Frame::Array(response)).await?;
+        Ok(())
+    }
+}
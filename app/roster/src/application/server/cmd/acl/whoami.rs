@@ -0,0 +1,31 @@
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Return the name of the user of the connection.
+///
+/// ```text
+/// ACL WHOAMI
+/// ```
+#[derive(Debug, Default)]
+pub struct AclWhoAmI {}
+
+impl AclWhoAmI {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<AclWhoAmI, CommandError> {
+        Ok(AclWhoAmI {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let user = ctx.connection.user().await;
+        dst.write_frame(&Frame::Bulk(user.into_bytes())).await?;
+        Ok(())
+    }
+}
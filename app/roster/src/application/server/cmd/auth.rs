@@ -0,0 +1,113 @@
+use bytes::Bytes;
+use bytestring::ByteString;
+
+use super::parse::{Parse, ParseError};
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::acl::{LogContext, LogReason};
+
+/// Authenticate the connection with an ACL user, the `default` user is used
+/// when only the password is given.
+///
+/// ```text
+/// AUTH [username] password
+/// ```
+#[derive(Debug, Default)]
+pub struct Auth {
+    username: Option<ByteString>,
+    password: Bytes,
+}
+
+impl Auth {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<Auth, CommandError> {
+        let first = parse.next_bytes()?;
+        let (username, password) = match parse.next_bytes() {
+            Ok(password) => {
+                let username = ByteString::try_from(first)
+                    .map_err(|_| CommandError::Syntax)?;
+                (Some(username), password)
+            }
+            Err(ParseError::EndOfStream) => (None, first),
+            Err(err) => return Err(err.into()),
+        };
+
+        if !parse.remaining().is_empty() {
+            return Err(CommandError::Syntax);
+        }
+
+        Ok(Auth { username, password })
+    }
+}
+
+/// Authenticate the connection of `ctx` as `username`, shared with
+/// `HELLO AUTH`.
+pub(crate) async fn authenticate(
+    ctx: &Context,
+    username: &ByteString,
+    password: &[u8],
+) -> Result<(), CommandError> {
+    if ctx
+        .supervisor
+        .users()
+        .authenticate(username, password)
+        .is_none()
+    {
+        ctx.supervisor.acl_log().push(
+            LogReason::Auth,
+            LogContext::Toplevel,
+            "AUTH".to_string(),
+            username.to_string(),
+            ctx.connection.format_conn().await.to_string(),
+        );
+        return Err(CommandError::with_code(
+            "WRONGPASS",
+            "invalid username-password pair or user is disabled.",
+        ));
+    }
+
+    ctx.connection.authenticate(username.clone()).await;
+    Ok(())
+}
+
+impl CommandExecution for Auth {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let username = match self.username {
+            Some(username) => username,
+            None => {
+                let username = ByteString::from_static("default");
+                // Like Redis, a password for a `default` user which doesn't
+                // need one is most likely a misconfigured client.
+                let nopass = ctx
+                    .supervisor
+                    .users()
+                    .get(&username)
+                    .is_some_and(|user| user.is_nopass());
+                if nopass {
+                    return Err(CommandError::err(
+                        "AUTH <password> called without any password \
+                         configured for the default user. Are you sure your \
+                         client is configured correctly?",
+                    )
+                    .into());
+                }
+                username
+            }
+        };
+
+        authenticate(&ctx, &username, &self.password).await?;
+
+        let response = Frame::Simple(ByteString::from_static("OK"));
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
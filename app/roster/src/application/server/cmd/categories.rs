@@ -0,0 +1,124 @@
+//! The ACL categories of every built-in command, subcommands are named
+//! `command|subcommand` like in `ACL CAT`.
+
+use crate::domain::acl::AclCategory::{self, *};
+
+pub(crate) const COMMAND_CATEGORIES: &[(&str, &[AclCategory])] = &[
+    ("acl|cat", &[Slow]),
+    ("acl|deluser", &[Admin, Slow, Dangerous]),
+    ("acl|dryrun", &[Admin, Slow, Dangerous]),
+    ("acl|genpass", &[Slow]),
+    ("acl|getuser", &[Admin, Slow, Dangerous]),
+    ("acl|help", &[Slow]),
+    ("acl|list", &[Admin, Slow, Dangerous]),
+    ("acl|load", &[Admin, Slow, Dangerous]),
+    ("acl|log", &[Admin, Slow, Dangerous]),
+    ("acl|save", &[Admin, Slow, Dangerous]),
+    ("acl|setuser", &[Admin, Slow, Dangerous]),
+    ("acl|users", &[Admin, Slow, Dangerous]),
+    ("acl|whoami", &[Slow]),
+    ("auth", &[Fast, Connection]),
+    ("client|caching", &[Slow, Connection]),
+    ("client|getname", &[Slow, Connection]),
+    ("client|getredir", &[Slow, Connection]),
+    ("client|help", &[Slow, Connection]),
+    ("client|id", &[Slow, Connection]),
+    ("client|info", &[Slow, Connection]),
+    ("client|kill", &[Admin, Slow, Dangerous, Connection]),
+    ("client|list", &[Admin, Slow, Dangerous, Connection]),
+    ("client|no-evict", &[Admin, Slow, Dangerous, Connection]),
+    ("client|no-touch", &[Slow, Connection]),
+    ("client|pause", &[Admin, Slow, Dangerous, Connection]),
+    ("client|reply", &[Slow, Connection]),
+    ("client|setinfo", &[Slow, Connection]),
+    ("client|setname", &[Slow, Connection]),
+    ("client|tracking", &[Slow, Connection]),
+    ("client|trackinginfo", &[Slow, Connection]),
+    ("client|unpause", &[Admin, Slow, Dangerous, Connection]),
+    ("discard", &[Fast, Transaction]),
+    ("eval", &[Slow, Scripting]),
+    ("eval_ro", &[Slow, Scripting]),
+    ("evalsha", &[Slow, Scripting]),
+    ("evalsha_ro", &[Slow, Scripting]),
+    ("exec", &[Slow, Transaction]),
+    ("fcall", &[Slow, Scripting]),
+    ("fcall_ro", &[Slow, Scripting]),
+    ("function|delete", &[Write, Slow, Scripting]),
+    ("function|dump", &[Slow, Scripting]),
+    ("function|flush", &[Write, Slow, Scripting]),
+    ("function|help", &[Slow, Scripting]),
+    ("function|kill", &[Slow, Scripting]),
+    ("function|list", &[Slow, Scripting]),
+    ("function|load", &[Write, Slow, Scripting]),
+    ("function|restore", &[Write, Slow, Scripting]),
+    ("function|stats", &[Slow, Scripting]),
+    ("get", &[Read, String, Fast]),
+    ("hello", &[Fast, Connection]),
+    ("info", &[Slow, Dangerous]),
+    ("multi", &[Fast, Transaction]),
+    ("ping", &[Fast, Connection]),
+    ("psubscribe", &[PubSub, Slow]),
+    ("publish", &[PubSub, Fast]),
+    ("pubsub|channels", &[PubSub, Slow]),
+    ("pubsub|help", &[Slow]),
+    ("pubsub|numpat", &[PubSub, Slow]),
+    ("pubsub|numsub", &[PubSub, Slow]),
+    ("pubsub|shardchannels", &[PubSub, Slow]),
+    ("pubsub|shardnumsub", &[PubSub, Slow]),
+    ("punsubscribe", &[PubSub, Slow]),
+    ("quit", &[Fast, Connection]),
+    ("script|exists", &[Slow, Scripting]),
+    ("script|flush", &[Slow, Scripting]),
+    ("script|help", &[Slow, Scripting]),
+    ("script|kill", &[Slow, Scripting]),
+    ("script|load", &[Slow, Scripting]),
+    ("set", &[Write, String, Slow]),
+    ("shutdown", &[Admin, Slow, Dangerous]),
+    ("spublish", &[PubSub, Fast]),
+    ("ssubscribe", &[PubSub, Slow]),
+    ("subscribe", &[PubSub, Slow]),
+    ("sunsubscribe", &[PubSub, Slow]),
+    ("unsubscribe", &[PubSub, Slow]),
+    ("unwatch", &[Fast, Transaction]),
+    ("wasm|exists", &[Slow, Scripting]),
+    ("wasm|flush", &[Slow, Scripting]),
+    ("wasm|help", &[Slow, Scripting]),
+    ("wasm|load", &[Slow, Scripting]),
+    ("watch", &[Fast, Transaction]),
+    ("wcall", &[Slow, Scripting]),
+    ("wcall_ro", &[Slow, Scripting]),
+];
+
+/// The categories of the built-in command `name`, `command|subcommand` for
+/// a subcommand.
+pub(crate) fn categories(name: &str) -> Option<&'static [AclCategory]> {
+    COMMAND_CATEGORIES
+        .binary_search_by_key(&name, |(known, _)| known)
+        .ok()
+        .map(|index| COMMAND_CATEGORIES[index].1)
+}
+
+/// Check if `name` is a built-in command, with or without its subcommand.
+pub(crate) fn is_known(name: &str) -> bool {
+    categories(name).is_some()
+        || COMMAND_CATEGORIES.iter().any(|(known, _)| {
+            known
+                .strip_prefix(name)
+                .is_some_and(|sub| sub.starts_with('|'))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{categories, is_known, COMMAND_CATEGORIES};
+
+    #[test]
+    fn sorted_table() {
+        assert!(COMMAND_CATEGORIES.windows(2).all(|w| w[0].0 < w[1].0));
+        assert!(categories("get").is_some());
+        assert!(categories("client").is_none());
+        assert!(is_known("client"));
+        assert!(is_known("client|id"));
+        assert!(!is_known("cli"));
+    }
+}
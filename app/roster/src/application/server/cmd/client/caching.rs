@@ -0,0 +1,68 @@
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// This command controls the tracking of the keys in the next command
+/// executed by the connection, when tracking is enabled in OPTIN or OPTOUT
+/// mode.
+///
+/// ```text
+/// CLIENT CACHING <YES | NO>
+/// ```
+#[derive(Debug, Default)]
+pub struct ClientCaching {
+    yes: bool,
+}
+
+impl ClientCaching {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<ClientCaching, CommandError> {
+        let yes = match &parse.next_string()?.to_lowercase()[..] {
+            "yes" => true,
+            "no" => false,
+            _ => return Err(CommandError::Syntax),
+        };
+
+        Ok(ClientCaching { yes })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let Some(tracking) =
+            ctx.tracking().filter(|opts| opts.optin || opts.optout)
+        else {
+            return Err(CommandError::err(
+                "CLIENT CACHING can be called only when the client is in \
+                 tracking mode with OPTIN or OPTOUT mode enabled",
+            )
+            .into());
+        };
+
+        if self.yes && !tracking.optin {
+            return Err(CommandError::err(
+                "CLIENT CACHING YES is only valid when tracking is enabled in \
+                 OPTIN mode.",
+            )
+            .into());
+        }
+
+        if !self.yes && !tracking.optout {
+            return Err(CommandError::err(
+                "CLIENT CACHING NO is only valid when tracking is enabled in \
+                 OPTOUT mode.",
+            )
+            .into());
+        }
+
+        ctx.set_caching(Some(self.yes));
+
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
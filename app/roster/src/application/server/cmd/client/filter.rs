@@ -0,0 +1,140 @@
+use std::sync::Arc;
+
+use bytestring::ByteString;
+
+use super::super::parse::Parse;
+use crate::application::server::cmd::parse::ParseError;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::context::Context;
+use crate::application::server::supervisor::{
+    ConnectionAddr, MetadataConnection,
+};
+
+/// The type of a client, used to filter the clients.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ClientType {
+    #[default]
+    Normal,
+    Replica,
+    Master,
+    Pubsub,
+}
+
+/// The filters selecting the clients of CLIENT LIST and CLIENT KILL, a client
+/// is selected when it matches every filter.
+#[derive(Debug, Default)]
+pub struct ClientFilter {
+    /// Every type of client is selected when no type is given.
+    pub r#type: Option<ClientType>,
+    /// Every client is selected when empty.
+    pub ids: Vec<u64>,
+    pub addr: Option<ByteString>,
+    pub laddr: Option<ByteString>,
+    pub user: Option<ByteString>,
+    /// The calling client is never selected.
+    pub skipme: bool,
+    /// Only the clients connected for more than this many seconds are
+    /// selected.
+    pub maxage: Option<u64>,
+}
+
+impl ClientFilter {
+    /// Parse the value of the filter `name` shared by CLIENT LIST and
+    /// CLIENT KILL, return `false` when `name` isn't one of them.
+    pub(crate) fn parse_filter(
+        &mut self,
+        name: &str,
+        parse: &mut Parse,
+    ) -> Result<bool, CommandError> {
+        match name {
+            "type" => {
+                let ty = parse.next_string().map(|x| x.to_lowercase());
+                self.r#type = Some(match ty.as_ref().map(|x| &x[..]) {
+                    Ok("normal") => ClientType::Normal,
+                    Ok("replica" | "slave") => ClientType::Replica,
+                    Ok("master") => ClientType::Master,
+                    Ok("pubsub") => ClientType::Pubsub,
+                    Ok(ty) => {
+                        return Err(CommandError::err(format!(
+                            "Unknown client type '{ty}'"
+                        )));
+                    }
+                    Err(ParseError::EndOfStream) => ClientType::Normal,
+                    Err(_) => return Err(CommandError::Syntax),
+                });
+            }
+            "addr" => self.addr = Some(parse.next_string()?),
+            "laddr" => self.laddr = Some(parse.next_string()?),
+            "user" => self.user = Some(parse.next_string()?),
+            "skipme" => {
+                self.skipme = match &parse.next_string()?.to_lowercase()[..] {
+                    "yes" => true,
+                    "no" => false,
+                    _ => return Err(CommandError::Syntax),
+                }
+            }
+            "maxage" => self.maxage = Some(parse.next_int()?),
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    /// The open connections selected by the filters.
+    pub(crate) async fn select(
+        &self,
+        ctx: &Context,
+    ) -> Result<Vec<Arc<MetadataConnection>>, CommandError> {
+        if let Some(user) = &self.user {
+            if ctx.supervisor.users().get(user).is_none() {
+                return Err(CommandError::err(format!(
+                    "No such user '{user}'"
+                )));
+            }
+        }
+
+        let mut selected = Vec::new();
+        for conn in ctx.supervisor.get_normal_connection().await {
+            if self.matches(&conn, ctx).await {
+                selected.push(conn);
+            }
+        }
+        // Like Redis, the oldest clients come first.
+        selected.sort_by_key(|conn| conn.id());
+        Ok(selected)
+    }
+
+    /// Tell if the connection `conn` matches every filter.
+    async fn matches(&self, conn: &MetadataConnection, ctx: &Context) -> bool {
+        let addr_matches = |filter: &Option<ByteString>,
+                            addr: &ConnectionAddr| {
+            filter
+                .as_ref()
+                .is_none_or(|filter| *filter == addr.to_string())
+        };
+
+        let type_matches = match self.r#type {
+            None => true,
+            Some(ClientType::Normal) => !conn.is_pubsub(),
+            Some(ClientType::Pubsub) => conn.is_pubsub(),
+            // There is no replication yet.
+            Some(ClientType::Master | ClientType::Replica) => false,
+        };
+
+        if !type_matches
+            || (self.skipme && conn.id() == ctx.connection.id())
+            || (!self.ids.is_empty() && !self.ids.contains(&conn.id()))
+            || !addr_matches(&self.addr, &conn.addr)
+            || !addr_matches(&self.laddr, &conn.laddr)
+            || self
+                .maxage
+                .is_some_and(|maxage| conn.age().as_secs() <= maxage)
+        {
+            return false;
+        }
+
+        match &self.user {
+            Some(user) => *user == conn.user().await,
+            None => true,
+        }
+    }
+}
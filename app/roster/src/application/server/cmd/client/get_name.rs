@@ -1,4 +1,5 @@
 use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
 use crate::application::server::connection::WriteConnection;
 use crate::application::server::context::Context;
 use crate::application::server::frame::Frame;
@@ -16,7 +17,7 @@ impl ClientGetName {
 
     pub(crate) fn parse_frames(
         parse: &mut Parse,
-    ) -> anyhow::Result<ClientGetName> {
+    ) -> Result<ClientGetName, CommandError> {
         parse.finish()?;
         Ok(ClientGetName::new())
     }
@@ -57,7 +58,7 @@ mod tests {
 
         let mut bytes = Cursor::new(bytes.freeze());
         let frame = Frame::parse(&mut bytes)?;
-        let client_list = Command::from_frame(frame)?;
+        let client_list = Command::from_frame(frame, &Default::default())?;
         Ok(client_list)
     }
 
@@ -81,8 +82,8 @@ mod tests {
         assert!(client_cmd.is_err());
         let client_cmd = client_cmd.unwrap_err();
         insta::assert_debug_snapshot!(client_cmd, @r###"
-        Other(
-            "protocol error; expected end of frame, but there was more",
+        WrongArity(
+            "client|getname",
         )
         "###);
     }
@@ -0,0 +1,36 @@
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// This command returns the client ID we are redirecting our tracking
+/// notifications to.
+///
+/// Returns `0` when tracking is enabled without redirection and `-1` when
+/// tracking is not enabled.
+#[derive(Debug, Default)]
+pub struct ClientGetRedir {}
+
+impl ClientGetRedir {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<ClientGetRedir, CommandError> {
+        parse.finish()?;
+        Ok(ClientGetRedir {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let redirect = match ctx.tracking() {
+            Some(options) => options.redirect.map(|id| id as i64).unwrap_or(0),
+            None => -1,
+        };
+
+        dst.write_frame(&Frame::Integer(redirect)).await?;
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
 use crate::application::server::connection::WriteConnection;
 use crate::application::server::context::Context;
 use crate::application::server::frame::Frame;
@@ -26,7 +27,9 @@ impl ClientID {
         ClientID {}
     }
 
-    pub(crate) fn parse_frames(_parse: &mut Parse) -> anyhow::Result<ClientID> {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<ClientID, CommandError> {
         Ok(ClientID::new())
     }
 
@@ -37,7 +40,7 @@ impl ClientID {
     ) -> anyhow::Result<()> {
         let id = ctx.connection.id();
 
-        let response = Frame::Integer(id);
+        let response = Frame::Integer(id as i64);
         dst.write_frame(&response).await?;
 
         Ok(())
@@ -63,7 +66,7 @@ mod tests {
 
         let mut bytes = Cursor::new(bytes.freeze());
         let frame = Frame::parse(&mut bytes)?;
-        let client_list = Command::from_frame(frame)?;
+        let client_list = Command::from_frame(frame, &Default::default())?;
         Ok(client_list)
     }
 
@@ -1,4 +1,5 @@
 use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
 use crate::application::server::connection::WriteConnection;
 use crate::application::server::context::Context;
 use crate::application::server::frame::Frame;
@@ -18,7 +19,7 @@ impl ClientInfo {
 
     pub(crate) fn parse_frames(
         parse: &mut Parse,
-    ) -> anyhow::Result<ClientInfo> {
+    ) -> Result<ClientInfo, CommandError> {
         parse.finish()?;
 
         Ok(ClientInfo::new())
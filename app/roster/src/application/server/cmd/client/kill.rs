@@ -0,0 +1,193 @@
+use super::super::parse::Parse;
+use super::filter::ClientFilter;
+use crate::application::server::cmd::parse::ParseError;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// The CLIENT KILL command closes a given client connection. The connection is
+/// closed by the thread owning it, the commands it already sent are not
+/// applied.
+///
+/// ```text
+/// CLIENT KILL <ip:port>
+/// CLIENT KILL <ID client-id | TYPE type | ADDR ip:port | LADDR ip:port
+///   | USER username | SKIPME <YES | NO> | MAXAGE maxage> [...]
+/// ```
+///
+/// The first form closes the client at the address `ip:port` and replies
+/// `OK`. The second form closes every client matching all the filters and
+/// replies the number of clients closed. Unless `SKIPME NO` is given, the
+/// calling client is never closed by the second form.
+#[derive(Debug, Default)]
+pub struct ClientKill {
+    /// The first form is used.
+    legacy: bool,
+    filter: ClientFilter,
+}
+
+impl ClientKill {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<ClientKill, CommandError> {
+        let mut filter = ClientFilter::default();
+        if parse.remaining().len() == 1 {
+            filter.addr = Some(parse.next_string()?);
+            return Ok(ClientKill {
+                legacy: true,
+                filter,
+            });
+        }
+
+        // At least one filter is needed.
+        if parse.remaining().is_empty() {
+            return Err(CommandError::Syntax);
+        }
+
+        filter.skipme = true;
+        loop {
+            let name = match parse.next_string() {
+                Ok(name) => name.to_lowercase(),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            if name == "id" {
+                let id = parse.next_int().map_err(|_| {
+                    CommandError::err("client-id should be greater than 0")
+                })?;
+                filter.ids = vec![id];
+            } else if !filter.parse_filter(&name, parse)? {
+                return Err(CommandError::Syntax);
+            }
+        }
+
+        Ok(ClientKill {
+            legacy: false,
+            filter,
+        })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let connections = self.filter.select(&ctx).await?;
+        for conn in &connections {
+            conn.kill();
+        }
+
+        let response = match self.legacy {
+            true if connections.is_empty() => {
+                return Err(CommandError::err("No such client").into());
+            }
+            true => Frame::Simple("OK".into()),
+            false => Frame::Integer(connections.len() as i64),
+        };
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let client_kill = Command::from_frame(frame, &Default::default())?;
+        Ok(client_kill)
+    }
+
+    #[test]
+    fn ensure_parsing_legacy() {
+        let entry: RespValue = resp_array!["CLIENT", "KILL", "127.0.0.1:6379"];
+        let client_cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(client_cmd, @r###"
+        Client(
+            Kill(
+                ClientKill {
+                    legacy: true,
+                    filter: ClientFilter {
+                        type: None,
+                        ids: [],
+                        addr: Some(
+                            "127.0.0.1:6379",
+                        ),
+                        laddr: None,
+                        user: None,
+                        skipme: false,
+                        maxage: None,
+                    },
+                },
+            ),
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_filters() {
+        let entry: RespValue = resp_array![
+            "CLIENT", "KILL", "ID", "3", "USER", "default", "SKIPME", "no",
+            "MAXAGE", "10"
+        ];
+        let client_cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(client_cmd, @r###"
+        Client(
+            Kill(
+                ClientKill {
+                    legacy: false,
+                    filter: ClientFilter {
+                        type: None,
+                        ids: [
+                            3,
+                        ],
+                        addr: None,
+                        laddr: None,
+                        user: Some(
+                            "default",
+                        ),
+                        skipme: false,
+                        maxage: Some(
+                            10,
+                        ),
+                    },
+                },
+            ),
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_fail() {
+        let entry: RespValue =
+            resp_array!["CLIENT", "KILL", "ID", "1", "NAME", "foo"];
+        assert!(parse_cmd(entry).is_err());
+
+        let entry: RespValue = resp_array!["CLIENT", "KILL"];
+        assert!(parse_cmd(entry).is_err());
+
+        let entry: RespValue = resp_array!["CLIENT", "KILL", "ID", "foo"];
+        insta::assert_debug_snapshot!(parse_cmd(entry), @r###"
+        Err(
+            Other(
+                "ERR client-id should be greater than 0",
+            ),
+        )
+        "###);
+    }
+}
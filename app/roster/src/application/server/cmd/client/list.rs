@@ -1,7 +1,7 @@
-use anyhow::bail;
-
 use super::super::parse::Parse;
+use super::filter::ClientFilter;
 use crate::application::server::cmd::parse::ParseError;
+use crate::application::server::cmd::CommandError;
 use crate::application::server::connection::WriteConnection;
 use crate::application::server::context::Context;
 use crate::application::server::frame::Frame;
@@ -15,85 +15,57 @@ use crate::application::server::frame::Frame;
 /// MONITOR command belong to the normal class.
 ///
 /// The ID filter only returns entries for clients with IDs matching the
-/// client-id arguments.
+/// client-id arguments, it must be the last one. Like CLIENT KILL, the clients
+/// can also be filtered with USER, ADDR, LADDR, SKIPME and MAXAGE.
+///
+/// ```text
+/// CLIENT LIST [TYPE <NORMAL | MASTER | REPLICA | PUBSUB>] [USER username]
+///   [ADDR ip:port] [LADDR ip:port] [SKIPME <YES | NO>] [MAXAGE maxage]
+///   [ID client-id [client-id ...]]
+/// ```
 #[derive(Debug, Default)]
 pub struct ClientList {
-    #[allow(dead_code)]
-    r#type: ClientType,
-    #[allow(dead_code)]
-    ids: Option<Vec<u64>>,
-}
-
-#[derive(Debug, Default)]
-pub enum ClientType {
-    #[default]
-    Normal,
-    Replica,
-    Master,
-    Pubsub,
+    filter: ClientFilter,
 }
 
 impl ClientList {
     /// Create a new `ClientList`
-    pub fn new(ty: ClientType, ids: Option<Vec<u64>>) -> ClientList {
-        ClientList { r#type: ty, ids }
+    pub fn new(filter: ClientFilter) -> ClientList {
+        ClientList { filter }
     }
 
     pub(crate) fn parse_frames(
         parse: &mut Parse,
-    ) -> anyhow::Result<ClientList> {
-        let mut ty = ClientType::Normal;
-        let mut ids = Vec::new();
+    ) -> Result<ClientList, CommandError> {
+        let mut filter = ClientFilter::default();
 
         loop {
-            let key_filter = parse.next_string().map(|x| x.to_lowercase());
-            let key_filter_str = key_filter.as_ref().map(|x| &x[..]);
-            match key_filter_str {
-                Ok("type") => {
-                    let ty_opt = parse.next_string().map(|x| x.to_lowercase());
-                    ty = match ty_opt.as_ref().map(|x| &x[..]) {
-                        Ok("normal") => ClientType::Normal,
-                        Ok("replica") => ClientType::Replica,
-                        Ok("master") => ClientType::Master,
-                        Ok("pubsub") => ClientType::Pubsub,
-                        Ok(_) => {
-                            bail!(
-                                "Unknown client type, should be either normal \
-                                 / replica / master / pubsub."
-                            );
-                        }
-                        Err(ParseError::EndOfStream) => ClientType::Normal,
-                        Err(err) => {
-                            bail!("{}", err);
-                        }
-                    };
-                }
-                Ok("id") => loop {
+            let name = match parse.next_string() {
+                Ok(name) => name.to_lowercase(),
+                Err(ParseError::EndOfStream) => break,
+                Err(_) => return Err(CommandError::Syntax),
+            };
+            match &name[..] {
+                "id" => loop {
                     match parse.next_int() {
                         Ok(id) => {
-                            ids.push(id);
+                            filter.ids.push(id);
                         }
                         Err(ParseError::EndOfStream) => {
                             break;
                         }
-                        Err(err) => {
-                            bail!(err);
-                        }
+                        Err(err) => return Err(err.into()),
                     }
                 },
-                Ok(rest) => {
-                    bail!("Unknown filter type '{rest}'");
-                }
-                Err(ParseError::EndOfStream) => {
-                    break;
-                }
-                Err(err) => {
-                    bail!("{}", err);
+                name => {
+                    if !filter.parse_filter(name, parse)? {
+                        return Err(CommandError::Syntax);
+                    }
                 }
             }
         }
 
-        Ok(ClientList::new(ty, Some(ids)))
+        Ok(ClientList::new(filter))
     }
 
     pub(crate) async fn apply(
@@ -101,9 +73,8 @@ impl ClientList {
         dst: &mut WriteConnection,
         ctx: Context,
     ) -> anyhow::Result<()> {
-        let connections = ctx.supervisor.get_normal_connection().await;
+        let connections = self.filter.select(&ctx).await?;
 
-        // TODO(@miaxos): lot of things missing here
         let mut conn_frames = Vec::with_capacity(connections.len());
         for conn in connections {
             conn_frames.push(Frame::Simple(conn.format_conn().await));
@@ -134,7 +105,7 @@ mod tests {
 
         let mut bytes = Cursor::new(bytes.freeze());
         let frame = Frame::parse(&mut bytes)?;
-        let client_list = Command::from_frame(frame)?;
+        let client_list = Command::from_frame(frame, &Default::default())?;
         Ok(client_list)
     }
 
@@ -146,10 +117,15 @@ mod tests {
         Client(
             List(
                 ClientList {
-                    type: Normal,
-                    ids: Some(
-                        [],
-                    ),
+                    filter: ClientFilter {
+                        type: None,
+                        ids: [],
+                        addr: None,
+                        laddr: None,
+                        user: None,
+                        skipme: false,
+                        maxage: None,
+                    },
                 },
             ),
         )
@@ -164,10 +140,17 @@ mod tests {
         Client(
             List(
                 ClientList {
-                    type: Normal,
-                    ids: Some(
-                        [],
-                    ),
+                    filter: ClientFilter {
+                        type: Some(
+                            Normal,
+                        ),
+                        ids: [],
+                        addr: None,
+                        laddr: None,
+                        user: None,
+                        skipme: false,
+                        maxage: None,
+                    },
                 },
             ),
         )
@@ -182,10 +165,17 @@ mod tests {
         Client(
             List(
                 ClientList {
-                    type: Master,
-                    ids: Some(
-                        [],
-                    ),
+                    filter: ClientFilter {
+                        type: Some(
+                            Master,
+                        ),
+                        ids: [],
+                        addr: None,
+                        laddr: None,
+                        user: None,
+                        skipme: false,
+                        maxage: None,
+                    },
                 },
             ),
         )
@@ -200,10 +190,17 @@ mod tests {
         Client(
             List(
                 ClientList {
-                    type: Replica,
-                    ids: Some(
-                        [],
-                    ),
+                    filter: ClientFilter {
+                        type: Some(
+                            Replica,
+                        ),
+                        ids: [],
+                        addr: None,
+                        laddr: None,
+                        user: None,
+                        skipme: false,
+                        maxage: None,
+                    },
                 },
             ),
         )
@@ -218,10 +215,17 @@ mod tests {
         Client(
             List(
                 ClientList {
-                    type: Pubsub,
-                    ids: Some(
-                        [],
-                    ),
+                    filter: ClientFilter {
+                        type: Some(
+                            Pubsub,
+                        ),
+                        ids: [],
+                        addr: None,
+                        laddr: None,
+                        user: None,
+                        skipme: false,
+                        maxage: None,
+                    },
                 },
             ),
         )
@@ -235,7 +239,9 @@ mod tests {
         assert!(client_cmd.is_err());
         insta::assert_debug_snapshot!(client_cmd, @r###"
         Err(
-            "Unknown client type, should be either normal / replica / master / pubsub.",
+            Other(
+                "ERR Unknown client type 'fail'",
+            ),
         )
         "###);
     }
@@ -249,13 +255,20 @@ mod tests {
         Client(
             List(
                 ClientList {
-                    type: Normal,
-                    ids: Some(
-                        [
+                    filter: ClientFilter {
+                        type: Some(
+                            Normal,
+                        ),
+                        ids: [
                             1,
                             2,
                         ],
-                    ),
+                        addr: None,
+                        laddr: None,
+                        user: None,
+                        skipme: false,
+                        maxage: None,
+                    },
                 },
             ),
         )
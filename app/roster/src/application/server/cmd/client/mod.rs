@@ -1,17 +1,29 @@
 use super::parse::{Parse, ParseError};
 use super::CommandExecution;
-use crate::application::server::cmd::unknown::Unknown;
-use crate::application::server::cmd::{Command, SubcommandRegistry};
+use crate::application::server::cmd::{
+    Command, CommandError, SubcommandRegistry,
+};
 use crate::application::server::connection::WriteConnection;
 use crate::application::server::context::Context;
 use crate::application::server::frame::Frame;
 
+mod caching;
+mod filter;
 mod get_name;
+mod get_redir;
 mod id;
 mod info;
+mod kill;
 mod list;
+mod no_evict;
+mod no_touch;
+mod pause;
+mod reply;
 mod set_info;
 mod set_name;
+mod tracking;
+mod tracking_info;
+mod unpause;
 
 #[derive(Debug)]
 pub enum Client {
@@ -22,34 +34,87 @@ pub enum Client {
     GetName(get_name::ClientGetName),
     List(list::ClientList),
     Id(id::ClientID),
+    Tracking(tracking::ClientTracking),
+    Caching(caching::ClientCaching),
+    GetRedir(get_redir::ClientGetRedir),
+    TrackingInfo(tracking_info::ClientTrackingInfo),
+    Kill(kill::ClientKill),
+    Pause(pause::ClientPause),
+    Unpause(unpause::ClientUnpause),
+    Reply(reply::ClientReply),
+    NoEvict(no_evict::ClientNoEvict),
+    NoTouch(no_touch::ClientNoTouch),
 }
 
 // TODO(@miaxos): This is a simple implementation of the HELP to have the
 // associated test, but the idea is to change it to an autogenerated help based
 // on commands available and the documentation of the structure.
 const HELP_TEXT: &str = r#"CLIENT <subcommand> [<arg> [value] [opt] ...]. subcommands are:
+CACHING (YES|NO)
+    Enable/disable tracking of the keys for next command in OPTIN/OPTOUT modes.
+GETREDIR
+    Return the client ID we are redirecting to when client tracking is enabled.
 GETNAME
     Return the name of the current connection.
 ID
     Return the ID of the current connection.
 INFO
     Return information about the current client connection.
+KILL <ip:port>
+    Kill connection made from <ip:port>.
+KILL <option> <value> [<option> <value> [...]]
+    Kill connections. Options are:
+    * ADDR (<ip:port>|<unixsocket>:0)
+      Kill connections made from the specified address
+    * LADDR (<ip:port>|<unixsocket>:0)
+      Kill connections made to specified local address
+    * ID <client-id>
+      Kill connections by client id.
+    * USER <username>
+      Kill connections authenticated by <username>.
+    * SKIPME (YES|NO)
+      Skip killing current connection (default: yes).
+    * MAXAGE <maxage>
+      Kill connections older than the specified age.
 LIST [options ...]
     Return information about client connections. Options:
     * TYPE (NORMAL|MASTER|REPLICA|PUBSUB)
       Return clients of specified type.
+    * USER <username>
+      Return clients authenticated by <username>.
+    * ADDR <ip:port> / LADDR <ip:port>
+      Return clients connected from / to the specified address.
+    * SKIPME (YES|NO)
+      Skip the current connection (default: no).
+    * ID <client-id> [<client-id> ...]
+      Return clients with the specified IDs.
+NO-EVICT (ON|OFF)
+    Protect current client connection from eviction.
+NO-TOUCH (ON|OFF)
+    Will not touch LRU/LFU stats when this mode is on.
+PAUSE <timeout> [WRITE|ALL]
+    Suspend all, or just write, clients for <timeout> milliseconds.
+REPLY (ON|OFF|SKIP)
+    Control the replies sent to the current connection.
 SETNAME <name>
     Assign the name <name> to the current connection.
 SETINFO <option> <value>
     Set client meta attr. Options are:
     * LIB-NAME: the client lib name.
     * LIB-VER: the client lib version.
+TRACKING (ON|OFF) [REDIRECT <id>] [BCAST] [PREFIX <prefix> [...]]
+         [OPTIN] [OPTOUT] [NOLOOP]
+    Control server assisted client side caching.
+TRACKINGINFO
+    Report tracking status for the current connection.
+UNPAUSE
+    Stop the current client pause, resuming traffic.
 HELP
     Print this help.
 "#;
 
 impl SubcommandRegistry for Client {
-    fn from_parse(mut parse: Parse) -> anyhow::Result<Command> {
+    fn from_parse(parse: &mut Parse) -> Result<Command, CommandError> {
         let sub_cmd = match parse.next_string() {
             Ok(elt) => elt,
             Err(ParseError::EndOfStream) => {
@@ -60,36 +125,108 @@ impl SubcommandRegistry for Client {
             }
         };
 
-        let sub_command_name = sub_cmd.to_lowercase();
+        Client::parse_subcommand(&sub_cmd, parse).map_err(|err| {
+            err.for_command(&format!("client|{}", sub_cmd.to_lowercase()))
+        })
+    }
 
-        let command = match &sub_command_name[..] {
+    async fn help(
+        dst: &mut WriteConnection,
+        _ctx: Context,
+    ) -> anyhow::Result<()> {
+        let response = Frame::Array(
+            HELP_TEXT
+                .split_terminator('\n')
+                .map(|x| Frame::Simple(x.into()))
+                .collect(),
+        );
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
+
+impl Client {
+    /// The name of the subcommand, in lower case.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Client::Help => "help",
+            Client::SetInfo(_) => "setinfo",
+            Client::Info(_) => "info",
+            Client::SetName(_) => "setname",
+            Client::GetName(_) => "getname",
+            Client::List(_) => "list",
+            Client::Id(_) => "id",
+            Client::Tracking(_) => "tracking",
+            Client::Caching(_) => "caching",
+            Client::GetRedir(_) => "getredir",
+            Client::TrackingInfo(_) => "trackinginfo",
+            Client::Kill(_) => "kill",
+            Client::Pause(_) => "pause",
+            Client::Unpause(_) => "unpause",
+            Client::Reply(_) => "reply",
+            Client::NoEvict(_) => "no-evict",
+            Client::NoTouch(_) => "no-touch",
+        }
+    }
+
+    fn parse_subcommand(
+        sub_cmd: &str,
+        parse: &mut Parse,
+    ) -> Result<Command, CommandError> {
+        let command = match &sub_cmd.to_lowercase()[..] {
             "setinfo" => Command::Client(Client::SetInfo(
-                set_info::ClientSetInfo::parse_frames(&mut parse)?,
+                set_info::ClientSetInfo::parse_frames(parse)?,
             )),
             "setname" => Command::Client(Client::SetName(
-                set_name::ClientSetName::parse_frames(&mut parse)?,
+                set_name::ClientSetName::parse_frames(parse)?,
             )),
             "getname" => Command::Client(Client::GetName(
-                get_name::ClientGetName::parse_frames(&mut parse)?,
+                get_name::ClientGetName::parse_frames(parse)?,
             )),
-            "id" => Command::Client(Client::Id(id::ClientID::parse_frames(
-                &mut parse,
-            )?)),
+            "id" => {
+                Command::Client(Client::Id(id::ClientID::parse_frames(parse)?))
+            }
             "info" => Command::Client(Client::Info(
-                info::ClientInfo::parse_frames(&mut parse)?,
+                info::ClientInfo::parse_frames(parse)?,
             )),
             "list" => Command::Client(Client::List(
-                list::ClientList::parse_frames(&mut parse)?,
+                list::ClientList::parse_frames(parse)?,
+            )),
+            "tracking" => Command::Client(Client::Tracking(
+                tracking::ClientTracking::parse_frames(parse)?,
+            )),
+            "caching" => Command::Client(Client::Caching(
+                caching::ClientCaching::parse_frames(parse)?,
+            )),
+            "getredir" => Command::Client(Client::GetRedir(
+                get_redir::ClientGetRedir::parse_frames(parse)?,
+            )),
+            "trackinginfo" => Command::Client(Client::TrackingInfo(
+                tracking_info::ClientTrackingInfo::parse_frames(parse)?,
+            )),
+            "kill" => Command::Client(Client::Kill(
+                kill::ClientKill::parse_frames(parse)?,
+            )),
+            "pause" => Command::Client(Client::Pause(
+                pause::ClientPause::parse_frames(parse)?,
+            )),
+            "unpause" => Command::Client(Client::Unpause(
+                unpause::ClientUnpause::parse_frames(parse)?,
+            )),
+            "reply" => Command::Client(Client::Reply(
+                reply::ClientReply::parse_frames(parse)?,
+            )),
+            "no-evict" => Command::Client(Client::NoEvict(
+                no_evict::ClientNoEvict::parse_frames(parse)?,
+            )),
+            "no-touch" => Command::Client(Client::NoTouch(
+                no_touch::ClientNoTouch::parse_frames(parse)?,
             )),
             "help" => Command::Client(Client::Help),
             _ => {
-                // The command is not recognized and an Unknown command is
-                // returned.
-                //
-                // `return` is called here to skip the `finish()` call below. As
-                // the command is not recognized, there is most likely
-                // unconsumed fields remaining in the `Parse` instance.
-                return Ok(Command::Unknown(Unknown::new(sub_command_name)));
+                return Err(CommandError::err(format!(
+                    "unknown subcommand '{sub_cmd}'. Try CLIENT HELP."
+                )));
             }
         };
 
@@ -101,20 +238,6 @@ impl SubcommandRegistry for Client {
         // The command has been successfully parsed
         Ok(command)
     }
-
-    async fn help(
-        dst: &mut WriteConnection,
-        _ctx: Context,
-    ) -> anyhow::Result<()> {
-        let response = Frame::Array(
-            HELP_TEXT
-                .split_terminator('\n')
-                .map(|x| Frame::Simple(x.into()))
-                .collect(),
-        );
-        dst.write_frame(&response).await?;
-        Ok(())
-    }
 }
 
 impl CommandExecution for Client {
@@ -131,6 +254,16 @@ impl CommandExecution for Client {
             Client::Id(cmd) => cmd.apply(dst, ctx).await,
             Client::Info(cmd) => cmd.apply(dst, ctx).await,
             Client::List(cmd) => cmd.apply(dst, ctx).await,
+            Client::Tracking(cmd) => cmd.apply(dst, ctx).await,
+            Client::Caching(cmd) => cmd.apply(dst, ctx).await,
+            Client::GetRedir(cmd) => cmd.apply(dst, ctx).await,
+            Client::TrackingInfo(cmd) => cmd.apply(dst, ctx).await,
+            Client::Kill(cmd) => cmd.apply(dst, ctx).await,
+            Client::Pause(cmd) => cmd.apply(dst, ctx).await,
+            Client::Unpause(cmd) => cmd.apply(dst, ctx).await,
+            Client::Reply(cmd) => cmd.apply(dst, ctx).await,
+            Client::NoEvict(cmd) => cmd.apply(dst, ctx).await,
+            Client::NoTouch(cmd) => cmd.apply(dst, ctx).await,
         }
     }
 }
@@ -0,0 +1,42 @@
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// The CLIENT NO-EVICT command sets the client eviction mode of the current
+/// connection. When turned on, the connection is excluded from the client
+/// eviction.
+///
+/// ```text
+/// CLIENT NO-EVICT <ON | OFF>
+/// ```
+#[derive(Debug)]
+pub struct ClientNoEvict {
+    on: bool,
+}
+
+impl ClientNoEvict {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<ClientNoEvict, CommandError> {
+        let on = match &parse.next_string()?.to_lowercase()[..] {
+            "on" => true,
+            "off" => false,
+            _ => return Err(CommandError::Syntax),
+        };
+
+        Ok(ClientNoEvict { on })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        ctx.connection.set_no_evict(self.on);
+
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
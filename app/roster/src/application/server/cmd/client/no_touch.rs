@@ -0,0 +1,42 @@
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// The CLIENT NO-TOUCH command controls whether the commands sent by the
+/// client alter the LRU/LFU of the keys they access. `TOUCH` still touches
+/// the keys.
+///
+/// ```text
+/// CLIENT NO-TOUCH <ON | OFF>
+/// ```
+#[derive(Debug)]
+pub struct ClientNoTouch {
+    on: bool,
+}
+
+impl ClientNoTouch {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<ClientNoTouch, CommandError> {
+        let on = match &parse.next_string()?.to_lowercase()[..] {
+            "on" => true,
+            "off" => false,
+            _ => return Err(CommandError::Syntax),
+        };
+
+        Ok(ClientNoTouch { on })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        ctx.connection.set_no_touch(self.on);
+
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
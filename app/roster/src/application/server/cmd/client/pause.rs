@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use super::super::parse::Parse;
+use crate::application::server::cmd::parse::ParseError;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::application::server::supervisor::PauseMode;
+
+/// The CLIENT PAUSE command suspends the commands of every client for the
+/// given number of milliseconds, a useful way to stop the writes during a
+/// failover.
+///
+/// ```text
+/// CLIENT PAUSE timeout [WRITE | ALL]
+/// ```
+///
+/// With `ALL`, the default, every command is suspended. With `WRITE`, only
+/// the commands which may write are, like `SET`, `PUBLISH`, `EXEC` or the
+/// scripts which aren't read only.
+///
+/// The pause ends after the timeout or with CLIENT UNPAUSE.
+#[derive(Debug)]
+pub struct ClientPause {
+    timeout: Duration,
+    mode: PauseMode,
+}
+
+impl ClientPause {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<ClientPause, CommandError> {
+        let timeout = parse.next_int().map_err(|_| {
+            CommandError::err("timeout is not an integer or out of range")
+        })?;
+
+        let mode = match parse.next_string() {
+            Ok(mode) => match &mode.to_lowercase()[..] {
+                "write" => PauseMode::Write,
+                "all" => PauseMode::All,
+                _ => return Err(CommandError::Syntax),
+            },
+            Err(ParseError::EndOfStream) => PauseMode::All,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(ClientPause {
+            timeout: Duration::from_millis(timeout),
+            mode,
+        })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        ctx.supervisor.pause_clients(self.timeout, self.mode);
+
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let client_pause = Command::from_frame(frame, &Default::default())?;
+        Ok(client_pause)
+    }
+
+    #[test]
+    fn ensure_parsing() {
+        let entry: RespValue = resp_array!["CLIENT", "PAUSE", "100", "WRITE"];
+        let client_cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(client_cmd, @r###"
+        Client(
+            Pause(
+                ClientPause {
+                    timeout: 100ms,
+                    mode: Write,
+                },
+            ),
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_fail() {
+        let entry: RespValue = resp_array!["CLIENT", "PAUSE", "-1"];
+        insta::assert_debug_snapshot!(parse_cmd(entry), @r###"
+        Err(
+            Other(
+                "ERR timeout is not an integer or out of range",
+            ),
+        )
+        "###);
+
+        let entry: RespValue = resp_array!["CLIENT", "PAUSE", "10", "READ"];
+        assert!(parse_cmd(entry).is_err());
+    }
+}
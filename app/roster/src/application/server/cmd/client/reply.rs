@@ -0,0 +1,50 @@
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::{Context, ReplyMode};
+use crate::application::server::frame::Frame;
+
+/// The CLIENT REPLY command controls whether the server replies to the
+/// commands of the client.
+///
+/// ```text
+/// CLIENT REPLY <ON | OFF | SKIP>
+/// ```
+///
+/// `OFF` turns the replies off, `SKIP` skips the reply of the next command
+/// only and `ON` turns them on again. Only `ON` is answered with `OK`. The
+/// Pub/Sub messages are always sent.
+#[derive(Debug)]
+pub struct ClientReply {
+    mode: ReplyMode,
+}
+
+impl ClientReply {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<ClientReply, CommandError> {
+        let mode = match &parse.next_string()?.to_lowercase()[..] {
+            "on" => ReplyMode::On,
+            "off" => ReplyMode::Off,
+            "skip" => ReplyMode::Skip,
+            _ => return Err(CommandError::Syntax),
+        };
+
+        Ok(ClientReply { mode })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        ctx.set_reply_mode(self.mode);
+
+        if self.mode == ReplyMode::On {
+            // The replies may be turned off until this command.
+            dst.set_muted(false);
+            dst.write_frame(&Frame::Simple("OK".into())).await?;
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
-use bytes::Bytes;
 use bytestring::ByteString;
 
-use super::super::parse::{Parse, ParseError};
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
 use crate::application::server::connection::WriteConnection;
 use crate::application::server::context::Context;
 use crate::application::server::frame::Frame;
@@ -25,20 +25,15 @@ use crate::application::server::frame::Frame;
 /// Note that these attributes are not cleared by the RESET command.
 #[derive(Debug, Default)]
 pub struct ClientSetInfo {
-    #[allow(dead_code)]
-    lib_name: Option<Bytes>,
-    #[allow(dead_code)]
-    lib_version: Option<Bytes>,
+    lib_name: Option<ByteString>,
+    lib_version: Option<ByteString>,
 }
 
-const LIB_NAME: Bytes = Bytes::from_static(b"LIB-NAME");
-const LIB_VERSION: Bytes = Bytes::from_static(b"LIB-VER");
-
 impl ClientSetInfo {
     /// Create a new `ClientSetInfo` command with optional `msg`.
     pub fn new(
-        lib_name: Option<Bytes>,
-        lib_version: Option<Bytes>,
+        lib_name: Option<ByteString>,
+        lib_version: Option<ByteString>,
     ) -> ClientSetInfo {
         ClientSetInfo {
             lib_name,
@@ -61,34 +56,45 @@ impl ClientSetInfo {
     ///
     /// # Format
     ///
-    /// Expects an array frame containing `ClientSetInfo` and an optional
-    /// lib_name & lib_version.
+    /// Expects an array frame containing `ClientSetInfo` and either the
+    /// lib_name or the lib_version.
     ///
     /// ```text
     /// CLIENT SETINFO <LIB-NAME libname | LIB-VER libver>
     /// ```
     pub(crate) fn parse_frames(
         parse: &mut Parse,
-    ) -> anyhow::Result<ClientSetInfo> {
-        let mut lib_name = None;
-        let mut lib_version = None;
+    ) -> Result<ClientSetInfo, CommandError> {
+        let attr = parse.next_string()?;
+        let value = parse.next_bytes()?;
 
-        match (parse.next_bytes(), parse.next_bytes()) {
-            (Ok(key), Ok(val)) => {
-                if key == LIB_NAME {
-                    lib_name = Some(val);
-                } else if key == LIB_VERSION {
-                    lib_version = Some(val);
-                }
-            }
-            (Err(ParseError::EndOfStream), _)
-            | (_, Err(ParseError::EndOfStream)) => {
-                return Ok(ClientSetInfo::new(lib_name, lib_version))
+        let lib_name = match &attr.to_lowercase()[..] {
+            "lib-name" => true,
+            "lib-ver" => false,
+            _ => {
+                return Err(CommandError::err(format!(
+                    "Unrecognized option '{attr}'"
+                )))
             }
-            (Err(e), _) | (_, Err(e)) => return Err(e.into()),
-        }
+        };
+
+        // Like the name, the attributes can't break the CLIENT LIST format.
+        let value = value
+            .iter()
+            .all(|b| (b'!'..=b'~').contains(b))
+            .then(|| ByteString::try_from(value).ok())
+            .flatten()
+            .ok_or_else(|| {
+                CommandError::err(format!(
+                    "{attr} cannot contain spaces, newlines or special \
+                     characters."
+                ))
+            })?;
 
-        Ok(ClientSetInfo::new(lib_name, lib_version))
+        Ok(match lib_name {
+            true => ClientSetInfo::new(Some(value), None),
+            false => ClientSetInfo::new(None, Some(value)),
+        })
     }
 
     /// Apply the `ClientSetInfo` command and return the message.
@@ -98,8 +104,15 @@ impl ClientSetInfo {
     pub(crate) async fn apply(
         self,
         dst: &mut WriteConnection,
-        _ctx: Context,
+        ctx: Context,
     ) -> anyhow::Result<()> {
+        if let Some(lib_name) = self.lib_name {
+            ctx.connection.set_lib_name(lib_name).await;
+        }
+        if let Some(lib_version) = self.lib_version {
+            ctx.connection.set_lib_ver(lib_version).await;
+        }
+
         let response = Frame::Simple(ByteString::from_static("OK"));
         dst.write_frame(&response).await?;
 
@@ -1,6 +1,7 @@
 use bytestring::ByteString;
 
 use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
 use crate::application::server::connection::WriteConnection;
 use crate::application::server::context::Context;
 use crate::application::server::frame::Frame;
@@ -42,7 +43,7 @@ impl ClientSetName {
 
     pub(crate) fn parse_frames(
         parse: &mut Parse,
-    ) -> anyhow::Result<ClientSetName> {
+    ) -> Result<ClientSetName, CommandError> {
         let name = parse.next_string()?;
         Ok(ClientSetName::new(name))
     }
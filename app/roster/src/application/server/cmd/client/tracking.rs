@@ -0,0 +1,242 @@
+use bytes::Bytes;
+
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::tracking::TrackingOptions;
+
+/// This command enables the tracking feature of the Redis server, that is
+/// used for server assisted client side caching.
+///
+/// When tracking is enabled Redis remembers the keys that the connection
+/// requested, in order to send later invalidation messages when such keys are
+/// modified. Invalidation messages are sent in the same connection (only
+/// available when the RESP3 protocol is used) or redirected in a different
+/// connection.
+///
+/// ```text
+/// CLIENT TRACKING <ON | OFF> [REDIRECT client-id] [PREFIX prefix
+///   [PREFIX prefix ...]] [BCAST] [OPTIN] [OPTOUT] [NOLOOP]
+/// ```
+#[derive(Debug, Default)]
+pub struct ClientTracking {
+    on: bool,
+    options: TrackingOptions,
+}
+
+impl ClientTracking {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<ClientTracking, CommandError> {
+        let on = match &parse.next_string()?.to_lowercase()[..] {
+            "on" => true,
+            "off" => false,
+            _ => return Err(CommandError::Syntax),
+        };
+
+        let mut options = TrackingOptions::default();
+        loop {
+            let option = match parse.next_string() {
+                Ok(option) => option.to_lowercase(),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            match &option[..] {
+                "redirect" => {
+                    let id = parse.next_int().map_err(syntax_if_missing)?;
+                    options.redirect = Some(id);
+                }
+                "prefix" => {
+                    let prefix =
+                        parse.next_bytes().map_err(syntax_if_missing)?;
+                    options.prefixes.push(prefix);
+                }
+                "bcast" => options.bcast = true,
+                "optin" => options.optin = true,
+                "optout" => options.optout = true,
+                "noloop" => options.noloop = true,
+                _ => return Err(CommandError::Syntax),
+            }
+        }
+
+        Ok(ClientTracking { on, options })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        if !self.on {
+            ctx.disable_tracking().await;
+            dst.write_frame(&Frame::Simple("OK".into())).await?;
+            return Ok(());
+        }
+
+        let options = self.options;
+        let current = ctx.tracking();
+        check_options(&options, current.as_ref())?;
+
+        let target = match options.redirect {
+            Some(id) => ctx
+                .supervisor
+                .connection(id)
+                .await
+                .and_then(|conn| conn.messages())
+                .ok_or_else(|| {
+                    CommandError::err(
+                        "The client ID you want redirect to does not exist",
+                    )
+                })?,
+            None => ctx.subscriptions().sender(),
+        };
+
+        ctx.enable_tracking(options, target).await;
+
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
+
+fn syntax_if_missing(err: ParseError) -> CommandError {
+    match err {
+        ParseError::EndOfStream => CommandError::Syntax,
+        err => err.into(),
+    }
+}
+
+/// Check the options are compatible together and with the tracking already
+/// enabled.
+fn check_options(
+    options: &TrackingOptions,
+    current: Option<&TrackingOptions>,
+) -> Result<(), CommandError> {
+    if !options.bcast && !options.prefixes.is_empty() {
+        return Err(CommandError::err(
+            "PREFIX option requires BCAST mode to be enabled",
+        ));
+    }
+
+    if current.is_some_and(|current| current.bcast != options.bcast) {
+        return Err(CommandError::err(
+            "You can't switch BCAST mode on/off before disabling tracking for \
+             this client, and then re-enabling it with a different mode.",
+        ));
+    }
+
+    if options.bcast && (options.optin || options.optout) {
+        return Err(CommandError::err(
+            "OPTIN and OPTOUT are not compatible with BCAST",
+        ));
+    }
+
+    if options.optin && options.optout {
+        return Err(CommandError::err("You can't use both OPTIN and OPTOUT"));
+    }
+
+    if current.is_some_and(|current| {
+        (options.optin && current.optout) || (options.optout && current.optin)
+    }) {
+        return Err(CommandError::err(
+            "You can't switch OPTIN/OPTOUT mode before disabling tracking for \
+             this client, and then re-enabling it with a different mode.",
+        ));
+    }
+
+    let existing = current.map(|current| &current.prefixes[..]).unwrap_or(&[]);
+    for (i, prefix) in options.prefixes.iter().enumerate() {
+        if let Some(collision) = existing.iter().find(|e| overlaps(e, prefix)) {
+            return Err(CommandError::err(format!(
+                "Prefix '{}' overlaps with an existing prefix '{}'. Prefixes \
+                 for a single client must not overlap.",
+                String::from_utf8_lossy(prefix),
+                String::from_utf8_lossy(collision),
+            )));
+        }
+
+        if let Some(collision) = options.prefixes[i + 1..]
+            .iter()
+            .find(|e| overlaps(e, prefix))
+        {
+            return Err(CommandError::err(format!(
+                "Prefix '{}' overlaps with another provided prefix '{}'. \
+                 Prefixes for a single client must not overlap.",
+                String::from_utf8_lossy(prefix),
+                String::from_utf8_lossy(collision),
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Two prefixes overlap when one of them is a prefix of the other.
+fn overlaps(a: &Bytes, b: &Bytes) -> bool {
+    a.starts_with(b) || b.starts_with(a)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let cmd = Command::from_frame(frame, &Default::default())?;
+        Ok(cmd)
+    }
+
+    #[test]
+    fn ensure_parsing() {
+        let entry: RespValue = resp_array![
+            "CLIENT", "TRACKING", "on", "BCAST", "PREFIX", "user:", "NOLOOP"
+        ];
+        let cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Client(
+            Tracking(
+                ClientTracking {
+                    on: true,
+                    options: TrackingOptions {
+                        redirect: None,
+                        bcast: true,
+                        prefixes: [
+                            b"user:",
+                        ],
+                        optin: false,
+                        optout: false,
+                        noloop: true,
+                    },
+                },
+            ),
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_missing_redirect() {
+        let entry: RespValue =
+            resp_array!["CLIENT", "TRACKING", "on", "REDIRECT"];
+        let cmd = parse_cmd(entry);
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Err(
+            Syntax,
+        )
+        "###);
+    }
+}
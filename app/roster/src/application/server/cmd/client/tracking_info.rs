@@ -0,0 +1,91 @@
+use bytes::Bytes;
+
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// The command returns information about the current client connection's use
+/// of the server assisted client side caching feature.
+///
+/// The reply is a map with the tracking `flags`, the client ID used for
+/// `redirect` and the `prefixes` broadcasted.
+#[derive(Debug, Default)]
+pub struct ClientTrackingInfo {}
+
+impl ClientTrackingInfo {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<ClientTrackingInfo, CommandError> {
+        parse.finish()?;
+        Ok(ClientTrackingInfo {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let tracking = ctx.tracking();
+
+        let mut flags = Vec::new();
+        let mut redirect = -1;
+        let mut prefixes = Vec::new();
+
+        match tracking {
+            None => flags.push("off"),
+            Some(options) => {
+                flags.push("on");
+                for (enabled, flag) in [
+                    (options.bcast, "bcast"),
+                    (options.optin, "optin"),
+                    (options.optout, "optout"),
+                    (ctx.caching() == Some(true), "caching-yes"),
+                    (ctx.caching() == Some(false), "caching-no"),
+                    (options.noloop, "noloop"),
+                ] {
+                    if enabled {
+                        flags.push(flag);
+                    }
+                }
+
+                if let Some(id) = options.redirect {
+                    redirect = id as i64;
+                    if ctx.supervisor.connection(id).await.is_none() {
+                        flags.push("broken_redirect");
+                    }
+                } else {
+                    redirect = 0;
+                }
+
+                prefixes = options.prefixes;
+            }
+        }
+
+        let response = Frame::Map(vec![
+            (
+                Frame::Bulk(Bytes::from_static(b"flags")),
+                Frame::Set(
+                    flags
+                        .into_iter()
+                        .map(|flag| {
+                            Frame::Bulk(Bytes::from_static(flag.as_bytes()))
+                        })
+                        .collect(),
+                ),
+            ),
+            (
+                Frame::Bulk(Bytes::from_static(b"redirect")),
+                Frame::Integer(redirect),
+            ),
+            (
+                Frame::Bulk(Bytes::from_static(b"prefixes")),
+                Frame::Array(prefixes.into_iter().map(Frame::Bulk).collect()),
+            ),
+        ]);
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
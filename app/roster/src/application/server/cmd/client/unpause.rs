@@ -0,0 +1,33 @@
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// The CLIENT UNPAUSE command resumes the commands suspended by
+/// CLIENT PAUSE.
+///
+/// ```text
+/// CLIENT UNPAUSE
+/// ```
+#[derive(Debug, Default)]
+pub struct ClientUnpause {}
+
+impl ClientUnpause {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<ClientUnpause, CommandError> {
+        Ok(ClientUnpause {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        ctx.supervisor.unpause_clients();
+
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
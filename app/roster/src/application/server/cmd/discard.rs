@@ -0,0 +1,44 @@
+use bytestring::ByteString;
+
+use super::parse::Parse;
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Flushes all previously queued commands in a transaction and restores the
+/// connection state to normal.
+///
+/// If WATCH was used, DISCARD unwatches all keys watched by the connection.
+///
+/// ```text
+/// DISCARD
+/// ```
+#[derive(Debug, Default)]
+pub struct Discard {}
+
+impl Discard {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<Discard, CommandError> {
+        Ok(Discard {})
+    }
+}
+
+impl CommandExecution for Discard {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        if ctx.transaction().finish().is_none() {
+            return Err(CommandError::err("DISCARD without MULTI").into());
+        }
+
+        let response = Frame::Simple(ByteString::from_static("OK"));
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+use bytestring::ByteString;
+
+use super::parse::ParseError;
+use crate::application::server::frame::Frame;
+use crate::domain::scripting::FunctionError;
+
+/// Error raised while parsing or applying a command.
+///
+/// Those errors are sent back to the client as an error reply and the
+/// connection stays open. The displayed message is the exact reply sent,
+/// starting with the error code (`ERR`, `WRONGTYPE`, `NOSCRIPT`...).
+#[derive(thiserror::Error, Debug)]
+pub enum CommandError {
+    /// The command doesn't have the expected number of arguments.
+    #[error("ERR wrong number of arguments for '{0}' command")]
+    WrongArity(String),
+
+    /// An argument of the command is not valid.
+    #[error("ERR syntax error")]
+    Syntax,
+
+    /// An argument couldn't be parsed as an integer.
+    #[error("ERR value is not an integer or out of range")]
+    NotAnInteger,
+
+    /// The keys of the command are not all owned by the same hash slot.
+    #[error("CROSSSLOT Keys in request don't hash to the same slot")]
+    CrossSlot,
+
+    /// Any other error, the message must start with its error code.
+    #[error("{0}")]
+    Other(ByteString),
+}
+
+impl CommandError {
+    /// Create a generic `ERR` error.
+    pub fn err(msg: impl AsRef<str>) -> CommandError {
+        CommandError::Other(ByteString::from(format!("ERR {}", msg.as_ref())))
+    }
+
+    /// Create an error with a specific error code like `NOPROTO` or
+    /// `WRONGPASS`.
+    pub fn with_code(code: &str, msg: impl AsRef<str>) -> CommandError {
+        CommandError::Other(ByteString::from(format!(
+            "{code} {}",
+            msg.as_ref()
+        )))
+    }
+
+    /// Attach the command name to an arity error which was raised before the
+    /// name was known, e.g. when a [ParseError::EndOfStream] is converted.
+    pub fn for_command(self, name: &str) -> CommandError {
+        match self {
+            CommandError::WrongArity(cmd) if cmd.is_empty() => {
+                CommandError::WrongArity(name.to_string())
+            }
+            err => err,
+        }
+    }
+
+    /// The error reply to send to the client.
+    pub fn into_frame(self) -> Frame {
+        match self {
+            CommandError::Other(msg) => Frame::Error(msg),
+            err => Frame::Error(ByteString::from(err.to_string())),
+        }
+    }
+}
+
+impl From<ParseError> for CommandError {
+    fn from(value: ParseError) -> Self {
+        match value {
+            ParseError::EndOfStream | ParseError::Remaining => {
+                CommandError::WrongArity(String::new())
+            }
+            ParseError::InvalidInteger => CommandError::NotAnInteger,
+            ParseError::Other(err) => CommandError::err(err.to_string()),
+        }
+    }
+}
+
+impl From<FunctionError> for CommandError {
+    fn from(value: FunctionError) -> Self {
+        CommandError::err(value.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CommandError;
+    use crate::application::server::cmd::parse::ParseError;
+    use crate::application::server::frame::Frame;
+
+    #[test]
+    fn arity_error_gets_the_command_name() {
+        let err =
+            CommandError::from(ParseError::EndOfStream).for_command("get");
+        assert_eq!(
+            err.into_frame(),
+            Frame::Error(
+                "ERR wrong number of arguments for 'get' command".into()
+            )
+        );
+
+        let err = CommandError::WrongArity("client|id".to_string())
+            .for_command("client");
+        assert_eq!(
+            err.to_string(),
+            "ERR wrong number of arguments for 'client|id' command"
+        );
+    }
+
+    #[test]
+    fn error_codes() {
+        assert_eq!(
+            CommandError::with_code("NOPROTO", "unsupported protocol version")
+                .into_frame(),
+            Frame::Error("NOPROTO unsupported protocol version".into())
+        );
+        assert_eq!(
+            CommandError::from(ParseError::InvalidInteger).into_frame(),
+            Frame::Error("ERR value is not an integer or out of range".into())
+        );
+    }
+}
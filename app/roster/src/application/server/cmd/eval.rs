@@ -0,0 +1,216 @@
+use bytes::Bytes;
+
+use super::parse::{Parse, ParseError};
+use super::ssubscribe::check_same_slot;
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::domain::scripting::{ScriptFlags, Shebang, ShebangError};
+use crate::infrastructure::hash::crc_hash;
+
+/// Invoke the execution of a server-side Lua script.
+///
+/// The keys accessed by the script are given as `KEYS`, they must hash to
+/// the same slot. The other arguments are given as `ARGV`. The script is
+/// added to the scripts cache so it can be called again with `EVALSHA`.
+///
+/// `EVAL_RO` is the read-only variant, the script can't call a command
+/// modifying the dataset.
+///
+/// ```text
+/// EVAL script numkeys [key [key ...]] [arg [arg ...]]
+/// EVAL_RO script numkeys [key [key ...]] [arg [arg ...]]
+/// ```
+#[derive(Debug, Default)]
+pub struct Eval {
+    script: Bytes,
+    keys: Vec<Bytes>,
+    args: Vec<Bytes>,
+    read_only: bool,
+}
+
+impl Eval {
+    pub fn keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+        read_only: bool,
+    ) -> Result<Eval, CommandError> {
+        let script = parse.next_bytes()?;
+        let (keys, args) = parse_keys_and_args(parse)?;
+        Ok(Eval {
+            script,
+            keys,
+            args,
+            read_only,
+        })
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+}
+
+impl CommandExecution for Eval {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let sha = ctx.supervisor.scripts().load(self.script.clone()).await;
+        run_script(
+            &sha,
+            &self.script,
+            &self.keys,
+            &self.args,
+            self.read_only,
+            dst,
+            ctx,
+        )
+        .await
+    }
+
+    fn hash_key(&self) -> Option<u16> {
+        self.keys.first().map(|key| crc_hash(key))
+    }
+}
+
+/// Parse `numkeys [key [key ...]] [arg [arg ...]]`, the keys must hash to the
+/// same slot.
+pub(super) fn parse_keys_and_args(
+    parse: &mut Parse,
+) -> Result<(Vec<Bytes>, Vec<Bytes>), CommandError> {
+    let numkeys = parse.next_i64()?;
+    if numkeys < 0 {
+        return Err(CommandError::err("Number of keys can't be negative"));
+    }
+
+    let mut args = Vec::new();
+    loop {
+        match parse.next_bytes() {
+            Ok(arg) => args.push(arg),
+            Err(ParseError::EndOfStream) => break,
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    let numkeys = numkeys as usize;
+    if numkeys > args.len() {
+        return Err(CommandError::err(
+            "Number of keys can't be greater than number of args",
+        ));
+    }
+    let keys = args.drain(..numkeys).collect::<Vec<_>>();
+    check_same_slot(&keys)?;
+
+    Ok((keys, args))
+}
+
+/// Run the script `body`, identified by `sha`, and send its reply.
+pub(super) async fn run_script(
+    sha: &str,
+    body: &[u8],
+    keys: &[Bytes],
+    args: &[Bytes],
+    read_only: bool,
+    dst: &mut WriteConnection,
+    ctx: Context,
+) -> anyhow::Result<()> {
+    let shebang = Shebang::parse(body)
+        .map_err(|err| CommandError::err(err.to_string()))?
+        .unwrap_or_default();
+    // Only the libraries of functions have a name.
+    if let Some(name) = shebang.name {
+        return Err(CommandError::err(
+            ShebangError::Option(format!("name={name}")).to_string(),
+        )
+        .into());
+    }
+
+    let mut flags = shebang.flags;
+    if read_only {
+        flags = flags | ScriptFlags::NO_WRITES;
+    }
+
+    let function = ctx.lua().compile(sha, body)?;
+
+    let guard = ctx.exclusive_storage().await;
+    let reply = ctx
+        .lua()
+        .run_script(&ctx, &function, keys, args, flags)
+        .await;
+    drop(guard);
+
+    dst.write_frame(&reply?).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let cmd = Command::from_frame(frame, &Default::default())?;
+        Ok(cmd)
+    }
+
+    #[test]
+    fn ensure_parsing() {
+        let entry: RespValue =
+            resp_array!["EVAL_RO", "return KEYS[1]", "1", "key", "arg"];
+        let cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Eval(
+            Eval {
+                script: b"return KEYS[1]",
+                keys: [
+                    b"key",
+                ],
+                args: [
+                    b"arg",
+                ],
+                read_only: true,
+            },
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_numkeys() {
+        let entry: RespValue = resp_array!["EVAL", "return 1", "2", "key"];
+        let cmd = parse_cmd(entry);
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Err(
+            Other(
+                "ERR Number of keys can't be greater than number of args",
+            ),
+        )
+        "###);
+
+        let entry: RespValue =
+            resp_array!["EVAL", "return 1", "2", "key", "other"];
+        let cmd = parse_cmd(entry);
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Err(
+            CrossSlot,
+        )
+        "###);
+    }
+}
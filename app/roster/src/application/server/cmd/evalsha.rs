@@ -0,0 +1,85 @@
+use bytes::Bytes;
+use bytestring::ByteString;
+
+use super::eval::{parse_keys_and_args, run_script};
+use super::parse::Parse;
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::infrastructure::hash::crc_hash;
+
+/// Evaluate a script from the server's cache by its SHA1 digest.
+///
+/// The script must have been loaded with `EVAL` or `SCRIPT LOAD` before,
+/// otherwise a `NOSCRIPT` error is returned.
+///
+/// `EVALSHA_RO` is the read-only variant, the script can't call a command
+/// modifying the dataset.
+///
+/// ```text
+/// EVALSHA sha1 numkeys [key [key ...]] [arg [arg ...]]
+/// EVALSHA_RO sha1 numkeys [key [key ...]] [arg [arg ...]]
+/// ```
+#[derive(Debug, Default)]
+pub struct EvalSha {
+    sha: ByteString,
+    keys: Vec<Bytes>,
+    args: Vec<Bytes>,
+    read_only: bool,
+}
+
+impl EvalSha {
+    pub fn keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+        read_only: bool,
+    ) -> Result<EvalSha, CommandError> {
+        let sha = ByteString::from(parse.next_string()?.to_lowercase());
+        let (keys, args) = parse_keys_and_args(parse)?;
+        Ok(EvalSha {
+            sha,
+            keys,
+            args,
+            read_only,
+        })
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+}
+
+impl CommandExecution for EvalSha {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let Some(body) = ctx.supervisor.scripts().get(&self.sha).await else {
+            return Err(CommandError::with_code(
+                "NOSCRIPT",
+                "No matching script. Please use EVAL.",
+            )
+            .into());
+        };
+
+        run_script(
+            &self.sha,
+            &body,
+            &self.keys,
+            &self.args,
+            self.read_only,
+            dst,
+            ctx,
+        )
+        .await
+    }
+
+    fn hash_key(&self) -> Option<u16> {
+        self.keys.first().map(|key| crc_hash(key))
+    }
+}
@@ -0,0 +1,76 @@
+use super::parse::Parse;
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Executes all previously queued commands in a transaction and restores the
+/// connection state to normal.
+///
+/// When using WATCH, EXEC will execute commands only if the watched keys were
+/// not modified, allowing for a check-and-set mechanism.
+///
+/// The queued commands are applied while holding an exclusive access on the
+/// storage so no other command can be interleaved, even from another thread.
+///
+/// ```text
+/// EXEC
+/// ```
+#[derive(Debug, Default)]
+pub struct Exec {}
+
+impl Exec {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<Exec, CommandError> {
+        Ok(Exec {})
+    }
+}
+
+impl CommandExecution for Exec {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let Some(transaction) = ctx.transaction().finish() else {
+            return Err(CommandError::err("EXEC without MULTI").into());
+        };
+
+        if transaction.aborted {
+            return Err(CommandError::with_code(
+                "EXECABORT",
+                "Transaction discarded because of previous errors.",
+            )
+            .into());
+        }
+
+        let guard = ctx.exclusive_storage().await;
+
+        let now = ctx.now();
+        for (key, version) in transaction.watched {
+            if ctx.storage.version_async(key, now).await != version {
+                drop(guard);
+                dst.write_frame(&Frame::NullArray).await?;
+                return Ok(());
+            }
+        }
+
+        // Replies are kept until every command is applied so we do not hold
+        // the storage while writing to the socket.
+        dst.start_capture();
+        for cmd in transaction.commands {
+            if let Err(err) = Box::pin(cmd.execute(dst, ctx.clone())).await {
+                dst.end_capture();
+                return Err(err);
+            }
+        }
+        let replies = dst.end_capture();
+        drop(guard);
+
+        dst.write_frame(&Frame::Array(replies)).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+use bytes::Bytes;
+use bytestring::ByteString;
+
+use super::eval::parse_keys_and_args;
+use super::parse::Parse;
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::infrastructure::hash::crc_hash;
+
+/// Invoke a function registered by a library loaded with `FUNCTION LOAD`.
+///
+/// The function is called with the keys and the arguments as its two
+/// parameters, the keys must hash to the same slot.
+///
+/// `FCALL_RO` is the read-only variant, it can only call the functions
+/// registered with the `no-writes` flag.
+///
+/// ```text
+/// FCALL function numkeys [key [key ...]] [arg [arg ...]]
+/// FCALL_RO function numkeys [key [key ...]] [arg [arg ...]]
+/// ```
+#[derive(Debug, Default)]
+pub struct FCall {
+    function: ByteString,
+    keys: Vec<Bytes>,
+    args: Vec<Bytes>,
+    read_only: bool,
+}
+
+impl FCall {
+    pub fn keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+        read_only: bool,
+    ) -> Result<FCall, CommandError> {
+        let function = parse.next_string()?;
+        let (keys, args) = parse_keys_and_args(parse)?;
+        Ok(FCall {
+            function,
+            keys,
+            args,
+            read_only,
+        })
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+}
+
+impl CommandExecution for FCall {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let Some(library) = ctx
+            .supervisor
+            .scripts()
+            .functions()
+            .library_of(&self.function)
+        else {
+            return Err(CommandError::err("Function not found").into());
+        };
+
+        let guard = ctx.exclusive_storage().await;
+        let reply = ctx
+            .lua()
+            .call_function(
+                &ctx,
+                &library,
+                &self.function,
+                &self.keys,
+                &self.args,
+                self.read_only,
+            )
+            .await;
+        drop(guard);
+
+        dst.write_frame(&reply?).await?;
+        Ok(())
+    }
+
+    fn hash_key(&self) -> Option<u16> {
+        self.keys.first().map(|key| crc_hash(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let cmd = Command::from_frame(frame, &Default::default())?;
+        Ok(cmd)
+    }
+
+    #[test]
+    fn ensure_parsing() {
+        let entry: RespValue =
+            resp_array!["FCALL_RO", "myfunc", "1", "key", "arg"];
+        let cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(cmd, @r###"
+        FCall(
+            FCall {
+                function: "myfunc",
+                keys: [
+                    b"key",
+                ],
+                args: [
+                    b"arg",
+                ],
+                read_only: true,
+            },
+        )
+        "###);
+    }
+}
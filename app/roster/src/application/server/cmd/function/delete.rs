@@ -0,0 +1,41 @@
+use bytestring::ByteString;
+
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Delete a library and all its functions.
+///
+/// ```text
+/// FUNCTION DELETE library-name
+/// ```
+#[derive(Debug, Default)]
+pub struct FunctionDelete {
+    library: ByteString,
+}
+
+impl FunctionDelete {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<FunctionDelete, CommandError> {
+        let library = parse.next_string()?;
+        Ok(FunctionDelete { library })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        ctx.supervisor
+            .scripts()
+            .functions()
+            .delete(&self.library)
+            .map_err(CommandError::from)?;
+
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Return a serialized payload of the loaded libraries, which can be loaded
+/// again with `FUNCTION RESTORE`.
+///
+/// ```text
+/// FUNCTION DUMP
+/// ```
+#[derive(Debug, Default)]
+pub struct FunctionDump {}
+
+impl FunctionDump {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<FunctionDump, CommandError> {
+        Ok(FunctionDump {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let payload = ctx.supervisor.scripts().functions().dump();
+
+        dst.write_frame(&Frame::Bulk(payload)).await?;
+        Ok(())
+    }
+}
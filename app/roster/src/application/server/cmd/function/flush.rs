@@ -0,0 +1,53 @@
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Delete all the libraries.
+///
+/// The libraries are always deleted synchronously, the `ASYNC` and `SYNC`
+/// modes are accepted for compatibility.
+///
+/// ```text
+/// FUNCTION FLUSH [ASYNC | SYNC]
+/// ```
+#[derive(Debug, Default)]
+pub struct FunctionFlush {}
+
+impl FunctionFlush {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<FunctionFlush, CommandError> {
+        match parse.next_string() {
+            Ok(mode)
+                if mode.eq_ignore_ascii_case("async")
+                    || mode.eq_ignore_ascii_case("sync") => {}
+            Ok(_) => {
+                return Err(CommandError::err(
+                    "FUNCTION FLUSH only supports SYNC|ASYNC option",
+                ));
+            }
+            Err(ParseError::EndOfStream) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(FunctionFlush {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        ctx.supervisor
+            .scripts()
+            .functions()
+            .flush()
+            .map_err(CommandError::from)?;
+        ctx.lua().flush_libraries();
+
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
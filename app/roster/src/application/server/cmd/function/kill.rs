@@ -0,0 +1,34 @@
+use super::super::parse::Parse;
+use super::super::script::kill::kill_running_script;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Kill the function currently executing, as long as it didn't modify the
+/// dataset.
+///
+/// ```text
+/// FUNCTION KILL
+/// ```
+#[derive(Debug, Default)]
+pub struct FunctionKill {}
+
+impl FunctionKill {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<FunctionKill, CommandError> {
+        Ok(FunctionKill {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        kill_running_script(&ctx)?;
+
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
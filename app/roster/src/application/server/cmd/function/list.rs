@@ -0,0 +1,125 @@
+use bytes::Bytes;
+
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::scripting::Library;
+use crate::infrastructure::glob::glob_match;
+
+/// Return information about the libraries and their functions.
+///
+/// `LIBRARYNAME` only lists the libraries matching the glob-style pattern
+/// and `WITHCODE` adds the code of each library.
+///
+/// ```text
+/// FUNCTION LIST [LIBRARYNAME library-name-pattern] [WITHCODE]
+/// ```
+#[derive(Debug, Default)]
+pub struct FunctionList {
+    pattern: Option<Bytes>,
+    with_code: bool,
+}
+
+impl FunctionList {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<FunctionList, CommandError> {
+        let mut list = FunctionList::default();
+        loop {
+            let option = match parse.next_string() {
+                Ok(option) => option,
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            match &option.to_lowercase()[..] {
+                "withcode" if !list.with_code => list.with_code = true,
+                "libraryname" if list.pattern.is_none() => {
+                    list.pattern = match parse.next_bytes() {
+                        Ok(pattern) => Some(pattern),
+                        Err(ParseError::EndOfStream) => {
+                            return Err(CommandError::err(
+                                "library name argument was not given",
+                            ));
+                        }
+                        Err(err) => return Err(err.into()),
+                    };
+                }
+                _ => {
+                    return Err(CommandError::err(format!(
+                        "Unknown argument {option}"
+                    )));
+                }
+            }
+        }
+
+        Ok(list)
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let libraries = ctx
+            .supervisor
+            .scripts()
+            .functions()
+            .libraries()
+            .into_iter()
+            .filter(|library| match &self.pattern {
+                Some(pattern) => {
+                    glob_match(pattern, library.name.as_bytes(), false)
+                }
+                None => true,
+            })
+            .map(|library| describe(&library, self.with_code))
+            .collect();
+
+        dst.write_frame(&Frame::Array(libraries)).await?;
+        Ok(())
+    }
+}
+
+fn bulk(value: &'static str) -> Frame {
+    Frame::Bulk(Bytes::from_static(value.as_bytes()))
+}
+
+fn describe(library: &Library, with_code: bool) -> Frame {
+    let functions = library
+        .functions
+        .iter()
+        .map(|function| {
+            Frame::Map(vec![
+                (bulk("name"), Frame::Bulk(function.name.clone().into())),
+                (
+                    bulk("description"),
+                    function
+                        .description
+                        .clone()
+                        .map(|description| Frame::Bulk(description.into()))
+                        .unwrap_or(Frame::Null),
+                ),
+                (
+                    bulk("flags"),
+                    Frame::Set(function.flags.names().map(bulk).collect()),
+                ),
+            ])
+        })
+        .collect();
+
+    let mut description = vec![
+        (
+            bulk("library_name"),
+            Frame::Bulk(library.name.clone().into()),
+        ),
+        (bulk("engine"), bulk("LUA")),
+        (bulk("functions"), Frame::Array(functions)),
+    ];
+    if with_code {
+        description
+            .push((bulk("library_code"), Frame::Bulk(library.code.clone())));
+    }
+    Frame::Map(description)
+}
@@ -0,0 +1,71 @@
+use bytes::Bytes;
+
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Load a library of functions and return its name.
+///
+/// The code must start with a shebang giving the name of the library, like
+/// `#!lua name=mylib`, and register its functions with
+/// `redis.register_function`. A library with the same name is only replaced
+/// with `REPLACE`.
+///
+/// ```text
+/// FUNCTION LOAD [REPLACE] function-code
+/// ```
+#[derive(Debug, Default)]
+pub struct FunctionLoad {
+    code: Bytes,
+    replace: bool,
+}
+
+impl FunctionLoad {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<FunctionLoad, CommandError> {
+        let mut args = vec![parse.next_bytes()?];
+        loop {
+            match parse.next_bytes() {
+                Ok(arg) => args.push(arg),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        // The code is always the last argument.
+        let code = args.pop().unwrap_or_default();
+        let mut replace = false;
+        for arg in args {
+            if arg.eq_ignore_ascii_case(b"replace") {
+                replace = true;
+            } else {
+                return Err(CommandError::err(format!(
+                    "Unknown option given: {}",
+                    String::from_utf8_lossy(&arg)
+                )));
+            }
+        }
+
+        Ok(FunctionLoad { code, replace })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let library = ctx.lua().load_library(self.code)?;
+        let name = Bytes::from(library.name.clone());
+        ctx.supervisor
+            .scripts()
+            .functions()
+            .load(library, self.replace)
+            .map_err(CommandError::from)?;
+
+        dst.write_frame(&Frame::Bulk(name)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+use super::parse::{Parse, ParseError};
+use super::CommandExecution;
+use crate::application::server::cmd::{
+    Command, CommandError, SubcommandRegistry,
+};
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+mod delete;
+mod dump;
+mod flush;
+mod kill;
+mod list;
+mod load;
+mod restore;
+mod stats;
+
+/// Manage the libraries of functions called with `FCALL`.
+#[derive(Debug)]
+pub enum Function {
+    Help,
+    Delete(delete::FunctionDelete),
+    Dump(dump::FunctionDump),
+    Flush(flush::FunctionFlush),
+    Kill(kill::FunctionKill),
+    List(list::FunctionList),
+    Load(load::FunctionLoad),
+    Restore(restore::FunctionRestore),
+    Stats(stats::FunctionStats),
+}
+
+const HELP_TEXT: &str = r#"FUNCTION <subcommand> [<arg> [value] [opt] ...]. Subcommands are:
+LOAD [REPLACE] <FUNCTION CODE>
+    Create a new library with the given library name and code.
+DELETE <LIBRARY NAME>
+    Delete the given library.
+LIST [LIBRARYNAME PATTERN] [WITHCODE]
+    Return general information on all the libraries:
+    * Library name
+    * The engine used to run the Library
+    * Functions list
+    * Library code (if WITHCODE is given)
+    It also possible to get only function that matches a pattern using LIBRARYNAME argument.
+STATS
+    Return information about the current function running:
+    * Function name
+    * Command used to run the function
+    * Duration in MS that the function is running
+    If no function is running, return nil
+    In addition, returns a list of available engines.
+KILL
+    Kill the current running function.
+FLUSH [ASYNC|SYNC]
+    Delete all the libraries.
+    Valid modes are:
+    * ASYNC: Asynchronously flush the libraries.
+    * SYNC: Synchronously flush the libraries.
+DUMP
+    Return a serialized payload representing the current libraries, can be restored using FUNCTION RESTORE command
+RESTORE <PAYLOAD> [FLUSH|APPEND|REPLACE]
+    Restore the libraries represented by the given payload, it is possible to give a restore policy to
+    control how to handle existing libraries (default APPEND):
+    * FLUSH: delete all existing libraries.
+    * APPEND: appends the restored libraries to the existing libraries. On collision, abort.
+    * REPLACE: appends the restored libraries to the existing libraries, On collision, replace the old
+      libraries with the new libraries (notice that even on this option there is a chance of failure
+      in case of functions name collision with another library).
+HELP
+    Print this help.
+"#;
+
+impl SubcommandRegistry for Function {
+    fn from_parse(parse: &mut Parse) -> Result<Command, CommandError> {
+        let sub_cmd = match parse.next_string() {
+            Ok(elt) => elt,
+            Err(ParseError::EndOfStream) => {
+                return Err(CommandError::WrongArity("function".to_string()));
+            }
+            Err(err) => {
+                return Err(err.into());
+            }
+        };
+
+        Function::parse_subcommand(&sub_cmd, parse).map_err(|err| {
+            err.for_command(&format!("function|{}", sub_cmd.to_lowercase()))
+        })
+    }
+
+    async fn help(
+        dst: &mut WriteConnection,
+        _ctx: Context,
+    ) -> anyhow::Result<()> {
+        let response = Frame::Array(
+            HELP_TEXT
+                .split_terminator('\n')
+                .map(|x| Frame::Simple(x.into()))
+                .collect(),
+        );
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
+
+impl Function {
+    /// The name of the subcommand, in lower case.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Function::Help => "help",
+            Function::Delete(_) => "delete",
+            Function::Dump(_) => "dump",
+            Function::Flush(_) => "flush",
+            Function::Kill(_) => "kill",
+            Function::List(_) => "list",
+            Function::Load(_) => "load",
+            Function::Restore(_) => "restore",
+            Function::Stats(_) => "stats",
+        }
+    }
+
+    fn parse_subcommand(
+        sub_cmd: &str,
+        parse: &mut Parse,
+    ) -> Result<Command, CommandError> {
+        let command = match &sub_cmd.to_lowercase()[..] {
+            "delete" => Command::Function(Function::Delete(
+                delete::FunctionDelete::parse_frames(parse)?,
+            )),
+            "dump" => Command::Function(Function::Dump(
+                dump::FunctionDump::parse_frames(parse)?,
+            )),
+            "flush" => Command::Function(Function::Flush(
+                flush::FunctionFlush::parse_frames(parse)?,
+            )),
+            "kill" => Command::Function(Function::Kill(
+                kill::FunctionKill::parse_frames(parse)?,
+            )),
+            "list" => Command::Function(Function::List(
+                list::FunctionList::parse_frames(parse)?,
+            )),
+            "load" => Command::Function(Function::Load(
+                load::FunctionLoad::parse_frames(parse)?,
+            )),
+            "restore" => Command::Function(Function::Restore(
+                restore::FunctionRestore::parse_frames(parse)?,
+            )),
+            "stats" => Command::Function(Function::Stats(
+                stats::FunctionStats::parse_frames(parse)?,
+            )),
+            "help" => Command::Function(Function::Help),
+            _ => {
+                return Err(CommandError::err(format!(
+                    "unknown subcommand '{sub_cmd}'. Try FUNCTION HELP."
+                )));
+            }
+        };
+
+        // Check if there is any remaining unconsumed fields in the `Parse`
+        // value. If fields remain, this indicates an unexpected frame format
+        // and an error is returned.
+        parse.finish()?;
+
+        // The command has been successfully parsed
+        Ok(command)
+    }
+}
+
+impl CommandExecution for Function {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        match self {
+            Function::Help => Function::help(dst, ctx).await,
+            Function::Delete(cmd) => cmd.apply(dst, ctx).await,
+            Function::Dump(cmd) => cmd.apply(dst, ctx).await,
+            Function::Flush(cmd) => cmd.apply(dst, ctx).await,
+            Function::Kill(cmd) => cmd.apply(dst, ctx).await,
+            Function::List(cmd) => cmd.apply(dst, ctx).await,
+            Function::Load(cmd) => cmd.apply(dst, ctx).await,
+            Function::Restore(cmd) => cmd.apply(dst, ctx).await,
+            Function::Stats(cmd) => cmd.apply(dst, ctx).await,
+        }
+    }
+}
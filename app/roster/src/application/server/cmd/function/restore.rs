@@ -0,0 +1,68 @@
+use bytes::Bytes;
+
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::scripting::{parse_dump, RestorePolicy};
+
+/// Restore the libraries of a payload produced by `FUNCTION DUMP`.
+///
+/// With `APPEND`, the default, the restore fails when a library already
+/// exists. `REPLACE` replaces the existing libraries and `FLUSH` deletes
+/// them all first. Nothing is restored when a library can't be loaded.
+///
+/// ```text
+/// FUNCTION RESTORE serialized-value [FLUSH | APPEND | REPLACE]
+/// ```
+#[derive(Debug, Default)]
+pub struct FunctionRestore {
+    payload: Bytes,
+    policy: RestorePolicy,
+}
+
+impl FunctionRestore {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<FunctionRestore, CommandError> {
+        let payload = parse.next_bytes()?;
+        let policy = match parse.next_string() {
+            Ok(policy) => match &policy.to_lowercase()[..] {
+                "flush" => RestorePolicy::Flush,
+                "append" => RestorePolicy::Append,
+                "replace" => RestorePolicy::Replace,
+                _ => {
+                    return Err(CommandError::err(
+                        "Wrong restore policy given, value should be either \
+                         FLUSH, APPEND or REPLACE.",
+                    ));
+                }
+            },
+            Err(ParseError::EndOfStream) => RestorePolicy::default(),
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(FunctionRestore { payload, policy })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let libraries = parse_dump(&self.payload)
+            .map_err(CommandError::from)?
+            .into_iter()
+            .map(|code| ctx.lua().load_library(code))
+            .collect::<Result<Vec<_>, _>>()?;
+        ctx.supervisor
+            .scripts()
+            .functions()
+            .restore(libraries, self.policy)
+            .map_err(CommandError::from)?;
+
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
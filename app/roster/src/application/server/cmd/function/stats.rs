@@ -0,0 +1,77 @@
+use bytes::Bytes;
+
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Return information about the function currently running and the number
+/// of libraries and functions loaded.
+///
+/// ```text
+/// FUNCTION STATS
+/// ```
+#[derive(Debug, Default)]
+pub struct FunctionStats {}
+
+impl FunctionStats {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<FunctionStats, CommandError> {
+        Ok(FunctionStats {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let scripts = ctx.supervisor.scripts();
+
+        let running = match scripts.running_function() {
+            Some((function, duration)) => Frame::Map(vec![
+                (
+                    Frame::Bulk(Bytes::from_static(b"name")),
+                    Frame::Bulk(function.name.into()),
+                ),
+                (
+                    Frame::Bulk(Bytes::from_static(b"command")),
+                    Frame::Array(
+                        function.command.into_iter().map(Frame::Bulk).collect(),
+                    ),
+                ),
+                (
+                    Frame::Bulk(Bytes::from_static(b"duration_ms")),
+                    Frame::Integer(duration.as_millis() as i64),
+                ),
+            ]),
+            None => Frame::Null,
+        };
+
+        let (libraries, functions) = scripts.functions().count();
+        let lua = Frame::Map(vec![
+            (
+                Frame::Bulk(Bytes::from_static(b"libraries_count")),
+                Frame::Integer(libraries as i64),
+            ),
+            (
+                Frame::Bulk(Bytes::from_static(b"functions_count")),
+                Frame::Integer(functions as i64),
+            ),
+        ]);
+
+        let response = Frame::Map(vec![
+            (Frame::Bulk(Bytes::from_static(b"running_script")), running),
+            (
+                Frame::Bulk(Bytes::from_static(b"engines")),
+                Frame::Map(vec![(
+                    Frame::Bulk(Bytes::from_static(b"LUA")),
+                    lua,
+                )]),
+            ),
+        ]);
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@ use bytestring::ByteString;
 
 use super::parse::Parse;
 use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
 use crate::application::server::connection::WriteConnection;
 use crate::application::server::context::Context;
 use crate::application::server::frame::Frame;
@@ -17,7 +18,12 @@ pub struct Get {
 }
 
 impl Get {
-    pub(crate) fn parse_frames(parse: &mut Parse) -> anyhow::Result<Get> {
+    /// The key read.
+    pub fn key(&self) -> &ByteString {
+        &self.key
+    }
+
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Get, CommandError> {
         let key = parse.next_string()?;
         Ok(Get { key })
     }
@@ -29,6 +35,8 @@ impl CommandExecution for Get {
         dst: &mut WriteConnection,
         ctx: Context,
     ) -> anyhow::Result<()> {
+        ctx.track_key(self.key.as_bytes()).await;
+
         let response = match ctx.storage.get_async(self.key, ctx.now()).await {
             Some(val) => Frame::Bulk(val),
             None => Frame::Null,
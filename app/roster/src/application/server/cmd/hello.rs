@@ -1,11 +1,13 @@
 use bytes::Bytes;
-use indexmap::IndexMap;
+use bytestring::ByteString;
 
+use super::auth::authenticate;
+use super::parse::ParseError;
 use super::CommandExecution;
-use crate::application::server::cmd::Parse;
+use crate::application::server::cmd::{CommandError, Parse};
 use crate::application::server::connection::WriteConnection;
 use crate::application::server::context::Context;
-use crate::application::server::frame::Frame;
+use crate::application::server::frame::{Frame, Protocol};
 
 /// Switch to a different protocol, optionally authenticating and setting the
 /// connection's name, or provide a contextual client report.
@@ -14,30 +16,144 @@ use crate::application::server::frame::Frame;
 /// properties, such as: versions, modules loaded, client ID, replication role
 /// and so forth.
 ///
-/// In Roster we only reply in RESP 3.
+/// Every connection starts in RESP2, the reply is sent with the protocol
+/// negotiated by this command.
+///
+/// ```text
+/// HELLO [protover [AUTH username password] [SETNAME clientname]]
+/// ```
 #[derive(Debug, Default)]
-pub struct Hello {}
+pub struct Hello {
+    /// The protocol version asked by the client.
+    protover: Option<i64>,
+    /// The username & password to authenticate with.
+    auth: Option<(ByteString, Bytes)>,
+    /// The name to assign to the connection.
+    setname: Option<ByteString>,
+}
 
 impl Hello {
-    pub fn new() -> Hello {
-        Hello {}
+    pub fn new(
+        protover: Option<i64>,
+        auth: Option<(ByteString, Bytes)>,
+        setname: Option<ByteString>,
+    ) -> Hello {
+        Hello {
+            protover,
+            auth,
+            setname,
+        }
     }
 
-    pub(crate) fn parse_frames(parse: &mut Parse) -> anyhow::Result<Hello> {
-        parse.finish()?;
-        Ok(Hello::new())
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<Hello, CommandError> {
+        let protover = match parse.next_i64() {
+            Ok(protover) => protover,
+            Err(ParseError::EndOfStream) => return Ok(Hello::default()),
+            Err(ParseError::InvalidInteger) => {
+                return Err(CommandError::err(
+                    "Protocol version is not an integer or out of range",
+                ));
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut auth = None;
+        let mut setname = None;
+
+        loop {
+            let opt = match parse.next_string() {
+                Ok(opt) => opt,
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+
+            match &opt.to_lowercase()[..] {
+                "auth" => {
+                    let (username, password) =
+                        match (parse.next_string(), parse.next_bytes()) {
+                            (Ok(username), Ok(password)) => {
+                                (username, password)
+                            }
+                            _ => return Err(hello_syntax_error(&opt)),
+                        };
+                    auth = Some((username, password));
+                }
+                "setname" => {
+                    let Ok(name) = parse.next_string() else {
+                        return Err(hello_syntax_error(&opt));
+                    };
+                    setname = Some(name);
+                }
+                _ => return Err(hello_syntax_error(&opt)),
+            }
+        }
+
+        Ok(Hello::new(Some(protover), auth, setname))
     }
 }
 
+fn hello_syntax_error(opt: &str) -> CommandError {
+    CommandError::err(format!("Syntax error in HELLO option '{opt}'"))
+}
+
 impl CommandExecution for Hello {
     async fn apply(
         self,
         dst: &mut WriteConnection,
         ctx: Context,
     ) -> anyhow::Result<()> {
+        let protocol = match self.protover {
+            Some(version) => match Protocol::from_version(version) {
+                Some(protocol) => protocol,
+                None => {
+                    return Err(CommandError::with_code(
+                        "NOPROTO",
+                        "unsupported protocol version",
+                    )
+                    .into());
+                }
+            },
+            None => ctx.protocol(),
+        };
+
+        if let Some(name) = &self.setname {
+            if name.bytes().any(|c| !(b'!'..=b'~').contains(&c)) {
+                return Err(CommandError::err(
+                    "Client names cannot contain spaces, newlines or special \
+                     characters.",
+                )
+                .into());
+            }
+        }
+
+        match &self.auth {
+            Some((username, password)) => {
+                authenticate(&ctx, username, password).await?;
+            }
+            None if !ctx.connection.is_authenticated() => {
+                return Err(CommandError::with_code(
+                    "NOAUTH",
+                    "HELLO must be called with the client already \
+                     authenticated, otherwise the HELLO <proto> AUTH <user> \
+                     <pass> option can be used to authenticate the client and \
+                     select the RESP protocol version at the same time",
+                )
+                .into());
+            }
+            None => {}
+        }
+
+        if let Some(name) = self.setname {
+            ctx.connection.set_name(name).await;
+        }
+
+        ctx.set_protocol(protocol);
+
         let id = ctx.connection.id();
 
-        let map = IndexMap::from_iter([
+        let map = vec![
             (
                 Frame::Bulk(Bytes::from_static(b"server")),
                 Frame::Bulk(Bytes::from_static(b"roster")),
@@ -46,8 +162,14 @@ impl CommandExecution for Hello {
                 Frame::Bulk(Bytes::from_static(b"version")),
                 Frame::Bulk(Bytes::from_static(crate::VERSION.as_bytes())),
             ),
-            (Frame::Bulk(Bytes::from_static(b"proto")), Frame::Integer(3)),
-            (Frame::Bulk(Bytes::from_static(b"id")), Frame::Integer(id)),
+            (
+                Frame::Bulk(Bytes::from_static(b"proto")),
+                Frame::Integer(protocol.version()),
+            ),
+            (
+                Frame::Bulk(Bytes::from_static(b"id")),
+                Frame::Integer(id as i64),
+            ),
             (
                 Frame::Bulk(Bytes::from_static(b"mode")),
                 Frame::Bulk(Bytes::from_static(b"standalone")),
@@ -60,7 +182,7 @@ impl CommandExecution for Hello {
                 Frame::Bulk(Bytes::from_static(b"modules")),
                 Frame::Array(Vec::new()),
             ),
-        ]);
+        ];
 
         let response = Frame::Map(map);
         dst.write_frame(&response).await?;
@@ -88,7 +210,7 @@ mod tests {
 
         let mut bytes = Cursor::new(bytes.freeze());
         let frame = Frame::parse(&mut bytes)?;
-        let client_list = Command::from_frame(frame)?;
+        let client_list = Command::from_frame(frame, &Default::default())?;
         Ok(client_list)
     }
 
@@ -98,20 +220,63 @@ mod tests {
         let client_cmd = parse_cmd(entry).unwrap();
         insta::assert_debug_snapshot!(client_cmd, @r###"
         Hello(
-            Hello,
+            Hello {
+                protover: None,
+                auth: None,
+                setname: None,
+            },
         )
         "###);
     }
 
     #[test]
-    fn ensure_parsing_too_much() {
+    fn ensure_parsing_options() {
+        let entry: RespValue = resp_array![
+            "HELLO", "3", "AUTH", "default", "pass", "SETNAME", "name"
+        ];
+        let client_cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(client_cmd, @r###"
+        Hello(
+            Hello {
+                protover: Some(
+                    3,
+                ),
+                auth: Some(
+                    (
+                        "default",
+                        b"pass",
+                    ),
+                ),
+                setname: Some(
+                    "name",
+                ),
+            },
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_not_an_integer() {
         let entry: RespValue = resp_array!["HELLO", "BLBL"];
         let client_cmd = parse_cmd(entry);
         assert!(client_cmd.is_err());
         let client_cmd = client_cmd.unwrap_err();
         insta::assert_debug_snapshot!(client_cmd, @r###"
         Other(
-            "protocol error; expected end of frame, but there was more",
+            "ERR Protocol version is not an integer or out of range",
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_syntax_error() {
+        let entry: RespValue = resp_array!["HELLO", "3", "BLBL"];
+        let client_cmd = parse_cmd(entry);
+        assert!(client_cmd.is_err());
+        let client_cmd = client_cmd.unwrap_err();
+        insta::assert_debug_snapshot!(client_cmd, @r###"
+        Other(
+            "ERR Syntax error in HELLO option 'BLBL'",
         )
         "###);
     }
@@ -0,0 +1,111 @@
+use std::fmt::Write;
+
+use bytes::Bytes;
+use bytestring::ByteString;
+
+use super::parse::{Parse, ParseError};
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// The sections of `INFO`, in the order they are shown.
+const SECTIONS: &[&str] = &["clients", "stats"];
+
+/// Information and statistics about the server, in a format easy to parse
+/// by computers and easy to read by humans.
+///
+/// Without a section, or with `default`, `all` or `everything`, every section
+/// is shown.
+///
+/// ```text
+/// INFO [section [section ...]]
+/// ```
+#[derive(Debug, Default)]
+pub struct Info {
+    /// The sections requested, in lowercase.
+    sections: Vec<ByteString>,
+}
+
+impl Info {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<Info, CommandError> {
+        let mut sections = Vec::new();
+        loop {
+            match parse.next_string() {
+                Ok(section) => {
+                    sections.push(ByteString::from(section.to_lowercase()))
+                }
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(Info { sections })
+    }
+
+    fn shows(&self, section: &str) -> bool {
+        self.sections.is_empty()
+            || self.sections.iter().any(|name| {
+                matches!(&name[..], "default" | "all" | "everything")
+                    || name == section
+            })
+    }
+}
+
+impl CommandExecution for Info {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let supervisor = &ctx.supervisor;
+        let stats = supervisor.stats();
+
+        let mut info = String::new();
+        for section in SECTIONS.iter().filter(|section| self.shows(section)) {
+            if !info.is_empty() {
+                info.push_str("\r\n");
+            }
+            match *section {
+                "clients" => {
+                    info.push_str("# Clients\r\n");
+                    write!(
+                        info,
+                        "connected_clients:{}\r\n",
+                        stats.connected()
+                    )?;
+                    write!(
+                        info,
+                        "maxclients:{}\r\n",
+                        supervisor.connections_limit()
+                    )?;
+                }
+                "stats" => {
+                    info.push_str("# Stats\r\n");
+                    write!(
+                        info,
+                        "total_connections_received:{}\r\n",
+                        stats.total_received()
+                    )?;
+                    write!(
+                        info,
+                        "total_connections_closed:{}\r\n",
+                        stats.closed()
+                    )?;
+                    write!(
+                        info,
+                        "rejected_connections:{}\r\n",
+                        stats.rejected()
+                    )?;
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        dst.write_frame(&Frame::Bulk(Bytes::from(info))).await?;
+
+        Ok(())
+    }
+}
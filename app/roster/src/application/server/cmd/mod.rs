@@ -1,24 +1,87 @@
+use std::borrow::Cow;
+
+use bytes::Bytes;
+
 use self::acl::Acl;
+use self::auth::Auth;
 use self::client::Client;
+use self::discard::Discard;
+pub use self::error::CommandError;
+use self::eval::Eval;
+use self::evalsha::EvalSha;
+use self::exec::Exec;
+use self::fcall::FCall;
+use self::function::Function;
 use self::get::Get;
 use self::hello::Hello;
-use self::parse::Parse;
+use self::info::Info;
+use self::multi::Multi;
+pub use self::parse::{Parse, ParseError};
 use self::ping::Ping;
+use self::psubscribe::PSubscribe;
+use self::publish::Publish;
+use self::pubsub::PubSub;
+use self::punsubscribe::PUnsubscribe;
+use self::quit::Quit;
+use self::registry::Custom;
+pub use self::registry::{CommandRegistry, CustomCommand, KeyPositions};
+use self::script::Script;
 use self::set::Set;
+use self::shutdown::Shutdown;
+use self::spublish::SPublish;
+use self::ssubscribe::SSubscribe;
+use self::subscribe::Subscribe;
+use self::sunsubscribe::SUnsubscribe;
 use self::unknown::Unknown;
+use self::unsubscribe::Unsubscribe;
+use self::unwatch::Unwatch;
+use self::wasm::Wasm;
+use self::watch::Watch;
+use self::wcall::WCall;
 use super::connection::WriteConnection;
 use super::context::Context;
 use super::frame::Frame;
+use crate::domain::acl::{
+    AclCategory, Denied, KeyAccess, LogContext, LogReason, Request, User,
+};
 
+mod error;
 mod parse;
 
 mod acl;
+mod auth;
+mod categories;
 mod client;
+mod discard;
+mod eval;
+mod evalsha;
+mod exec;
+mod fcall;
+mod function;
 mod get;
 mod hello;
+mod info;
+mod multi;
 mod ping;
+mod psubscribe;
+mod publish;
+mod pubsub;
+mod punsubscribe;
+mod quit;
+mod registry;
+mod script;
 mod set;
+mod shutdown;
+mod spublish;
+mod ssubscribe;
+mod subscribe;
+mod sunsubscribe;
 mod unknown;
+mod unsubscribe;
+mod unwatch;
+mod wasm;
+mod watch;
+mod wcall;
 
 /// Enumeration of supported Redis commands.
 ///
@@ -26,19 +89,51 @@ mod unknown;
 #[derive(Debug)]
 pub enum Command {
     Acl(Acl),
+    Auth(Auth),
     Client(Client),
     Hello(Hello),
+    Info(Info),
     Ping(Ping),
+    Quit(Quit),
     Set(Set),
+    Shutdown(Shutdown),
     Get(Get),
+    Multi(Multi),
+    Exec(Exec),
+    Discard(Discard),
+    Watch(Watch),
+    Unwatch(Unwatch),
+    Subscribe(Subscribe),
+    Unsubscribe(Unsubscribe),
+    PSubscribe(PSubscribe),
+    PUnsubscribe(PUnsubscribe),
+    Publish(Publish),
+    PubSub(PubSub),
+    SSubscribe(SSubscribe),
+    SUnsubscribe(SUnsubscribe),
+    SPublish(SPublish),
+    Eval(Eval),
+    EvalSha(EvalSha),
+    Script(Script),
+    FCall(FCall),
+    Function(Function),
+    Wasm(Wasm),
+    WCall(WCall),
+    /// A command registered in the [CommandRegistry].
+    Custom(Custom),
     Unknown(Unknown),
 }
 
+// The connections are bound to a thread, the futures don't need to be `Send`.
+#[allow(async_fn_in_trait)]
 pub trait CommandExecution: Sized {
     /// Apply the command to the specified `Db` instance.
     ///
     /// The response is written to `dst`. This is called by the server in order
     /// to execute a received command.
+    ///
+    /// A [CommandError] returned here is sent back to the client, any other
+    /// error closes the connection.
     async fn apply(
         self,
         dst: &mut WriteConnection,
@@ -60,7 +155,7 @@ pub trait SubcommandRegistry {
     /// # Returns
     ///
     /// On success, the command value is returned, otherwise, `Err` is returned.
-    fn from_parse(parse: Parse) -> anyhow::Result<Command>;
+    fn from_parse(parse: &mut Parse) -> Result<Command, CommandError>;
 
     /// Show help for this subcommand.
     async fn help(
@@ -75,12 +170,17 @@ impl Command {
     /// Parse a command from a received frame.
     ///
     /// The `Frame` must represent a Redis command supported by `roster` and
-    /// be the array variant.
+    /// be the array variant. The commands registered in `commands` are
+    /// parsed when no built-in command has the same name.
     ///
     /// # Returns
     ///
-    /// On success, the command value is returned, otherwise, `Err` is returned.
-    pub fn from_frame(frame: Frame) -> anyhow::Result<Command> {
+    /// On success, the command value is returned, otherwise, the
+    /// [CommandError] to send back to the client is returned.
+    pub fn from_frame(
+        frame: Frame,
+        commands: &CommandRegistry,
+    ) -> Result<Command, CommandError> {
         // The frame value is decorated with `Parse`. `Parse` provides a
         // "cursor" like API which makes parsing the command easier.
         //
@@ -95,26 +195,88 @@ impl Command {
 
         // Match the command name, delegating the rest of the parsing to the
         // specific command.
-        let command = match &command_name[..] {
+        Command::parse_command(&command_name, &mut parse, commands)
+            .map_err(|err| err.for_command(&command_name))
+    }
+
+    fn parse_command(
+        command_name: &str,
+        parse: &mut Parse,
+        commands: &CommandRegistry,
+    ) -> Result<Command, CommandError> {
+        let command = match command_name {
             "acl" => {
                 return Acl::from_parse(parse);
             }
             "client" => {
                 return Client::from_parse(parse);
             }
-            "ping" => Command::Ping(Ping::parse_frames(&mut parse)?),
-            "hello" => Command::Hello(Hello::parse_frames(&mut parse)?),
-            "set" => Command::Set(Set::parse_frames(&mut parse)?),
-            "get" => Command::Get(Get::parse_frames(&mut parse)?),
-            _ => {
-                // The command is not recognized and an Unknown command is
-                // returned.
-                //
-                // `return` is called here to skip the `finish()` call below. As
-                // the command is not recognized, there is most likely
-                // unconsumed fields remaining in the `Parse` instance.
-                return Ok(Command::Unknown(Unknown::new(command_name)));
+            "pubsub" => {
+                return PubSub::from_parse(parse);
+            }
+            "script" => {
+                return Script::from_parse(parse);
+            }
+            "function" => {
+                return Function::from_parse(parse);
+            }
+            "wasm" => {
+                return Wasm::from_parse(parse);
+            }
+            "auth" => Command::Auth(Auth::parse_frames(parse)?),
+            "ping" => Command::Ping(Ping::parse_frames(parse)?),
+            "info" => Command::Info(Info::parse_frames(parse)?),
+            "quit" => Command::Quit(Quit::parse_frames(parse)?),
+            "hello" => Command::Hello(Hello::parse_frames(parse)?),
+            "set" => Command::Set(Set::parse_frames(parse)?),
+            "shutdown" => Command::Shutdown(Shutdown::parse_frames(parse)?),
+            "get" => Command::Get(Get::parse_frames(parse)?),
+            "multi" => Command::Multi(Multi::parse_frames(parse)?),
+            "exec" => Command::Exec(Exec::parse_frames(parse)?),
+            "discard" => Command::Discard(Discard::parse_frames(parse)?),
+            "watch" => Command::Watch(Watch::parse_frames(parse)?),
+            "unwatch" => Command::Unwatch(Unwatch::parse_frames(parse)?),
+            "subscribe" => Command::Subscribe(Subscribe::parse_frames(parse)?),
+            "unsubscribe" => {
+                Command::Unsubscribe(Unsubscribe::parse_frames(parse)?)
+            }
+            "psubscribe" => {
+                Command::PSubscribe(PSubscribe::parse_frames(parse)?)
+            }
+            "punsubscribe" => {
+                Command::PUnsubscribe(PUnsubscribe::parse_frames(parse)?)
             }
+            "publish" => Command::Publish(Publish::parse_frames(parse)?),
+            "ssubscribe" => {
+                Command::SSubscribe(SSubscribe::parse_frames(parse)?)
+            }
+            "sunsubscribe" => {
+                Command::SUnsubscribe(SUnsubscribe::parse_frames(parse)?)
+            }
+            "spublish" => Command::SPublish(SPublish::parse_frames(parse)?),
+            "eval" => Command::Eval(Eval::parse_frames(parse, false)?),
+            "eval_ro" => Command::Eval(Eval::parse_frames(parse, true)?),
+            "evalsha" => Command::EvalSha(EvalSha::parse_frames(parse, false)?),
+            "evalsha_ro" => {
+                Command::EvalSha(EvalSha::parse_frames(parse, true)?)
+            }
+            "fcall" => Command::FCall(FCall::parse_frames(parse, false)?),
+            "fcall_ro" => Command::FCall(FCall::parse_frames(parse, true)?),
+            "wcall" => Command::WCall(WCall::parse_frames(parse, false)?),
+            "wcall_ro" => Command::WCall(WCall::parse_frames(parse, true)?),
+            _ => match commands.parse(command_name, parse) {
+                Some(cmd) => Command::Custom(cmd?),
+                None => {
+                    // The command is not recognized and an Unknown command is
+                    // returned.
+                    //
+                    // `return` is called here to skip the `finish()` call
+                    // below. As the command is not recognized, there is most
+                    // likely unconsumed fields remaining in the `Parse`
+                    // instance.
+                    return Ok(Command::Unknown(Unknown::new(command_name)));
+                }
+            },
         };
 
         // Check if there is any remaining unconsumed fields in the `Parse`
@@ -125,6 +287,377 @@ impl Command {
         // The command has been successfully parsed
         Ok(command)
     }
+
+    /// Apply the command, a [CommandError] is sent back to the client while
+    /// any other error is returned.
+    pub async fn execute(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        match self.apply(dst, ctx).await {
+            Ok(()) => Ok(()),
+            Err(err) => match err.downcast::<CommandError>() {
+                Ok(err) => Ok(dst.write_frame(&err.into_frame()).await?),
+                Err(err) => Err(err),
+            },
+        }
+    }
+
+    /// Should this command be queued when the connection is inside a
+    /// transaction.
+    pub fn is_queued_in_transaction(&self) -> bool {
+        !matches!(
+            self,
+            Command::Multi(_)
+                | Command::Exec(_)
+                | Command::Discard(_)
+                | Command::Watch(_)
+                | Command::Quit(_)
+                | Command::Shutdown(_)
+        )
+    }
+
+    /// Can this command be used by a connection which isn't authenticated,
+    /// these commands are not checked against the ACL rules either.
+    pub fn is_allowed_before_auth(&self) -> bool {
+        matches!(
+            self,
+            Command::Auth(_) | Command::Hello(_) | Command::Quit(_)
+        )
+    }
+
+    /// Is this command `QUIT`, which closes the connection once applied.
+    pub fn is_quit(&self) -> bool {
+        matches!(self, Command::Quit(_))
+    }
+
+    /// Is this command `CLIENT CACHING`, which applies to the next command.
+    pub fn is_client_caching(&self) -> bool {
+        matches!(self, Command::Client(client::Client::Caching(_)))
+    }
+
+    /// Can this command be called by a script with `redis.call`.
+    pub fn is_allowed_in_script(&self) -> bool {
+        !matches!(
+            self,
+            Command::Auth(_)
+                | Command::Client(_)
+                | Command::Hello(_)
+                | Command::Quit(_)
+                | Command::Multi(_)
+                | Command::Exec(_)
+                | Command::Discard(_)
+                | Command::Watch(_)
+                | Command::Unwatch(_)
+                | Command::Subscribe(_)
+                | Command::Unsubscribe(_)
+                | Command::PSubscribe(_)
+                | Command::PUnsubscribe(_)
+                | Command::SSubscribe(_)
+                | Command::SUnsubscribe(_)
+                | Command::Eval(_)
+                | Command::EvalSha(_)
+                | Command::Script(_)
+                | Command::FCall(_)
+                | Command::Function(_)
+                | Command::Wasm(_)
+                | Command::WCall(_)
+                | Command::Shutdown(_)
+        )
+    }
+
+    /// Does this command modify the dataset, like the commands of the `write`
+    /// category.
+    pub fn is_write(&self) -> bool {
+        self.categories().contains(&AclCategory::Write)
+    }
+
+    /// Can this command modify the storage or be replicated, these commands
+    /// are suspended by `CLIENT PAUSE WRITE`.
+    ///
+    /// `EXEC` is always considered as writing, whatever the queued commands.
+    pub fn may_write(&self) -> bool {
+        match self {
+            Command::Eval(cmd) => !cmd.is_read_only(),
+            Command::EvalSha(cmd) => !cmd.is_read_only(),
+            Command::FCall(cmd) => !cmd.is_read_only(),
+            Command::WCall(cmd) => !cmd.is_read_only(),
+            Command::Exec(_) | Command::Publish(_) | Command::SPublish(_) => {
+                true
+            }
+            cmd => cmd.is_write(),
+        }
+    }
+
+    /// Can this command be applied while the clients are paused by
+    /// `CLIENT PAUSE`.
+    pub fn is_allowed_when_paused(&self) -> bool {
+        matches!(self, Command::Client(client::Client::Unpause(_)))
+    }
+
+    /// Can this command be applied while a script runs for longer than the
+    /// busy threshold.
+    pub fn is_allowed_when_busy(&self) -> bool {
+        matches!(
+            self,
+            Command::Script(script::Script::Kill(_))
+                | Command::Function(
+                    function::Function::Kill(_) | function::Function::Stats(_)
+                )
+                | Command::Shutdown(_)
+        )
+    }
+
+    /// Does this command take the access it needs on the storage itself,
+    /// instead of the shared access taken for every command.
+    ///
+    /// `EXEC` and the scripts take the exclusive access while `SCRIPT KILL`,
+    /// `FUNCTION KILL` and `SHUTDOWN` must not wait for the running script.
+    pub fn manages_storage_access(&self) -> bool {
+        matches!(
+            self,
+            Command::Exec(_)
+                | Command::Eval(_)
+                | Command::EvalSha(_)
+                | Command::FCall(_)
+                | Command::WCall(_)
+                | Command::Script(script::Script::Kill(_))
+                | Command::Function(function::Function::Kill(_))
+                | Command::Shutdown(_)
+        )
+    }
+
+    /// Can this command be used by a RESP2 connection subscribed to a
+    /// channel.
+    pub fn is_allowed_in_subscribed_mode(&self) -> bool {
+        matches!(
+            self,
+            Command::Subscribe(_)
+                | Command::Unsubscribe(_)
+                | Command::PSubscribe(_)
+                | Command::PUnsubscribe(_)
+                | Command::SSubscribe(_)
+                | Command::SUnsubscribe(_)
+                | Command::Ping(_)
+                | Command::Quit(_)
+        )
+    }
+
+    /// The name of the command, as sent by the client.
+    pub fn name(&self) -> &str {
+        match self {
+            Command::Acl(_) => "acl",
+            Command::Auth(_) => "auth",
+            Command::Client(_) => "client",
+            Command::Hello(_) => "hello",
+            Command::Ping(_) => "ping",
+            Command::Info(_) => "info",
+            Command::Quit(_) => "quit",
+            Command::Set(_) => "set",
+            Command::Shutdown(_) => "shutdown",
+            Command::Get(_) => "get",
+            Command::Multi(_) => "multi",
+            Command::Exec(_) => "exec",
+            Command::Discard(_) => "discard",
+            Command::Watch(_) => "watch",
+            Command::Unwatch(_) => "unwatch",
+            Command::Subscribe(_) => "subscribe",
+            Command::Unsubscribe(_) => "unsubscribe",
+            Command::PSubscribe(_) => "psubscribe",
+            Command::PUnsubscribe(_) => "punsubscribe",
+            Command::Publish(_) => "publish",
+            Command::PubSub(_) => "pubsub",
+            Command::SSubscribe(_) => "ssubscribe",
+            Command::SUnsubscribe(_) => "sunsubscribe",
+            Command::SPublish(_) => "spublish",
+            Command::Eval(cmd) if cmd.is_read_only() => "eval_ro",
+            Command::Eval(_) => "eval",
+            Command::EvalSha(cmd) if cmd.is_read_only() => "evalsha_ro",
+            Command::EvalSha(_) => "evalsha",
+            Command::Script(_) => "script",
+            Command::FCall(cmd) if cmd.is_read_only() => "fcall_ro",
+            Command::FCall(_) => "fcall",
+            Command::Function(_) => "function",
+            Command::Wasm(_) => "wasm",
+            Command::WCall(cmd) if cmd.is_read_only() => "wcall_ro",
+            Command::WCall(_) => "wcall",
+            Command::Custom(cmd) => cmd.name(),
+            Command::Unknown(cmd) => cmd.name(),
+        }
+    }
+}
+
+impl Command {
+    /// The name checked by the ACL rules, `command|subcommand` for a
+    /// subcommand.
+    pub fn acl_name(&self) -> Cow<'_, str> {
+        let sub = match self {
+            Command::Acl(cmd) => cmd.name(),
+            Command::Client(cmd) => cmd.name(),
+            Command::PubSub(cmd) => cmd.name(),
+            Command::Script(cmd) => cmd.name(),
+            Command::Function(cmd) => cmd.name(),
+            Command::Wasm(cmd) => cmd.name(),
+            _ => return Cow::Borrowed(self.name()),
+        };
+        Cow::Owned(format!("{}|{sub}", self.name()))
+    }
+
+    /// The ACL categories of the command.
+    pub fn categories(&self) -> &[AclCategory] {
+        match self {
+            Command::Custom(cmd) => cmd.categories(),
+            cmd => categories::categories(&cmd.acl_name()).unwrap_or_default(),
+        }
+    }
+
+    /// The keys accessed by the command and how they are accessed.
+    fn acl_keys(&self) -> Vec<(&[u8], KeyAccess)> {
+        // The keys given to a script can be read and written, unless it's
+        // called with a read only variant.
+        fn script_keys(
+            keys: &[Bytes],
+            read_only: bool,
+        ) -> Vec<(&[u8], KeyAccess)> {
+            let access = match read_only {
+                true => KeyAccess::Read,
+                false => KeyAccess::ReadWrite,
+            };
+            keys.iter().map(|key| (&key[..], access)).collect()
+        }
+
+        match self {
+            Command::Set(cmd) => {
+                vec![(&cmd.key().as_bytes()[..], KeyAccess::Write)]
+            }
+            Command::Get(cmd) => {
+                vec![(&cmd.key().as_bytes()[..], KeyAccess::Read)]
+            }
+            Command::Watch(cmd) => cmd
+                .keys()
+                .iter()
+                .map(|key| (&key.as_bytes()[..], KeyAccess::Read))
+                .collect(),
+            Command::Eval(cmd) => script_keys(cmd.keys(), cmd.is_read_only()),
+            Command::EvalSha(cmd) => {
+                script_keys(cmd.keys(), cmd.is_read_only())
+            }
+            Command::FCall(cmd) => script_keys(cmd.keys(), cmd.is_read_only()),
+            Command::WCall(cmd) => script_keys(cmd.keys(), cmd.is_read_only()),
+            Command::Custom(cmd) => script_keys(cmd.keys(), !cmd.is_write()),
+            _ => Vec::new(),
+        }
+    }
+
+    /// The Pub/Sub channels accessed by the command, and if each one is a
+    /// pattern.
+    fn acl_channels(&self) -> Vec<(&[u8], bool)> {
+        match self {
+            Command::Subscribe(cmd) => {
+                cmd.channels().iter().map(|c| (&c[..], false)).collect()
+            }
+            Command::SSubscribe(cmd) => {
+                cmd.channels().iter().map(|c| (&c[..], false)).collect()
+            }
+            Command::PSubscribe(cmd) => {
+                cmd.patterns().iter().map(|p| (&p[..], true)).collect()
+            }
+            Command::Publish(cmd) => vec![(&cmd.channel()[..], false)],
+            Command::SPublish(cmd) => vec![(&cmd.channel()[..], false)],
+            _ => Vec::new(),
+        }
+    }
+
+    /// Check if `user` can run this command, on its keys and channels.
+    pub fn check_acl(&self, user: &User) -> Result<(), Denied> {
+        if user.is_unrestricted() {
+            return Ok(());
+        }
+
+        let name = self.acl_name();
+        let keys = self.acl_keys();
+        let channels = self.acl_channels();
+        user.check(&Request {
+            name: &name,
+            categories: self.categories(),
+            keys: &keys,
+            channels: &channels,
+        })
+    }
+
+    /// Check if the user of the connection can run this command, the error
+    /// returned is sent back to the client.
+    ///
+    /// A denied command is added to the ACL log, with the `context` it was
+    /// called from.
+    pub async fn check_permissions(
+        &self,
+        ctx: &Context,
+        context: LogContext,
+    ) -> Result<(), CommandError> {
+        if !ctx.connection.is_authenticated() {
+            return match self.is_allowed_before_auth() {
+                true => Ok(()),
+                false => Err(CommandError::with_code(
+                    "NOAUTH",
+                    "Authentication required.",
+                )),
+            };
+        }
+
+        if matches!(self, Command::Unknown(_)) || self.is_allowed_before_auth()
+        {
+            return Ok(());
+        }
+
+        let username = ctx.connection.user().await;
+        // A connection keeps the user it authenticated with even when the
+        // user is deleted, it can't run anything anymore.
+        let denied = match ctx.supervisor.users().get(&username) {
+            Some(user) => match self.check_acl(&user) {
+                Ok(()) => return Ok(()),
+                Err(denied) => denied,
+            },
+            None => Denied::Command,
+        };
+
+        let (reason, object) = match &denied {
+            Denied::Command => (LogReason::Command, self.acl_name().into()),
+            Denied::Key(key) => {
+                (LogReason::Key, String::from_utf8_lossy(key).into())
+            }
+            Denied::Channel(channel) => {
+                (LogReason::Channel, String::from_utf8_lossy(channel).into())
+            }
+        };
+        ctx.supervisor.acl_log().push(
+            reason,
+            context,
+            object,
+            username.to_string(),
+            ctx.connection.format_conn().await.to_string(),
+        );
+
+        Err(match denied {
+            Denied::Command => CommandError::with_code(
+                "NOPERM",
+                format!(
+                    "User {username} has no permissions to run the '{}' \
+                     command",
+                    self.acl_name()
+                ),
+            ),
+            Denied::Key(_) => CommandError::with_code(
+                "NOPERM",
+                "No permissions to access a key",
+            ),
+            Denied::Channel(_) => CommandError::with_code(
+                "NOPERM",
+                "No permissions to access a channel",
+            ),
+        })
+    }
 }
 
 impl CommandExecution for Command {
@@ -137,12 +670,38 @@ impl CommandExecution for Command {
 
         match self {
             Acl(cmd) => cmd.apply(dst, ctx).await,
+            Auth(cmd) => cmd.apply(dst, ctx).await,
             Ping(cmd) => cmd.apply(dst, ctx).await,
+            Info(cmd) => cmd.apply(dst, ctx).await,
+            Quit(cmd) => cmd.apply(dst, ctx).await,
             Unknown(cmd) => cmd.apply(dst, ctx).await,
             Client(cmd) => cmd.apply(dst, ctx).await,
             Hello(cmd) => cmd.apply(dst, ctx).await,
             Set(cmd) => cmd.apply(dst, ctx).await,
+            Shutdown(cmd) => cmd.apply(dst, ctx).await,
             Get(cmd) => cmd.apply(dst, ctx).await,
+            Multi(cmd) => cmd.apply(dst, ctx).await,
+            Exec(cmd) => cmd.apply(dst, ctx).await,
+            Discard(cmd) => cmd.apply(dst, ctx).await,
+            Watch(cmd) => cmd.apply(dst, ctx).await,
+            Unwatch(cmd) => cmd.apply(dst, ctx).await,
+            Subscribe(cmd) => cmd.apply(dst, ctx).await,
+            Unsubscribe(cmd) => cmd.apply(dst, ctx).await,
+            PSubscribe(cmd) => cmd.apply(dst, ctx).await,
+            PUnsubscribe(cmd) => cmd.apply(dst, ctx).await,
+            Publish(cmd) => cmd.apply(dst, ctx).await,
+            PubSub(cmd) => cmd.apply(dst, ctx).await,
+            SSubscribe(cmd) => cmd.apply(dst, ctx).await,
+            SUnsubscribe(cmd) => cmd.apply(dst, ctx).await,
+            SPublish(cmd) => cmd.apply(dst, ctx).await,
+            Eval(cmd) => cmd.apply(dst, ctx).await,
+            EvalSha(cmd) => cmd.apply(dst, ctx).await,
+            Script(cmd) => cmd.apply(dst, ctx).await,
+            FCall(cmd) => cmd.apply(dst, ctx).await,
+            Function(cmd) => cmd.apply(dst, ctx).await,
+            Wasm(cmd) => cmd.apply(dst, ctx).await,
+            WCall(cmd) => cmd.apply(dst, ctx).await,
+            Custom(cmd) => cmd.apply(dst, ctx).await,
         }
     }
 
@@ -151,12 +710,38 @@ impl CommandExecution for Command {
 
         match self {
             Acl(cmd) => cmd.hash_key(),
+            Auth(cmd) => cmd.hash_key(),
             Ping(cmd) => cmd.hash_key(),
+            Info(cmd) => cmd.hash_key(),
+            Quit(cmd) => cmd.hash_key(),
             Unknown(cmd) => cmd.hash_key(),
             Client(cmd) => cmd.hash_key(),
             Hello(cmd) => cmd.hash_key(),
             Set(cmd) => cmd.hash_key(),
+            Shutdown(cmd) => cmd.hash_key(),
             Get(cmd) => cmd.hash_key(),
+            Multi(cmd) => cmd.hash_key(),
+            Exec(cmd) => cmd.hash_key(),
+            Discard(cmd) => cmd.hash_key(),
+            Watch(cmd) => cmd.hash_key(),
+            Unwatch(cmd) => cmd.hash_key(),
+            Subscribe(cmd) => cmd.hash_key(),
+            Unsubscribe(cmd) => cmd.hash_key(),
+            PSubscribe(cmd) => cmd.hash_key(),
+            PUnsubscribe(cmd) => cmd.hash_key(),
+            Publish(cmd) => cmd.hash_key(),
+            PubSub(cmd) => cmd.hash_key(),
+            SSubscribe(cmd) => cmd.hash_key(),
+            SUnsubscribe(cmd) => cmd.hash_key(),
+            SPublish(cmd) => cmd.hash_key(),
+            Eval(cmd) => cmd.hash_key(),
+            EvalSha(cmd) => cmd.hash_key(),
+            Script(cmd) => cmd.hash_key(),
+            FCall(cmd) => cmd.hash_key(),
+            Function(cmd) => cmd.hash_key(),
+            Wasm(cmd) => cmd.hash_key(),
+            WCall(cmd) => cmd.hash_key(),
+            Custom(cmd) => cmd.hash_key(),
         }
     }
 }
@@ -0,0 +1,92 @@
+use bytestring::ByteString;
+
+use super::parse::Parse;
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Marks the start of a transaction block. Subsequent commands will be queued
+/// for atomic execution using EXEC.
+///
+/// ```text
+/// MULTI
+/// ```
+#[derive(Debug, Default)]
+pub struct Multi {}
+
+impl Multi {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<Multi, CommandError> {
+        Ok(Multi {})
+    }
+}
+
+impl CommandExecution for Multi {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        if !ctx.transaction().start() {
+            return Err(
+                CommandError::err("MULTI calls can not be nested").into()
+            );
+        }
+
+        let response = Frame::Simple(ByteString::from_static("OK"));
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let cmd = Command::from_frame(frame, &Default::default())?;
+        Ok(cmd)
+    }
+
+    #[test]
+    fn ensure_parsing() {
+        let entry: RespValue = resp_array!["MULTI"];
+        let cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Multi(
+            Multi,
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_too_much() {
+        let entry: RespValue = resp_array!["MULTI", "BLBL"];
+        let cmd = parse_cmd(entry);
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Err(
+            WrongArity(
+                "multi",
+            ),
+        )
+        "###);
+    }
+}
@@ -12,21 +12,29 @@ use crate::application::server::frame::Frame;
 /// cursor-like API. Each command struct includes a `parse_frame` method that
 /// uses a `Parse` to extract its fields.
 #[derive(Debug)]
-pub(crate) struct Parse {
+pub struct Parse {
     parts: vec::IntoIter<Frame>,
 }
 
 /// Error encountered while parsing a frame.
 ///
-/// Only `EndOfStream` errors are handled at runtime. All other errors result in
-/// the connection being terminated.
+/// Those errors are converted into a [super::CommandError] which is sent back
+/// to the client.
 #[derive(thiserror::Error, Debug)]
-pub(crate) enum ParseError {
+pub enum ParseError {
     /// Attempting to extract a value failed due to the frame being fully
     /// consumed.
     #[error("protocol error; unexpected end of stream")]
     EndOfStream,
 
+    /// The frame still had entries when it was expected to be fully consumed.
+    #[error("protocol error; expected end of frame, but there was more")]
+    Remaining,
+
+    /// The entry couldn't be represented as a signed 64 bits integer.
+    #[error("ERR value is not an integer or out of range")]
+    InvalidInteger,
+
     /// All other errors
     #[error("{0}")]
     Other(#[from] anyhow::Error),
@@ -36,7 +44,7 @@ impl Parse {
     /// Create a new `Parse` to parse the contents of `frame`.
     ///
     /// Returns `Err` if `frame` is not an array frame.
-    pub(crate) fn new(frame: Frame) -> Result<Parse, ParseError> {
+    pub fn new(frame: Frame) -> Result<Parse, ParseError> {
         let array = match frame {
             Frame::Array(array) => array,
             frame => {
@@ -63,7 +71,7 @@ impl Parse {
     ///
     /// If the next entry cannot be represented as a String, then an error is
     /// returned.
-    pub(crate) fn next_string(&mut self) -> Result<ByteString, ParseError> {
+    pub fn next_string(&mut self) -> Result<ByteString, ParseError> {
         match self.next()? {
             // Both `Simple` and `Bulk` representation may be strings. Strings
             // are parsed to UTF-8.
@@ -88,7 +96,7 @@ impl Parse {
     ///
     /// If the next entry cannot be represented as raw bytes, an error is
     /// returned.
-    pub(crate) fn next_bytes(&mut self) -> Result<Bytes, ParseError> {
+    pub fn next_bytes(&mut self) -> Result<Bytes, ParseError> {
         match self.next()? {
             // Both `Simple` and `Bulk` representation may be raw bytes.
             //
@@ -111,14 +119,14 @@ impl Parse {
     ///
     /// If the next entry cannot be represented as an integer, then an error is
     /// returned.
-    pub(crate) fn next_int(&mut self) -> Result<u64, ParseError> {
+    pub fn next_int(&mut self) -> Result<u64, ParseError> {
         use atoi_simd::parse;
 
         const MSG: &str = "protocol error; invalid number";
 
         match self.next()? {
             // An integer frame type is already stored as an integer.
-            Frame::Integer(v) => Ok(v),
+            Frame::Integer(v) => u64::try_from(v).map_err(|_| MSG.into()),
             // Simple and bulk frames must be parsed as integers. If the parsing
             // fails, an error is returned.
             Frame::Simple(data) => {
@@ -133,14 +141,74 @@ impl Parse {
         }
     }
 
+    /// Return the next entry as a signed integer.
+    ///
+    /// `Simple` and `Bulk` frames are parsed with the same rules as Redis: no
+    /// leading `+`, no leading zeros, no spaces and the value must fit in a
+    /// signed 64 bits integer.
+    ///
+    /// If the next entry cannot be represented as an integer,
+    /// [ParseError::InvalidInteger] is returned.
+    pub fn next_i64(&mut self) -> Result<i64, ParseError> {
+        match self.next()? {
+            Frame::Integer(v) => Ok(v),
+            Frame::Simple(data) => {
+                string_to_i64(data.as_bytes()).ok_or(ParseError::InvalidInteger)
+            }
+            Frame::Bulk(data) => {
+                string_to_i64(&data).ok_or(ParseError::InvalidInteger)
+            }
+            _ => Err(ParseError::InvalidInteger),
+        }
+    }
+
+    /// The entries not consumed yet.
+    pub fn remaining(&self) -> &[Frame] {
+        self.parts.as_slice()
+    }
+
     /// Ensure there are no more entries in the array
-    pub(crate) fn finish(&mut self) -> Result<(), ParseError> {
+    pub fn finish(&mut self) -> Result<(), ParseError> {
         if self.parts.next().is_none() {
             Ok(())
         } else {
-            Err("protocol error; expected end of frame, but there was more"
-                .into())
+            Err(ParseError::Remaining)
+        }
+    }
+}
+
+/// Convert a string into a signed integer following the `string2ll` rules of
+/// Redis.
+pub(crate) fn string_to_i64(bytes: &[u8]) -> Option<i64> {
+    let (negative, digits) = match bytes {
+        [] => return None,
+        [b'0'] => return Some(0),
+        [b'-', rest @ ..] => (true, rest),
+        _ => (false, bytes),
+    };
+
+    // The first digit should be 1-9, otherwise the string should just be 0.
+    match digits.first() {
+        Some(b'1'..=b'9') => {}
+        _ => return None,
+    }
+
+    let mut value: i64 = 0;
+    for digit in digits {
+        if !digit.is_ascii_digit() {
+            return None;
         }
+
+        // We accumulate on the negative side so `i64::MIN` can be represented.
+        value = value
+            .checked_mul(10)?
+            .checked_sub(i64::from(digit - b'0'))?;
+    }
+
+    if negative {
+        Some(value)
+    } else {
+        value.checked_neg()
     }
 }
 
@@ -155,3 +223,50 @@ impl From<&str> for ParseError {
         src.to_string().into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::{string_to_i64, Parse, ParseError};
+    use crate::application::server::frame::Frame;
+
+    #[test]
+    fn parse_i64_like_redis() {
+        assert_eq!(string_to_i64(b"0"), Some(0));
+        assert_eq!(string_to_i64(b"-1"), Some(-1));
+        assert_eq!(string_to_i64(b"42"), Some(42));
+        assert_eq!(string_to_i64(b"9223372036854775807"), Some(i64::MAX));
+        assert_eq!(string_to_i64(b"-9223372036854775808"), Some(i64::MIN));
+
+        assert_eq!(string_to_i64(b""), None);
+        assert_eq!(string_to_i64(b"-"), None);
+        assert_eq!(string_to_i64(b"-0"), None);
+        assert_eq!(string_to_i64(b"01"), None);
+        assert_eq!(string_to_i64(b"+1"), None);
+        assert_eq!(string_to_i64(b" 1"), None);
+        assert_eq!(string_to_i64(b"1a"), None);
+        assert_eq!(string_to_i64(b"9223372036854775808"), None);
+        assert_eq!(string_to_i64(b"-9223372036854775809"), None);
+    }
+
+    #[test]
+    fn parse_next_numbers() {
+        let mut parse = Parse::new(Frame::Array(vec![
+            Frame::Bulk(Bytes::from_static(b"-1")),
+            Frame::Integer(-2),
+            Frame::Bulk(Bytes::from_static(b"nope")),
+        ]))
+        .unwrap();
+
+        assert_eq!(parse.next_i64().unwrap(), -1);
+        assert_eq!(parse.next_i64().unwrap(), -2);
+
+        let err = parse.next_i64().unwrap_err();
+        assert!(matches!(err, ParseError::InvalidInteger));
+        assert_eq!(
+            err.to_string(),
+            "ERR value is not an integer or out of range"
+        );
+    }
+}
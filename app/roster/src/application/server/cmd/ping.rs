@@ -3,9 +3,10 @@ use bytestring::ByteString;
 
 use super::parse::{Parse, ParseError};
 use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
 use crate::application::server::connection::WriteConnection;
 use crate::application::server::context::Context;
-use crate::application::server::frame::Frame;
+use crate::application::server::frame::{Frame, Protocol};
 
 /// Returns PONG if no argument is provided, otherwise
 /// return a copy of the argument as a bulk.
@@ -44,7 +45,9 @@ impl Ping {
     /// ```text
     /// PING [message]
     /// ```
-    pub(crate) fn parse_frames(parse: &mut Parse) -> anyhow::Result<Ping> {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<Ping, CommandError> {
         match parse.next_bytes() {
             Ok(msg) => Ok(Ping::new(Some(msg))),
             Err(ParseError::EndOfStream) => Ok(Ping::default()),
@@ -57,11 +60,20 @@ impl CommandExecution for Ping {
     async fn apply(
         self,
         dst: &mut WriteConnection,
-        _ctx: Context,
+        ctx: Context,
     ) -> anyhow::Result<()> {
-        let response = match self.msg {
-            None => Frame::Simple(ByteString::from_static("PONG")),
-            Some(msg) => Frame::Bulk(msg),
+        // A RESP2 subscribed connection can't tell a reply from a message, so
+        // the reply looks like a message.
+        let subscribed =
+            ctx.protocol() == Protocol::Resp2 && ctx.connection.is_pubsub();
+
+        let response = match (self.msg, subscribed) {
+            (msg, true) => Frame::Array(vec![
+                Frame::Bulk(Bytes::from_static(b"pong")),
+                Frame::Bulk(msg.unwrap_or_default()),
+            ]),
+            (None, false) => Frame::Simple(ByteString::from_static("PONG")),
+            (Some(msg), false) => Frame::Bulk(msg),
         };
 
         // Write the response back to the client
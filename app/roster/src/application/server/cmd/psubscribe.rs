@@ -0,0 +1,55 @@
+use bytes::Bytes;
+
+use super::parse::Parse;
+use super::subscribe::{parse_channels, subscribe};
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::subscription::SubscriptionKind;
+
+/// Subscribes the client to the given patterns.
+///
+/// Supported glob-style patterns:
+///
+/// - `h?llo` subscribes to hello, hallo and hxllo
+/// - `h*llo` subscribes to hllo and heeeello
+/// - `h[ae]llo` subscribes to hello and hallo, but not hillo
+///
+/// ```text
+/// PSUBSCRIBE pattern [pattern ...]
+/// ```
+#[derive(Debug, Default)]
+pub struct PSubscribe {
+    patterns: Vec<Bytes>,
+}
+
+impl PSubscribe {
+    pub fn patterns(&self) -> &[Bytes] {
+        &self.patterns
+    }
+
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<PSubscribe, CommandError> {
+        let patterns = parse_channels(parse)?;
+        Ok(PSubscribe { patterns })
+    }
+}
+
+impl CommandExecution for PSubscribe {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        subscribe(
+            SubscriptionKind::Pattern,
+            self.patterns,
+            b"psubscribe",
+            dst,
+            ctx,
+        )
+        .await
+    }
+}
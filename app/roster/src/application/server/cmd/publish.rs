@@ -0,0 +1,106 @@
+use bytes::Bytes;
+
+use super::parse::Parse;
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Posts a message to the given channel.
+///
+/// Return the number of clients that received the message, the subscribers
+/// of matching patterns are counted too.
+///
+/// ```text
+/// PUBLISH channel message
+/// ```
+#[derive(Debug, Default)]
+pub struct Publish {
+    channel: Bytes,
+    message: Bytes,
+}
+
+impl Publish {
+    pub fn channel(&self) -> &Bytes {
+        &self.channel
+    }
+
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<Publish, CommandError> {
+        let channel = parse.next_bytes()?;
+        let message = parse.next_bytes()?;
+        Ok(Publish { channel, message })
+    }
+}
+
+impl CommandExecution for Publish {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let receivers = ctx
+            .supervisor
+            .pubsub()
+            .publish(self.channel, self.message)
+            .await;
+
+        let response = Frame::Integer(receivers as i64);
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let cmd = Command::from_frame(frame, &Default::default())?;
+        Ok(cmd)
+    }
+
+    #[test]
+    fn ensure_parsing() {
+        let entry: RespValue = resp_array!["PUBLISH", "news", "hello"];
+        let cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Publish(
+            Publish {
+                channel: b"news",
+                message: b"hello",
+            },
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_missing_message() {
+        let entry: RespValue = resp_array!["PUBLISH", "news"];
+        let cmd = parse_cmd(entry);
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Err(
+            WrongArity(
+                "publish",
+            ),
+        )
+        "###);
+    }
+}
@@ -0,0 +1,110 @@
+use bytes::Bytes;
+
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Lists the currently active channels.
+///
+/// An active channel is a Pub/Sub channel with one or more subscribers (not
+/// including clients subscribed to patterns).
+///
+/// If no pattern is specified, all the channels are listed, otherwise only
+/// channels matching the specified glob-style pattern are listed.
+///
+/// ```text
+/// PUBSUB CHANNELS [pattern]
+/// ```
+#[derive(Debug, Default)]
+pub struct PubSubChannels {
+    pattern: Option<Bytes>,
+}
+
+impl PubSubChannels {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<PubSubChannels, CommandError> {
+        let pattern = match parse.next_bytes() {
+            Ok(pattern) => Some(pattern),
+            Err(ParseError::EndOfStream) => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(PubSubChannels { pattern })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let channels = ctx
+            .supervisor
+            .pubsub()
+            .channels()
+            .channels(self.pattern.as_deref())
+            .await;
+
+        let response =
+            Frame::Array(channels.into_iter().map(Frame::Bulk).collect());
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let cmd = Command::from_frame(frame, &Default::default())?;
+        Ok(cmd)
+    }
+
+    #[test]
+    fn ensure_parsing() {
+        let entry: RespValue = resp_array!["PUBSUB", "CHANNELS", "news.*"];
+        let cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(cmd, @r###"
+        PubSub(
+            Channels(
+                PubSubChannels {
+                    pattern: Some(
+                        b"news.*",
+                    ),
+                },
+            ),
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_too_much() {
+        let entry: RespValue = resp_array!["PUBSUB", "CHANNELS", "a", "b"];
+        let cmd = parse_cmd(entry);
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Err(
+            WrongArity(
+                "pubsub|channels",
+            ),
+        )
+        "###);
+    }
+}
@@ -0,0 +1,141 @@
+use super::parse::{Parse, ParseError};
+use super::CommandExecution;
+use crate::application::server::cmd::{
+    Command, CommandError, SubcommandRegistry,
+};
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+mod channels;
+mod numpat;
+mod numsub;
+mod shard_channels;
+mod shard_numsub;
+
+/// Introspection of the Pub/Sub subsystem.
+#[derive(Debug)]
+pub enum PubSub {
+    Help,
+    Channels(channels::PubSubChannels),
+    NumPat(numpat::PubSubNumPat),
+    NumSub(numsub::PubSubNumSub),
+    ShardChannels(shard_channels::PubSubShardChannels),
+    ShardNumSub(shard_numsub::PubSubShardNumSub),
+}
+
+const HELP_TEXT: &str = r#"PUBSUB <subcommand> [<arg> [value] [opt] ...]. Subcommands are:
+CHANNELS [<pattern>]
+    Return the currently active channels matching a <pattern> (default: '*').
+NUMPAT
+    Return number of subscriptions to patterns.
+NUMSUB [<channel> ...]
+    Return the number of subscribers for the specified channels, excluding
+    pattern subscriptions(default: no channels).
+SHARDCHANNELS [<pattern>]
+    Return the currently active shard level channels matching a <pattern> (default: '*').
+SHARDNUMSUB [<shardchannel> ...]
+    Return the number of subscribers for the specified shard level channel(s)
+HELP
+    Print this help.
+"#;
+
+impl SubcommandRegistry for PubSub {
+    fn from_parse(parse: &mut Parse) -> Result<Command, CommandError> {
+        let sub_cmd = match parse.next_string() {
+            Ok(elt) => elt,
+            Err(ParseError::EndOfStream) => {
+                return Ok(Command::PubSub(PubSub::Help))
+            }
+            Err(err) => {
+                return Err(err.into());
+            }
+        };
+
+        PubSub::parse_subcommand(&sub_cmd, parse).map_err(|err| {
+            err.for_command(&format!("pubsub|{}", sub_cmd.to_lowercase()))
+        })
+    }
+
+    async fn help(
+        dst: &mut WriteConnection,
+        _ctx: Context,
+    ) -> anyhow::Result<()> {
+        let response = Frame::Array(
+            HELP_TEXT
+                .split_terminator('\n')
+                .map(|x| Frame::Simple(x.into()))
+                .collect(),
+        );
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
+
+impl PubSub {
+    /// The name of the subcommand, in lower case.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PubSub::Help => "help",
+            PubSub::Channels(_) => "channels",
+            PubSub::NumPat(_) => "numpat",
+            PubSub::NumSub(_) => "numsub",
+            PubSub::ShardChannels(_) => "shardchannels",
+            PubSub::ShardNumSub(_) => "shardnumsub",
+        }
+    }
+
+    fn parse_subcommand(
+        sub_cmd: &str,
+        parse: &mut Parse,
+    ) -> Result<Command, CommandError> {
+        let command = match &sub_cmd.to_lowercase()[..] {
+            "channels" => Command::PubSub(PubSub::Channels(
+                channels::PubSubChannels::parse_frames(parse)?,
+            )),
+            "numpat" => Command::PubSub(PubSub::NumPat(
+                numpat::PubSubNumPat::parse_frames(parse)?,
+            )),
+            "numsub" => Command::PubSub(PubSub::NumSub(
+                numsub::PubSubNumSub::parse_frames(parse)?,
+            )),
+            "shardchannels" => Command::PubSub(PubSub::ShardChannels(
+                shard_channels::PubSubShardChannels::parse_frames(parse)?,
+            )),
+            "shardnumsub" => Command::PubSub(PubSub::ShardNumSub(
+                shard_numsub::PubSubShardNumSub::parse_frames(parse)?,
+            )),
+            "help" => Command::PubSub(PubSub::Help),
+            _ => {
+                return Err(CommandError::err(format!(
+                    "unknown subcommand '{sub_cmd}'. Try PUBSUB HELP."
+                )));
+            }
+        };
+
+        // Check if there is any remaining unconsumed fields in the `Parse`
+        // value. If fields remain, this indicates an unexpected frame format
+        // and an error is returned.
+        parse.finish()?;
+
+        // The command has been successfully parsed
+        Ok(command)
+    }
+}
+
+impl CommandExecution for PubSub {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        match self {
+            PubSub::Help => PubSub::help(dst, ctx).await,
+            PubSub::Channels(cmd) => cmd.apply(dst, ctx).await,
+            PubSub::NumPat(cmd) => cmd.apply(dst, ctx).await,
+            PubSub::NumSub(cmd) => cmd.apply(dst, ctx).await,
+            PubSub::ShardChannels(cmd) => cmd.apply(dst, ctx).await,
+            PubSub::ShardNumSub(cmd) => cmd.apply(dst, ctx).await,
+        }
+    }
+}
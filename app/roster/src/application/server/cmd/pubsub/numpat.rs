@@ -0,0 +1,35 @@
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Returns the number of unique patterns that are subscribed to by clients
+/// (that are performed using the PSUBSCRIBE command).
+///
+/// ```text
+/// PUBSUB NUMPAT
+/// ```
+#[derive(Debug, Default)]
+pub struct PubSubNumPat {}
+
+impl PubSubNumPat {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<PubSubNumPat, CommandError> {
+        Ok(PubSubNumPat {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let patterns = ctx.supervisor.pubsub().patterns().count();
+
+        let response = Frame::Integer(patterns as i64);
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use bytes::Bytes;
+
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Returns the number of subscribers (exclusive of clients subscribed to
+/// patterns) for the specified channels.
+///
+/// The reply is a flat list of channels and their number of subscribers.
+///
+/// ```text
+/// PUBSUB NUMSUB [channel [channel ...]]
+/// ```
+#[derive(Debug, Default)]
+pub struct PubSubNumSub {
+    channels: Vec<Bytes>,
+}
+
+impl PubSubNumSub {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<PubSubNumSub, CommandError> {
+        let mut channels = Vec::new();
+
+        loop {
+            match parse.next_bytes() {
+                Ok(channel) => channels.push(channel),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(PubSubNumSub { channels })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let registry = ctx.supervisor.pubsub().channels();
+
+        let mut result = Vec::with_capacity(self.channels.len() * 2);
+        for channel in self.channels {
+            let subscribers = registry.subscribers(&channel).await;
+            result.push(Frame::Bulk(channel));
+            result.push(Frame::Integer(subscribers as i64));
+        }
+
+        let response = Frame::Array(result);
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,60 @@
+use bytes::Bytes;
+
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Lists the currently active shard channels.
+///
+/// An active shard channel is a Pub/Sub shard channel with one or more
+/// subscribers. The shard channels of every storage segment of the server are
+/// listed.
+///
+/// If no pattern is specified, all the shard channels are listed, otherwise
+/// only shard channels matching the specified glob-style pattern are listed.
+///
+/// ```text
+/// PUBSUB SHARDCHANNELS [pattern]
+/// ```
+#[derive(Debug, Default)]
+pub struct PubSubShardChannels {
+    pattern: Option<Bytes>,
+}
+
+impl PubSubShardChannels {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<PubSubShardChannels, CommandError> {
+        let pattern = match parse.next_bytes() {
+            Ok(pattern) => Some(pattern),
+            Err(ParseError::EndOfStream) => None,
+            Err(err) => return Err(err.into()),
+        };
+
+        Ok(PubSubShardChannels { pattern })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let mut channels = Vec::new();
+        for segment in ctx.segments.segments() {
+            channels.extend(
+                segment
+                    .shard_channels()
+                    .channels(self.pattern.as_deref())
+                    .await,
+            );
+        }
+
+        let response =
+            Frame::Array(channels.into_iter().map(Frame::Bulk).collect());
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+use bytes::Bytes;
+
+use super::parse::Parse;
+use super::unsubscribe::{parse_optional_channels, unsubscribe};
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::subscription::SubscriptionKind;
+
+/// Unsubscribes the client from the given patterns, or from all of them if
+/// none is given.
+///
+/// ```text
+/// PUNSUBSCRIBE [pattern [pattern ...]]
+/// ```
+#[derive(Debug, Default)]
+pub struct PUnsubscribe {
+    patterns: Vec<Bytes>,
+}
+
+impl PUnsubscribe {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<PUnsubscribe, CommandError> {
+        let patterns = parse_optional_channels(parse)?;
+        Ok(PUnsubscribe { patterns })
+    }
+}
+
+impl CommandExecution for PUnsubscribe {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        unsubscribe(
+            SubscriptionKind::Pattern,
+            self.patterns,
+            b"punsubscribe",
+            dst,
+            ctx,
+        )
+        .await
+    }
+}
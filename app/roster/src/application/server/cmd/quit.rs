@@ -0,0 +1,38 @@
+use bytestring::ByteString;
+
+use super::parse::Parse;
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Ask the server to close the connection, once the pending replies are
+/// written.
+///
+/// ```text
+/// QUIT
+/// ```
+#[derive(Debug, Default)]
+pub struct Quit {}
+
+impl Quit {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<Quit, CommandError> {
+        Ok(Quit {})
+    }
+}
+
+impl CommandExecution for Quit {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        _ctx: Context,
+    ) -> anyhow::Result<()> {
+        let response = Frame::Simple(ByteString::from_static("OK"));
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
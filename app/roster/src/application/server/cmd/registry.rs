@@ -0,0 +1,324 @@
+//! The commands registered by the users of the crate, so a domain specific
+//! command can be added without forking roster:
+//!
+//! ```ignore
+//! let commands = CommandRegistry::new().register::<MyCommand>(
+//!     "mycommand",
+//!     KeyPositions::single(1),
+//!     &[AclCategory::Write, AclCategory::Fast],
+//! );
+//! let config = ServerConfigBuilder::default().commands(commands);
+//! ```
+//!
+//! The built-in commands take precedence, a command registered with the name
+//! of a built-in command is never called.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::future::LocalBoxFuture;
+
+use super::parse::Parse;
+use super::{categories, CommandError, CommandExecution};
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::acl::AclCategory;
+use crate::infrastructure::hash::crc_hash;
+
+/// A command which can be registered in a [CommandRegistry].
+pub trait CustomCommand:
+    CommandExecution + fmt::Debug + Send + 'static
+{
+    /// Parse the arguments of the command, its name is already consumed.
+    ///
+    /// The arguments left once this returns are refused with a wrong arity
+    /// error.
+    fn parse_frames(parse: &mut Parse) -> Result<Self, CommandError>;
+}
+
+/// Where the keys are in the arguments of a command, used to route the
+/// command to the hash slot owning its keys, like the `first key`,
+/// `last key` and `step` of `COMMAND INFO`.
+///
+/// The positions start at `1`, the name of the command being at `0`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyPositions {
+    first: usize,
+    /// Negative positions count from the end, `-1` being the last argument.
+    last: isize,
+    step: usize,
+}
+
+impl KeyPositions {
+    /// The command has no key.
+    pub const fn none() -> Self {
+        KeyPositions {
+            first: 0,
+            last: 0,
+            step: 0,
+        }
+    }
+
+    /// The command has a single key at `position`.
+    pub const fn single(position: usize) -> Self {
+        KeyPositions {
+            first: position,
+            last: position as isize,
+            step: 1,
+        }
+    }
+
+    /// The keys are every `step` arguments from `first` to `last`, a negative
+    /// `last` counting from the end.
+    pub const fn range(first: usize, last: isize, step: usize) -> Self {
+        KeyPositions { first, last, step }
+    }
+
+    /// The keys in `args`, the arguments following the command name.
+    fn keys<'a>(&self, args: &'a [Frame]) -> impl Iterator<Item = &'a [u8]> {
+        let last = match usize::try_from(self.last) {
+            Ok(last) => last,
+            Err(_) => (args.len() + 1).saturating_sub(self.last.unsigned_abs()),
+        };
+        let positions = match self.first {
+            0 => 0..0,
+            first => first..last.min(args.len()) + 1,
+        };
+
+        positions.step_by(self.step.max(1)).filter_map(|position| {
+            match &args[position - 1] {
+                Frame::Bulk(key) => Some(&key[..]),
+                Frame::Simple(key) => Some(key.as_bytes()),
+                _ => None,
+            }
+        })
+    }
+}
+
+/// A [CustomCommand] once parsed, its type is erased to be kept in
+/// [Custom].
+trait DynCommand: fmt::Debug + Send {
+    fn apply_boxed<'a>(
+        self: Box<Self>,
+        dst: &'a mut WriteConnection,
+        ctx: Context,
+    ) -> LocalBoxFuture<'a, anyhow::Result<()>>;
+}
+
+impl<C: CustomCommand> DynCommand for C {
+    fn apply_boxed<'a>(
+        self: Box<Self>,
+        dst: &'a mut WriteConnection,
+        ctx: Context,
+    ) -> LocalBoxFuture<'a, anyhow::Result<()>> {
+        Box::pin((*self).apply(dst, ctx))
+    }
+}
+
+type ParseFn = fn(&mut Parse) -> Result<Box<dyn DynCommand>, CommandError>;
+
+fn parse_boxed<C: CustomCommand>(
+    parse: &mut Parse,
+) -> Result<Box<dyn DynCommand>, CommandError> {
+    Ok(Box::new(C::parse_frames(parse)?))
+}
+
+/// How a registered command is parsed and described.
+#[derive(Clone)]
+struct CommandSpec {
+    parse: ParseFn,
+    keys: KeyPositions,
+    categories: Arc<[AclCategory]>,
+}
+
+/// The commands registered by the users of the crate, given to the server
+/// with [crate::ServerConfigBuilder::commands].
+///
+/// **Should be cheap to clone**
+#[derive(Clone, Default)]
+pub struct CommandRegistry {
+    commands: Arc<HashMap<String, CommandSpec>>,
+}
+
+impl fmt::Debug for CommandRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.commands.keys()).finish()
+    }
+}
+
+impl CommandRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the command `C` under `name`, case insensitive, replacing a
+    /// command previously registered with the same name.
+    pub fn register<C: CustomCommand>(
+        mut self,
+        name: &str,
+        keys: KeyPositions,
+        categories: &[AclCategory],
+    ) -> Self {
+        let spec = CommandSpec {
+            parse: parse_boxed::<C>,
+            keys,
+            categories: categories.into(),
+        };
+        Arc::make_mut(&mut self.commands).insert(name.to_lowercase(), spec);
+        self
+    }
+
+    /// Check if a command is registered under `name`, in lower case.
+    pub fn contains(&self, name: &str) -> bool {
+        self.commands.contains_key(name)
+    }
+
+    /// Check if `name` is a built-in command, a subcommand or a registered
+    /// command, as allowed in the ACL rules.
+    pub(crate) fn is_known(&self, name: &str) -> bool {
+        categories::is_known(name) || self.contains(name)
+    }
+
+    /// The categories of the command registered under `name`, in lower case.
+    pub fn categories(&self, name: &str) -> Option<&[AclCategory]> {
+        self.commands.get(name).map(|spec| &spec.categories[..])
+    }
+
+    /// The names of the commands in `category`.
+    pub fn names_in(
+        &self,
+        category: AclCategory,
+    ) -> impl Iterator<Item = String> + '_ {
+        self.commands
+            .iter()
+            .filter(move |(_, spec)| spec.categories.contains(&category))
+            .map(|(name, _)| name.clone())
+    }
+
+    /// Parse the command registered under `name`, `None` when there is no
+    /// such command.
+    pub(super) fn parse(
+        &self,
+        name: &str,
+        parse: &mut Parse,
+    ) -> Option<Result<Custom, CommandError>> {
+        let spec = self.commands.get(name)?;
+        Some(Custom::parse(name, spec, parse))
+    }
+}
+
+/// A registered command once parsed.
+#[derive(Debug)]
+pub struct Custom {
+    name: String,
+    keys: Vec<Bytes>,
+    hash_key: Option<u16>,
+    categories: Arc<[AclCategory]>,
+    command: Box<dyn DynCommand>,
+}
+
+impl Custom {
+    fn parse(
+        name: &str,
+        spec: &CommandSpec,
+        parse: &mut Parse,
+    ) -> Result<Custom, CommandError> {
+        // Every key must be owned by the same slot.
+        let keys = spec
+            .keys
+            .keys(parse.remaining())
+            .map(Bytes::copy_from_slice)
+            .collect::<Vec<_>>();
+        let mut hash_key = None;
+        for key in &keys {
+            let hash = crc_hash(key);
+            match hash_key {
+                Some(slot) if slot != hash => {
+                    return Err(CommandError::CrossSlot);
+                }
+                _ => hash_key = Some(hash),
+            }
+        }
+
+        let command = (spec.parse)(parse)?;
+        Ok(Custom {
+            name: name.to_string(),
+            keys,
+            hash_key,
+            categories: spec.categories.clone(),
+            command,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    pub fn categories(&self) -> &[AclCategory] {
+        &self.categories
+    }
+
+    /// Is the command in the `write` category.
+    pub fn is_write(&self) -> bool {
+        self.categories.contains(&AclCategory::Write)
+    }
+}
+
+impl CommandExecution for Custom {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        self.command.apply_boxed(dst, ctx).await
+    }
+
+    fn hash_key(&self) -> Option<u16> {
+        self.hash_key
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::KeyPositions;
+    use crate::application::server::frame::Frame;
+
+    fn keys(positions: KeyPositions, args: &[&'static str]) -> Vec<String> {
+        let args = args
+            .iter()
+            .map(|arg| Frame::Bulk(Bytes::from_static(arg.as_bytes())))
+            .collect::<Vec<_>>();
+        positions
+            .keys(&args)
+            .map(|key| String::from_utf8_lossy(key).into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn key_positions() {
+        assert!(keys(KeyPositions::none(), &["a", "b"]).is_empty());
+        assert_eq!(keys(KeyPositions::single(1), &["a", "b"]), ["a"]);
+        assert!(keys(KeyPositions::single(1), &[]).is_empty());
+        assert_eq!(
+            keys(KeyPositions::range(1, -1, 1), &["a", "b", "c"]),
+            ["a", "b", "c"]
+        );
+        assert_eq!(
+            keys(KeyPositions::range(1, -1, 2), &["a", "1", "b", "2"]),
+            ["a", "b"]
+        );
+        assert_eq!(
+            keys(KeyPositions::range(2, -2, 1), &["n", "a", "b", "arg"]),
+            ["a", "b"]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use bytestring::ByteString;
+
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Returns information about the existence of the scripts in the script
+/// cache, `1` for each script which exists and `0` for the others.
+///
+/// ```text
+/// SCRIPT EXISTS sha1 [sha1 ...]
+/// ```
+#[derive(Debug, Default)]
+pub struct ScriptExists {
+    shas: Vec<ByteString>,
+}
+
+impl ScriptExists {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<ScriptExists, CommandError> {
+        let mut shas = vec![parse.next_string()?];
+        loop {
+            match parse.next_string() {
+                Ok(sha) => shas.push(sha),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(ScriptExists { shas })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let scripts = ctx.supervisor.scripts();
+        let mut response = Vec::with_capacity(self.shas.len());
+        for sha in self.shas {
+            let exists = scripts.contains(&sha.to_lowercase()).await;
+            response.push(Frame::Integer(exists as i64));
+        }
+
+        dst.write_frame(&Frame::Array(response)).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Flush the Lua scripts cache.
+///
+/// The cache is always flushed synchronously, the `ASYNC` and `SYNC` modes
+/// are accepted for compatibility.
+///
+/// ```text
+/// SCRIPT FLUSH [ASYNC | SYNC]
+/// ```
+#[derive(Debug, Default)]
+pub struct ScriptFlush {}
+
+impl ScriptFlush {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<ScriptFlush, CommandError> {
+        match parse.next_string() {
+            Ok(mode)
+                if mode.eq_ignore_ascii_case("async")
+                    || mode.eq_ignore_ascii_case("sync") => {}
+            Ok(_) => {
+                return Err(CommandError::err(
+                    "SCRIPT FLUSH only support SYNC|ASYNC option",
+                ));
+            }
+            Err(ParseError::EndOfStream) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(ScriptFlush {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        ctx.supervisor.scripts().flush().await;
+        ctx.lua().flush();
+
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::scripting::KillError;
+
+/// Kills the currently executing script, as long as it didn't modify the
+/// dataset.
+///
+/// It's mostly useful to stop a script running for too long, once the
+/// other connections are answered with a `BUSY` error.
+///
+/// ```text
+/// SCRIPT KILL
+/// ```
+#[derive(Debug, Default)]
+pub struct ScriptKill {}
+
+impl ScriptKill {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<ScriptKill, CommandError> {
+        Ok(ScriptKill {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        kill_running_script(&ctx)?;
+
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
+
+/// Kill the running script, shared by `SCRIPT KILL` and `FUNCTION KILL`.
+pub(crate) fn kill_running_script(ctx: &Context) -> Result<(), CommandError> {
+    match ctx.supervisor.scripts().kill() {
+        Ok(()) => Ok(()),
+        Err(KillError::NotBusy) => Err(CommandError::with_code(
+            "NOTBUSY",
+            "No scripts in execution right now.",
+        )),
+        Err(KillError::Unkillable) => Err(CommandError::with_code(
+            "UNKILLABLE",
+            "Sorry the script already executed write commands against the \
+             dataset. You can either wait the script termination or kill the \
+             server in a hard way using the SHUTDOWN NOSAVE command.",
+        )),
+    }
+}
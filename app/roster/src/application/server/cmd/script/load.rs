@@ -0,0 +1,42 @@
+use bytes::Bytes;
+
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::scripting::sha1hex;
+
+/// Load a script into the scripts cache, without executing it, and return
+/// its SHA1 digest to be used with `EVALSHA`.
+///
+/// ```text
+/// SCRIPT LOAD script
+/// ```
+#[derive(Debug, Default)]
+pub struct ScriptLoad {
+    script: Bytes,
+}
+
+impl ScriptLoad {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<ScriptLoad, CommandError> {
+        let script = parse.next_bytes()?;
+        Ok(ScriptLoad { script })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        // The script is only cached once it compiles.
+        let sha = sha1hex(&self.script);
+        ctx.lua().compile(&sha, &self.script)?;
+        ctx.supervisor.scripts().load(self.script).await;
+
+        dst.write_frame(&Frame::Bulk(Bytes::from(sha))).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use super::parse::{Parse, ParseError};
+use super::CommandExecution;
+use crate::application::server::cmd::{
+    Command, CommandError, SubcommandRegistry,
+};
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+mod exists;
+mod flush;
+pub(super) mod kill;
+mod load;
+
+/// Manage the scripts cache and the running script.
+#[derive(Debug)]
+pub enum Script {
+    Help,
+    Exists(exists::ScriptExists),
+    Flush(flush::ScriptFlush),
+    Kill(kill::ScriptKill),
+    Load(load::ScriptLoad),
+}
+
+const HELP_TEXT: &str = r#"SCRIPT <subcommand> [<arg> [value] [opt] ...]. Subcommands are:
+EXISTS <sha1> [<sha1> ...]
+    Return information about the existence of the scripts in the script cache.
+FLUSH [ASYNC|SYNC]
+    Flush the Lua scripts cache. Very dangerous on replicas.
+    When called without the optional mode argument, the behavior is determined by the
+    lazyfree-lazy-user-flush configuration directive. Valid modes are:
+    * ASYNC: Asynchronously flush the scripts cache.
+    * SYNC: Synchronously flush the scripts cache.
+KILL
+    Kill the currently executing Lua script.
+LOAD <script>
+    Load a script into the scripts cache without executing it.
+HELP
+    Print this help.
+"#;
+
+impl SubcommandRegistry for Script {
+    fn from_parse(parse: &mut Parse) -> Result<Command, CommandError> {
+        let sub_cmd = match parse.next_string() {
+            Ok(elt) => elt,
+            Err(ParseError::EndOfStream) => {
+                return Err(CommandError::WrongArity("script".to_string()));
+            }
+            Err(err) => {
+                return Err(err.into());
+            }
+        };
+
+        Script::parse_subcommand(&sub_cmd, parse).map_err(|err| {
+            err.for_command(&format!("script|{}", sub_cmd.to_lowercase()))
+        })
+    }
+
+    async fn help(
+        dst: &mut WriteConnection,
+        _ctx: Context,
+    ) -> anyhow::Result<()> {
+        let response = Frame::Array(
+            HELP_TEXT
+                .split_terminator('\n')
+                .map(|x| Frame::Simple(x.into()))
+                .collect(),
+        );
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
+
+impl Script {
+    /// The name of the subcommand, in lower case.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Script::Help => "help",
+            Script::Exists(_) => "exists",
+            Script::Flush(_) => "flush",
+            Script::Kill(_) => "kill",
+            Script::Load(_) => "load",
+        }
+    }
+
+    fn parse_subcommand(
+        sub_cmd: &str,
+        parse: &mut Parse,
+    ) -> Result<Command, CommandError> {
+        let command = match &sub_cmd.to_lowercase()[..] {
+            "exists" => Command::Script(Script::Exists(
+                exists::ScriptExists::parse_frames(parse)?,
+            )),
+            "flush" => Command::Script(Script::Flush(
+                flush::ScriptFlush::parse_frames(parse)?,
+            )),
+            "kill" => Command::Script(Script::Kill(
+                kill::ScriptKill::parse_frames(parse)?,
+            )),
+            "load" => Command::Script(Script::Load(
+                load::ScriptLoad::parse_frames(parse)?,
+            )),
+            "help" => Command::Script(Script::Help),
+            _ => {
+                return Err(CommandError::err(format!(
+                    "unknown subcommand '{sub_cmd}'. Try SCRIPT HELP."
+                )));
+            }
+        };
+
+        // Check if there is any remaining unconsumed fields in the `Parse`
+        // value. If fields remain, this indicates an unexpected frame format
+        // and an error is returned.
+        parse.finish()?;
+
+        // The command has been successfully parsed
+        Ok(command)
+    }
+}
+
+impl CommandExecution for Script {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        match self {
+            Script::Help => Script::help(dst, ctx).await,
+            Script::Exists(cmd) => cmd.apply(dst, ctx).await,
+            Script::Flush(cmd) => cmd.apply(dst, ctx).await,
+            Script::Kill(cmd) => cmd.apply(dst, ctx).await,
+            Script::Load(cmd) => cmd.apply(dst, ctx).await,
+        }
+    }
+}
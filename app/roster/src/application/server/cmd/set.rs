@@ -5,6 +5,7 @@ use bytestring::ByteString;
 
 use super::parse::{Parse, ParseError};
 use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
 use crate::application::server::connection::WriteConnection;
 use crate::application::server::context::Context;
 use crate::application::server::frame::Frame;
@@ -16,21 +17,20 @@ use crate::infrastructure::hash::crc_hash;
 ///
 /// Any previous time to live associated with the key is discarded on successful
 /// SET operation.
-
+///
 /// # Options
 /// The SET command supports a set of options that modify its behavior:
 ///
 /// - EX seconds -- Set the specified expire time, in seconds (a positive
-/// integer).
-/// - PX milliseconds -- Set the specified expire time, in milliseconds
-/// (a positive integer).
-/// - EXAT timestamp-seconds -- Set the specified Unix time
-/// at which the key will expire, in seconds (a positive integer).
+///   integer).
+/// - PX milliseconds -- Set the specified expire time, in milliseconds (a
+///   positive integer).
+/// - EXAT timestamp-seconds -- Set the specified Unix time at which the key
+///   will expire, in seconds (a positive integer).
 /// - PXAT timestamp-milliseconds -- Set the specified Unix time at which the
-///   key
-/// will expire, in milliseconds (a positive integer).
-/// - NX -- Only set the key if
-/// it does not already exist. XX -- Only set the key if it already exists.
+///   key will expire, in milliseconds (a positive integer).
+/// - NX -- Only set the key if it does not already exist.
+/// - XX -- Only set the key if it already exists.
 /// - KEEPTTL -- Retain the time to live associated with the key.
 /// - GET -- Return the old string stored at key, or nil if key did not exist.
 ///
@@ -55,6 +55,11 @@ pub struct Set {
 static OK_STR: ByteString = ByteString::from_static("OK");
 
 impl Set {
+    /// The key written.
+    pub fn key(&self) -> &ByteString {
+        &self.key
+    }
+
     /// Parse a `Set` instance from a received frame.
     ///
     /// The `Parse` argument provides a cursor-like API to read fields from the
@@ -75,7 +80,7 @@ impl Set {
     /// ```text
     /// SET key value [EX seconds|PX milliseconds]
     /// ```
-    pub(crate) fn parse_frames(parse: &mut Parse) -> anyhow::Result<Set> {
+    pub(crate) fn parse_frames(parse: &mut Parse) -> Result<Set, CommandError> {
         use ParseError::EndOfStream;
 
         // Read the key to set. This is a required field
@@ -93,23 +98,24 @@ impl Set {
             Ok(s) if s.to_uppercase() == "EX" => {
                 // An expiration is specified in seconds. The next value is an
                 // integer.
-                let secs = parse.next_int()?;
-                expire = Some(Duration::from_secs(secs));
+                let secs = parse.next_i64()?;
+                if secs <= 0 {
+                    return Err(invalid_expire_time());
+                }
+                expire = Some(Duration::from_secs(secs as u64));
             }
             Ok(s) if s.to_uppercase() == "PX" => {
                 // An expiration is specified in milliseconds. The next value is
                 // an integer.
-                let ms = parse.next_int()?;
-                expire = Some(Duration::from_millis(ms));
+                let ms = parse.next_i64()?;
+                if ms <= 0 {
+                    return Err(invalid_expire_time());
+                }
+                expire = Some(Duration::from_millis(ms as u64));
             }
             // Currently, roster does not support any of the other SET
-            // options. An error here results in the connection being
-            // terminated.
-            Ok(_) => {
-                return Err(anyhow::anyhow!(
-                    "currently `SET` only supports the expiration option"
-                ));
-            }
+            // options.
+            Ok(_) => return Err(CommandError::Syntax),
             Err(EndOfStream) => {}
             Err(err) => return Err(err.into()),
         }
@@ -118,6 +124,10 @@ impl Set {
     }
 }
 
+fn invalid_expire_time() -> CommandError {
+    CommandError::err("invalid expire time in 'set' command")
+}
+
 impl CommandExecution for Set {
     async fn apply(
         self,
@@ -127,14 +137,16 @@ impl CommandExecution for Set {
         let expired = self.expire.map(|dur| ctx.now() + dur.into());
 
         // let now = Instant::now();
-        let response = match ctx.storage.set_async(
-            self.key,
-            self.value,
-            SetOptions { expired },
-        ) {
+        let key = self.key.clone();
+        let response = match ctx
+            .storage
+            .set_async(self.key, self.value, SetOptions { expired }, ctx.now())
+            .await
+        {
             Ok(_) => Frame::Simple(OK_STR.clone()),
             Err(_) => Frame::Null,
         };
+        ctx.signal_modified_key(key.as_bytes()).await;
         // let elapsed = now.elapsed();
         // dbg!(elapsed);
 
@@ -0,0 +1,155 @@
+use tracing::{info, warn};
+
+use super::parse::{Parse, ParseError};
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+
+/// Stop the server gracefully: the connections aren't accepted anymore and
+/// the open ones, the caller included, are closed once their current command
+/// is applied. Nothing is replied on success.
+///
+/// ```text
+/// SHUTDOWN [NOSAVE | SAVE] [NOW] [FORCE] [ABORT]
+/// ```
+///
+/// The dataset isn't persisted yet, so `SAVE` has nothing to flush and
+/// `FORCE` never has an error to ignore. There are no replicas to wait for
+/// either: `NOW` is the only behavior, and there is never a shutdown to
+/// `ABORT`.
+#[derive(Debug, Default)]
+pub struct Shutdown {
+    /// `Some(true)` with `SAVE`, `Some(false)` with `NOSAVE`.
+    save: Option<bool>,
+    now: bool,
+    force: bool,
+    abort: bool,
+}
+
+impl Shutdown {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<Shutdown, CommandError> {
+        let mut shutdown = Shutdown::default();
+        loop {
+            let flag = match parse.next_string() {
+                Ok(flag) => flag.to_lowercase(),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            };
+            let save = match &flag[..] {
+                "save" => true,
+                "nosave" => false,
+                "now" => {
+                    shutdown.now = true;
+                    continue;
+                }
+                "force" => {
+                    shutdown.force = true;
+                    continue;
+                }
+                "abort" => {
+                    shutdown.abort = true;
+                    continue;
+                }
+                _ => return Err(CommandError::Syntax),
+            };
+            // `SAVE` and `NOSAVE` can't be both given.
+            if shutdown.save.replace(save).is_some_and(|prev| prev != save) {
+                return Err(CommandError::Syntax);
+            }
+        }
+
+        // `ABORT` can't be combined with another flag.
+        if shutdown.abort
+            && (shutdown.save.is_some() || shutdown.now || shutdown.force)
+        {
+            return Err(CommandError::Syntax);
+        }
+        Ok(shutdown)
+    }
+}
+
+impl CommandExecution for Shutdown {
+    async fn apply(
+        self,
+        _dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        if ctx.transaction().is_started() {
+            ctx.transaction().abort();
+            return Err(CommandError::err(
+                "Command not allowed inside a transaction",
+            )
+            .into());
+        }
+
+        if self.abort {
+            return Err(CommandError::err("No shutdown in progress.").into());
+        }
+
+        if self.save == Some(true) {
+            warn!("The dataset isn't persisted, there is nothing to save");
+        }
+        if ctx.supervisor.shutdown().request() {
+            info!("User requested shutdown...");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let shutdown = Command::from_frame(frame, &Default::default())?;
+        Ok(shutdown)
+    }
+
+    #[test]
+    fn ensure_parsing() {
+        let entry: RespValue =
+            resp_array!["SHUTDOWN", "force", "NOSAVE", "now", "nosave"];
+        let shutdown = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(shutdown, @r###"
+        Shutdown(
+            Shutdown {
+                save: Some(
+                    false,
+                ),
+                now: true,
+                force: true,
+                abort: false,
+            },
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_fail() {
+        let entry: RespValue = resp_array!["SHUTDOWN", "SAVE", "NOSAVE"];
+        assert!(parse_cmd(entry).is_err());
+
+        let entry: RespValue = resp_array!["SHUTDOWN", "ABORT", "NOW"];
+        assert!(parse_cmd(entry).is_err());
+
+        let entry: RespValue = resp_array!["SHUTDOWN", "LATER"];
+        assert!(parse_cmd(entry).is_err());
+    }
+}
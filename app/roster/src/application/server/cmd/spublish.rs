@@ -0,0 +1,103 @@
+use bytes::Bytes;
+
+use super::parse::Parse;
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::pubsub::Message;
+use crate::infrastructure::hash::crc_hash;
+
+/// Posts a message to the given shard channel.
+///
+/// The message is delivered by the storage segment owning the slot of the
+/// shard channel. Return the number of clients that received it.
+///
+/// ```text
+/// SPUBLISH shardchannel message
+/// ```
+#[derive(Debug, Default)]
+pub struct SPublish {
+    channel: Bytes,
+    message: Bytes,
+}
+
+impl SPublish {
+    pub fn channel(&self) -> &Bytes {
+        &self.channel
+    }
+
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<SPublish, CommandError> {
+        let channel = parse.next_bytes()?;
+        let message = parse.next_bytes()?;
+        Ok(SPublish { channel, message })
+    }
+}
+
+impl CommandExecution for SPublish {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let msg = Message::SMessage {
+            channel: self.channel.clone(),
+            payload: self.message,
+        };
+        let receivers = ctx
+            .segments
+            .shard_channels(&self.channel)
+            .publish(&self.channel, msg)
+            .await;
+
+        let response = Frame::Integer(receivers as i64);
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+
+    fn hash_key(&self) -> Option<u16> {
+        Some(crc_hash(&self.channel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let cmd = Command::from_frame(frame, &Default::default())?;
+        Ok(cmd)
+    }
+
+    #[test]
+    fn ensure_parsing() {
+        let entry: RespValue = resp_array!["SPUBLISH", "news", "hello"];
+        let cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(cmd, @r###"
+        SPublish(
+            SPublish {
+                channel: b"news",
+                message: b"hello",
+            },
+        )
+        "###);
+    }
+}
@@ -0,0 +1,121 @@
+use bytes::Bytes;
+
+use super::parse::Parse;
+use super::subscribe::{parse_channels, subscribe};
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::subscription::SubscriptionKind;
+use crate::infrastructure::hash::crc_hash;
+
+/// Subscribes the client to the specified shard channels.
+///
+/// A shard channel is assigned to a slot like a key, every channel of the
+/// command must hash to the same slot. The subscription is kept by the
+/// storage segment owning this slot, so messages are only routed there.
+///
+/// ```text
+/// SSUBSCRIBE shardchannel [shardchannel ...]
+/// ```
+#[derive(Debug, Default)]
+pub struct SSubscribe {
+    channels: Vec<Bytes>,
+}
+
+impl SSubscribe {
+    pub fn channels(&self) -> &[Bytes] {
+        &self.channels
+    }
+
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<SSubscribe, CommandError> {
+        let channels = parse_channels(parse)?;
+        check_same_slot(&channels)?;
+        Ok(SSubscribe { channels })
+    }
+}
+
+impl CommandExecution for SSubscribe {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        subscribe(
+            SubscriptionKind::Shard,
+            self.channels,
+            b"ssubscribe",
+            dst,
+            ctx,
+        )
+        .await
+    }
+
+    fn hash_key(&self) -> Option<u16> {
+        self.channels.first().map(|channel| crc_hash(channel))
+    }
+}
+
+/// Ensure every shard channel hash to the same slot.
+pub(super) fn check_same_slot(channels: &[Bytes]) -> Result<(), CommandError> {
+    let mut slots = channels.iter().map(|channel| crc_hash(channel));
+    match slots.next() {
+        Some(slot) if slots.any(|other| other != slot) => {
+            Err(CommandError::CrossSlot)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let cmd = Command::from_frame(frame, &Default::default())?;
+        Ok(cmd)
+    }
+
+    #[test]
+    fn ensure_parsing() {
+        let entry: RespValue = resp_array!["SSUBSCRIBE", "news", "news"];
+        let cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(cmd, @r###"
+        SSubscribe(
+            SSubscribe {
+                channels: [
+                    b"news",
+                    b"news",
+                ],
+            },
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_cross_slot() {
+        let entry: RespValue = resp_array!["SSUBSCRIBE", "news", "other"];
+        let cmd = parse_cmd(entry);
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Err(
+            CrossSlot,
+        )
+        "###);
+    }
+}
@@ -0,0 +1,148 @@
+use bytes::Bytes;
+
+use super::parse::{Parse, ParseError};
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::application::server::subscription::SubscriptionKind;
+
+/// Subscribes the client to the specified channels.
+///
+/// Once the client enters the subscribed state it is not supposed to issue
+/// any other commands, except for additional SUBSCRIBE, SSUBSCRIBE,
+/// PSUBSCRIBE, UNSUBSCRIBE, SUNSUBSCRIBE, PUNSUBSCRIBE, PING, RESET and QUIT
+/// commands. However, if RESP3 is used it is possible for the client to issue
+/// any commands while in subscribed state.
+///
+/// ```text
+/// SUBSCRIBE channel [channel ...]
+/// ```
+#[derive(Debug, Default)]
+pub struct Subscribe {
+    channels: Vec<Bytes>,
+}
+
+impl Subscribe {
+    pub fn channels(&self) -> &[Bytes] {
+        &self.channels
+    }
+
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<Subscribe, CommandError> {
+        let channels = parse_channels(parse)?;
+        Ok(Subscribe { channels })
+    }
+}
+
+impl CommandExecution for Subscribe {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        subscribe(
+            SubscriptionKind::Channel,
+            self.channels,
+            b"subscribe",
+            dst,
+            ctx,
+        )
+        .await
+    }
+}
+
+/// Parse at least one channel.
+pub(super) fn parse_channels(
+    parse: &mut Parse,
+) -> Result<Vec<Bytes>, CommandError> {
+    let mut channels = vec![parse.next_bytes()?];
+
+    loop {
+        match parse.next_bytes() {
+            Ok(channel) => channels.push(channel),
+            Err(ParseError::EndOfStream) => break,
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(channels)
+}
+
+/// Subscribe to every channel, each subscription is confirmed with a push
+/// containing the number of subscriptions of the connection.
+pub(super) async fn subscribe(
+    kind: SubscriptionKind,
+    channels: Vec<Bytes>,
+    reply: &'static [u8],
+    dst: &mut WriteConnection,
+    ctx: Context,
+) -> anyhow::Result<()> {
+    for channel in channels {
+        let count = ctx.subscribe(kind, channel.clone()).await;
+
+        let response = Frame::Push(vec![
+            Frame::Bulk(Bytes::from_static(reply)),
+            Frame::Bulk(channel),
+            Frame::Integer(count as i64),
+        ]);
+        dst.write_frame(&response).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let cmd = Command::from_frame(frame, &Default::default())?;
+        Ok(cmd)
+    }
+
+    #[test]
+    fn ensure_parsing() {
+        let entry: RespValue = resp_array!["SUBSCRIBE", "a", "b"];
+        let cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Subscribe(
+            Subscribe {
+                channels: [
+                    b"a",
+                    b"b",
+                ],
+            },
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_no_channel() {
+        let entry: RespValue = resp_array!["PSUBSCRIBE"];
+        let cmd = parse_cmd(entry);
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Err(
+            WrongArity(
+                "psubscribe",
+            ),
+        )
+        "###);
+    }
+}
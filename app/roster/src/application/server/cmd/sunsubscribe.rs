@@ -0,0 +1,53 @@
+use bytes::Bytes;
+
+use super::parse::Parse;
+use super::ssubscribe::check_same_slot;
+use super::unsubscribe::{parse_optional_channels, unsubscribe};
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::subscription::SubscriptionKind;
+use crate::infrastructure::hash::crc_hash;
+
+/// Unsubscribes the client from the given shard channels, or from all of them
+/// if none is given.
+///
+/// ```text
+/// SUNSUBSCRIBE [shardchannel [shardchannel ...]]
+/// ```
+#[derive(Debug, Default)]
+pub struct SUnsubscribe {
+    channels: Vec<Bytes>,
+}
+
+impl SUnsubscribe {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<SUnsubscribe, CommandError> {
+        let channels = parse_optional_channels(parse)?;
+        check_same_slot(&channels)?;
+        Ok(SUnsubscribe { channels })
+    }
+}
+
+impl CommandExecution for SUnsubscribe {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        unsubscribe(
+            SubscriptionKind::Shard,
+            self.channels,
+            b"sunsubscribe",
+            dst,
+            ctx,
+        )
+        .await
+    }
+
+    fn hash_key(&self) -> Option<u16> {
+        self.channels.first().map(|channel| crc_hash(channel))
+    }
+}
@@ -20,6 +20,11 @@ impl Unknown {
             command_name: key.to_string(),
         }
     }
+
+    /// The name of the unknown command.
+    pub fn name(&self) -> &str {
+        &self.command_name
+    }
 }
 
 impl CommandExecution for Unknown {
@@ -0,0 +1,105 @@
+use bytes::Bytes;
+
+use super::parse::{Parse, ParseError};
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::application::server::subscription::SubscriptionKind;
+
+/// Unsubscribes the client from the given channels, or from all of them if
+/// none is given.
+///
+/// ```text
+/// UNSUBSCRIBE [channel [channel ...]]
+/// ```
+#[derive(Debug, Default)]
+pub struct Unsubscribe {
+    channels: Vec<Bytes>,
+}
+
+impl Unsubscribe {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<Unsubscribe, CommandError> {
+        let channels = parse_optional_channels(parse)?;
+        Ok(Unsubscribe { channels })
+    }
+}
+
+impl CommandExecution for Unsubscribe {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        unsubscribe(
+            SubscriptionKind::Channel,
+            self.channels,
+            b"unsubscribe",
+            dst,
+            ctx,
+        )
+        .await
+    }
+}
+
+/// Parse any number of channels.
+pub(super) fn parse_optional_channels(
+    parse: &mut Parse,
+) -> Result<Vec<Bytes>, CommandError> {
+    let mut channels = Vec::new();
+
+    loop {
+        match parse.next_bytes() {
+            Ok(channel) => channels.push(channel),
+            Err(ParseError::EndOfStream) => break,
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(channels)
+}
+
+/// Unsubscribe from every channel, or from every subscription of this kind
+/// when no channel is given. Each one is confirmed with a push containing the
+/// number of subscriptions left.
+pub(super) async fn unsubscribe(
+    kind: SubscriptionKind,
+    channels: Vec<Bytes>,
+    reply: &'static [u8],
+    dst: &mut WriteConnection,
+    ctx: Context,
+) -> anyhow::Result<()> {
+    let channels = if channels.is_empty() {
+        ctx.subscriptions().list(kind)
+    } else {
+        channels
+    };
+
+    // Nothing to unsubscribe from, we still need to answer.
+    if channels.is_empty() {
+        let count = ctx.subscriptions().count(kind);
+        let response = Frame::Push(vec![
+            Frame::Bulk(Bytes::from_static(reply)),
+            Frame::Null,
+            Frame::Integer(count as i64),
+        ]);
+        dst.write_frame(&response).await?;
+        return Ok(());
+    }
+
+    for channel in channels {
+        let count = ctx.unsubscribe(kind, &channel).await;
+
+        let response = Frame::Push(vec![
+            Frame::Bulk(Bytes::from_static(reply)),
+            Frame::Bulk(channel),
+            Frame::Integer(count as i64),
+        ]);
+        dst.write_frame(&response).await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use bytestring::ByteString;
+
+use super::parse::Parse;
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Flushes all the previously watched keys for a transaction.
+///
+/// If EXEC or DISCARD, there's no need to manually call UNWATCH.
+///
+/// ```text
+/// UNWATCH
+/// ```
+#[derive(Debug, Default)]
+pub struct Unwatch {}
+
+impl Unwatch {
+    pub(crate) fn parse_frames(
+        _parse: &mut Parse,
+    ) -> Result<Unwatch, CommandError> {
+        Ok(Unwatch {})
+    }
+}
+
+impl CommandExecution for Unwatch {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        ctx.transaction().unwatch();
+
+        let response = Frame::Simple(ByteString::from_static("OK"));
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use super::super::parse::{Parse, ParseError};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Flush the WebAssembly modules cache.
+///
+/// The cache is always flushed synchronously, the `ASYNC` and `SYNC` modes
+/// are accepted for compatibility with `SCRIPT FLUSH`.
+///
+/// ```text
+/// WASM FLUSH [ASYNC | SYNC]
+/// ```
+#[derive(Debug, Default)]
+pub struct WasmFlush {}
+
+impl WasmFlush {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<WasmFlush, CommandError> {
+        match parse.next_string() {
+            Ok(mode)
+                if mode.eq_ignore_ascii_case("async")
+                    || mode.eq_ignore_ascii_case("sync") => {}
+            Ok(_) => {
+                return Err(CommandError::err(
+                    "WASM FLUSH only support SYNC|ASYNC option",
+                ));
+            }
+            Err(ParseError::EndOfStream) => {}
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(WasmFlush {})
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        ctx.supervisor.scripts().flush_modules().await;
+        ctx.supervisor.wasm().flush();
+
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,42 @@
+use bytes::Bytes;
+
+use super::super::parse::Parse;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::scripting::sha1hex;
+
+/// Load a WebAssembly module into the modules cache, without executing it,
+/// and return its SHA1 digest to be used with `WCALL`.
+///
+/// ```text
+/// WASM LOAD module
+/// ```
+#[derive(Debug, Default)]
+pub struct WasmLoad {
+    module: Bytes,
+}
+
+impl WasmLoad {
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<WasmLoad, CommandError> {
+        let module = parse.next_bytes()?;
+        Ok(WasmLoad { module })
+    }
+
+    pub(crate) async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        // The module is only cached once it compiles.
+        let sha = sha1hex(&self.module);
+        ctx.supervisor.wasm().compile(&sha, &self.module)?;
+        ctx.supervisor.scripts().load_module(self.module).await;
+
+        dst.write_frame(&Frame::Bulk(Bytes::from(sha))).await?;
+        Ok(())
+    }
+}
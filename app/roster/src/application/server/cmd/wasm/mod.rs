@@ -0,0 +1,122 @@
+use super::parse::{Parse, ParseError};
+use super::CommandExecution;
+use crate::application::server::cmd::{
+    Command, CommandError, SubcommandRegistry,
+};
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+mod exists;
+mod flush;
+mod load;
+
+/// Manage the WebAssembly modules called with `WCALL`.
+#[derive(Debug)]
+pub enum Wasm {
+    Help,
+    Exists(exists::WasmExists),
+    Flush(flush::WasmFlush),
+    Load(load::WasmLoad),
+}
+
+const HELP_TEXT: &str = r#"WASM <subcommand> [<arg> [value] [opt] ...]. Subcommands are:
+EXISTS <sha1> [<sha1> ...]
+    Return information about the existence of the modules in the modules cache.
+FLUSH [ASYNC|SYNC]
+    Flush the WebAssembly modules cache.
+LOAD <module>
+    Load a binary or a text module into the modules cache without executing it.
+HELP
+    Print this help.
+"#;
+
+impl SubcommandRegistry for Wasm {
+    fn from_parse(parse: &mut Parse) -> Result<Command, CommandError> {
+        let sub_cmd = match parse.next_string() {
+            Ok(elt) => elt,
+            Err(ParseError::EndOfStream) => {
+                return Err(CommandError::WrongArity("wasm".to_string()));
+            }
+            Err(err) => {
+                return Err(err.into());
+            }
+        };
+
+        Wasm::parse_subcommand(&sub_cmd, parse).map_err(|err| {
+            err.for_command(&format!("wasm|{}", sub_cmd.to_lowercase()))
+        })
+    }
+
+    async fn help(
+        dst: &mut WriteConnection,
+        _ctx: Context,
+    ) -> anyhow::Result<()> {
+        let response = Frame::Array(
+            HELP_TEXT
+                .split_terminator('\n')
+                .map(|x| Frame::Simple(x.into()))
+                .collect(),
+        );
+        dst.write_frame(&response).await?;
+        Ok(())
+    }
+}
+
+impl Wasm {
+    /// The name of the subcommand, in lower case.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Wasm::Help => "help",
+            Wasm::Exists(_) => "exists",
+            Wasm::Flush(_) => "flush",
+            Wasm::Load(_) => "load",
+        }
+    }
+
+    fn parse_subcommand(
+        sub_cmd: &str,
+        parse: &mut Parse,
+    ) -> Result<Command, CommandError> {
+        let command = match &sub_cmd.to_lowercase()[..] {
+            "exists" => Command::Wasm(Wasm::Exists(
+                exists::WasmExists::parse_frames(parse)?,
+            )),
+            "flush" => Command::Wasm(Wasm::Flush(
+                flush::WasmFlush::parse_frames(parse)?,
+            )),
+            "load" => {
+                Command::Wasm(Wasm::Load(load::WasmLoad::parse_frames(parse)?))
+            }
+            "help" => Command::Wasm(Wasm::Help),
+            _ => {
+                return Err(CommandError::err(format!(
+                    "unknown subcommand '{sub_cmd}'. Try WASM HELP."
+                )));
+            }
+        };
+
+        // Check if there is any remaining unconsumed fields in the `Parse`
+        // value. If fields remain, this indicates an unexpected frame format
+        // and an error is returned.
+        parse.finish()?;
+
+        // The command has been successfully parsed
+        Ok(command)
+    }
+}
+
+impl CommandExecution for Wasm {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        match self {
+            Wasm::Help => Wasm::help(dst, ctx).await,
+            Wasm::Exists(cmd) => cmd.apply(dst, ctx).await,
+            Wasm::Flush(cmd) => cmd.apply(dst, ctx).await,
+            Wasm::Load(cmd) => cmd.apply(dst, ctx).await,
+        }
+    }
+}
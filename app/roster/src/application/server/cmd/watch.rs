@@ -0,0 +1,122 @@
+use bytestring::ByteString;
+
+use super::parse::{Parse, ParseError};
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+
+/// Marks the given keys to be watched for conditional execution of a
+/// transaction.
+///
+/// If one of the keys is modified before the `EXEC`, the transaction is
+/// aborted and `EXEC` returns a null reply.
+///
+/// ```text
+/// WATCH key [key ...]
+/// ```
+#[derive(Debug, Default)]
+pub struct Watch {
+    keys: Vec<ByteString>,
+}
+
+impl Watch {
+    pub fn keys(&self) -> &[ByteString] {
+        &self.keys
+    }
+
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+    ) -> Result<Watch, CommandError> {
+        let mut keys = vec![parse.next_string()?];
+
+        loop {
+            match parse.next_string() {
+                Ok(key) => keys.push(key),
+                Err(ParseError::EndOfStream) => break,
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        Ok(Watch { keys })
+    }
+}
+
+impl CommandExecution for Watch {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        if ctx.transaction().is_started() {
+            return Err(
+                CommandError::err("WATCH inside MULTI is not allowed").into()
+            );
+        }
+
+        let now = ctx.now();
+        for key in self.keys {
+            let version = ctx.storage.version_async(key.clone(), now).await;
+            ctx.transaction().watch(key, version);
+        }
+
+        let response = Frame::Simple(ByteString::from_static("OK"));
+        dst.write_frame(&response).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let cmd = Command::from_frame(frame, &Default::default())?;
+        Ok(cmd)
+    }
+
+    #[test]
+    fn ensure_parsing() {
+        let entry: RespValue = resp_array!["WATCH", "a", "b"];
+        let cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Watch(
+            Watch {
+                keys: [
+                    "a",
+                    "b",
+                ],
+            },
+        )
+        "###);
+    }
+
+    #[test]
+    fn ensure_parsing_no_key() {
+        let entry: RespValue = resp_array!["WATCH"];
+        let cmd = parse_cmd(entry);
+        insta::assert_debug_snapshot!(cmd, @r###"
+        Err(
+            WrongArity(
+                "watch",
+            ),
+        )
+        "###);
+    }
+}
@@ -0,0 +1,141 @@
+use bytes::Bytes;
+use bytestring::ByteString;
+
+use super::eval::parse_keys_and_args;
+use super::parse::Parse;
+use super::CommandExecution;
+use crate::application::server::cmd::CommandError;
+use crate::application::server::connection::WriteConnection;
+use crate::application::server::context::Context;
+use crate::infrastructure::hash::crc_hash;
+
+/// Call a function exported by a WebAssembly module loaded with `WASM LOAD`.
+///
+/// The keys and the arguments are available to the function through the
+/// host functions, the keys must hash to the same slot.
+///
+/// `WCALL_RO` is the read-only variant, the function can't write a key.
+///
+/// ```text
+/// WCALL sha1 function numkeys [key [key ...]] [arg [arg ...]]
+/// WCALL_RO sha1 function numkeys [key [key ...]] [arg [arg ...]]
+/// ```
+#[derive(Debug, Default)]
+pub struct WCall {
+    sha: ByteString,
+    function: ByteString,
+    keys: Vec<Bytes>,
+    args: Vec<Bytes>,
+    read_only: bool,
+}
+
+impl WCall {
+    pub fn keys(&self) -> &[Bytes] {
+        &self.keys
+    }
+
+    pub(crate) fn parse_frames(
+        parse: &mut Parse,
+        read_only: bool,
+    ) -> Result<WCall, CommandError> {
+        let sha = ByteString::from(parse.next_string()?.to_lowercase());
+        let function = parse.next_string()?;
+        let (keys, args) = parse_keys_and_args(parse)?;
+        Ok(WCall {
+            sha,
+            function,
+            keys,
+            args,
+            read_only,
+        })
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+}
+
+impl CommandExecution for WCall {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let Some(module) = ctx.supervisor.scripts().module(&self.sha).await
+        else {
+            return Err(CommandError::with_code(
+                "NOSCRIPT",
+                "No matching module. Please use WASM LOAD.",
+            )
+            .into());
+        };
+        let wasm = ctx.supervisor.wasm();
+        let module = wasm.compile(&self.sha, &module)?;
+
+        let guard = ctx.exclusive_storage().await;
+        let reply = wasm
+            .call(
+                &ctx,
+                &module,
+                &self.function,
+                &self.keys,
+                &self.args,
+                self.read_only,
+            )
+            .await;
+        drop(guard);
+
+        dst.write_frame(&reply?).await?;
+        Ok(())
+    }
+
+    fn hash_key(&self) -> Option<u16> {
+        self.keys.first().map(|key| crc_hash(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use bytes::BytesMut;
+    use redis_async::resp::{RespCodec, RespValue};
+    use redis_async::resp_array;
+    use tokio_util::codec::Encoder;
+
+    use crate::application::server::cmd::Command;
+    use crate::application::server::frame::Frame;
+
+    fn parse_cmd(obj: RespValue) -> anyhow::Result<Command> {
+        let mut bytes = BytesMut::new();
+        let mut codec = RespCodec;
+        codec.encode(obj, &mut bytes).unwrap();
+
+        let mut bytes = Cursor::new(bytes.freeze());
+        let frame = Frame::parse(&mut bytes)?;
+        let cmd = Command::from_frame(frame, &Default::default())?;
+        Ok(cmd)
+    }
+
+    #[test]
+    fn ensure_parsing() {
+        let entry: RespValue =
+            resp_array!["WCALL", "ABC", "run", "1", "key", "arg"];
+        let cmd = parse_cmd(entry).unwrap();
+        insta::assert_debug_snapshot!(cmd, @r###"
+        WCall(
+            WCall {
+                sha: "abc",
+                function: "run",
+                keys: [
+                    b"key",
+                ],
+                args: [
+                    b"arg",
+                ],
+                read_only: false,
+            },
+        )
+        "###);
+    }
+}
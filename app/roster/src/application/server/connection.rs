@@ -1,15 +1,118 @@
+use std::cell::Cell;
 use std::fmt::Debug;
 use std::io::{self, Cursor};
+use std::rc::Rc;
+use std::sync::Arc;
 
 use bytes::BytesMut;
+use monoio::buf::{IoBuf, IoBufMut, IoVecBuf, IoVecBufMut};
 use monoio::io::{
-    AsyncReadRent, BufReader, BufWriter, OwnedReadHalf, OwnedWriteHalf,
-    Splitable,
+    AsyncReadRent, AsyncWriteRent, BufReader, BufWriter, OwnedReadHalf,
+    OwnedWriteHalf, Splitable,
 };
-use monoio::net::TcpStream;
+use monoio::net::{TcpStream, UnixStream};
+use monoio::BufResult;
 
-use super::frame::write::write_frame;
-use super::frame::Frame;
+use super::frame::inline::parse_inline;
+use super::frame::write::write_value;
+use super::frame::{Frame, Protocol};
+use super::supervisor::MetadataConnection;
+use super::tls::{TlsReadHalf, TlsStream, TlsWriteHalf};
+
+/// The size of the buffer the replies are encoded into before they're sent.
+const OUTPUT_BUFFER_SIZE: usize = 8 * 1024;
+
+/// A stream accepted by the server.
+pub enum Stream {
+    Tcp(TcpStream),
+    Tls(TlsStream),
+    Unix(UnixStream),
+}
+
+/// The reading half of a [Stream].
+pub enum ReadHalf {
+    Tcp(OwnedReadHalf<TcpStream>),
+    Tls(TlsReadHalf),
+    Unix(OwnedReadHalf<UnixStream>),
+}
+
+/// The writing half of a [Stream].
+pub enum WriteHalf {
+    Tcp(OwnedWriteHalf<TcpStream>),
+    Tls(TlsWriteHalf),
+    Unix(OwnedWriteHalf<UnixStream>),
+}
+
+impl Stream {
+    pub fn into_split(self) -> (ReadHalf, WriteHalf) {
+        match self {
+            Stream::Tcp(stream) => {
+                let (read, write) = stream.into_split();
+                (ReadHalf::Tcp(read), WriteHalf::Tcp(write))
+            }
+            Stream::Tls(stream) => {
+                let (read, write) = stream.into_split();
+                (ReadHalf::Tls(read), WriteHalf::Tls(write))
+            }
+            Stream::Unix(stream) => {
+                let (read, write) = stream.into_split();
+                (ReadHalf::Unix(read), WriteHalf::Unix(write))
+            }
+        }
+    }
+}
+
+impl AsyncReadRent for ReadHalf {
+    async fn read<T: IoBufMut>(&mut self, buf: T) -> BufResult<usize, T> {
+        match self {
+            ReadHalf::Tcp(stream) => stream.read(buf).await,
+            ReadHalf::Tls(stream) => stream.read(buf).await,
+            ReadHalf::Unix(stream) => stream.read(buf).await,
+        }
+    }
+
+    async fn readv<T: IoVecBufMut>(&mut self, buf: T) -> BufResult<usize, T> {
+        match self {
+            ReadHalf::Tcp(stream) => stream.readv(buf).await,
+            ReadHalf::Tls(stream) => stream.readv(buf).await,
+            ReadHalf::Unix(stream) => stream.readv(buf).await,
+        }
+    }
+}
+
+impl AsyncWriteRent for WriteHalf {
+    async fn write<T: IoBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        match self {
+            WriteHalf::Tcp(stream) => stream.write(buf).await,
+            WriteHalf::Tls(stream) => stream.write(buf).await,
+            WriteHalf::Unix(stream) => stream.write(buf).await,
+        }
+    }
+
+    async fn writev<T: IoVecBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        match self {
+            WriteHalf::Tcp(stream) => stream.writev(buf).await,
+            WriteHalf::Tls(stream) => stream.writev(buf).await,
+            WriteHalf::Unix(stream) => stream.writev(buf).await,
+        }
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        match self {
+            WriteHalf::Tcp(stream) => stream.flush().await,
+            WriteHalf::Tls(stream) => stream.flush().await,
+            WriteHalf::Unix(stream) => stream.flush().await,
+        }
+    }
+
+    async fn shutdown(&mut self) -> io::Result<()> {
+        match self {
+            WriteHalf::Tcp(stream) => stream.shutdown().await,
+            WriteHalf::Tls(stream) => stream.shutdown().await,
+            WriteHalf::Unix(stream) => stream.shutdown().await,
+        }
+    }
+}
 
 /// Send and receive `Frame` values from a remote peer.
 ///
@@ -25,12 +128,23 @@ use super::frame::Frame;
 /// The contents of the write buffer are then written to the socket.
 pub struct WriteConnection {
     // The `TcpStream`. It is decorated with a `BufWriter`, which provides
-    // write level buffering.
-    stream_w: BufWriter<OwnedWriteHalf<TcpStream>>,
+    // write level buffering. `None` for a detached connection.
+    stream_w: Option<BufWriter<WriteHalf>>,
+    /// The protocol negotiated for this connection, shared with the
+    /// [super::context::Context].
+    protocol: Rc<Cell<Protocol>>,
+    /// When set, frames are kept here instead of being written, used by
+    /// `EXEC` to answer every queued command with a single array.
+    captured: Option<Vec<Frame>>,
+    /// When set, the replies are discarded, see `CLIENT REPLY`.
+    muted: bool,
+    /// Where the use of the output buffer is recorded, for `CLIENT LIST`.
+    /// `None` for a detached connection.
+    meta: Option<Arc<MetadataConnection>>,
 }
 
 pub struct ReadConnection {
-    pub stream_r: BufReader<OwnedReadHalf<TcpStream>>,
+    pub stream_r: BufReader<ReadHalf>,
     buffer: BytesMut,
 }
 
@@ -60,12 +174,24 @@ impl ReadConnection {
                 return Ok(Some(frame));
             }
 
+            // The socket is read into the spare capacity of the buffer, a full
+            // buffer would be read as the end of the stream.
+            if self.buffer.capacity() - self.buffer.len() < 1024 {
+                self.buffer.reserve(4 * 1024);
+            }
+
             let mut in_going = BytesMut::new();
             std::mem::swap(&mut self.buffer, &mut in_going);
 
-            // TODO: Timeout
-            let (size, buf) = self.stream_r.read(in_going).await;
-            self.buffer = buf;
+            // The read is appended after the buffered data, the buffer itself
+            // would be written from its start.
+            let start = in_going.len();
+            let end = in_going.capacity();
+            // The idle timeout is handled by the `Handler`, which stops
+            // reading.
+            let (size, buf) =
+                self.stream_r.read(in_going.slice_mut(start..end)).await;
+            self.buffer = buf.into_inner();
 
             // There is not enough buffered data to read a frame. Attempt to
             // read more data from the socket.
@@ -89,6 +215,20 @@ impl ReadConnection {
     fn parse_frame(&mut self) -> anyhow::Result<Option<Frame>> {
         use super::frame::Error::Incomplete;
 
+        // Like Redis, anything which doesn't start as a multibulk request is
+        // considered as an inline command.
+        loop {
+            match self.buffer.first() {
+                None => return Ok(None),
+                Some(b'*') => break,
+                Some(_) => match parse_inline(&mut self.buffer)? {
+                    // An empty line is ignored, like in Redis.
+                    Some(Frame::Array(args)) if args.is_empty() => continue,
+                    frame => return Ok(frame),
+                },
+            }
+        }
+
         let mut buf = Cursor::new(&self.buffer);
 
         // TODO: Change this because we do a lot of useless copy
@@ -145,7 +285,13 @@ impl ReadConnection {
         }
     }
 
-    pub fn into_inner(self) -> OwnedReadHalf<TcpStream> {
+    /// The number of bytes read but not parsed yet, with the capacity of the
+    /// read buffer.
+    pub fn buffered(&self) -> (usize, usize) {
+        (self.buffer.len(), self.buffer.capacity())
+    }
+
+    pub fn into_inner(self) -> ReadHalf {
         self.stream_r.into_inner()
     }
 }
@@ -153,15 +299,28 @@ impl ReadConnection {
 impl WriteConnection {
     /// Create a new `Connection`, backed by `socket`. Read and write buffers
     /// are initialized.
+    ///
+    /// Frames are encoded with the `protocol` currently negotiated for this
+    /// connection, the use of the output buffer is recorded on `meta`.
     pub fn new(
-        socket: TcpStream,
+        socket: Stream,
         buf_size: usize,
+        protocol: Rc<Cell<Protocol>>,
+        meta: Arc<MetadataConnection>,
     ) -> (WriteConnection, ReadConnection) {
         let (read, write) = socket.into_split();
+        meta.set_output_buffer(0, OUTPUT_BUFFER_SIZE, false);
 
         (
             WriteConnection {
-                stream_w: BufWriter::new(write),
+                stream_w: Some(BufWriter::with_capacity(
+                    OUTPUT_BUFFER_SIZE,
+                    write,
+                )),
+                protocol,
+                captured: None,
+                muted: false,
+                meta: Some(meta),
             },
             ReadConnection {
                 stream_r: BufReader::new(read),
@@ -170,6 +329,18 @@ impl WriteConnection {
         )
     }
 
+    /// Create a connection which isn't backed by a socket, every frame
+    /// written is captured. Used to apply the commands called by a script.
+    pub fn detached(protocol: Rc<Cell<Protocol>>) -> WriteConnection {
+        WriteConnection {
+            stream_w: None,
+            protocol,
+            captured: Some(Vec::new()),
+            muted: false,
+            meta: None,
+        }
+    }
+
     /// Write a single `Frame` value to the underlying stream.
     ///
     /// The `Frame` value is written to the socket using the various `write_*`
@@ -178,15 +349,88 @@ impl WriteConnection {
     /// of syscalls. However, it is fine to call these functions on a
     /// *buffered* write stream. The data will be written to the buffer.
     /// Once the buffer is full, it is flushed to the underlying socket.
+    ///
+    /// RESP3 only frames are downgraded if the connection speaks RESP2.
     pub async fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
-        write_frame(&mut self.stream_w, frame).await
+        if self.muted {
+            return Ok(());
+        }
+
+        if let Some(captured) = &mut self.captured {
+            captured.push(frame.clone());
+            return Ok(());
+        }
+
+        let record = |len, writing| {
+            if let Some(meta) = &self.meta {
+                meta.set_output_buffer(len, OUTPUT_BUFFER_SIZE, writing);
+            }
+        };
+
+        match &mut self.stream_w {
+            Some(stream_w) => {
+                record(0, true);
+                let mut written =
+                    write_value(stream_w, frame, self.protocol.get()).await;
+                if written.is_ok() {
+                    // The encoded frame waits in the buffer, `flush` writes
+                    // it to the socket.
+                    record(stream_w.buffer().len(), true);
+                    written = stream_w.flush().await;
+                }
+                record(0, false);
+                written
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "the connection is detached",
+            )),
+        }
+    }
+
+    /// Discard the next written frames instead of sending them, until it's
+    /// unset.
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted = muted;
+    }
+
+    /// Keep the next written frames instead of sending them, until
+    /// [WriteConnection::end_capture] is called.
+    pub fn start_capture(&mut self) {
+        self.captured = Some(Vec::new());
+    }
+
+    /// Stop capturing frames and return the ones written since
+    /// [WriteConnection::start_capture].
+    pub fn end_capture(&mut self) -> Vec<Frame> {
+        self.captured.take().unwrap_or_default()
+    }
+
+    /// Return the frames captured so far and keep capturing.
+    pub fn take_captured(&mut self) -> Vec<Frame> {
+        self.captured
+            .as_mut()
+            .map(std::mem::take)
+            .unwrap_or_default()
     }
 
-    pub fn into_inner(self) -> OwnedWriteHalf<TcpStream> {
-        self.stream_w.into_inner()
+    /// # Panics
+    ///
+    /// Panics if the connection is detached.
+    pub fn into_inner(self) -> WriteHalf {
+        self.stream_w
+            .expect("A detached connection has no stream")
+            .into_inner()
     }
 
-    pub fn reunite(self, read: ReadConnection) -> TcpStream {
-        self.into_inner().reunite(read.into_inner()).unwrap()
+    /// Reunite the halves of a plain TCP connection, `None` for the other
+    /// streams which can't be sent to another thread.
+    pub fn reunite(self, read: ReadConnection) -> Option<TcpStream> {
+        match (self.into_inner(), read.into_inner()) {
+            (WriteHalf::Tcp(write), ReadHalf::Tcp(read)) => {
+                Some(write.reunite(read).unwrap())
+            }
+            _ => None,
+        }
     }
 }
@@ -1,34 +1,336 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell, RefMut};
+use std::rc::Rc;
 use std::sync::Arc;
 
+use bytes::Bytes;
 use coarsetime::Instant;
+use tokio::sync::OwnedRwLockWriteGuard;
 
-use super::supervisor::{MetadataConnection, Supervisor};
-use crate::domain::storage::StorageSegment;
+use super::frame::Protocol;
+use super::lua::LuaEngine;
+use super::subscription::{SubscriptionKind, Subscriptions};
+use super::supervisor::{ConnectionState, MetadataConnection, Supervisor};
+use super::transaction::Transaction;
+use crate::domain::pubsub::{ChannelRegistry, Subscriber};
+use crate::domain::storage::{Storage, StorageSegment};
+use crate::domain::tracking::TrackingOptions;
 
 /// [Context] is available for the whole duration of the TCP Connection.
 #[derive(Clone)]
 pub struct Context {
     pub storage: StorageSegment,
+    /// Every [StorageSegment] of the server, the shard channels live on the
+    /// segment owning their hash slot.
+    pub segments: Rc<Storage>,
     pub supervisor: Supervisor,
     pub connection: Arc<MetadataConnection>,
+    /// The protocol negotiated with `HELLO`, shared by every clone of the
+    /// [Context] of this connection.
+    protocol: Rc<Cell<Protocol>>,
+    /// The `MULTI` / `WATCH` state of this connection.
+    transaction: Rc<RefCell<Transaction>>,
+    /// The Pub/Sub subscriptions of this connection.
+    subscriptions: Rc<RefCell<Subscriptions>>,
+    /// The client side caching options of this connection, `None` when the
+    /// tracking is disabled.
+    tracking: Rc<RefCell<Option<TrackingOptions>>>,
+    /// Set by `CLIENT CACHING` for the next command only.
+    caching: Rc<Cell<Option<bool>>>,
+    /// Set by `CLIENT REPLY`.
+    reply: Rc<Cell<ReplyMode>>,
+    /// Set while the connection holds the exclusive access on the storage.
+    exclusive: Rc<Cell<bool>>,
+    /// The Lua interpreter of the thread.
+    lua: Rc<LuaEngine>,
     now: Cell<bool>,
 }
 
+/// Which replies are sent to the client, set by `CLIENT REPLY`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ReplyMode {
+    #[default]
+    On,
+    Off,
+    /// The reply of the next command only is skipped.
+    Skip,
+}
+
+/// The exclusive access on the storage held by a connection, see
+/// [Context::exclusive_storage].
+pub struct ExclusiveStorage {
+    _guard: OwnedRwLockWriteGuard<()>,
+    held: Rc<Cell<bool>>,
+}
+
+impl Drop for ExclusiveStorage {
+    fn drop(&mut self) {
+        self.held.set(false);
+    }
+}
+
 impl Context {
     pub fn new(
         storage: StorageSegment,
+        segments: Rc<Storage>,
         supervisor: Supervisor,
         meta_conn: Arc<MetadataConnection>,
+        lua: Rc<LuaEngine>,
     ) -> Self {
+        let subscriptions = Subscriptions::default();
+        meta_conn.attach_messages(subscriptions.sender());
+
         Self {
             storage,
+            segments,
             supervisor,
             connection: meta_conn,
+            protocol: Rc::new(Cell::new(Protocol::default())),
+            transaction: Default::default(),
+            subscriptions: Rc::new(RefCell::new(subscriptions)),
+            tracking: Default::default(),
+            caching: Default::default(),
+            reply: Default::default(),
+            exclusive: Default::default(),
+            lua,
             now: Cell::new(false),
         }
     }
 
+    /// The protocol currently used by the connection.
+    pub fn protocol(&self) -> Protocol {
+        self.protocol.get()
+    }
+
+    /// Switch the protocol used by the connection.
+    pub fn set_protocol(&self, protocol: Protocol) {
+        self.protocol.set(protocol);
+    }
+
+    /// Give a handle on the protocol of the connection so the encoder can
+    /// follow the negotiated version.
+    pub fn protocol_handle(&self) -> Rc<Cell<Protocol>> {
+        self.protocol.clone()
+    }
+
+    /// The Lua interpreter running the scripts of the connection.
+    pub fn lua(&self) -> &LuaEngine {
+        &self.lua
+    }
+
+    /// Acquire the exclusive access on the storage, `None` when the connection
+    /// already holds it, like a script called inside a transaction.
+    pub async fn exclusive_storage(&self) -> Option<ExclusiveStorage> {
+        if self.exclusive.get() {
+            return None;
+        }
+
+        let guard = self.storage.exclusive().await;
+        self.exclusive.set(true);
+        Some(ExclusiveStorage {
+            _guard: guard,
+            held: self.exclusive.clone(),
+        })
+    }
+
+    /// Access the transaction state of the connection.
+    ///
+    /// The borrow must not be held across an `.await`.
+    pub fn transaction(&self) -> RefMut<'_, Transaction> {
+        self.transaction.borrow_mut()
+    }
+
+    /// Access the Pub/Sub subscriptions of the connection.
+    ///
+    /// The borrow must not be held across an `.await`.
+    pub fn subscriptions(&self) -> RefMut<'_, Subscriptions> {
+        self.subscriptions.borrow_mut()
+    }
+
+    fn registry(
+        &self,
+        kind: SubscriptionKind,
+        channel: &[u8],
+    ) -> &ChannelRegistry {
+        match kind {
+            SubscriptionKind::Channel => self.supervisor.pubsub().channels(),
+            SubscriptionKind::Pattern => self.supervisor.pubsub().patterns(),
+            SubscriptionKind::Shard => self.segments.shard_channels(channel),
+        }
+    }
+
+    /// Subscribe the connection to a channel, a pattern or a shard channel.
+    ///
+    /// Return the number of subscriptions of the connection, see
+    /// [Subscriptions::count].
+    pub async fn subscribe(
+        &self,
+        kind: SubscriptionKind,
+        channel: Bytes,
+    ) -> usize {
+        let (inserted, sender) = {
+            let mut subscriptions = self.subscriptions();
+            (
+                subscriptions.insert(kind, channel.clone()),
+                subscriptions.sender(),
+            )
+        };
+
+        if inserted {
+            self.registry(kind, &channel)
+                .subscribe(channel, self.connection.id(), sender)
+                .await;
+        }
+
+        self.update_subscriptions(kind)
+    }
+
+    /// Unsubscribe the connection from a channel, a pattern or a shard
+    /// channel.
+    ///
+    /// Return the number of subscriptions of the connection, see
+    /// [Subscriptions::count].
+    pub async fn unsubscribe(
+        &self,
+        kind: SubscriptionKind,
+        channel: &Bytes,
+    ) -> usize {
+        let removed = self.subscriptions().remove(kind, channel);
+        if removed {
+            self.registry(kind, channel)
+                .unsubscribe(channel, self.connection.id())
+                .await;
+        }
+
+        self.update_subscriptions(kind)
+    }
+
+    /// Remove every subscription of the connection, used when the connection
+    /// is closed.
+    pub async fn unsubscribe_all(&self) {
+        for kind in [
+            SubscriptionKind::Channel,
+            SubscriptionKind::Pattern,
+            SubscriptionKind::Shard,
+        ] {
+            let channels = self.subscriptions().list(kind);
+            for channel in channels {
+                self.unsubscribe(kind, &channel).await;
+            }
+        }
+    }
+
+    /// The client side caching options of the connection, `None` when the
+    /// tracking is disabled.
+    pub fn tracking(&self) -> Option<TrackingOptions> {
+        self.tracking.borrow().clone()
+    }
+
+    /// Enable the tracking of the keys read by the connection, the
+    /// invalidation messages are sent to `target`.
+    pub async fn enable_tracking(
+        &self,
+        options: TrackingOptions,
+        target: Subscriber,
+    ) {
+        let table = self.supervisor.tracking();
+        let id = self.connection.id();
+        let own = self.subscriptions().sender();
+
+        table.enable(id, options, own, target).await;
+        *self.tracking.borrow_mut() = table.options(id).await;
+    }
+
+    /// Disable the tracking of the keys read by the connection.
+    pub async fn disable_tracking(&self) {
+        if self.tracking.borrow_mut().take().is_some() {
+            self.supervisor
+                .tracking()
+                .disable(self.connection.id())
+                .await;
+        }
+    }
+
+    /// Copy the state of the connection to its [MetadataConnection], so
+    /// it's shown by `CLIENT LIST` on any thread.
+    pub fn sync_connection(&self) {
+        let transaction = self.transaction.borrow();
+        let tracking = self.tracking.borrow();
+        self.connection.set_state(ConnectionState {
+            multi: transaction.queued(),
+            watch: transaction.watched(),
+            protocol: self.protocol(),
+            tracking: tracking.is_some(),
+            bcast: tracking.as_ref().is_some_and(|options| options.bcast),
+            redirect: tracking.as_ref().and_then(|options| options.redirect),
+        });
+    }
+
+    /// The `CLIENT CACHING` choice applied to the current command.
+    pub fn caching(&self) -> Option<bool> {
+        self.caching.get()
+    }
+
+    /// Set the `CLIENT CACHING` choice of the next command.
+    pub fn set_caching(&self, caching: Option<bool>) {
+        self.caching.set(caching);
+    }
+
+    /// Set which replies are sent to the client.
+    pub fn set_reply_mode(&self, mode: ReplyMode) {
+        self.reply.set(mode);
+    }
+
+    /// Tell if the reply of the command about to run is discarded, a
+    /// [ReplyMode::Skip] only applies to this command.
+    pub fn take_reply_skipped(&self) -> bool {
+        match self.reply.get() {
+            ReplyMode::On => false,
+            ReplyMode::Off => true,
+            ReplyMode::Skip => {
+                self.reply.set(ReplyMode::On);
+                true
+            }
+        }
+    }
+
+    /// The connection read `key`, remember it if the connection is tracking
+    /// the keys it reads.
+    pub async fn track_key(&self, key: &[u8]) {
+        let tracked = match &*self.tracking.borrow() {
+            Some(options) if options.bcast => false,
+            Some(options) if options.optin => self.caching() == Some(true),
+            Some(options) if options.optout => self.caching() != Some(false),
+            Some(_) => true,
+            None => false,
+        };
+
+        if tracked {
+            self.supervisor
+                .tracking()
+                .remember(self.connection.id(), Bytes::copy_from_slice(key))
+                .await;
+        }
+    }
+
+    /// The connection modified `key`, the connections tracking it are
+    /// notified.
+    pub async fn signal_modified_key(&self, key: &[u8]) {
+        self.supervisor
+            .tracking()
+            .invalidate(key, Some(self.connection.id()))
+            .await;
+    }
+
+    fn update_subscriptions(&self, kind: SubscriptionKind) -> usize {
+        let subscriptions = self.subscriptions();
+        self.connection.set_subscriptions(
+            subscriptions.len(SubscriptionKind::Channel),
+            subscriptions.len(SubscriptionKind::Pattern),
+            subscriptions.len(SubscriptionKind::Shard),
+        );
+        subscriptions.count(kind)
+    }
+
     #[allow(dead_code)]
     pub fn is_in_slot(&self, hash: u16) -> bool {
         self.storage.is_in_slot(hash)
@@ -0,0 +1,272 @@
+//! Inline commands are the way to talk to roster without a redis client, like
+//! with `telnet` or `netcat`: a command is a line of space separated
+//! arguments.
+//!
+//! ```text
+//! SET "my key" 'my value'
+//! ```
+
+use bytes::{Bytes, BytesMut};
+
+use super::{Error, Frame};
+
+/// Maximum size of an inline request, after this we consider the client is
+/// sending garbage.
+const INLINE_MAX_SIZE: usize = 64 * 1024;
+
+/// Try to parse an inline command from the start of `buffer`.
+///
+/// The consumed bytes are removed from `buffer`.
+///
+/// # Returns
+///
+/// `Ok(None)` if there is not enough data to find a full line, otherwise the
+/// command is returned as an array of bulk strings. An empty line gives an
+/// empty array which should be ignored.
+pub(crate) fn parse_inline(
+    buffer: &mut BytesMut,
+) -> Result<Option<Frame>, Error> {
+    let Some(newline) = buffer.iter().position(|b| *b == b'\n') else {
+        if buffer.len() > INLINE_MAX_SIZE {
+            return Err("Protocol error: too big inline request".into());
+        }
+        return Ok(None);
+    };
+
+    let line = buffer.split_to(newline + 1);
+    let mut line = &line[..newline];
+    if let [rest @ .., b'\r'] = line {
+        line = rest;
+    }
+
+    let args = split_args(line)
+        .ok_or("Protocol error: unbalanced quotes in request")?;
+
+    Ok(Some(Frame::Array(
+        args.into_iter().map(Frame::Bulk).collect(),
+    )))
+}
+
+/// Split a line into arguments, following the rules of `sdssplitargs` from
+/// Redis:
+///
+/// - Arguments are separated by spaces.
+/// - Double quoted arguments support the `\n`, `\r`, `\t`, `\b`, `\a` and
+///   `\xHH` escapes.
+/// - Single quoted arguments only support `\'`.
+/// - A closing quote must be followed by a space or by the end of the line.
+///
+/// Return `None` if the quotes are unbalanced.
+pub(crate) fn split_args(line: &[u8]) -> Option<Vec<Bytes>> {
+    let mut args = Vec::new();
+    let mut src = line;
+
+    loop {
+        // Skip blanks
+        while let [first, rest @ ..] = src {
+            if !is_space(*first) {
+                break;
+            }
+            src = rest;
+        }
+
+        // Like the C implementation, a `\0` ends the line.
+        if matches!(src, [] | [b'\0', ..]) {
+            return Some(args);
+        }
+
+        let mut current = BytesMut::new();
+        let mut in_double_quotes = false;
+        let mut in_single_quotes = false;
+
+        loop {
+            if in_double_quotes {
+                match src {
+                    [b'\\', b'x', a, b, rest @ ..]
+                        if a.is_ascii_hexdigit() && b.is_ascii_hexdigit() =>
+                    {
+                        current.extend_from_slice(&[hex(*a) * 16 + hex(*b)]);
+                        src = rest;
+                    }
+                    [b'\\', c, rest @ ..] => {
+                        let c = match c {
+                            b'n' => b'\n',
+                            b'r' => b'\r',
+                            b't' => b'\t',
+                            b'b' => 0x08,
+                            b'a' => 0x07,
+                            c => *c,
+                        };
+                        current.extend_from_slice(&[c]);
+                        src = rest;
+                    }
+                    [b'"', rest @ ..] => {
+                        // Closing quote must be followed by a space or
+                        // nothing at all.
+                        if rest.first().is_some_and(|c| !is_space(*c)) {
+                            return None;
+                        }
+                        src = rest;
+                        break;
+                    }
+                    [c, rest @ ..] => {
+                        current.extend_from_slice(&[*c]);
+                        src = rest;
+                    }
+                    // Unterminated quotes
+                    [] => return None,
+                }
+            } else if in_single_quotes {
+                match src {
+                    [b'\\', b'\'', rest @ ..] => {
+                        current.extend_from_slice(b"'");
+                        src = rest;
+                    }
+                    [b'\'', rest @ ..] => {
+                        if rest.first().is_some_and(|c| !is_space(*c)) {
+                            return None;
+                        }
+                        src = rest;
+                        break;
+                    }
+                    [c, rest @ ..] => {
+                        current.extend_from_slice(&[*c]);
+                        src = rest;
+                    }
+                    [] => return None,
+                }
+            } else {
+                match src {
+                    [c, ..] if is_space(*c) || *c == b'\0' => break,
+                    [b'"', rest @ ..] => {
+                        in_double_quotes = true;
+                        src = rest;
+                    }
+                    [b'\'', rest @ ..] => {
+                        in_single_quotes = true;
+                        src = rest;
+                    }
+                    [c, rest @ ..] => {
+                        current.extend_from_slice(&[*c]);
+                        src = rest;
+                    }
+                    [] => break,
+                }
+            }
+        }
+
+        args.push(current.freeze());
+    }
+}
+
+#[inline]
+fn is_space(c: u8) -> bool {
+    matches!(c, b' ' | b'\n' | b'\r' | b'\t' | 0x0b | 0x0c)
+}
+
+#[inline]
+fn hex(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        b'a'..=b'f' => c - b'a' + 10,
+        b'A'..=b'F' => c - b'A' + 10,
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::{Bytes, BytesMut};
+
+    use super::{parse_inline, split_args};
+    use crate::application::server::frame::Frame;
+
+    fn args(line: &[u8]) -> Option<Vec<String>> {
+        split_args(line).map(|args| {
+            args.into_iter()
+                .map(|x| String::from_utf8_lossy(&x).to_string())
+                .collect()
+        })
+    }
+
+    #[test]
+    fn split_simple() {
+        insta::assert_debug_snapshot!(args(b"  SET   key  value "), @r###"
+        Some(
+            [
+                "SET",
+                "key",
+                "value",
+            ],
+        )
+        "###);
+        assert_eq!(args(b""), Some(vec![]));
+        assert_eq!(args(b"   "), Some(vec![]));
+    }
+
+    #[test]
+    fn split_quotes() {
+        insta::assert_debug_snapshot!(args(br#"SET "my key" 'it\'s' "a\x41\n" """#), @r###"
+        Some(
+            [
+                "SET",
+                "my key",
+                "it's",
+                "aA\n",
+                "",
+            ],
+        )
+        "###);
+        // A quote in the middle of an argument also starts a quoted part.
+        assert_eq!(args(br#"a"b c"#), None);
+        assert_eq!(
+            args(br#"a"b" c"#),
+            Some(vec!["ab".to_string(), "c".to_string()])
+        );
+    }
+
+    #[test]
+    fn split_unbalanced() {
+        assert_eq!(args(br#"SET "key"#), None);
+        assert_eq!(args(b"SET 'key"), None);
+        assert_eq!(args(br#"SET "key"value"#), None);
+        assert_eq!(args(b"SET 'key'value"), None);
+    }
+
+    #[test]
+    fn parse_inline_lines() {
+        let mut buffer = BytesMut::from(&b"\r\nPING\r\nGET a\nSET"[..]);
+
+        let frame = parse_inline(&mut buffer).unwrap();
+        assert_eq!(frame, Some(Frame::Array(vec![])));
+
+        let frame = parse_inline(&mut buffer).unwrap();
+        assert_eq!(
+            frame,
+            Some(Frame::Array(vec![Frame::Bulk(Bytes::from_static(b"PING"))]))
+        );
+
+        let frame = parse_inline(&mut buffer).unwrap();
+        assert_eq!(
+            frame,
+            Some(Frame::Array(vec![
+                Frame::Bulk(Bytes::from_static(b"GET")),
+                Frame::Bulk(Bytes::from_static(b"a"))
+            ]))
+        );
+
+        assert_eq!(parse_inline(&mut buffer).unwrap(), None);
+        assert_eq!(&buffer[..], b"SET");
+    }
+
+    #[test]
+    fn parse_inline_unbalanced() {
+        let mut buffer = BytesMut::from(&b"GET \"a\r\n"[..]);
+        let err = parse_inline(&mut buffer).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid message encoding: Protocol error: unbalanced quotes in \
+             request"
+        );
+    }
+}
@@ -8,49 +8,59 @@ use std::string::FromUtf8Error;
 
 use bytes::{Buf, Bytes, BytesMut};
 use bytestring::ByteString;
-use indexmap::IndexMap;
 
+pub(crate) mod inline;
 pub(crate) mod write;
 
+/// The version of the Redis protocol spoken on a connection.
+///
+/// Every connection starts in RESP2 and can switch with `HELLO`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Protocol {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
+impl Protocol {
+    /// Get the [Protocol] associated to the version sent in `HELLO`.
+    pub fn from_version(version: i64) -> Option<Protocol> {
+        match version {
+            2 => Some(Protocol::Resp2),
+            3 => Some(Protocol::Resp3),
+            _ => None,
+        }
+    }
+
+    pub fn version(&self) -> i64 {
+        match self {
+            Protocol::Resp2 => 2,
+            Protocol::Resp3 => 3,
+        }
+    }
+}
+
 /// A frame in the Redis protocol.
-#[derive(Clone, Debug, Eq, PartialEq)]
+///
+/// Frames which only exist in RESP3 are downgraded to their RESP2
+/// counterpart when written to a RESP2 connection.
+#[derive(Clone, Debug, PartialEq)]
 pub enum Frame {
     Simple(ByteString),
     Error(ByteString),
-    Integer(u64),
+    Integer(i64),
     Bulk(Bytes),
     Null,
+    /// The null array of RESP2 (`*-1`), which is a plain null in RESP3.
+    NullArray,
     Array(Vec<Frame>),
-    Map(IndexMap<Frame, Frame>),
-}
-
-// TODO(@miaxos): hacky hash derivation for now, to test a little.
-impl core::hash::Hash for Frame {
-    fn hash<H: core::hash::Hasher>(&self, ra_expand_state: &mut H) {
-        core::mem::discriminant(self).hash(ra_expand_state);
-        match self {
-            Frame::Simple(f0) => {
-                f0.hash(ra_expand_state);
-            }
-            Frame::Error(f0) => {
-                f0.hash(ra_expand_state);
-            }
-            Frame::Integer(f0) => {
-                f0.hash(ra_expand_state);
-            }
-            Frame::Bulk(f0) => {
-                f0.hash(ra_expand_state);
-            }
-            Frame::Null => {}
-            Frame::Array(f0) => {
-                f0.hash(ra_expand_state);
-            }
-            Frame::Map(_) => {
-                // TODO: Should test the behavior of redis in this case.
-                unimplemented!("")
-            }
-        }
-    }
+    /// The pairs of a map, in the order they are sent.
+    Map(Vec<(Frame, Frame)>),
+    Set(Vec<Frame>),
+    /// Out of band data like Pub/Sub messages, an array in RESP2.
+    Push(Vec<Frame>),
+    Double(f64),
+    Boolean(bool),
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -81,20 +91,28 @@ impl Frame {
                 Ok(())
             }
             b'$' => {
-                if b'-' == peek_u8_mut(src)? {
-                    // Skip '-1\r\n'
-                    skip_mut(src, 4)
-                } else {
-                    // Read the bulk string
-                    let len: usize = get_decimal_mut(src)?.try_into()?;
+                let len = get_decimal_mut(src)?;
 
-                    // skip that number of bytes + 2 (\r\n).
-                    skip_mut(src, len + 2)
+                // A null bulk string is encoded as `$-1\r\n`.
+                if len == -1 {
+                    return Ok(());
                 }
+
+                // Read the bulk string
+                let len: usize = len.try_into()?;
+
+                // skip that number of bytes + 2 (\r\n).
+                skip_mut(src, len + 2)
             }
             b'*' => {
                 let len = get_decimal_mut(src)?;
 
+                // A null array is encoded as `*-1\r\n`.
+                if len == -1 {
+                    return Ok(());
+                }
+
+                let len: usize = len.try_into()?;
                 for _ in 0..len {
                     Frame::check(src)?;
                 }
@@ -102,7 +120,7 @@ impl Frame {
                 Ok(())
             }
             b'%' => {
-                let len = get_decimal_mut(src)?;
+                let len: usize = get_decimal_mut(src)?.try_into()?;
 
                 // Key and value frames
                 for _ in 0..(len * 2) {
@@ -111,6 +129,19 @@ impl Frame {
 
                 Ok(())
             }
+            b'~' | b'>' => {
+                let len: usize = get_decimal_mut(src)?.try_into()?;
+
+                for _ in 0..len {
+                    Frame::check(src)?;
+                }
+
+                Ok(())
+            }
+            b'_' | b'#' | b',' => {
+                get_line_mut_no_return(src)?;
+                Ok(())
+            }
             actual => Err(format!(
                 "protocol error; invalid frame type byte `{}`",
                 actual
@@ -137,8 +168,8 @@ impl Frame {
                 Ok(Frame::Error(string))
             }
             b':' => {
-                let len = get_decimal(src)?;
-                Ok(Frame::Integer(len))
+                let value = get_decimal(src)?;
+                Ok(Frame::Integer(value))
             }
             b'$' => {
                 if b'-' == peek_u8(src)? {
@@ -171,7 +202,13 @@ impl Frame {
                 }
             }
             b'*' => {
-                let len = get_decimal(src)?.try_into()?;
+                let len = get_decimal(src)?;
+
+                if len == -1 {
+                    return Ok(Frame::NullArray);
+                }
+
+                let len = len.try_into()?;
                 let mut out = Vec::with_capacity(len);
 
                 for _ in 0..len {
@@ -182,17 +219,67 @@ impl Frame {
             }
             b'%' => {
                 let len = get_decimal(src)?.try_into()?;
-                let mut out = IndexMap::with_capacity(len);
+                let mut out = Vec::with_capacity(len);
 
                 for _ in (0..(len * 2)).step_by(2) {
                     let key = Frame::parse(src)?;
                     let value = Frame::parse(src)?;
-                    out.insert(key, value);
+                    out.push((key, value));
                 }
 
                 Ok(Frame::Map(out))
             }
-            _ => unimplemented!(),
+            kind @ (b'~' | b'>') => {
+                let len = get_decimal(src)?.try_into()?;
+                let mut out = Vec::with_capacity(len);
+
+                for _ in 0..len {
+                    out.push(Frame::parse(src)?);
+                }
+
+                if kind == b'>' {
+                    Ok(Frame::Push(out))
+                } else {
+                    Ok(Frame::Set(out))
+                }
+            }
+            b'_' => {
+                let line = get_line(src)?;
+
+                if !line.is_empty() {
+                    return Err("protocol error; invalid frame format".into());
+                }
+
+                Ok(Frame::Null)
+            }
+            b'#' => {
+                let line = get_line(src)?;
+
+                match &line[..] {
+                    b"t" => Ok(Frame::Boolean(true)),
+                    b"f" => Ok(Frame::Boolean(false)),
+                    _ => Err("protocol error; invalid frame format".into()),
+                }
+            }
+            b',' => {
+                let line = get_line(src)?;
+
+                let value = std::str::from_utf8(&line)
+                    .ok()
+                    .and_then(|x| match x {
+                        "inf" => Some(f64::INFINITY),
+                        "-inf" => Some(f64::NEG_INFINITY),
+                        x => x.parse::<f64>().ok(),
+                    })
+                    .ok_or("protocol error; invalid frame format")?;
+
+                Ok(Frame::Double(value))
+            }
+            actual => Err(format!(
+                "protocol error; invalid frame type byte `{}`",
+                actual
+            )
+            .into()),
         }
     }
 }
@@ -205,14 +292,6 @@ fn peek_u8(src: &mut Cursor<Bytes>) -> Result<u8, Error> {
     Ok(src.chunk()[0])
 }
 
-fn peek_u8_mut(src: &mut Cursor<&BytesMut>) -> Result<u8, Error> {
-    if !src.has_remaining() {
-        return Err(Error::Incomplete);
-    }
-
-    Ok(src.chunk()[0])
-}
-
 fn get_u8(src: &mut Cursor<Bytes>) -> Result<u8, Error> {
     if !src.has_remaining() {
         return Err(Error::Incomplete);
@@ -247,27 +326,27 @@ fn skip_mut(src: &mut Cursor<&BytesMut>, n: usize) -> Result<(), Error> {
     Ok(())
 }
 
-/// Read a new-line terminated decimal
+/// Read a new-line terminated signed decimal
 #[inline]
-fn get_decimal(src: &mut Cursor<Bytes>) -> Result<u64, Error> {
+fn get_decimal(src: &mut Cursor<Bytes>) -> Result<i64, Error> {
     use atoi_simd::parse;
 
     let line = get_line(src)?;
 
-    parse::<u64>(&line)
+    parse::<i64>(&line)
         .map_err(|_| "protocol error; invalid frame format".into())
 }
 
-/// Read a new-line terminated decimal
+/// Read a new-line terminated signed decimal
 #[inline]
-fn get_decimal_mut(src: &mut Cursor<&BytesMut>) -> Result<u64, Error> {
+fn get_decimal_mut(src: &mut Cursor<&BytesMut>) -> Result<i64, Error> {
     use atoi_simd::parse;
 
     let range = get_line_mut(src)?;
 
     let line = &src.get_ref().as_ref()[range];
 
-    parse::<u64>(line)
+    parse::<i64>(line)
         .map_err(|_| "protocol error; invalid frame format".into())
 }
 
@@ -359,10 +438,20 @@ impl From<TryFromIntError> for Error {
 mod tests {
     use std::io::Cursor;
 
-    use bytes::BytesMut;
+    use bytes::{Bytes, BytesMut};
 
     use super::Frame;
 
+    fn parse(input: &'static [u8]) -> Frame {
+        let b = BytesMut::from(input);
+        let mut cur = Cursor::new(&b);
+        Frame::check(&mut cur).unwrap();
+        assert_eq!(cur.position() as usize, input.len());
+
+        let mut cur = Cursor::new(Bytes::from_static(input));
+        Frame::parse(&mut cur).unwrap()
+    }
+
     #[test]
     fn test_simple_frame() {
         let test_case: Vec<&[u8]> = vec![
@@ -388,4 +477,58 @@ mod tests {
             assert!(Frame::check(&mut cur).is_ok());
         }
     }
+
+    #[test]
+    fn test_negative_integer_frame() {
+        assert_eq!(parse(b":-42\r\n"), Frame::Integer(-42));
+        assert_eq!(
+            parse(b":-9223372036854775808\r\n"),
+            Frame::Integer(i64::MIN)
+        );
+        assert_eq!(
+            parse(b":9223372036854775807\r\n"),
+            Frame::Integer(i64::MAX)
+        );
+    }
+
+    #[test]
+    fn test_null_array_frame() {
+        assert_eq!(parse(b"*-1\r\n"), Frame::NullArray);
+        assert_eq!(
+            parse(b"*2\r\n*-1\r\n:-1\r\n"),
+            Frame::Array(vec![Frame::NullArray, Frame::Integer(-1)])
+        );
+    }
+
+    #[test]
+    fn test_resp3_frames() {
+        assert_eq!(parse(b"_\r\n"), Frame::Null);
+        assert_eq!(parse(b"#t\r\n"), Frame::Boolean(true));
+        assert_eq!(parse(b"#f\r\n"), Frame::Boolean(false));
+        assert_eq!(parse(b",1.5\r\n"), Frame::Double(1.5));
+        assert_eq!(parse(b",-inf\r\n"), Frame::Double(f64::NEG_INFINITY));
+        assert_eq!(
+            parse(b"~2\r\n:1\r\n+a\r\n"),
+            Frame::Set(vec![Frame::Integer(1), Frame::Simple("a".into())])
+        );
+        assert_eq!(
+            parse(b">2\r\n+message\r\n:1\r\n"),
+            Frame::Push(vec![
+                Frame::Simple("message".into()),
+                Frame::Integer(1)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_negative_length_rejected() {
+        let test_case: Vec<&[u8]> =
+            vec![b"*-2\r\n", b"%-1\r\n", b"$-2\r\nab\r\n"];
+
+        for t in test_case {
+            let b = BytesMut::from(t);
+            let mut cur = Cursor::new(&b);
+            assert!(Frame::check(&mut cur).is_err());
+        }
+    }
 }
@@ -4,12 +4,12 @@ use std::io::Cursor;
 use monoio::buf::IoBuf;
 use monoio::io::AsyncWriteRent;
 
-use crate::application::server::frame::Frame;
+use crate::application::server::frame::{Frame, Protocol};
 
-/// Write a decimal value
+/// Write a signed decimal value
 async fn write_decimal(
     buf_w: &mut impl AsyncWriteRent,
-    val: u64,
+    val: i64,
 ) -> io::Result<()> {
     use std::io::Write;
 
@@ -26,57 +26,138 @@ async fn write_decimal(
     Ok(())
 }
 
+/// Format a double the way Redis does: `inf`, `-inf` and a shortest
+/// representation which switches to the exponent notation for really small or
+/// really big values.
+pub(crate) fn format_double(val: f64) -> String {
+    if val.is_nan() {
+        "nan".to_string()
+    } else if val.is_infinite() {
+        if val.is_sign_positive() {
+            "inf".to_string()
+        } else {
+            "-inf".to_string()
+        }
+    } else if val != 0.0 && (val.abs() >= 1e17 || val.abs() < 1e-4) {
+        let formatted = format!("{:e}", val);
+        match formatted.split_once('e') {
+            Some((mantissa, exp)) if !exp.starts_with('-') => {
+                format!("{mantissa}e+{exp}")
+            }
+            _ => formatted,
+        }
+    } else {
+        format!("{}", val)
+    }
+}
+
 /// Write a value
 #[async_recursion::async_recursion(?Send)]
-async fn write_value(
+pub(crate) async fn write_value(
     buf_w: &mut impl AsyncWriteRent,
     frame: &Frame,
+    protocol: Protocol,
 ) -> io::Result<()> {
     match frame {
         Frame::Simple(val) => {
-            buf_w.write(&[b'+']).await.0?;
+            buf_w.write(b"+").await.0?;
             buf_w.write(val.as_bytes().slice(..)).await.0?;
-            buf_w.write(&[b'\r', b'\n']).await.0?;
+            buf_w.write(b"\r\n").await.0?;
         }
         Frame::Error(val) => {
-            buf_w.write(&[b'-']).await.0?;
+            buf_w.write(b"-").await.0?;
             buf_w.write(val.as_bytes().slice(..)).await.0?;
-            buf_w.write(&[b'\r', b'\n']).await.0?;
+            buf_w.write(b"\r\n").await.0?;
         }
         Frame::Integer(val) => {
-            buf_w.write(&[b':']).await.0?;
+            buf_w.write(b":").await.0?;
             write_decimal(buf_w, *val).await?;
         }
-        Frame::Null => {
-            buf_w.write(b"$-1\r\n").await.0?;
+        Frame::Null => match protocol {
+            Protocol::Resp2 => {
+                buf_w.write(b"$-1\r\n").await.0?;
+            }
+            Protocol::Resp3 => {
+                buf_w.write(b"_\r\n").await.0?;
+            }
+        },
+        Frame::NullArray => match protocol {
+            Protocol::Resp2 => {
+                buf_w.write(b"*-1\r\n").await.0?;
+            }
+            Protocol::Resp3 => {
+                buf_w.write(b"_\r\n").await.0?;
+            }
+        },
+        Frame::Boolean(val) => {
+            let encoded: &'static [u8] = match (protocol, val) {
+                (Protocol::Resp2, true) => b":1\r\n",
+                (Protocol::Resp2, false) => b":0\r\n",
+                (Protocol::Resp3, true) => b"#t\r\n",
+                (Protocol::Resp3, false) => b"#f\r\n",
+            };
+            buf_w.write(encoded).await.0?;
+        }
+        Frame::Double(val) => {
+            let formatted = format_double(*val);
+            match protocol {
+                Protocol::Resp2 => {
+                    buf_w.write(b"$").await.0?;
+                    write_decimal(buf_w, formatted.len() as i64).await?;
+                }
+                Protocol::Resp3 => {
+                    buf_w.write(b",").await.0?;
+                }
+            }
+            buf_w.write(formatted.into_bytes()).await.0?;
+            buf_w.write(b"\r\n").await.0?;
         }
         Frame::Bulk(val) => {
             let len = val.len();
 
-            buf_w.write([b'$'].as_slice()).await.0?;
-            write_decimal(buf_w, len as u64).await?;
+            buf_w.write(b"$").await.0?;
+            write_decimal(buf_w, len as i64).await?;
             buf_w.write(val.slice(..)).await.0?;
-            buf_w.write(&[b'\r', b'\n']).await.0?;
+            buf_w.write(b"\r\n").await.0?;
         }
         Frame::Map(val) => {
             let len = val.len();
 
-            buf_w.write([b'%'].as_slice()).await.0?;
-            write_decimal(buf_w, len as u64).await?;
+            // A map is a flat array of key & value in RESP2.
+            match protocol {
+                Protocol::Resp2 => {
+                    buf_w.write(b"*").await.0?;
+                    write_decimal(buf_w, (len * 2) as i64).await?;
+                }
+                Protocol::Resp3 => {
+                    buf_w.write(b"%").await.0?;
+                    write_decimal(buf_w, len as i64).await?;
+                }
+            }
             for (key, value) in val {
-                write_value(buf_w, key).await?;
-                write_value(buf_w, value).await?;
+                write_value(buf_w, key, protocol).await?;
+                write_value(buf_w, value, protocol).await?;
             }
-            // buf_w.write(&[b'\r', b'\n']).await.0?;
         }
-        Frame::Array(val) => {
-            // Encode the length of the array.
-            buf_w.write(&[b'*']).await.0?;
-            write_decimal(buf_w, val.len() as u64).await?;
+        Frame::Array(val) | Frame::Set(val) | Frame::Push(val) => {
+            // Encode the length of the array, sets & pushes are arrays in
+            // RESP2.
+            match (protocol, frame) {
+                (Protocol::Resp3, Frame::Set(_)) => {
+                    buf_w.write(b"~").await.0?;
+                }
+                (Protocol::Resp3, Frame::Push(_)) => {
+                    buf_w.write(b">").await.0?;
+                }
+                _ => {
+                    buf_w.write(b"*").await.0?;
+                }
+            }
+            write_decimal(buf_w, val.len() as i64).await?;
 
             // Iterate and encode each entry in the array.
             for entry in &**val {
-                write_value(buf_w, entry).await?;
+                write_value(buf_w, entry, protocol).await?;
             }
         }
     }
@@ -84,29 +165,27 @@ async fn write_value(
     Ok(())
 }
 
-pub async fn write_frame(
-    buf_w: &mut impl AsyncWriteRent,
-    frame: &Frame,
-) -> io::Result<()> {
-    write_value(buf_w, frame).await?;
-    // Ensure the encoded frame is written to the socket. The calls above
-    // are to the buffered stream and writes. Calling `flush` writes the
-    // remaining contents of the buffer to the socket.
-    buf_w.flush().await
-}
-
 #[cfg(test)]
 mod tests {
     use std::io::Write;
 
     use bytestring::ByteString;
-    use indexmap::IndexMap;
     use monoio::buf::{IoBuf, IoVecBuf};
     use monoio::io::AsyncWriteRent;
     use monoio::BufResult;
 
-    use super::{write_decimal, write_frame, write_value};
-    use crate::application::server::frame::Frame;
+    use super::{format_double, write_decimal, write_value};
+    use crate::application::server::frame::{Frame, Protocol};
+
+    /// Write a frame and flush it, like a connection does.
+    async fn write_frame(
+        buf_w: &mut impl AsyncWriteRent,
+        frame: &Frame,
+        protocol: Protocol,
+    ) -> std::io::Result<()> {
+        write_value(buf_w, frame, protocol).await?;
+        buf_w.flush().await
+    }
 
     struct TestUtilVec<W>(pub Vec<W>);
 
@@ -142,10 +221,19 @@ mod tests {
         assert_eq!(v.0, b"12\r\n");
     }
 
+    #[monoio::test]
+    async fn simple_decimal_write_negative() {
+        let mut v = TestUtilVec(Vec::new());
+        write_decimal(&mut v, i64::MIN).await.unwrap();
+        assert_eq!(v.0, b"-9223372036854775808\r\n");
+    }
+
     #[monoio::test]
     async fn simple_decimal_write_value_null() {
         let mut v = TestUtilVec(Vec::new());
-        write_value(&mut v, &Frame::Null).await.unwrap();
+        write_value(&mut v, &Frame::Null, Protocol::Resp2)
+            .await
+            .unwrap();
         insta::assert_debug_snapshot!(String::from_utf8(v.0).unwrap(), @r###""$-1\r\n""###);
     }
 
@@ -153,7 +241,7 @@ mod tests {
     async fn simple_decimal_write_value_string() {
         let mut v = TestUtilVec(Vec::new());
         let frame = Frame::Simple(ByteString::from_static("blblblbl"));
-        write_value(&mut v, &frame).await.unwrap();
+        write_value(&mut v, &frame, Protocol::Resp3).await.unwrap();
         insta::assert_debug_snapshot!(String::from_utf8(v.0).unwrap(), @r###""+blblblbl\r\n""###);
     }
 
@@ -161,22 +249,30 @@ mod tests {
     async fn simple_decimal_write_value_int() {
         let mut v = TestUtilVec(Vec::new());
         let frame = Frame::Integer(123456);
-        write_value(&mut v, &frame).await.unwrap();
+        write_value(&mut v, &frame, Protocol::Resp3).await.unwrap();
         insta::assert_debug_snapshot!(String::from_utf8(v.0).unwrap(), @r###"":123456\r\n""###);
     }
 
+    #[monoio::test]
+    async fn simple_decimal_write_value_negative_int() {
+        let mut v = TestUtilVec(Vec::new());
+        let frame = Frame::Integer(-1);
+        write_value(&mut v, &frame, Protocol::Resp3).await.unwrap();
+        insta::assert_debug_snapshot!(String::from_utf8(v.0).unwrap(), @r###"":-1\r\n""###);
+    }
+
     #[monoio::test]
     async fn simple_decimal_write_value_err() {
         let mut v = TestUtilVec(Vec::new());
         let frame = Frame::Error(ByteString::from_static("blblblbl"));
-        write_value(&mut v, &frame).await.unwrap();
+        write_value(&mut v, &frame, Protocol::Resp3).await.unwrap();
         insta::assert_debug_snapshot!(String::from_utf8(v.0).unwrap(), @r###""-blblblbl\r\n""###);
     }
 
     #[monoio::test]
     async fn simple_decimal_write_value_hashmap() {
         let mut v = TestUtilVec(Vec::new());
-        let frame = Frame::Map(IndexMap::from_iter([
+        let frame = Frame::Map(vec![
             (
                 Frame::Simple(ByteString::from_static("first")),
                 Frame::Integer(1),
@@ -185,15 +281,15 @@ mod tests {
                 Frame::Simple(ByteString::from_static("second")),
                 Frame::Integer(2),
             ),
-        ]));
-        write_frame(&mut v, &frame).await.unwrap();
+        ]);
+        write_frame(&mut v, &frame, Protocol::Resp3).await.unwrap();
         insta::assert_debug_snapshot!(String::from_utf8(v.0).unwrap(), @r###""%2\r\n+first\r\n:1\r\n+second\r\n:2\r\n""###);
     }
 
     #[monoio::test]
     async fn simple_decimal_write_value_hashmap_string() {
         let mut v = TestUtilVec(Vec::new());
-        let frame = Frame::Map(IndexMap::from_iter([
+        let frame = Frame::Map(vec![
             (
                 Frame::Simple(ByteString::from_static("first")),
                 Frame::Simple(ByteString::from_static("one")),
@@ -202,8 +298,89 @@ mod tests {
                 Frame::Simple(ByteString::from_static("second")),
                 Frame::Integer(2),
             ),
-        ]));
-        write_frame(&mut v, &frame).await.unwrap();
+        ]);
+        write_frame(&mut v, &frame, Protocol::Resp3).await.unwrap();
         insta::assert_debug_snapshot!(String::from_utf8(v.0).unwrap(), @r###""%2\r\n+first\r\n+one\r\n+second\r\n:2\r\n""###);
     }
+
+    #[monoio::test]
+    async fn simple_decimal_write_value_null_resp3() {
+        let mut v = TestUtilVec(Vec::new());
+        write_value(&mut v, &Frame::Null, Protocol::Resp3)
+            .await
+            .unwrap();
+        insta::assert_debug_snapshot!(String::from_utf8(v.0).unwrap(), @r###""_\r\n""###);
+    }
+
+    #[monoio::test]
+    async fn write_value_null_array() {
+        let mut v = TestUtilVec(Vec::new());
+        write_value(&mut v, &Frame::NullArray, Protocol::Resp2)
+            .await
+            .unwrap();
+        assert_eq!(v.0, b"*-1\r\n");
+
+        let mut v = TestUtilVec(Vec::new());
+        write_value(&mut v, &Frame::NullArray, Protocol::Resp3)
+            .await
+            .unwrap();
+        assert_eq!(v.0, b"_\r\n");
+    }
+
+    #[monoio::test]
+    async fn simple_write_value_hashmap_resp2() {
+        let mut v = TestUtilVec(Vec::new());
+        let frame = Frame::Map(vec![
+            (
+                Frame::Simple(ByteString::from_static("first")),
+                Frame::Boolean(true),
+            ),
+            (
+                Frame::Simple(ByteString::from_static("second")),
+                Frame::Double(1.5),
+            ),
+        ]);
+        write_frame(&mut v, &frame, Protocol::Resp2).await.unwrap();
+        insta::assert_debug_snapshot!(String::from_utf8(v.0).unwrap(), @r###""*4\r\n+first\r\n:1\r\n+second\r\n$3\r\n1.5\r\n""###);
+    }
+
+    #[monoio::test]
+    async fn simple_write_value_set() {
+        let frame = Frame::Set(vec![Frame::Integer(1), Frame::Boolean(false)]);
+
+        let mut v = TestUtilVec(Vec::new());
+        write_frame(&mut v, &frame, Protocol::Resp3).await.unwrap();
+        insta::assert_debug_snapshot!(String::from_utf8(v.0).unwrap(), @r###""~2\r\n:1\r\n#f\r\n""###);
+
+        let mut v = TestUtilVec(Vec::new());
+        write_frame(&mut v, &frame, Protocol::Resp2).await.unwrap();
+        insta::assert_debug_snapshot!(String::from_utf8(v.0).unwrap(), @r###""*2\r\n:1\r\n:0\r\n""###);
+    }
+
+    #[monoio::test]
+    async fn write_value_push() {
+        let frame = Frame::Push(vec![
+            Frame::Simple(ByteString::from_static("message")),
+            Frame::Integer(1),
+        ]);
+
+        let mut v = TestUtilVec(Vec::new());
+        write_frame(&mut v, &frame, Protocol::Resp3).await.unwrap();
+        assert_eq!(v.0, b">2\r\n+message\r\n:1\r\n");
+
+        let mut v = TestUtilVec(Vec::new());
+        write_frame(&mut v, &frame, Protocol::Resp2).await.unwrap();
+        assert_eq!(v.0, b"*2\r\n+message\r\n:1\r\n");
+    }
+
+    #[test]
+    fn double_formatting() {
+        assert_eq!(format_double(1.5), "1.5");
+        assert_eq!(format_double(10.0), "10");
+        assert_eq!(format_double(-0.25), "-0.25");
+        assert_eq!(format_double(f64::INFINITY), "inf");
+        assert_eq!(format_double(f64::NEG_INFINITY), "-inf");
+        assert_eq!(format_double(1e300), "1e+300");
+        assert_eq!(format_double(1.5e-7), "1.5e-7");
+    }
 }
@@ -6,13 +6,17 @@ use std::os::fd::IntoRawFd;
 use std::rc::Rc;
 use std::time::Duration;
 
+use bytestring::ByteString;
+use futures::StreamExt;
 use sharded_thread::shard::Shard;
 
 use super::cmd::Command;
 use super::connection::{ReadConnection, WriteConnection};
 use super::context::Context;
-use super::frame::Frame;
+use super::frame::{Error as FrameError, Frame, Protocol};
+use super::supervisor::MetadataConnection;
 use crate::application::server::cmd::CommandExecution;
+use crate::domain::acl::LogContext;
 
 /// Per-connection handler. Reads requests from `connection` and applies the
 /// commands.
@@ -22,6 +26,9 @@ pub struct Handler {
     pub connection: WriteConnection,
     pub connection_r: ReadConnection,
     pub shard: Rc<Shard<ConnectionMsg>>,
+    /// Close the connection once the client is idle for this long, disabled
+    /// when zero.
+    pub idle_timeout: Duration,
 }
 
 /// Current connection that is going to be send
@@ -60,29 +67,50 @@ impl Handler {
             mut connection,
             connection_r,
             shard,
+            idle_timeout,
         } = self;
         let (tx, mut rx) = local_sync::mpsc::unbounded::channel();
 
         let (shutdown_tx, shutdown_rx) = local_sync::oneshot::channel::<()>();
         let (shutdown_ok_tx, shutdown_ok_rx) = local_sync::oneshot::channel();
 
+        let meta_conn = ctx.connection.clone();
         let accepting_frames_handle = monoio::spawn(async move {
             let connection_r = Rc::new(RefCell::new(connection_r));
+            let idle_meta_conn = meta_conn.clone();
 
             let conn = connection_r.clone();
             let reading_frames = async move {
                 loop {
-                    let frame_opt = conn.borrow_mut().read_frame().await?;
+                    let frame_opt = match conn.borrow_mut().read_frame().await {
+                        Ok(frame_opt) => frame_opt,
+                        // The client sent something we can't understand, we
+                        // let the writer answer with the error and we stop
+                        // reading from this connection.
+                        Err(err)
+                            if err.downcast_ref::<FrameError>().is_some() =>
+                        {
+                            let _ = tx.send(Err(err));
+                            return Ok::<_, anyhow::Error>(());
+                        }
+                        Err(err) => return Err(err),
+                    };
 
                     // If `None` is returned from `read_frame()` then the peer
                     // closed the socket. There is no further work
                     // to do and the task can be terminated.
                     let frame = match frame_opt {
                         Some(frame) => frame,
-                        None => return Ok::<_, anyhow::Error>(()),
+                        None => return Ok(()),
                     };
+                    meta_conn.touch();
+                    let (len, capacity) = conn.borrow().buffered();
+                    meta_conn.set_query_buffer(len, capacity);
 
-                    tx.send(frame).unwrap();
+                    // The writer is gone, the connection is closing.
+                    if tx.send(Ok(frame)).is_err() {
+                        return Ok(());
+                    }
                 }
             };
 
@@ -90,6 +118,15 @@ impl Handler {
                 r = reading_frames => {
                     r
                 }
+                // The pending read is cancelled, it doesn't matter as the
+                // connection is closed.
+                _ = wait_idle_timeout(&idle_meta_conn, idle_timeout) => {
+                    Ok(())
+                }
+                // Killed by `CLIENT KILL`, maybe from another thread.
+                _ = idle_meta_conn.killed() => {
+                    Ok(())
+                }
                 r = shutdown_rx => {
                     match r {
                       Ok(_) => {
@@ -108,15 +145,151 @@ impl Handler {
 
         let answer_in_order_handle = monoio::spawn(async move {
             if let Some(current_command) = current_command {
-                current_command.apply(&mut connection, ctx.clone()).await?;
+                apply_command(current_command, &mut connection, &ctx).await?;
             }
 
-            while let Some(frame) = rx.recv().await {
+            // Pub/Sub messages are sent to the client between the replies.
+            let mut messages = ctx
+                .subscriptions()
+                .take_receiver()
+                .expect("The messages can only be taken by the handler");
+
+            loop {
+                // The Pub/Sub messages are always sent, even when the replies
+                // are turned off.
+                connection.set_muted(false);
+                ctx.sync_connection();
+                let frame = monoio::select! {
+                    frame = rx.recv() => frame,
+                    Some(msg) = messages.next() => {
+                        let frame = ctx
+                            .subscriptions()
+                            .message_frame(msg, ctx.protocol());
+                        if let Some(frame) = frame {
+                            connection.write_frame(&frame).await?;
+                        }
+                        continue;
+                    }
+                };
+
+                let Some(frame) = frame else {
+                    break;
+                };
+
+                // The frames already read aren't applied once the connection
+                // is killed.
+                if ctx.connection.is_killed() {
+                    break;
+                }
+                connection.set_muted(ctx.take_reply_skipped());
+
+                let frame = match frame {
+                    Ok(frame) => frame,
+                    Err(err) => {
+                        // Protocol error: like Redis, we answer with the
+                        // error and close the connection.
+                        let msg = match err.downcast::<FrameError>() {
+                            Ok(FrameError::Other(err)) => err.to_string(),
+                            Ok(err) => err.to_string(),
+                            Err(err) => err.to_string(),
+                        };
+                        let response = Frame::Error(ByteString::from(format!(
+                            "ERR {msg}"
+                        )));
+                        connection.write_frame(&response).await?;
+                        break;
+                    }
+                };
+
                 // Convert the redis frame into a command struct. This returns
                 // an error if the frame is not a valid redis
-                // command or it is an unsupported command.
+                // command or it is an unsupported command, the error is
+                // sent back to the client.
                 // 100 ns
-                let cmd = Command::from_frame(frame)?;
+                let cmd =
+                    match Command::from_frame(frame, ctx.supervisor.commands())
+                    {
+                        Ok(cmd) => cmd,
+                        Err(err) => {
+                            // Like Redis, a transaction with a command which
+                            // couldn't be queued is refused on `EXEC`.
+                            ctx.transaction().abort();
+                            connection.write_frame(&err.into_frame()).await?;
+                            continue;
+                        }
+                    };
+
+                if !matches!(cmd, Command::Unknown(_)) {
+                    ctx.connection.set_last_command(&cmd.acl_name()).await;
+                }
+
+                // The authentication and the ACL rules are checked before
+                // anything else, a denied command also aborts the
+                // transaction.
+                let context = match ctx.transaction().is_started() {
+                    true => LogContext::Multi,
+                    false => LogContext::Toplevel,
+                };
+                if let Err(err) = cmd.check_permissions(&ctx, context).await {
+                    ctx.transaction().abort();
+                    connection.write_frame(&err.into_frame()).await?;
+                    continue;
+                }
+
+                // In RESP2, a subscribed connection can only manage its
+                // subscriptions.
+                if ctx.protocol() == Protocol::Resp2
+                    && ctx.connection.is_pubsub()
+                    && !cmd.is_allowed_in_subscribed_mode()
+                {
+                    let response = Frame::Error(ByteString::from(format!(
+                        "ERR Can't execute '{}': only (P|S)SUBSCRIBE / \
+                         (P|S)UNSUBSCRIBE / PING / QUIT / RESET are allowed \
+                         in this context",
+                        cmd.name()
+                    )));
+                    connection.write_frame(&response).await?;
+                    continue;
+                }
+
+                // Like Redis, a script running for too long only lets the
+                // other connections kill it.
+                if ctx.supervisor.scripts().is_busy()
+                    && !cmd.is_allowed_when_busy()
+                {
+                    let response = Frame::Error(ByteString::from_static(
+                        "BUSY Redis is busy running a script. You can only \
+                         call SCRIPT KILL or SHUTDOWN NOSAVE.",
+                    ));
+                    connection.write_frame(&response).await?;
+                    continue;
+                }
+
+                // Like Redis, the command waits while the clients are paused
+                // by `CLIENT PAUSE`.
+                if !cmd.is_allowed_when_paused() {
+                    ctx.supervisor
+                        .wait_unpaused(&ctx.connection, cmd.may_write())
+                        .await;
+                    if ctx.connection.is_killed() {
+                        break;
+                    }
+                }
+
+                if ctx.transaction().is_started()
+                    && cmd.is_queued_in_transaction()
+                {
+                    if let Command::Unknown(_) = cmd {
+                        ctx.transaction().abort();
+                        cmd.execute(&mut connection, ctx.clone()).await?;
+                    } else {
+                        ctx.transaction().queue(cmd);
+                        let response =
+                            Frame::Simple(ByteString::from_static("QUEUED"));
+                        connection.write_frame(&response).await?;
+                    }
+                    continue;
+                }
 
                 // ----------------------------------------------------------------
                 // Sharding here
@@ -138,11 +311,18 @@ impl Handler {
                         anyhow::anyhow!("couldn't send shutdown")
                     })?;
 
-                    let tcp = connection.reunite(
-                        Rc::try_unwrap(shutdown_ok_rx.await?)
-                            .unwrap()
-                            .into_inner(),
-                    );
+                    let tcp = connection
+                        .reunite(
+                            Rc::try_unwrap(shutdown_ok_rx.await?)
+                                .unwrap()
+                                .into_inner(),
+                        )
+                        .ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "only a TCP connection can be sent to another \
+                                 thread"
+                            )
+                        })?;
                     // Sad, but it seems the destructor of tcp are not running
                     // if we do not put this. To investigate
                     // on monoio repo later.
@@ -164,19 +344,88 @@ impl Handler {
                     // good thread, we still have to
                     // communicate the command and wait for
                     // the response
-                    cmd.apply(&mut connection, ctx.clone()).await?;
+                    let quit = cmd.is_quit();
+                    apply_command(cmd, &mut connection, &ctx).await?;
+                    if quit {
+                        break;
+                    }
                 }
             }
             Ok::<_, anyhow::Error>(())
         });
 
-        monoio::select! {
+        let mut answer_in_order_handle = std::pin::pin!(answer_in_order_handle);
+        let read = monoio::select! {
             r = accepting_frames_handle => {
                 r
             }
-            r = answer_in_order_handle => {
-                r
+            r = &mut answer_in_order_handle => {
+                return r;
+            }
+        };
+
+        // The frames are not read anymore: the command being applied is
+        // completed before the connection is closed, so a killed connection
+        // or a shutdown doesn't interrupt it.
+        let answered = answer_in_order_handle.await;
+        read.and(answered)
+    }
+}
+
+/// Apply a command while holding a shared access on the storage, so it can't
+/// be interleaved with a transaction or a script, see
+/// [Command::manages_storage_access].
+async fn apply_command(
+    cmd: Command,
+    dst: &mut WriteConnection,
+    ctx: &Context,
+) -> anyhow::Result<()> {
+    // `CLIENT CACHING` only applies to the command following it.
+    let keep_caching = cmd.is_client_caching();
+
+    ctx.connection.set_executing(true);
+    let (result, replies) = if cmd.manages_storage_access() {
+        (cmd.execute(dst, ctx.clone()).await, Vec::new())
+    } else {
+        // Like `EXEC`, the replies are kept until the command is applied so
+        // the storage isn't held while writing to the socket: a client which
+        // doesn't read its replies can't block the transactions.
+        let _guard = ctx.storage.shared().await;
+        dst.start_capture();
+        let result = cmd.execute(dst, ctx.clone()).await;
+        (result, dst.end_capture())
+    };
+    ctx.connection.set_executing(false);
+
+    for reply in &replies {
+        dst.write_frame(reply).await?;
+    }
+    ctx.connection.touch();
+
+    if !keep_caching {
+        ctx.set_caching(None);
+    }
+    result
+}
+
+/// Wait until the connection can be closed by the idle `timeout`, forever
+/// when the timeout is disabled.
+async fn wait_idle_timeout(conn: &MetadataConnection, timeout: Duration) {
+    if timeout.is_zero() {
+        return std::future::pending().await;
+    }
+
+    loop {
+        let remaining = timeout.saturating_sub(conn.idle());
+        if remaining.is_zero() {
+            if conn.is_idle_for(timeout) {
+                return;
             }
+            // The connection can't be closed for now, it's checked again
+            // later.
+            monoio::time::sleep(timeout).await;
+        } else {
+            monoio::time::sleep(remaining).await;
         }
     }
 }
@@ -0,0 +1,139 @@
+//! The `bit` library available to the scripts, compatible with LuaBitOp:
+//! the numbers are converted to signed 32 bits integers before each
+//! operation.
+
+use mlua::{Lua, Table, Variadic};
+
+/// Normalize a number to a signed 32 bits integer, the way LuaBitOp does.
+fn tobit(val: f64) -> i32 {
+    val.round_ties_even().rem_euclid(4_294_967_296.0) as u32 as i32
+}
+
+/// A shift or a rotation of a number by `n` bits.
+type Shift = fn(i32, u32) -> i32;
+
+fn fold(first: f64, rest: Variadic<f64>, op: impl Fn(i32, i32) -> i32) -> f64 {
+    rest.iter()
+        .fold(tobit(first), |acc, val| op(acc, tobit(*val))) as f64
+}
+
+pub(super) fn register(lua: &Lua) -> mlua::Result<Table> {
+    let bit = lua.create_table()?;
+
+    bit.raw_set(
+        "tobit",
+        lua.create_function(|_, val: f64| Ok(tobit(val) as f64))?,
+    )?;
+    bit.raw_set(
+        "tohex",
+        lua.create_function(|_, (val, len): (f64, Option<f64>)| {
+            let len = tobit(len.unwrap_or(8.0));
+            let hex = if len < 0 {
+                format!("{:08X}", tobit(val) as u32)
+            } else {
+                format!("{:08x}", tobit(val) as u32)
+            };
+            let len = (len.unsigned_abs() as usize).min(8);
+            Ok(hex[8 - len..].to_string())
+        })?,
+    )?;
+    bit.raw_set(
+        "bnot",
+        lua.create_function(|_, val: f64| Ok(!tobit(val) as f64))?,
+    )?;
+    bit.raw_set(
+        "band",
+        lua.create_function(|_, (first, rest): (f64, Variadic<f64>)| {
+            Ok(fold(first, rest, |a, b| a & b))
+        })?,
+    )?;
+    bit.raw_set(
+        "bor",
+        lua.create_function(|_, (first, rest): (f64, Variadic<f64>)| {
+            Ok(fold(first, rest, |a, b| a | b))
+        })?,
+    )?;
+    bit.raw_set(
+        "bxor",
+        lua.create_function(|_, (first, rest): (f64, Variadic<f64>)| {
+            Ok(fold(first, rest, |a, b| a ^ b))
+        })?,
+    )?;
+
+    let shifts: [(&str, Shift); 5] = [
+        ("lshift", |val, n| ((val as u32) << n) as i32),
+        ("rshift", |val, n| ((val as u32) >> n) as i32),
+        ("arshift", |val, n| val >> n),
+        ("rol", |val, n| (val as u32).rotate_left(n) as i32),
+        ("ror", |val, n| (val as u32).rotate_right(n) as i32),
+    ];
+    for (name, shift) in shifts {
+        bit.raw_set(
+            name,
+            lua.create_function(move |_, (val, n): (f64, f64)| {
+                Ok(shift(tobit(val), tobit(n) as u32 & 31) as f64)
+            })?,
+        )?;
+    }
+
+    bit.raw_set(
+        "bswap",
+        lua.create_function(|_, val: f64| Ok(tobit(val).swap_bytes() as f64))?,
+    )?;
+
+    Ok(bit)
+}
+
+#[cfg(test)]
+mod tests {
+    use mlua::Lua;
+
+    #[test]
+    fn bit_operations() {
+        let lua = Lua::new();
+        lua.globals()
+            .set("bit", super::register(&lua).unwrap())
+            .unwrap();
+
+        let results: Vec<f64> = lua
+            .load(
+                r#"
+                return {
+                    bit.tobit(0xffffffff),
+                    bit.band(0x12345678, 0xff),
+                    bit.bor(1, 2, 4),
+                    bit.bxor(5, 3),
+                    bit.bnot(0),
+                    bit.lshift(1, 31),
+                    bit.rshift(-1, 28),
+                    bit.arshift(-256, 4),
+                    bit.rol(0x12345678, 8),
+                    bit.bswap(0x12345678),
+                }
+                "#,
+            )
+            .eval()
+            .unwrap();
+        assert_eq!(
+            results,
+            [
+                -1.0,
+                120.0,
+                7.0,
+                6.0,
+                -1.0,
+                -2147483648.0,
+                15.0,
+                -16.0,
+                878082066.0,
+                2018915346.0
+            ]
+        );
+
+        let hex: (String, String) = lua
+            .load("return bit.tohex(255), bit.tohex(-1, -4)")
+            .eval()
+            .unwrap();
+        assert_eq!(hex, ("000000ff".to_string(), "FFFF".to_string()));
+    }
+}
@@ -0,0 +1,206 @@
+//! The `cjson` library available to the scripts: `cjson.encode`,
+//! `cjson.decode` and the `cjson.null` sentinel.
+
+use mlua::{Lua, LuaString, Table, Value};
+use serde_json::{Map, Number};
+
+/// Tables nested deeper than this can't be encoded, which also protects
+/// against recursive tables.
+const MAX_NESTING: usize = 200;
+
+pub(super) fn register(lua: &Lua) -> mlua::Result<Table> {
+    let cjson = lua.create_table()?;
+
+    cjson.raw_set(
+        "encode",
+        lua.create_function(|lua, value: Value| {
+            let json = to_json(&value, 0)?;
+            lua.create_string(json.to_string())
+        })?,
+    )?;
+    cjson.raw_set(
+        "decode",
+        lua.create_function(|lua, json: LuaString| {
+            let json: serde_json::Value =
+                serde_json::from_slice(&json.as_bytes())
+                    .map_err(|err| mlua::Error::runtime(err.to_string()))?;
+            from_json(lua, json)
+        })?,
+    )?;
+    cjson.raw_set("null", Value::NULL)?;
+
+    Ok(cjson)
+}
+
+fn to_json(value: &Value, depth: usize) -> mlua::Result<serde_json::Value> {
+    let json = match value {
+        Value::Nil => serde_json::Value::Null,
+        Value::LightUserData(data) if data.0.is_null() => {
+            serde_json::Value::Null
+        }
+        Value::Boolean(val) => serde_json::Value::Bool(*val),
+        Value::Integer(val) => serde_json::Value::from(*val),
+        Value::Number(val) => number(*val)?,
+        Value::String(val) => serde_json::Value::String(
+            String::from_utf8_lossy(&val.as_bytes()).into_owned(),
+        ),
+        Value::Table(table) => {
+            if depth >= MAX_NESTING {
+                return Err(mlua::Error::runtime(format!(
+                    "Cannot serialise, excessive nesting ({})",
+                    depth + 1
+                )));
+            }
+            table_to_json(table, depth + 1)?
+        }
+        other => {
+            return Err(mlua::Error::runtime(format!(
+                "Cannot serialise {}: type not supported",
+                other.type_name()
+            )));
+        }
+    };
+
+    Ok(json)
+}
+
+fn number(val: f64) -> mlua::Result<serde_json::Value> {
+    if val.fract() == 0.0 && val.abs() < 1e15 {
+        return Ok(serde_json::Value::from(val as i64));
+    }
+    Number::from_f64(val)
+        .map(serde_json::Value::Number)
+        .ok_or_else(|| {
+            mlua::Error::runtime(
+                "Cannot serialise number: must not be NaN or Inf",
+            )
+        })
+}
+
+fn table_to_json(
+    table: &Table,
+    depth: usize,
+) -> mlua::Result<serde_json::Value> {
+    let pairs = table
+        .pairs::<Value, Value>()
+        .collect::<mlua::Result<Vec<_>>>()?;
+
+    // Like cjson, a table only indexed by positive integers is an array, the
+    // missing indexes are encoded as null.
+    let indexes = pairs
+        .iter()
+        .map(|(key, _)| match key {
+            Value::Integer(i) if *i >= 1 => Some(*i as usize),
+            Value::Number(n) if *n >= 1.0 && n.fract() == 0.0 => {
+                Some(*n as usize)
+            }
+            _ => None,
+        })
+        .collect::<Option<Vec<_>>>();
+
+    match indexes {
+        Some(indexes) if !indexes.is_empty() => {
+            let len = indexes.iter().copied().max().unwrap_or_default();
+            if len > indexes.len() * 2 && len > 10 {
+                return Err(mlua::Error::runtime(
+                    "Cannot serialise table: excessively sparse array",
+                ));
+            }
+            let mut array = vec![serde_json::Value::Null; len];
+            for (index, (_, value)) in indexes.into_iter().zip(&pairs) {
+                array[index - 1] = to_json(value, depth)?;
+            }
+            Ok(serde_json::Value::Array(array))
+        }
+        _ => {
+            let mut object = Map::new();
+            for (key, value) in &pairs {
+                let key = match key {
+                    Value::String(key) => {
+                        String::from_utf8_lossy(&key.as_bytes()).into_owned()
+                    }
+                    Value::Integer(key) => key.to_string(),
+                    Value::Number(key) => key.to_string(),
+                    _ => {
+                        return Err(mlua::Error::runtime(
+                            "Cannot serialise table: table key must be a \
+                             number or string",
+                        ));
+                    }
+                };
+                object.insert(key, to_json(value, depth)?);
+            }
+            Ok(serde_json::Value::Object(object))
+        }
+    }
+}
+
+fn from_json(lua: &Lua, json: serde_json::Value) -> mlua::Result<Value> {
+    let value = match json {
+        serde_json::Value::Null => Value::NULL,
+        serde_json::Value::Bool(val) => Value::Boolean(val),
+        serde_json::Value::Number(val) => {
+            Value::Number(val.as_f64().unwrap_or_default())
+        }
+        serde_json::Value::String(val) => {
+            Value::String(lua.create_string(val)?)
+        }
+        serde_json::Value::Array(values) => {
+            let table = lua.create_table_with_capacity(values.len(), 0)?;
+            for (i, value) in values.into_iter().enumerate() {
+                table.raw_set(i + 1, from_json(lua, value)?)?;
+            }
+            Value::Table(table)
+        }
+        serde_json::Value::Object(object) => {
+            let table = lua.create_table_with_capacity(0, object.len())?;
+            for (key, value) in object {
+                table.raw_set(key, from_json(lua, value)?)?;
+            }
+            Value::Table(table)
+        }
+    };
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use mlua::Lua;
+
+    #[test]
+    fn encode_decode() {
+        let lua = Lua::new();
+        lua.globals()
+            .set("cjson", super::register(&lua).unwrap())
+            .unwrap();
+
+        let encoded: String = lua
+            .load(r#"return cjson.encode({1, "two", {a = true}, 1.5})"#)
+            .eval()
+            .unwrap();
+        assert_eq!(encoded, r#"[1,"two",{"a":true},1.5]"#);
+
+        let encoded: String =
+            lua.load("return cjson.encode({})").eval().unwrap();
+        assert_eq!(encoded, "{}");
+
+        let (name, is_null): (String, bool) = lua
+            .load(
+                r#"
+                local t = cjson.decode('{"name":"roster","none":null}')
+                return t.name, t.none == cjson.null
+                "#,
+            )
+            .eval()
+            .unwrap();
+        assert_eq!(name, "roster");
+        assert!(is_null);
+
+        let err = lua
+            .load("local t = {} t[1] = t return cjson.encode(t)")
+            .exec()
+            .unwrap_err();
+        assert!(err.to_string().contains("excessive nesting"));
+    }
+}
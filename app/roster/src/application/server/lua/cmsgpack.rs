@@ -0,0 +1,181 @@
+//! The `cmsgpack` library available to the scripts: `cmsgpack.pack` and
+//! `cmsgpack.unpack`.
+
+use mlua::{Lua, LuaString, MultiValue, Table, Value, Variadic};
+
+/// Tables nested deeper than this are encoded as nil, like cmsgpack does.
+const MAX_NESTING: usize = 16;
+
+pub(super) fn register(lua: &Lua) -> mlua::Result<Table> {
+    let cmsgpack = lua.create_table()?;
+
+    cmsgpack.raw_set(
+        "pack",
+        lua.create_function(|lua, values: Variadic<Value>| {
+            if values.is_empty() {
+                return Err(mlua::Error::runtime(
+                    "MessagePack pack needs input.",
+                ));
+            }
+            let mut buf = Vec::new();
+            for value in values.iter() {
+                encode(&mut buf, value, 0)?;
+            }
+            lua.create_string(buf)
+        })?,
+    )?;
+    cmsgpack.raw_set(
+        "unpack",
+        lua.create_function(|lua, input: LuaString| {
+            let input = input.as_bytes();
+            let mut rest: &[u8] = &input;
+            let mut values = MultiValue::new();
+            while !rest.is_empty() {
+                let value =
+                    rmpv::decode::read_value(&mut rest).map_err(|_| {
+                        mlua::Error::runtime("Missing bytes in input.")
+                    })?;
+                values.push_back(decode(lua, value)?);
+            }
+            Ok(values)
+        })?,
+    )?;
+
+    Ok(cmsgpack)
+}
+
+// Writing to a `Vec` can't fail, so the errors of `rmp` are ignored.
+fn encode(buf: &mut Vec<u8>, value: &Value, depth: usize) -> mlua::Result<()> {
+    use rmp::encode;
+
+    match value {
+        Value::Boolean(val) => {
+            let _ = encode::write_bool(buf, *val);
+        }
+        Value::Integer(val) => {
+            let _ = encode::write_sint(buf, *val);
+        }
+        Value::Number(val)
+            if val.fract() == 0.0
+                && *val >= i64::MIN as f64
+                && *val <= i64::MAX as f64 =>
+        {
+            let _ = encode::write_sint(buf, *val as i64);
+        }
+        Value::Number(val) if (*val as f32) as f64 == *val => {
+            let _ = encode::write_f32(buf, *val as f32);
+        }
+        Value::Number(val) => {
+            let _ = encode::write_f64(buf, *val);
+        }
+        Value::String(val) => {
+            let bytes = val.as_bytes();
+            let _ = encode::write_str_len(buf, bytes.len() as u32);
+            buf.extend_from_slice(&bytes);
+        }
+        Value::Table(table) if depth < MAX_NESTING => {
+            encode_table(buf, table, depth + 1)?;
+        }
+        _ => {
+            let _ = encode::write_nil(buf);
+        }
+    }
+
+    Ok(())
+}
+
+fn encode_table(
+    buf: &mut Vec<u8>,
+    table: &Table,
+    depth: usize,
+) -> mlua::Result<()> {
+    use rmp::encode;
+
+    let pairs = table
+        .pairs::<Value, Value>()
+        .collect::<mlua::Result<Vec<_>>>()?;
+    let len = table.raw_len();
+
+    // A table is an array when its keys are exactly 1..n.
+    if len == pairs.len() {
+        let _ = encode::write_array_len(buf, len as u32);
+        for i in 1..=len {
+            encode(buf, &table.raw_get::<Value>(i)?, depth)?;
+        }
+    } else {
+        let _ = encode::write_map_len(buf, pairs.len() as u32);
+        for (key, value) in &pairs {
+            encode(buf, key, depth)?;
+            encode(buf, value, depth)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn decode(lua: &Lua, value: rmpv::Value) -> mlua::Result<Value> {
+    use rmpv::Value as Msg;
+
+    let value = match value {
+        Msg::Nil | Msg::Ext(..) => Value::Nil,
+        Msg::Boolean(val) => Value::Boolean(val),
+        Msg::Integer(val) => Value::Number(
+            val.as_i64()
+                .map(|val| val as f64)
+                .or(val.as_u64().map(|val| val as f64))
+                .unwrap_or_default(),
+        ),
+        Msg::F32(val) => Value::Number(val as f64),
+        Msg::F64(val) => Value::Number(val),
+        Msg::String(val) => Value::String(lua.create_string(val.as_bytes())?),
+        Msg::Binary(val) => Value::String(lua.create_string(val)?),
+        Msg::Array(values) => {
+            let table = lua.create_table_with_capacity(values.len(), 0)?;
+            for (i, value) in values.into_iter().enumerate() {
+                table.raw_set(i + 1, decode(lua, value)?)?;
+            }
+            Value::Table(table)
+        }
+        Msg::Map(pairs) => {
+            let table = lua.create_table_with_capacity(0, pairs.len())?;
+            for (key, value) in pairs {
+                let key = decode(lua, key)?;
+                if !key.is_nil() {
+                    table.raw_set(key, decode(lua, value)?)?;
+                }
+            }
+            Value::Table(table)
+        }
+    };
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use mlua::Lua;
+
+    #[test]
+    fn pack_unpack() {
+        let lua = Lua::new();
+        lua.globals()
+            .set("cmsgpack", super::register(&lua).unwrap())
+            .unwrap();
+
+        let (a, b, c, d): (i64, String, f64, String) = lua
+            .load(
+                r#"
+                local packed = cmsgpack.pack(1, "two", {1.5, {k = "v"}})
+                local a, b, t = cmsgpack.unpack(packed)
+                return a, b, t[1], t[2].k
+                "#,
+            )
+            .eval()
+            .unwrap();
+        assert_eq!((a, b.as_str(), c, d.as_str()), (1, "two", 1.5, "v"));
+
+        let packed: mlua::LuaString =
+            lua.load("return cmsgpack.pack({1, 2})").eval().unwrap();
+        assert_eq!(&packed.as_bytes()[..], &[0x92, 0x01, 0x02]);
+    }
+}
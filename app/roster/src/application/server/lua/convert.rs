@@ -0,0 +1,254 @@
+//! Conversion between the replies of the commands and the Lua values, following
+//! the rules of Redis.
+//!
+//! A reply is converted depending on the protocol selected by the script with
+//! `redis.setresp`:
+//!
+//! ```text
+//! RESP2                           RESP3
+//! integer   -> number             null      -> nil
+//! bulk      -> string             map       -> {map={...}}
+//! array     -> table              set       -> {set={member=true}}
+//! status    -> {ok=...}           double    -> {double=number}
+//! error     -> {err=...}          boolean   -> boolean
+//! null      -> false
+//! ```
+//!
+//! In RESP2 the RESP3 only replies are converted like they are sent to a RESP2
+//! client.
+
+use bytestring::ByteString;
+use mlua::{Lua, Table, Value};
+
+use crate::application::server::frame::write::format_double;
+use crate::application::server::frame::{Frame, Protocol};
+
+/// Convert a reply into a Lua value.
+pub(super) fn frame_to_lua(
+    lua: &Lua,
+    frame: Frame,
+    protocol: Protocol,
+) -> mlua::Result<Value> {
+    let value = match (frame, protocol) {
+        (Frame::Simple(status), _) => {
+            Value::Table(single_field(lua, "ok", status.as_bytes())?)
+        }
+        (Frame::Error(err), _) => {
+            Value::Table(single_field(lua, "err", err.as_bytes())?)
+        }
+        (Frame::Integer(val), _) => Value::Integer(val),
+        (Frame::Bulk(val), _) => Value::String(lua.create_string(&val)?),
+        (Frame::Null | Frame::NullArray, Protocol::Resp2) => {
+            Value::Boolean(false)
+        }
+        (Frame::Null | Frame::NullArray, Protocol::Resp3) => Value::Nil,
+        (Frame::Array(frames) | Frame::Push(frames), protocol)
+        | (Frame::Set(frames), protocol @ Protocol::Resp2) => {
+            Value::Table(sequence(lua, frames, protocol)?)
+        }
+        (Frame::Map(map), Protocol::Resp2) => {
+            let frames = map.into_iter().flat_map(|(k, v)| [k, v]).collect();
+            Value::Table(sequence(lua, frames, protocol)?)
+        }
+        (Frame::Map(map), Protocol::Resp3) => {
+            let table = lua.create_table()?;
+            for (key, value) in map {
+                let key = frame_to_lua(lua, key, protocol)?;
+                if !key.is_nil() {
+                    table.raw_set(key, frame_to_lua(lua, value, protocol)?)?;
+                }
+            }
+            let wrapper = lua.create_table()?;
+            wrapper.raw_set("map", table)?;
+            Value::Table(wrapper)
+        }
+        (Frame::Set(frames), Protocol::Resp3) => {
+            let table = lua.create_table()?;
+            for member in frames {
+                let member = frame_to_lua(lua, member, protocol)?;
+                if !member.is_nil() {
+                    table.raw_set(member, true)?;
+                }
+            }
+            let wrapper = lua.create_table()?;
+            wrapper.raw_set("set", table)?;
+            Value::Table(wrapper)
+        }
+        (Frame::Double(val), Protocol::Resp2) => {
+            Value::String(lua.create_string(format_double(val))?)
+        }
+        (Frame::Double(val), Protocol::Resp3) => {
+            let wrapper = lua.create_table()?;
+            wrapper.raw_set("double", val)?;
+            Value::Table(wrapper)
+        }
+        (Frame::Boolean(val), Protocol::Resp2) => Value::Integer(val as i64),
+        (Frame::Boolean(val), Protocol::Resp3) => Value::Boolean(val),
+    };
+
+    Ok(value)
+}
+
+fn single_field(lua: &Lua, name: &str, value: &[u8]) -> mlua::Result<Table> {
+    let table = lua.create_table()?;
+    table.raw_set(name, lua.create_string(value)?)?;
+    Ok(table)
+}
+
+fn sequence(
+    lua: &Lua,
+    frames: Vec<Frame>,
+    protocol: Protocol,
+) -> mlua::Result<Table> {
+    let table = lua.create_table_with_capacity(frames.len(), 0)?;
+    for (i, frame) in frames.into_iter().enumerate() {
+        table.raw_set(i + 1, frame_to_lua(lua, frame, protocol)?)?;
+    }
+    Ok(table)
+}
+
+/// Convert the value returned by a script into the reply sent to a client
+/// using `protocol`.
+///
+/// ```text
+/// number         -> integer (truncated)
+/// string         -> bulk
+/// true           -> 1, or true in RESP3
+/// false, nil     -> null, or false in RESP3 for false
+/// {err=...}      -> error
+/// {ok=...}       -> status
+/// {double=...}   -> double
+/// {map={...}}    -> map
+/// {set={...}}    -> set
+/// table          -> array, up to the first nil
+/// ```
+pub(super) fn lua_to_frame(value: Value, protocol: Protocol) -> Frame {
+    match value {
+        Value::Integer(val) => Frame::Integer(val),
+        Value::Number(val) => Frame::Integer(val as i64),
+        Value::String(val) => Frame::Bulk(val.as_bytes().to_vec().into()),
+        Value::Boolean(true) => match protocol {
+            Protocol::Resp2 => Frame::Integer(1),
+            Protocol::Resp3 => Frame::Boolean(true),
+        },
+        Value::Boolean(false) => match protocol {
+            Protocol::Resp2 => Frame::Null,
+            Protocol::Resp3 => Frame::Boolean(false),
+        },
+        Value::Table(table) => table_to_frame(table, protocol),
+        _ => Frame::Null,
+    }
+}
+
+fn table_to_frame(table: Table, protocol: Protocol) -> Frame {
+    if let Ok(Value::String(err)) = table.raw_get("err") {
+        return Frame::Error(lossy(err.as_bytes().as_ref()));
+    }
+    if let Ok(Value::String(status)) = table.raw_get("ok") {
+        return Frame::Simple(lossy(status.as_bytes().as_ref()));
+    }
+    if let Ok(Value::Number(val)) = table.raw_get("double") {
+        return Frame::Double(val);
+    }
+    if let Ok(Value::Integer(val)) = table.raw_get("double") {
+        return Frame::Double(val as f64);
+    }
+    if let Ok(Value::Table(map)) = table.raw_get("map") {
+        let mut frames = Vec::new();
+        for (key, value) in map.pairs::<Value, Value>().flatten() {
+            frames.push((
+                lua_to_frame(key, protocol),
+                lua_to_frame(value, protocol),
+            ));
+        }
+        return Frame::Map(frames);
+    }
+    if let Ok(Value::Table(set)) = table.raw_get("set") {
+        let members = set
+            .pairs::<Value, Value>()
+            .flatten()
+            .map(|(member, _)| lua_to_frame(member, protocol))
+            .collect();
+        return Frame::Set(members);
+    }
+
+    let mut frames = Vec::new();
+    for i in 1.. {
+        match table.raw_get::<Value>(i) {
+            Ok(Value::Nil) | Err(_) => break,
+            Ok(value) => frames.push(lua_to_frame(value, protocol)),
+        }
+    }
+    Frame::Array(frames)
+}
+
+/// Error and status replies can't contain invalid UTF-8.
+pub(super) fn lossy(bytes: &[u8]) -> ByteString {
+    ByteString::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use mlua::{Lua, Value};
+
+    use super::{frame_to_lua, lua_to_frame};
+    use crate::application::server::frame::{Frame, Protocol};
+
+    #[test]
+    fn reply_round_trip() {
+        let lua = Lua::new();
+        let reply = Frame::Array(vec![
+            Frame::Integer(1),
+            Frame::Bulk("two".into()),
+            Frame::Simple("OK".into()),
+            Frame::Error("ERR bad".into()),
+        ]);
+
+        let value = frame_to_lua(&lua, reply.clone(), Protocol::Resp2).unwrap();
+        assert_eq!(lua_to_frame(value, Protocol::Resp2), reply);
+    }
+
+    #[test]
+    fn resp3_replies() {
+        let lua = Lua::new();
+        let map =
+            Frame::Map(vec![(Frame::Bulk("field".into()), Frame::Double(1.5))]);
+
+        let value = frame_to_lua(&lua, map.clone(), Protocol::Resp3).unwrap();
+        assert_eq!(lua_to_frame(value, Protocol::Resp3), map);
+
+        let value = frame_to_lua(&lua, map, Protocol::Resp2).unwrap();
+        assert_eq!(
+            lua_to_frame(value, Protocol::Resp2),
+            Frame::Array(vec![
+                Frame::Bulk("field".into()),
+                Frame::Bulk("1.5".into())
+            ])
+        );
+
+        let value = frame_to_lua(&lua, Frame::Null, Protocol::Resp3).unwrap();
+        assert_eq!(value, Value::Nil);
+        let value = frame_to_lua(&lua, Frame::Null, Protocol::Resp2).unwrap();
+        assert_eq!(value, Value::Boolean(false));
+    }
+
+    #[test]
+    fn booleans_depend_on_the_client_protocol() {
+        assert_eq!(
+            lua_to_frame(Value::Boolean(true), Protocol::Resp2),
+            Frame::Integer(1)
+        );
+        assert_eq!(
+            lua_to_frame(Value::Boolean(false), Protocol::Resp2),
+            Frame::Null
+        );
+        assert_eq!(
+            lua_to_frame(Value::Boolean(false), Protocol::Resp3),
+            Frame::Boolean(false)
+        );
+        assert_eq!(
+            lua_to_frame(Value::Number(3.7), Protocol::Resp2),
+            Frame::Integer(3)
+        );
+    }
+}
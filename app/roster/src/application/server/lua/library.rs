@@ -0,0 +1,321 @@
+//! The libraries of functions: their code runs once per interpreter to
+//! register their functions with `redis.register_function`, which are then
+//! called by `FCALL` with the keys and the arguments as parameters.
+
+use std::cell::RefCell;
+
+use bytes::Bytes;
+use mlua::chunk::ChunkMode;
+use mlua::{Function, Lua, MultiValue, Table, Value};
+use rustc_hash::FxHashMap;
+
+use super::{error_cause, strip_shebang, LuaEngine};
+use crate::application::server::cmd::CommandError;
+use crate::application::server::context::Context;
+use crate::application::server::frame::Frame;
+use crate::domain::scripting::{
+    FunctionInfo, Library, RunningFunction, ScriptFlags, Shebang, ShebangError,
+};
+
+/// A library loaded by an interpreter.
+pub(super) struct LoadedLibrary {
+    /// The id of the [Library] loaded, to know when it was replaced.
+    id: u64,
+    functions: FxHashMap<String, Function>,
+}
+
+/// The functions registered while the code of a library runs.
+#[derive(Default)]
+struct Registration {
+    functions: RefCell<Vec<(FunctionInfo, Function)>>,
+}
+
+/// The names of the libraries and of the functions share the same rules.
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'_')
+}
+
+impl LuaEngine {
+    /// Run the code of a library to check it and to know the functions it
+    /// registers.
+    pub fn load_library(&self, code: Bytes) -> Result<Library, CommandError> {
+        let (name, functions) = self.run_library(&code)?;
+        let functions = functions.into_iter().map(|(info, _)| info).collect();
+        Ok(Library::new(name, code, functions))
+    }
+
+    fn run_library(
+        &self,
+        code: &[u8],
+    ) -> Result<(String, Vec<(FunctionInfo, Function)>), CommandError> {
+        let shebang = Shebang::parse(code)
+            .map_err(|err| CommandError::err(err.to_string()))?
+            .ok_or_else(|| CommandError::err("Missing library metadata"))?;
+        if shebang.flags != ScriptFlags::default() {
+            let flags = shebang.flags.names().collect::<Vec<_>>().join(",");
+            return Err(CommandError::err(
+                ShebangError::Option(format!("flags={flags}")).to_string(),
+            ));
+        }
+        let name = shebang
+            .name
+            .ok_or_else(|| CommandError::err("Library name was not given"))?;
+        if !is_valid_name(&name) {
+            return Err(CommandError::err(
+                "Library names can only contain letters, numbers, or \
+                 underscores(_) and must be at least one character long",
+            ));
+        }
+
+        let body = self
+            .lua
+            .load(strip_shebang(code))
+            .set_name("@user_function")
+            .set_mode(ChunkMode::Text)
+            .into_function()
+            .map_err(|err| {
+                let message = match err {
+                    mlua::Error::SyntaxError { message, .. } => message,
+                    err => err.to_string(),
+                };
+                CommandError::err(format!(
+                    "Error compiling function: {message}"
+                ))
+            })?;
+
+        self.lua.set_app_data(Registration::default());
+        let result = body.call::<()>(());
+        let registration = self
+            .lua
+            .remove_app_data::<Registration>()
+            .unwrap_or_default();
+        if let Err(err) = result {
+            return Err(CommandError::err(format!(
+                "Error registering functions: {}",
+                error_cause(&err)
+            )));
+        }
+
+        let functions = registration.functions.into_inner();
+        if functions.is_empty() {
+            return Err(CommandError::err("No functions registered"));
+        }
+        Ok((name, functions))
+    }
+
+    /// The function `name` of `library`, the library is loaded by this
+    /// interpreter when needed.
+    fn library_function(
+        &self,
+        library: &Library,
+        name: &str,
+    ) -> Result<Function, CommandError> {
+        let loaded = self
+            .libraries
+            .borrow()
+            .get(&library.name)
+            .filter(|loaded| loaded.id == library.id())
+            .and_then(|loaded| loaded.functions.get(name).cloned());
+        if let Some(function) = loaded {
+            return Ok(function);
+        }
+
+        let (_, functions) = self.run_library(&library.code)?;
+        let functions = functions
+            .into_iter()
+            .map(|(info, function)| (info.name, function))
+            .collect::<FxHashMap<_, _>>();
+        let function = functions
+            .get(name)
+            .cloned()
+            .ok_or_else(|| CommandError::err("Function not found"))?;
+        self.libraries.borrow_mut().insert(
+            library.name.clone(),
+            LoadedLibrary {
+                id: library.id(),
+                functions,
+            },
+        );
+        Ok(function)
+    }
+
+    /// Forget the libraries loaded by this interpreter.
+    pub fn flush_libraries(&self) {
+        self.libraries.borrow_mut().clear();
+    }
+
+    /// Call the function `name` of `library` with the keys and the arguments
+    /// as parameters.
+    ///
+    /// The caller must hold an exclusive access on the storage.
+    pub async fn call_function(
+        &self,
+        ctx: &Context,
+        library: &Library,
+        name: &str,
+        keys: &[Bytes],
+        args: &[Bytes],
+        read_only: bool,
+    ) -> Result<Frame, CommandError> {
+        let info = library
+            .function(name)
+            .ok_or_else(|| CommandError::err("Function not found"))?;
+        if read_only && !info.flags.contains(ScriptFlags::NO_WRITES) {
+            return Err(CommandError::err(
+                "Can not execute a script with write flag using *_ro command.",
+            ));
+        }
+        let mut flags = info.flags;
+        if read_only {
+            flags = flags | ScriptFlags::NO_WRITES;
+        }
+
+        let function = self.library_function(library, name)?;
+
+        let mut params = MultiValue::new();
+        params.push_back(Value::Table(self.sequence(keys)?));
+        params.push_back(Value::Table(self.sequence(args)?));
+
+        let command = [
+            Bytes::from_static(if read_only { b"FCALL_RO" } else { b"FCALL" }),
+            Bytes::from(name.to_string()),
+            Bytes::from(keys.len().to_string()),
+        ]
+        .into_iter()
+        .chain(keys.iter().cloned())
+        .chain(args.iter().cloned())
+        .collect();
+        let running = RunningFunction {
+            name: name.to_string(),
+            command,
+        };
+
+        self.run(ctx, &function, params, keys, flags, Some(running))
+            .await
+    }
+}
+
+/// `redis.register_function`, only available while a library is loaded:
+///
+/// ```lua
+/// redis.register_function('name', callback)
+/// redis.register_function{function_name='name', callback=callback,
+///     flags={'no-writes'}, description='...'}
+/// ```
+pub(super) fn register_function(
+    lua: &Lua,
+    args: MultiValue,
+) -> mlua::Result<()> {
+    let Some(registration) = lua.app_data_ref::<Registration>() else {
+        return Err(mlua::Error::runtime(
+            "redis.register_function can only be called on FUNCTION LOAD \
+             command",
+        ));
+    };
+
+    let mut args = args.into_iter();
+    let (name, callback, description, flags) =
+        match (args.next(), args.next(), args.next()) {
+            (Some(name), Some(callback), None) => {
+                (name, callback, None, ScriptFlags::default())
+            }
+            (Some(Value::Table(table)), None, None) => named_arguments(table)?,
+            _ => {
+                return Err(mlua::Error::runtime(
+                    "wrong number of arguments to redis.register_function",
+                ));
+            }
+        };
+
+    let Value::String(name) = name else {
+        return Err(mlua::Error::runtime(
+            "function_name argument given to redis.register_function must be \
+             a string",
+        ));
+    };
+    let Value::Function(callback) = callback else {
+        return Err(mlua::Error::runtime(
+            "callback argument given to redis.register_function must be a \
+             function",
+        ));
+    };
+    let name = name.to_string_lossy();
+    if !is_valid_name(&name) {
+        return Err(mlua::Error::runtime(
+            "Function names can only contain letters, numbers, or \
+             underscores(_) and must be at least one character long",
+        ));
+    }
+
+    let mut functions = registration.functions.borrow_mut();
+    if functions.iter().any(|(info, _)| info.name == name) {
+        return Err(mlua::Error::runtime(
+            "Function already exists in the library",
+        ));
+    }
+    functions.push((
+        FunctionInfo {
+            name,
+            description,
+            flags,
+        },
+        callback,
+    ));
+    Ok(())
+}
+
+fn named_arguments(
+    table: Table,
+) -> mlua::Result<(Value, Value, Option<String>, ScriptFlags)> {
+    let mut name = Value::Nil;
+    let mut callback = Value::Nil;
+    let mut description = None;
+    let mut flags = ScriptFlags::default();
+
+    for pair in table.pairs::<String, Value>() {
+        let (key, value) = pair.map_err(|_| {
+            mlua::Error::runtime(
+                "named argument key given to redis.register_function is not a \
+                 string",
+            )
+        })?;
+        match (key.as_str(), value) {
+            ("function_name", value) => name = value,
+            ("callback", value) => callback = value,
+            ("description", Value::String(value)) => {
+                description = Some(value.to_string_lossy());
+            }
+            ("description", _) => {
+                return Err(mlua::Error::runtime(
+                    "description given to redis.register_function must be a \
+                     string",
+                ));
+            }
+            ("flags", Value::Table(values)) => {
+                for value in values.sequence_values::<String>() {
+                    let flag = value
+                        .ok()
+                        .and_then(|value| value.parse::<ScriptFlags>().ok())
+                        .ok_or_else(|| {
+                            mlua::Error::runtime("unknown flag given")
+                        })?;
+                    flags = flags | flag;
+                }
+            }
+            ("flags", _) => {
+                return Err(mlua::Error::runtime(
+                    "flags argument to redis.register_function must be a \
+                     table representing function flags",
+                ));
+            }
+            _ => {
+                return Err(mlua::Error::runtime(
+                    "unknown argument given to redis.register_function",
+                ));
+            }
+        }
+    }
+
+    Ok((name, callback, description, flags))
+}
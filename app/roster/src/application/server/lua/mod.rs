@@ -0,0 +1,694 @@
+//! The Lua 5.1 engine running the scripts sent with `EVAL` and the functions
+//! called with `FCALL`.
+//!
+//! Each thread has its own interpreter, the scripts run while the connection
+//! holds an exclusive access on the storage so they are atomic. The commands
+//! called with `redis.call` are applied on a detached connection, a command
+//! which has to wait suspends the script in a coroutine so the thread keeps
+//! running its other tasks. The Lua 5.1 `pcall` can't be suspended, it's
+//! replaced by one running the function in a coroutine: a script can
+//! `pcall(redis.call, ...)` like in Redis.
+//!
+//! Besides `redis`, the scripts can use the `cjson`, `cmsgpack` and `bit`
+//! libraries. Creating global variables is forbidden.
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use bytes::Bytes;
+use bytestring::ByteString;
+use mlua::chunk::ChunkMode;
+use mlua::{
+    Function, HookTriggers, Lua, LuaOptions, LuaString, MultiValue, StdLib,
+    Table, Value, Variadic, VmState,
+};
+use rustc_hash::FxHashMap;
+use tracing::{debug, info, warn};
+
+use self::convert::{frame_to_lua, lossy, lua_to_frame};
+use super::cmd::{Command, CommandError, CommandExecution};
+use super::connection::WriteConnection;
+use super::context::Context;
+use super::frame::{Frame, Protocol};
+use crate::domain::acl::LogContext;
+use crate::domain::scripting::{
+    sha1hex, RunningFunction, ScriptFlags, Scripts,
+};
+use crate::infrastructure::hash::crc_hash;
+
+mod bit;
+mod cjson;
+mod cmsgpack;
+mod convert;
+mod library;
+
+/// How many instructions are run between two checks of `SCRIPT KILL`.
+const KILL_CHECK_INSTRUCTIONS: u32 = 100_000;
+
+/// The `redis.call` raising the errors returned by `redis.pcall`, defined in
+/// Lua as an error can only be raised with a table from Lua.
+const REDIS_CALL: &str = r#"
+local pcall_impl = ...
+redis.pcall = pcall_impl
+redis.call = function(...)
+    local reply = pcall_impl(...)
+    if type(reply) == "table" and reply.err ~= nil then
+        error(reply, 0)
+    end
+    return reply
+end
+"#;
+
+/// The `pcall` running the function in a coroutine, the yields of a command
+/// waiting in `redis.call` are forwarded to the caller.
+const PCALL: &str = r#"
+local create, resume, status, yield =
+    coroutine.create, coroutine.resume, coroutine.status, coroutine.yield
+
+local function forward(co, ok, ...)
+    if not ok or status(co) == "dead" then
+        return ok, ...
+    end
+    return forward(co, resume(co, yield(...)))
+end
+
+_G.pcall = function(f, ...)
+    local co = create(function(...) return f(...) end)
+    return forward(co, resume(co, ...))
+end
+"#;
+
+/// Forbid loading bytecode and creating or reading missing globals.
+const SANDBOX: &str = r#"
+local loadstring = loadstring
+_G.loadstring = function(source, name)
+    if type(source) == "string" and source:byte(1) == 27 then
+        return nil, "bytecode is not allowed"
+    end
+    return loadstring(source, name)
+end
+_G.load = nil
+_G.dofile = nil
+_G.loadfile = nil
+_G.os = { clock = os.clock }
+
+setmetatable(_G, {
+    __index = function(_, name)
+        error("Script attempted to access nonexistent global variable '"
+            .. tostring(name) .. "'", 2)
+    end,
+    __newindex = function()
+        error("Attempt to modify a readonly table", 2)
+    end,
+})
+"#;
+
+/// The script currently running, available to the `redis` library.
+struct ScriptRun {
+    ctx: Context,
+    /// The protocol selected with `redis.setresp`.
+    protocol: Rc<Cell<Protocol>>,
+    flags: ScriptFlags,
+    /// The slot of the keys accessed by the script, the first one fixes it.
+    slot: Cell<Option<u16>>,
+}
+
+pub struct LuaEngine {
+    lua: Lua,
+    scripts: Scripts,
+    /// The [PCALL], used to run the scripts so the errors they raise keep
+    /// their Lua value.
+    pcall: Function,
+    /// The scripts compiled by this interpreter, by SHA1.
+    compiled: RefCell<FxHashMap<String, Function>>,
+    /// The libraries of functions loaded by this interpreter, by name.
+    libraries: RefCell<FxHashMap<String, library::LoadedLibrary>>,
+}
+
+impl LuaEngine {
+    pub fn new(scripts: Scripts) -> mlua::Result<LuaEngine> {
+        let lua = Lua::new_with(
+            StdLib::TABLE | StdLib::STRING | StdLib::MATH | StdLib::OS,
+            LuaOptions::default(),
+        )?;
+
+        let globals = lua.globals();
+        globals.raw_set("cjson", cjson::register(&lua)?)?;
+        globals.raw_set("cmsgpack", cmsgpack::register(&lua)?)?;
+        globals.raw_set("bit", bit::register(&lua)?)?;
+        globals.raw_set("redis", redis_library(&lua)?)?;
+        lua.load(REDIS_CALL)
+            .set_name("=redis")
+            .call::<()>(lua.create_async_function(redis_pcall)?)?;
+        lua.load(PCALL).set_name("=pcall").exec()?;
+        lua.load(SANDBOX).set_name("=sandbox").exec()?;
+
+        let killed = scripts.clone();
+        lua.set_global_hook(
+            HookTriggers::new().every_nth_instruction(KILL_CHECK_INSTRUCTIONS),
+            move |_, _| {
+                if killed.is_killed() {
+                    Err(mlua::Error::runtime(
+                        "Script killed by user with SCRIPT KILL...",
+                    ))
+                } else {
+                    Ok(VmState::Continue)
+                }
+            },
+        )?;
+
+        let pcall = globals.raw_get("pcall")?;
+        Ok(LuaEngine {
+            lua,
+            scripts,
+            pcall,
+            compiled: Default::default(),
+            libraries: Default::default(),
+        })
+    }
+
+    /// Compile the script `body` identified by `sha`, the compiled scripts are
+    /// kept until [LuaEngine::flush].
+    pub fn compile(
+        &self,
+        sha: &str,
+        body: &[u8],
+    ) -> Result<Function, CommandError> {
+        if let Some(function) = self.compiled.borrow().get(sha) {
+            return Ok(function.clone());
+        }
+
+        let function = self
+            .lua
+            .load(strip_shebang(body))
+            .set_name("@user_script")
+            .set_mode(ChunkMode::Text)
+            .into_function()
+            .map_err(|err| {
+                let message = match err {
+                    mlua::Error::SyntaxError { message, .. } => message,
+                    err => err.to_string(),
+                };
+                CommandError::err(format!(
+                    "Error compiling script (new function): {message}"
+                ))
+            })?;
+
+        self.compiled
+            .borrow_mut()
+            .insert(sha.to_string(), function.clone());
+        Ok(function)
+    }
+
+    /// Forget the scripts compiled by this interpreter.
+    pub fn flush(&self) {
+        self.compiled.borrow_mut().clear();
+    }
+
+    /// Run a compiled script with the `KEYS` and `ARGV` globals.
+    ///
+    /// The caller must hold an exclusive access on the storage.
+    pub async fn run_script(
+        &self,
+        ctx: &Context,
+        function: &Function,
+        keys: &[Bytes],
+        args: &[Bytes],
+        flags: ScriptFlags,
+    ) -> Result<Frame, CommandError> {
+        let globals = self.lua.globals();
+        globals
+            .raw_set("KEYS", self.sequence(keys)?)
+            .map_err(lua_error)?;
+        globals
+            .raw_set("ARGV", self.sequence(args)?)
+            .map_err(lua_error)?;
+
+        self.run(ctx, function, MultiValue::new(), keys, flags, None)
+            .await
+    }
+
+    fn sequence(&self, values: &[Bytes]) -> Result<Table, CommandError> {
+        self.lua
+            .create_sequence_from(
+                values
+                    .iter()
+                    .map(|value| self.lua.create_string(value))
+                    .collect::<mlua::Result<Vec<_>>>()
+                    .map_err(lua_error)?,
+            )
+            .map_err(lua_error)
+    }
+
+    async fn run(
+        &self,
+        ctx: &Context,
+        function: &Function,
+        params: MultiValue,
+        keys: &[Bytes],
+        flags: ScriptFlags,
+        function_called: Option<RunningFunction>,
+    ) -> Result<Frame, CommandError> {
+        let _running = self.scripts.start(function_called);
+        self.lua.set_app_data(ScriptRun {
+            ctx: ctx.clone(),
+            protocol: Rc::new(Cell::new(Protocol::Resp2)),
+            flags,
+            slot: Cell::new(keys.first().map(|key| crc_hash(key))),
+        });
+
+        let mut call = MultiValue::new();
+        call.push_back(Value::Function(function.clone()));
+        call.extend(params);
+        let result = self.pcall.call_async::<MultiValue>(call).await;
+
+        self.lua.remove_app_data::<ScriptRun>();
+
+        if self.scripts.is_killed() {
+            return Err(CommandError::err(
+                "Script killed by user with SCRIPT KILL...",
+            ));
+        }
+
+        let mut values = result.map_err(lua_error)?.into_iter();
+        match (values.next(), values.next()) {
+            (Some(Value::Boolean(true)), value) => {
+                Ok(lua_to_frame(value.unwrap_or(Value::Nil), ctx.protocol()))
+            }
+            (_, value) => Err(CommandError::Other(error_message(
+                value.unwrap_or(Value::Nil),
+            ))),
+        }
+    }
+}
+
+/// The shebang is not Lua, the line is kept empty so the line numbers of the
+/// errors are right.
+fn strip_shebang(body: &[u8]) -> &[u8] {
+    match body.strip_prefix(b"#!") {
+        Some(rest) => {
+            let start =
+                rest.iter().position(|c| *c == b'\n').unwrap_or(rest.len());
+            &rest[start..]
+        }
+        None => body,
+    }
+}
+
+fn lua_error(err: mlua::Error) -> CommandError {
+    CommandError::err(err.to_string())
+}
+
+/// The reply sent for an error raised by a script.
+fn error_message(value: Value) -> ByteString {
+    match value {
+        Value::Table(table) => match table.raw_get("err") {
+            Ok(Value::String(err)) => lossy(&err.as_bytes()),
+            _ => ByteString::from_static("ERR unknown error"),
+        },
+        Value::String(err) => {
+            lossy(format!("ERR {}", err.to_string_lossy()).as_bytes())
+        }
+        Value::Error(err) => {
+            lossy(format!("ERR {}", error_cause(&err)).as_bytes())
+        }
+        _ => ByteString::from_static("ERR unknown error"),
+    }
+}
+
+/// The message of an error raised from Rust, without the traceback of the
+/// callback wrapping it.
+fn error_cause(mut err: &mlua::Error) -> String {
+    while let mlua::Error::CallbackError { cause, .. } = err {
+        err = cause;
+    }
+    match err {
+        mlua::Error::RuntimeError(message) => message.clone(),
+        err => err.to_string(),
+    }
+}
+
+/// Create a `{err = message}` table, the way errors are represented in Lua.
+fn error_table(lua: &Lua, message: &str) -> mlua::Result<Value> {
+    let table = lua.create_table()?;
+    table.raw_set("err", message)?;
+    Ok(Value::Table(table))
+}
+
+/// `redis.pcall`: apply a command and return its reply, an error reply is
+/// returned as a `{err = message}` table.
+async fn redis_pcall(lua: Lua, args: Variadic<Value>) -> mlua::Result<Value> {
+    let (ctx, protocol, flags, slot) = {
+        let run = lua.app_data_ref::<ScriptRun>().ok_or_else(|| {
+            mlua::Error::runtime("redis.call can only be used in a script")
+        })?;
+        (
+            run.ctx.clone(),
+            run.protocol.clone(),
+            run.flags,
+            run.slot.get(),
+        )
+    };
+
+    if args.is_empty() {
+        return error_table(
+            &lua,
+            "ERR Please specify at least one argument for this redis lib call",
+        );
+    }
+
+    let mut frames = Vec::with_capacity(args.len());
+    for arg in args.iter() {
+        let arg = match arg {
+            Value::String(arg) => Bytes::from(arg.as_bytes().to_vec()),
+            Value::Integer(arg) => Bytes::from(arg.to_string()),
+            Value::Number(arg) if arg.fract() == 0.0 => {
+                Bytes::from((*arg as i64).to_string())
+            }
+            Value::Number(arg) => Bytes::from(arg.to_string()),
+            _ => {
+                return error_table(
+                    &lua,
+                    "ERR Lua redis lib command arguments must be strings or \
+                     integers",
+                );
+            }
+        };
+        frames.push(Frame::Bulk(arg));
+    }
+
+    let cmd = match Command::from_frame(
+        Frame::Array(frames),
+        ctx.supervisor.commands(),
+    ) {
+        Ok(Command::Unknown(_)) => {
+            return error_table(
+                &lua,
+                "ERR Unknown Redis command called from script",
+            );
+        }
+        Ok(cmd) => cmd,
+        Err(err) => return error_table(&lua, &err.to_string()),
+    };
+
+    if !cmd.is_allowed_in_script() {
+        return error_table(
+            &lua,
+            "ERR This Redis command is not allowed from script",
+        );
+    }
+
+    // The command runs with the permissions of the user calling the script.
+    if let Err(err) = cmd.check_permissions(&ctx, LogContext::Lua).await {
+        return error_table(&lua, &err.to_string());
+    }
+
+    if cmd.is_write() {
+        if flags.contains(ScriptFlags::NO_WRITES) {
+            return error_table(
+                &lua,
+                "ERR Write commands are not allowed from read-only scripts.",
+            );
+        }
+        ctx.supervisor.scripts().mark_write();
+    }
+
+    if let Some(hash) = cmd.hash_key() {
+        match slot {
+            _ if flags.contains(ScriptFlags::ALLOW_CROSS_SLOT_KEYS) => {}
+            Some(slot) if slot != hash => {
+                return error_table(
+                    &lua,
+                    "ERR Script attempted to access keys that do not hash to \
+                     the same slot",
+                );
+            }
+            Some(_) => {}
+            None => {
+                if let Some(run) = lua.app_data_ref::<ScriptRun>() {
+                    run.slot.set(Some(hash));
+                }
+            }
+        }
+    }
+
+    let mut dst = WriteConnection::detached(protocol.clone());
+    if let Err(err) = cmd.execute(&mut dst, ctx.clone()).await {
+        return error_table(&lua, &format!("ERR {err}"));
+    }
+
+    let reply = dst
+        .take_captured()
+        .into_iter()
+        .next()
+        .unwrap_or(Frame::Null);
+    frame_to_lua(&lua, reply, protocol.get())
+}
+
+/// The `redis` library, `redis.call` & `redis.pcall` are added by
+/// [REDIS_CALL].
+fn redis_library(lua: &Lua) -> mlua::Result<Table> {
+    let redis = lua.create_table()?;
+
+    redis.raw_set(
+        "error_reply",
+        lua.create_function(|lua, message: LuaString| {
+            let table = lua.create_table()?;
+            table.raw_set("err", message)?;
+            Ok(table)
+        })?,
+    )?;
+    redis.raw_set(
+        "status_reply",
+        lua.create_function(|lua, status: LuaString| {
+            let table = lua.create_table()?;
+            table.raw_set("ok", status)?;
+            Ok(table)
+        })?,
+    )?;
+    redis.raw_set(
+        "sha1hex",
+        lua.create_function(|_, body: LuaString| {
+            Ok(sha1hex(&body.as_bytes()))
+        })?,
+    )?;
+    redis.raw_set(
+        "register_function",
+        lua.create_function(library::register_function)?,
+    )?;
+    redis.raw_set(
+        "setresp",
+        lua.create_function(|lua, version: i64| {
+            let protocol =
+                Protocol::from_version(version).ok_or_else(|| {
+                    mlua::Error::runtime("RESP version must be 2 or 3.")
+                })?;
+            if let Some(run) = lua.app_data_ref::<ScriptRun>() {
+                run.protocol.set(protocol);
+            }
+            Ok(())
+        })?,
+    )?;
+    redis.raw_set(
+        "log",
+        lua.create_function(|_, args: Variadic<LuaString>| {
+            let Some((level, messages)) = args.split_first() else {
+                return Err(mlua::Error::runtime(
+                    "redis.log() requires two arguments or more.",
+                ));
+            };
+            if messages.is_empty() {
+                return Err(mlua::Error::runtime(
+                    "redis.log() requires two arguments or more.",
+                ));
+            }
+            let message = messages
+                .iter()
+                .map(|message| message.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(" ");
+            match &*level.to_string_lossy() {
+                "0" | "1" => debug!(target: "script", "{message}"),
+                "2" => info!(target: "script", "{message}"),
+                "3" => warn!(target: "script", "{message}"),
+                _ => return Err(mlua::Error::runtime("Invalid debug level.")),
+            }
+            Ok(())
+        })?,
+    )?;
+    // Scripts are always replicated by effects, those are kept for the
+    // compatibility of the old scripts.
+    redis.raw_set("set_repl", lua.create_function(|_, _flags: i64| Ok(()))?)?;
+    redis.raw_set(
+        "replicate_commands",
+        lua.create_function(|_, ()| Ok(true))?,
+    )?;
+
+    for (name, value) in [
+        ("LOG_DEBUG", 0),
+        ("LOG_VERBOSE", 1),
+        ("LOG_NOTICE", 2),
+        ("LOG_WARNING", 3),
+        ("REPL_NONE", 0),
+        ("REPL_AOF", 1),
+        ("REPL_SLAVE", 2),
+        ("REPL_REPLICA", 2),
+        ("REPL_ALL", 3),
+    ] {
+        redis.raw_set(name, value)?;
+    }
+
+    Ok(redis)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+    use std::sync::atomic::AtomicU16;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use bytes::Bytes;
+
+    use super::LuaEngine;
+    use crate::application::server::context::Context;
+    use crate::application::server::frame::Frame;
+    use crate::application::server::supervisor::{ConnectionAddr, Supervisor};
+    use crate::application::server::wasm::WasmEngine;
+    use crate::domain::dialer::Slot;
+    use crate::domain::notification::Notifier;
+    use crate::domain::scripting::{sha1hex, Functions, ScriptFlags, Scripts};
+    use crate::domain::storage::Storage;
+    use crate::infrastructure::hash::HASH_SLOT_MAX;
+
+    fn context(scripts: Scripts) -> Context {
+        let supervisor = Supervisor::new(
+            0,
+            scripts.clone(),
+            WasmEngine::new(0, 0).unwrap(),
+            Default::default(),
+            Default::default(),
+            None,
+            Arc::new(AtomicU16::new(1)),
+        );
+        let addr = ConnectionAddr::Tcp("127.0.0.1:6379".parse().unwrap());
+        let meta_conn = supervisor.assign_new_connection(addr.clone(), addr, 0);
+        let storage =
+            Storage::new(1, Slot::from(0..HASH_SLOT_MAX), Notifier::default());
+        Context::new(
+            storage.part(0).1,
+            Rc::new(storage),
+            supervisor,
+            meta_conn,
+            Rc::new(LuaEngine::new(scripts).unwrap()),
+        )
+    }
+
+    async fn eval(ctx: &Context, body: &str, flags: ScriptFlags) -> Frame {
+        let function = ctx
+            .lua()
+            .compile(&sha1hex(body.as_bytes()), body.as_bytes());
+        let keys = [Bytes::from_static(b"key")];
+        let reply = match function {
+            Ok(function) => {
+                ctx.lua()
+                    .run_script(ctx, &function, &keys, &[], flags)
+                    .await
+            }
+            Err(err) => Err(err),
+        };
+        match reply {
+            Ok(frame) => frame,
+            Err(err) => err.into_frame(),
+        }
+    }
+
+    #[monoio::test]
+    async fn call_commands() {
+        let ctx = context(Scripts::default());
+
+        let reply = eval(
+            &ctx,
+            "redis.call('SET', KEYS[1], 'value') return redis.call('GET', \
+             KEYS[1])",
+            ScriptFlags::default(),
+        )
+        .await;
+        assert_eq!(reply, Frame::Bulk("value".into()));
+
+        let reply = eval(
+            &ctx,
+            "local ok, err = pcall(redis.call, 'NOPE') return err",
+            ScriptFlags::default(),
+        )
+        .await;
+        assert_eq!(
+            reply,
+            Frame::Error("ERR Unknown Redis command called from script".into())
+        );
+
+        let reply = eval(
+            &ctx,
+            "return redis.call('SET', KEYS[1], 'other')",
+            ScriptFlags::NO_WRITES,
+        )
+        .await;
+        assert_eq!(
+            reply,
+            Frame::Error(
+                "ERR Write commands are not allowed from read-only scripts."
+                    .into()
+            )
+        );
+
+        let reply = eval(
+            &ctx,
+            "return redis.call('GET', 'other')",
+            ScriptFlags::default(),
+        )
+        .await;
+        assert_eq!(
+            reply,
+            Frame::Error(
+                "ERR Script attempted to access keys that do not hash to the \
+                 same slot"
+                    .into()
+            )
+        );
+    }
+
+    #[monoio::test]
+    async fn globals_are_protected() {
+        let ctx = context(Scripts::default());
+
+        let reply = eval(&ctx, "a = 1", ScriptFlags::default()).await;
+        assert!(matches!(reply, Frame::Error(err)
+            if err.contains("Attempt to modify a readonly table")));
+
+        let reply = eval(&ctx, "return missing", ScriptFlags::default()).await;
+        assert!(matches!(reply, Frame::Error(err)
+            if err.contains("nonexistent global variable 'missing'")));
+    }
+
+    #[monoio::test]
+    async fn kill_script() {
+        let scripts = Scripts::new(Duration::ZERO, Functions::default());
+        let ctx = context(scripts.clone());
+
+        let killer = std::thread::spawn(move || {
+            while scripts.kill().is_err() {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        });
+
+        let reply =
+            eval(&ctx, "while true do end", ScriptFlags::default()).await;
+        killer.join().unwrap();
+        assert_eq!(
+            reply,
+            Frame::Error(
+                "ERR Script killed by user with SCRIPT KILL...".into()
+            )
+        );
+    }
+}
@@ -1,34 +1,113 @@
 //! The whole redis server implementation is here.
+use std::fs::Permissions;
 use std::net::SocketAddr;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::os::unix::net::UnixListener;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicU16;
 use std::sync::Arc;
 use std::thread::JoinHandle;
+use std::time::Duration;
 
 use derive_builder::Builder;
 
+mod bind;
 mod connection;
 mod context;
 pub mod frame;
 pub(crate) mod handle;
 
 mod cmd;
+mod lua;
 mod server_thread;
+mod signals;
+mod subscription;
+mod tls;
+mod transaction;
+mod wasm;
 
 mod supervisor;
 
+pub use self::bind::BindAddr;
+pub use self::cmd::{
+    CommandError, CommandExecution, CommandRegistry, CustomCommand,
+    KeyPositions, Parse, ParseError,
+};
+pub use self::connection::WriteConnection;
+pub use self::context::Context;
+use self::lua::LuaEngine;
 use self::server_thread::ServerMonoThreadedHandle;
-use self::supervisor::Supervisor;
+use self::supervisor::{Shutdown, Supervisor};
+pub use self::tls::{TlsAuthClients, TlsConfig};
+use self::wasm::WasmEngine;
 use crate::application::server::handle::ConnectionMsg;
+use crate::domain::acl::Users;
 use crate::domain::dialer::{RootDialer, Slot};
+use crate::domain::notification::{KeyspaceEvents, Notifier};
+use crate::domain::scripting::{parse_dump, Functions, RestorePolicy, Scripts};
 use crate::domain::storage::Storage;
 use crate::infrastructure::hash::HASH_SLOT_MAX;
 
 #[derive(Debug, Builder, Clone)]
 #[builder(pattern = "owned", setter(into, strip_option))]
 pub struct ServerConfig {
-    bind_addr: SocketAddr,
-    #[allow(dead_code)]
+    /// The addresses the TCP connections are accepted on, every thread binds
+    /// all of them with `SO_REUSEPORT`.
+    #[builder(setter(custom))]
+    bind_addr: Vec<BindAddr>,
+    /// Like Redis, only the loopback & unix socket clients are accepted
+    /// while the `default` user has no password.
+    #[builder(default = "true")]
+    protected_mode: bool,
+    /// The maximum number of clients connected at the same time, like
+    /// `maxclients` in Redis.
     connections_limit: Arc<AtomicU16>,
+    /// The classes of keyspace notifications published, see
+    /// [KeyspaceEvents].
+    #[builder(default)]
+    notify_keyspace_events: KeyspaceEvents,
+    /// How long a script can run before the other connections are answered
+    /// with a `BUSY` error and the script can be killed.
+    #[builder(default = "Duration::from_secs(5)")]
+    busy_reply_threshold: Duration,
+    /// The fuel a WebAssembly function can consume before it's stopped.
+    #[builder(default = "10_000_000")]
+    wasm_fuel: u64,
+    /// The memory a WebAssembly function can use, in bytes.
+    #[builder(default = "64 * 1024 * 1024")]
+    wasm_max_memory: usize,
+    /// Close the connections once their client is idle for this long, like
+    /// `timeout` in Redis. Disabled when zero.
+    #[builder(default)]
+    timeout: Duration,
+    /// Send TCP keepalive probes to the clients idle for this long, like
+    /// `tcp-keepalive` in Redis. Disabled when zero.
+    #[builder(default = "Duration::from_secs(300)")]
+    tcp_keepalive: Duration,
+    /// The commands added to the built-in ones, see [CommandRegistry].
+    #[builder(default)]
+    commands: CommandRegistry,
+    /// The password of the `default` user, every connection must
+    /// authenticate with `AUTH` when it's set.
+    #[builder(default)]
+    requirepass: Option<String>,
+    /// The file the users are loaded from at startup and by `ACL LOAD`, and
+    /// saved to by `ACL SAVE`.
+    #[builder(default)]
+    aclfile: Option<PathBuf>,
+    /// The file the libraries of functions are loaded from at startup and
+    /// saved to after every change.
+    #[builder(default)]
+    functions_file: Option<PathBuf>,
+    /// The TLS listener, accepting encrypted connections on its own port.
+    #[builder(default)]
+    tls: Option<TlsConfig>,
+    /// The unix socket connections are also accepted on.
+    #[builder(default)]
+    unixsocket: Option<PathBuf>,
+    /// The permissions of the unix socket, e.g. `0o700`.
+    #[builder(default)]
+    unixsocketperm: Option<u32>,
 }
 
 impl ServerConfig {
@@ -45,8 +124,58 @@ impl ServerConfig {
                 .unwrap();
 
         let config_slot = Slot::from(0..HASH_SLOT_MAX);
-        let storage = Storage::new(1, config_slot);
-        let supervisor = Supervisor::new(0);
+        let wasm = WasmEngine::new(self.wasm_fuel, self.wasm_max_memory)
+            .expect("Unable to create the WebAssembly engine");
+        let tls_config = self.tls.as_ref().map(|tls| {
+            tls.load().unwrap_or_else(|err| {
+                panic!("Invalid TLS configuration: {err}")
+            })
+        });
+        let unix_listener = self.unixsocket.as_ref().map(|path| {
+            self.bind_unixsocket(path)
+                .expect("Unable to listen on the unix socket")
+        });
+        let users = Users::default();
+        if let Some(path) = &self.aclfile {
+            let contents = std::fs::read_to_string(path)
+                .expect("Unable to read the ACL file");
+            users
+                .load(&contents, &|name| self.commands.is_known(name))
+                .unwrap_or_else(|err| {
+                    panic!("Invalid ACL file {}:{err}", path.display())
+                });
+        }
+        if let Some(password) = &self.requirepass {
+            users
+                .set_user(
+                    "default",
+                    &["resetpass", &format!(">{password}")],
+                    &|_| false,
+                )
+                .expect("The rules are valid");
+        }
+        let scripts = Scripts::new(
+            self.busy_reply_threshold,
+            Functions::new(self.functions_file.clone()),
+        );
+        if let Some(path) = &self.functions_file {
+            load_functions(&scripts, path);
+        }
+        let supervisor = Supervisor::new(
+            0,
+            scripts,
+            wasm,
+            self.commands.clone(),
+            users,
+            self.aclfile.clone(),
+            self.connections_limit.clone(),
+        );
+        let notifier = Notifier::new(
+            self.notify_keyspace_events,
+            supervisor.pubsub().clone(),
+            supervisor.tracking().clone(),
+        );
+        let storage = Storage::new(1, config_slot, notifier);
         let main_dialer = RootDialer::new(mesh, &storage);
 
         for cpu in 0..cpus {
@@ -60,24 +189,115 @@ impl ServerConfig {
                 &supervisor,
                 cpu,
                 &storage,
+                tls_config.clone(),
+                unix_listener.as_ref().map(|listener| {
+                    listener
+                        .try_clone()
+                        .expect("Unable to copy the unix socket")
+                }),
             );
 
             threads.push(handle.initialize());
         }
 
         ServerHandle {
-            bind: self.bind_addr,
+            bind: self.bind_addr[0].addr,
             threads,
+            shutdown: supervisor.shutdown().clone(),
         }
     }
+
+    /// Bind the unix socket, replacing the one left by a previous run.
+    fn bind_unixsocket(&self, path: &Path) -> std::io::Result<UnixListener> {
+        if std::fs::symlink_metadata(path)
+            .is_ok_and(|metadata| metadata.file_type().is_socket())
+        {
+            std::fs::remove_file(path)?;
+        }
+
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+        if let Some(mode) = self.unixsocketperm {
+            std::fs::set_permissions(path, Permissions::from_mode(mode))?;
+        }
+        Ok(listener)
+    }
+}
+
+/// Load the libraries saved to the functions file, which doesn't exist until
+/// a library is loaded.
+fn load_functions(scripts: &Scripts, path: &Path) {
+    let payload = match std::fs::read(path) {
+        Ok(payload) => payload,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return,
+        Err(err) => {
+            panic!(
+                "Unable to read the functions file {}: {err}",
+                path.display()
+            )
+        }
+    };
+
+    let lua = LuaEngine::new(scripts.clone())
+        .expect("Unable to create the Lua engine");
+    parse_dump(&payload)
+        .map_err(CommandError::from)
+        .and_then(|codes| {
+            codes
+                .into_iter()
+                .map(|code| lua.load_library(code))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .and_then(|libraries| {
+            scripts
+                .functions()
+                .restore(libraries, RestorePolicy::Append)
+                .map_err(CommandError::from)
+        })
+        .unwrap_or_else(|err| {
+            panic!("Invalid functions file {}: {err}", path.display())
+        });
+}
+
+impl ServerConfigBuilder {
+    /// Add an address the connections are accepted on.
+    pub fn bind_addr(mut self, addr: impl Into<BindAddr>) -> Self {
+        self.bind_addr
+            .get_or_insert_with(Vec::new)
+            .push(addr.into());
+        self
+    }
 }
 
 pub struct ServerHandle {
     pub bind: SocketAddr,
     threads: Vec<JoinHandle<()>>,
+    shutdown: Shutdown,
 }
 
 impl ServerHandle {
+    /// Stop the server gracefully, like `SHUTDOWN`: the connections aren't
+    /// accepted anymore and the open ones are closed once their current
+    /// command is applied. Resolved once every thread stopped, from any
+    /// async runtime.
+    pub async fn shutdown(&self) {
+        self.shutdown.request();
+        self.shutdown.completed().await;
+    }
+
+    /// Stop the server gracefully when the process receives `SIGINT` or
+    /// `SIGTERM`, a second signal exits the process right away.
+    pub fn shutdown_on_signals(&self) -> std::io::Result<()> {
+        signals::shutdown_on_signals(self.shutdown.clone())
+    }
+
+    /// The number of tasks waiting for the shutdown, the listeners and the
+    /// threads serving the connections.
+    pub fn shutdown_waiters(&self) -> usize {
+        self.shutdown.waiters()
+    }
+
+    /// Wait until every thread stopped, after a shutdown.
     pub fn join(self) {
         for t in self.threads {
             let _ = t.join();
@@ -1,14 +1,26 @@
 use std::os::fd::AsRawFd;
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::thread::JoinHandle;
+use std::time::Duration;
 
-use monoio::net::{ListenerConfig, TcpListener};
+use bytestring::ByteString;
+use monoio::io::{AsyncWriteRent, AsyncWriteRentExt};
+use monoio::net::{ListenerConfig, TcpListener, TcpStream, UnixListener};
+use sharded_thread::shard::Shard;
+use tracing::{error, warn};
 
-use super::supervisor::Supervisor;
+use super::bind::BindAddr;
+use super::supervisor::{
+    ConnectionAddr, ConnectionSlot, MetadataConnection, Supervisor,
+};
+use super::tls::TlsStream;
 use super::ServerConfig;
-use crate::application::server::connection::WriteConnection;
+use crate::application::server::connection::{Stream, WriteConnection};
 use crate::application::server::context::Context;
-use crate::application::server::handle::Handler;
+use crate::application::server::handle::{ConnectionMsg, Handler};
+use crate::application::server::lua::LuaEngine;
 use crate::domain::dialer::{Dialer, RootDialer};
 use crate::domain::storage::{Storage, StorageSegment};
 
@@ -20,6 +32,30 @@ cfg_if::cfg_if! {
     }
 }
 
+/// The error sent to the clients refused by the protected mode.
+const PROTECTED_MODE_ERROR: &str =
+    "-DENIED Roster is running in protected mode because protected mode is \
+     enabled and no password is set for the default user. In this mode \
+     connections are only accepted from the loopback interface and the unix \
+     socket. To accept connections from other hosts, either set a password \
+     for the default user with `requirepass`, or disable the protected mode \
+     with `protected_mode = false` in the configuration.\r\n";
+
+/// The error sent to the clients refused by the `connections_limit`.
+const MAX_CLIENTS_ERROR: &str = "-ERR max number of clients reached\r\n";
+
+/// How long a TLS client has to complete its handshake before its
+/// connection is closed.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a listener waits before accepting again when the process is out
+/// of file descriptors.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// How often a thread checks that every connection is closed during a
+/// shutdown.
+const SHUTDOWN_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
 /// This structure is used only in a single thread.
 pub struct ServerMonoThreadedHandle {
     config: ServerConfig,
@@ -30,8 +66,15 @@ pub struct ServerMonoThreadedHandle {
     cpu: usize,
     /// The [StorageSegment] for this thread.
     storage: StorageSegment,
+    /// Every [StorageSegment], to reach the one owning a hash slot.
+    segments: Storage,
     /// The local supervisor
     supervisor: Supervisor,
+    /// The rustls configuration, when the TLS listener is enabled.
+    tls_config: Option<Arc<rustls::ServerConfig>>,
+    /// The unix socket of this thread, a copy of the one bound by the
+    /// server.
+    unix_listener: Option<std::os::unix::net::UnixListener>,
 }
 
 impl ServerMonoThreadedHandle {
@@ -42,6 +85,8 @@ impl ServerMonoThreadedHandle {
         local_supervisor: &Supervisor,
         cpu: usize,
         storage: &Storage,
+        tls_config: Option<Arc<rustls::ServerConfig>>,
+        unix_listener: Option<std::os::unix::net::UnixListener>,
     ) -> Self {
         let (_slot, storage_segment) = storage.part(cpu as u16);
         let dialer = dialer.part(cpu as u16).unwrap();
@@ -52,10 +97,19 @@ impl ServerMonoThreadedHandle {
             supervisor: local_supervisor.clone(),
             cpu,
             storage: storage_segment,
+            segments: storage.clone(),
+            tls_config,
+            unix_listener,
         }
     }
 
     pub fn initialize(self) -> JoinHandle<()> {
+        // Counted before the thread is spawned, so the shutdown can't be
+        // completed before every thread stopped.
+        let shutdown = self.supervisor.shutdown().clone();
+        shutdown.thread_started();
+        let unixsocket = self.config.unixsocket.clone();
+
         std::thread::spawn(move || {
             monoio::utils::bind_to_cpu_set(Some(self.cpu)).unwrap();
 
@@ -65,65 +119,402 @@ impl ServerMonoThreadedHandle {
                 .build()
                 .expect("Cannot build runtime");
 
-            rt.block_on(async move {
-                let listener = TcpListener::bind_with_config(
-                    self.config.bind_addr,
-                    &ListenerConfig::new().backlog(16192),
-                )?;
-
-                let shard = Rc::new(self.dial.shard);
-
-                // We initialize the listener on the TCP for this thread.
-                loop {
-                    // TODO(@miaxos): Check cancellation
-                    let storage = self.storage.clone();
-                    let shard = shard.clone();
-
-                    // We accept the TCP Connection
-                    let (conn, addr) = listener
-                        .accept()
-                        .await
-                        .expect("Unable to accept connections");
-
-                    let laddr = conn.local_addr()?;
-                    let fd = conn.as_raw_fd();
-
-                    let meta_conn =
-                        self.supervisor.assign_new_connection(addr, laddr, fd);
-                    let supervisor = self.supervisor.clone();
-
-                    conn.set_nodelay(true).unwrap();
-                    let ctx =
-                        Context::new(storage, supervisor, meta_conn.clone());
-
-                    // We map it to an `Handler` which is able to understand
-                    // the Redis protocol
-                    let _spawned = monoio::spawn(async move {
-                        let (connection, r) =
-                            WriteConnection::new(conn, 4 * 1024);
-
-                        let handler = Handler {
-                            connection,
-                            connection_r: r,
-                            shard,
-                        };
-
-                        if let Err(err) = handler.run(ctx).await {
-                            dbg!(err.backtrace());
-                            dbg!(&err);
-                            // error!(?err);
-                            panic!("blbl");
+            let result = rt.block_on(async move {
+                let mut listeners = Vec::new();
+                for bind in &self.config.bind_addr {
+                    match TcpListener::bind_with_config(
+                        bind.addr,
+                        &ListenerConfig::new().reuse_port(true).backlog(16192),
+                    ) {
+                        Ok(listener) => listeners.push(listener),
+                        Err(err)
+                            if bind.optional
+                                && BindAddr::is_unavailable(&err) =>
+                        {
+                            warn!(%bind, ?err, "skipping unavailable address");
+                        }
+                        Err(err) => {
+                            return Err(anyhow::Error::new(err)
+                                .context(format!("Unable to bind {bind}")))
                         }
+                    }
+                }
+
+                let lua = LuaEngine::new(self.supervisor.scripts().clone())
+                    .map_err(|err| anyhow::anyhow!(err.to_string()))?;
+                let services = Services {
+                    storage: self.storage.clone(),
+                    segments: Rc::new(self.segments.clone()),
+                    supervisor: self.supervisor.clone(),
+                    shard: Rc::new(self.dial.shard),
+                    lua: Rc::new(lua),
+                    protected_mode: self.config.protected_mode,
+                    idle_timeout: self.config.timeout,
+                    tcp_keepalive: self.config.tcp_keepalive,
+                };
 
-                        meta_conn.stop();
-                        // handler.connection.stop().await.unwrap();
-                    });
+                // The TLS connections are accepted on their own port, with
+                // the same thread serving them.
+                if let Some((tls, tls_config)) =
+                    self.config.tls.as_ref().zip(self.tls_config.clone())
+                {
+                    let listener = TcpListener::bind_with_config(
+                        tls.bind_addr,
+                        &ListenerConfig::new().backlog(16192),
+                    )?;
+                    monoio::spawn(accept_tls(
+                        listener,
+                        tls_config,
+                        services.clone(),
+                    ));
                 }
 
-                #[allow(unreachable_code)]
+                // The unix socket is bound once for every thread, each one
+                // accepting the connections on it.
+                if let Some((path, listener)) =
+                    self.config.unixsocket.clone().zip(self.unix_listener)
+                {
+                    let listener = UnixListener::from_std(listener)?;
+                    monoio::spawn(accept_unix(
+                        listener,
+                        path,
+                        services.clone(),
+                    ));
+                }
+
+                // We initialize the listeners on the TCP for this thread.
+                for listener in listeners {
+                    monoio::spawn(accept_tcp(listener, services.clone()));
+                }
+
+                // The listeners are running until the shutdown is requested,
+                // then the connections are closed.
+                self.supervisor.shutdown().requested().await;
+                drain(&self.supervisor).await;
                 Ok::<(), anyhow::Error>(())
-            })
-            .unwrap();
+            });
+
+            // The listeners of this thread are closed with its runtime.
+            drop(rt);
+            shutdown.thread_stopped(|| {
+                if let Some(path) = unixsocket {
+                    let _ = std::fs::remove_file(path);
+                }
+            });
+            result.unwrap();
         })
     }
 }
+
+/// Close every connection once its current command is applied, and wait
+/// until they are all closed, whichever thread serves them.
+async fn drain(supervisor: &Supervisor) {
+    loop {
+        for conn in supervisor.get_normal_connection().await {
+            conn.kill();
+        }
+        // Like `SHUTDOWN NOSAVE`, a script which didn't write is stopped
+        // instead of delaying the shutdown.
+        let _ = supervisor.scripts().kill();
+
+        if supervisor.stats().connected() == 0 {
+            return;
+        }
+        monoio::time::sleep(SHUTDOWN_CHECK_INTERVAL).await;
+    }
+}
+
+/// Log an error of `accept`, and wait a little when the process or the system
+/// is out of file descriptors as accepting again right away would fail the
+/// same way.
+async fn accept_failed(err: std::io::Error) {
+    error!(?err, "unable to accept a connection");
+    if matches!(err.raw_os_error(), Some(libc::EMFILE | libc::ENFILE)) {
+        monoio::time::sleep(ACCEPT_BACKOFF).await;
+    }
+}
+
+/// Accept the TCP connections until the shutdown is requested.
+async fn accept_tcp(listener: TcpListener, services: Services) {
+    // Waited across the accepts, a new future for each one would leave its
+    // waker behind.
+    let shutdown = services.supervisor.shutdown();
+    let mut requested = std::pin::pin!(shutdown.requested());
+    loop {
+        // We accept the TCP Connection
+        let accepted = monoio::select! {
+            accepted = listener.accept() => accepted,
+            _ = &mut requested => return,
+        };
+        let (conn, addr) = match accepted {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                accept_failed(err).await;
+                continue;
+            }
+        };
+
+        let addr = ConnectionAddr::Tcp(addr);
+        let slot = match services.admit(&addr) {
+            Ok(slot) => slot,
+            Err(error) => {
+                monoio::spawn(refuse(Stream::Tcp(conn), error));
+                continue;
+            }
+        };
+
+        let Ok(laddr) = conn.local_addr() else {
+            continue;
+        };
+        let fd = conn.as_raw_fd();
+
+        let meta_conn = services.supervisor.assign_new_connection(
+            addr,
+            ConnectionAddr::Tcp(laddr),
+            fd,
+        );
+
+        if let Err(err) = services.configure_tcp(&conn) {
+            error!(?err, "unable to configure the connection");
+        }
+
+        // We map it to an `Handler` which is able to understand
+        // the Redis protocol
+        let _spawned = monoio::spawn(services.clone().serve(
+            Stream::Tcp(conn),
+            meta_conn,
+            slot,
+        ));
+    }
+}
+
+/// Accept the TLS connections until the shutdown is requested.
+///
+/// A client is admitted before its handshake, so the clients in the middle of
+/// a handshake count against the `connections_limit`. A refused client is
+/// closed without a handshake, as it couldn't read the error in clear text.
+/// The handshake is done before the connection is registered to the
+/// [Supervisor].
+async fn accept_tls(
+    listener: TcpListener,
+    tls_config: Arc<rustls::ServerConfig>,
+    services: Services,
+) {
+    // Waited across the accepts, a new future for each one would leave its
+    // waker behind.
+    let shutdown = services.supervisor.shutdown();
+    let mut requested = std::pin::pin!(shutdown.requested());
+    loop {
+        let accepted = monoio::select! {
+            accepted = listener.accept() => accepted,
+            _ = &mut requested => return,
+        };
+        let (conn, addr) = match accepted {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                accept_failed(err).await;
+                continue;
+            }
+        };
+
+        let addr = ConnectionAddr::Tcp(addr);
+        let slot = match services.admit(&addr) {
+            Ok(slot) => slot,
+            Err(error) => {
+                warn!(?addr, error, "TLS connection refused");
+                continue;
+            }
+        };
+
+        let services = services.clone();
+        let tls_config = tls_config.clone();
+        monoio::spawn(async move {
+            let laddr = conn.local_addr()?;
+            let fd = conn.as_raw_fd();
+            services.configure_tcp(&conn)?;
+
+            let stream = monoio::time::timeout(
+                TLS_HANDSHAKE_TIMEOUT,
+                TlsStream::accept(tls_config, conn),
+            )
+            .await
+            .unwrap_or_else(|elapsed| {
+                Err(std::io::Error::new(std::io::ErrorKind::TimedOut, elapsed))
+            })
+            .inspect_err(|err| error!(?err, "TLS handshake failed"))?;
+            let user = stream.peer_common_name();
+
+            let supervisor = &services.supervisor;
+            let meta_conn = supervisor.assign_new_connection(
+                addr,
+                ConnectionAddr::Tcp(laddr),
+                fd,
+            );
+            // The common name of the client certificate authenticates the
+            // connection as the ACL user of the same name.
+            if let Some(user) = user.filter(|user| {
+                supervisor
+                    .users()
+                    .get(user)
+                    .is_some_and(|user| user.is_enabled())
+            }) {
+                meta_conn.authenticate(ByteString::from(user)).await;
+            }
+
+            services.serve(Stream::Tls(stream), meta_conn, slot).await;
+            Ok::<(), anyhow::Error>(())
+        });
+    }
+}
+
+/// Accept the connections on the unix socket at `path` until the shutdown is
+/// requested.
+async fn accept_unix(
+    listener: UnixListener,
+    path: PathBuf,
+    services: Services,
+) {
+    // Waited across the accepts, a new future for each one would leave its
+    // waker behind.
+    let shutdown = services.supervisor.shutdown();
+    let mut requested = std::pin::pin!(shutdown.requested());
+    loop {
+        let accepted = monoio::select! {
+            accepted = listener.accept() => accepted,
+            _ = &mut requested => return,
+        };
+        let (conn, _) = match accepted {
+            Ok(accepted) => accepted,
+            Err(err) => {
+                accept_failed(err).await;
+                continue;
+            }
+        };
+
+        // Like Redis, both addresses of a unix connection are the socket.
+        let addr = ConnectionAddr::Unix(path.clone());
+        let slot = match services.admit(&addr) {
+            Ok(slot) => slot,
+            Err(error) => {
+                monoio::spawn(refuse(Stream::Unix(conn), error));
+                continue;
+            }
+        };
+        let meta_conn = services.supervisor.assign_new_connection(
+            addr.clone(),
+            addr,
+            conn.as_raw_fd(),
+        );
+
+        monoio::spawn(services.clone().serve(
+            Stream::Unix(conn),
+            meta_conn,
+            slot,
+        ));
+    }
+}
+
+/// What the listeners of a thread share to serve their connections.
+#[derive(Clone)]
+struct Services {
+    storage: StorageSegment,
+    segments: Rc<Storage>,
+    supervisor: Supervisor,
+    shard: Rc<Shard<ConnectionMsg>>,
+    lua: Rc<LuaEngine>,
+    protected_mode: bool,
+    idle_timeout: Duration,
+    tcp_keepalive: Duration,
+}
+
+impl Services {
+    /// Set the options of a new TCP connection, like Redis the keepalive
+    /// probes are sent every third of `tcp_keepalive`, when it's enabled.
+    fn configure_tcp(&self, conn: &TcpStream) -> std::io::Result<()> {
+        conn.set_nodelay(true)?;
+        if !self.tcp_keepalive.is_zero() {
+            let interval = (self.tcp_keepalive / 3).max(Duration::from_secs(1));
+            conn.set_tcp_keepalive(
+                Some(self.tcp_keepalive),
+                Some(interval),
+                Some(3),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Count a new client connecting from `addr`, or the error it's refused
+    /// with.
+    fn admit(
+        &self,
+        addr: &ConnectionAddr,
+    ) -> Result<ConnectionSlot, &'static str> {
+        if self.is_protected(addr) {
+            self.supervisor.stats().reject();
+            return Err(PROTECTED_MODE_ERROR);
+        }
+        self.supervisor
+            .acquire_connection_slot()
+            .ok_or(MAX_CLIENTS_ERROR)
+    }
+
+    /// Tell if a client connecting from `addr` is refused by the protected
+    /// mode: the `default` user has no password and the client isn't local.
+    fn is_protected(&self, addr: &ConnectionAddr) -> bool {
+        self.protected_mode
+            && !addr.is_local()
+            && self
+                .supervisor
+                .users()
+                .get("default")
+                .is_some_and(|user| user.is_nopass())
+    }
+
+    /// Serve a connection accepted by one of the listeners until it's closed,
+    /// the `slot` is released then.
+    async fn serve(
+        self,
+        stream: Stream,
+        meta_conn: Arc<MetadataConnection>,
+        slot: ConnectionSlot,
+    ) {
+        let ctx = Context::new(
+            self.storage,
+            self.segments,
+            self.supervisor,
+            meta_conn,
+            self.lua,
+        );
+        let (connection, r) = WriteConnection::new(
+            stream,
+            4 * 1024,
+            ctx.protocol_handle(),
+            ctx.connection.clone(),
+        );
+
+        let handler = Handler {
+            connection,
+            connection_r: r,
+            shard: self.shard,
+            idle_timeout: self.idle_timeout,
+        };
+
+        if let Err(err) = handler.run(ctx.clone()).await {
+            error!(?err, "connection closed with an error");
+        }
+
+        ctx.unsubscribe_all().await;
+        ctx.disable_tracking().await;
+
+        ctx.supervisor.unregister_connection(&ctx.connection).await;
+        drop(slot);
+        // handler.connection.stop().await.unwrap();
+    }
+}
+
+/// Refuse a connection by sending it an error before closing it.
+async fn refuse(stream: Stream, error: &'static str) {
+    let (_, mut write) = stream.into_split();
+    let _ = write.write_all(error.as_bytes()).await;
+    let _ = write.shutdown().await;
+}
@@ -0,0 +1,78 @@
+//! The signals stopping the server: like Redis, `SIGINT` and `SIGTERM`
+//! request a graceful shutdown, and a second one exits right away.
+use std::io::Read;
+use std::os::fd::{FromRawFd, IntoRawFd, OwnedFd};
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+use tracing::{info, warn};
+
+use super::supervisor::Shutdown;
+
+/// The signals requesting the shutdown.
+const SIGNALS: [libc::c_int; 2] = [libc::SIGINT, libc::SIGTERM];
+
+/// The write end of the socket the signals are forwarded to, `-1` until the
+/// handler is installed.
+static SIGNAL_SENDER: AtomicI32 = AtomicI32::new(-1);
+
+/// Only what is async-signal-safe is done here: the signal is forwarded to
+/// the thread watching the socket.
+extern "C" fn forward_signal(signal: libc::c_int) {
+    let fd = SIGNAL_SENDER.load(Ordering::Relaxed);
+    let byte = signal as u8;
+    // SAFETY: `write` is async-signal-safe and `byte` outlives the call.
+    unsafe {
+        libc::write(fd, std::ptr::addr_of!(byte).cast(), 1);
+    }
+}
+
+/// Request `shutdown` when the process receives `SIGINT` or `SIGTERM`. The
+/// handler is only installed once per process.
+pub(crate) fn shutdown_on_signals(shutdown: Shutdown) -> std::io::Result<()> {
+    let (mut receiver, sender) = UnixStream::pair()?;
+    // The write end stays open as long as the process runs.
+    let sender = sender.into_raw_fd();
+    if SIGNAL_SENDER
+        .compare_exchange(-1, sender, Ordering::SeqCst, Ordering::SeqCst)
+        .is_err()
+    {
+        // SAFETY: `sender` was just opened and isn't shared.
+        drop(unsafe { OwnedFd::from_raw_fd(sender) });
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AlreadyExists,
+            "the signal handler is already installed",
+        ));
+    }
+
+    for signal in SIGNALS {
+        // SAFETY: `forward_signal` only calls async-signal-safe functions.
+        let previous = unsafe {
+            libc::signal(
+                signal,
+                forward_signal as extern "C" fn(libc::c_int)
+                    as libc::sighandler_t,
+            )
+        };
+        if previous == libc::SIG_ERR {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+
+    std::thread::spawn(move || {
+        let mut signal = [0; 1];
+        while receiver.read_exact(&mut signal).is_ok() {
+            let name = match libc::c_int::from(signal[0]) {
+                libc::SIGINT => "SIGINT",
+                _ => "SIGTERM",
+            };
+            if shutdown.request() {
+                info!("Received {name}, scheduling the shutdown");
+            } else {
+                warn!("Received {name} during the shutdown, exiting now");
+                std::process::exit(1);
+            }
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,177 @@
+use bytes::Bytes;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver};
+use indexmap::IndexSet;
+
+use super::frame::{Frame, Protocol};
+use crate::domain::pubsub::{Message, Subscriber};
+
+/// The kind of subscription of a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubscriptionKind {
+    /// Subscribed with `SUBSCRIBE`.
+    Channel,
+    /// Subscribed with `PSUBSCRIBE`.
+    Pattern,
+    /// Subscribed with `SSUBSCRIBE`.
+    Shard,
+}
+
+/// The channels & patterns a connection is subscribed to.
+///
+/// Messages are delivered through the [Subscriber] of the connection, the
+/// [super::handle::Handler] owns the receiving side and writes them to the
+/// client.
+#[derive(Debug)]
+pub struct Subscriptions {
+    channels: IndexSet<Bytes>,
+    patterns: IndexSet<Bytes>,
+    shard_channels: IndexSet<Bytes>,
+    sender: Subscriber,
+    receiver: Option<UnboundedReceiver<Message>>,
+}
+
+impl Default for Subscriptions {
+    fn default() -> Self {
+        let (sender, receiver) = unbounded();
+        Self {
+            channels: IndexSet::new(),
+            patterns: IndexSet::new(),
+            shard_channels: IndexSet::new(),
+            sender,
+            receiver: Some(receiver),
+        }
+    }
+}
+
+impl Subscriptions {
+    fn set(&mut self, kind: SubscriptionKind) -> &mut IndexSet<Bytes> {
+        match kind {
+            SubscriptionKind::Channel => &mut self.channels,
+            SubscriptionKind::Pattern => &mut self.patterns,
+            SubscriptionKind::Shard => &mut self.shard_channels,
+        }
+    }
+
+    /// Add a subscription, return `false` if it already existed.
+    pub fn insert(&mut self, kind: SubscriptionKind, channel: Bytes) -> bool {
+        self.set(kind).insert(channel)
+    }
+
+    /// Remove a subscription, return `false` if it didn't exist.
+    pub fn remove(&mut self, kind: SubscriptionKind, channel: &Bytes) -> bool {
+        self.set(kind).shift_remove(channel)
+    }
+
+    /// Every subscription of this kind, in subscription order.
+    pub fn list(&self, kind: SubscriptionKind) -> Vec<Bytes> {
+        match kind {
+            SubscriptionKind::Channel => {
+                self.channels.iter().cloned().collect()
+            }
+            SubscriptionKind::Pattern => {
+                self.patterns.iter().cloned().collect()
+            }
+            SubscriptionKind::Shard => {
+                self.shard_channels.iter().cloned().collect()
+            }
+        }
+    }
+
+    /// Number of subscriptions sent back to the client: channels & patterns
+    /// are counted together while shard channels are counted apart.
+    pub fn count(&self, kind: SubscriptionKind) -> usize {
+        match kind {
+            SubscriptionKind::Channel | SubscriptionKind::Pattern => {
+                self.channels.len() + self.patterns.len()
+            }
+            SubscriptionKind::Shard => self.shard_channels.len(),
+        }
+    }
+
+    /// Number of subscriptions of this kind only.
+    pub fn len(&self, kind: SubscriptionKind) -> usize {
+        match kind {
+            SubscriptionKind::Channel => self.channels.len(),
+            SubscriptionKind::Pattern => self.patterns.len(),
+            SubscriptionKind::Shard => self.shard_channels.len(),
+        }
+    }
+
+    /// The sending side to register in the Pub/Sub registry.
+    pub fn sender(&self) -> Subscriber {
+        self.sender.clone()
+    }
+
+    /// Take the receiving side of the messages, it can only be taken once.
+    pub fn take_receiver(&mut self) -> Option<UnboundedReceiver<Message>> {
+        self.receiver.take()
+    }
+}
+
+/// The channel a RESP2 connection subscribes to in order to receive the
+/// invalidation messages of a tracking connection redirecting them.
+const INVALIDATE_CHANNEL: &[u8] = b"__redis__:invalidate";
+
+impl Subscriptions {
+    /// The frame sent to the client for a message, `None` when the client
+    /// can't receive it.
+    pub fn message_frame(
+        &self,
+        msg: Message,
+        protocol: Protocol,
+    ) -> Option<Frame> {
+        let frame = match msg {
+            Message::Message { channel, payload } => Frame::Push(vec![
+                Frame::Bulk(Bytes::from_static(b"message")),
+                Frame::Bulk(channel),
+                Frame::Bulk(payload),
+            ]),
+            Message::PMessage {
+                pattern,
+                channel,
+                payload,
+            } => Frame::Push(vec![
+                Frame::Bulk(Bytes::from_static(b"pmessage")),
+                Frame::Bulk(pattern),
+                Frame::Bulk(channel),
+                Frame::Bulk(payload),
+            ]),
+            Message::SMessage { channel, payload } => Frame::Push(vec![
+                Frame::Bulk(Bytes::from_static(b"smessage")),
+                Frame::Bulk(channel),
+                Frame::Bulk(payload),
+            ]),
+            // RESP2 doesn't have push frames, the invalidation messages are
+            // published on a channel instead.
+            Message::Invalidate { keys } => {
+                let keys =
+                    Frame::Array(keys.into_iter().map(Frame::Bulk).collect());
+                if protocol == Protocol::Resp3 {
+                    Frame::Push(vec![
+                        Frame::Bulk(Bytes::from_static(b"invalidate")),
+                        keys,
+                    ])
+                } else if self.channels.contains(INVALIDATE_CHANNEL) {
+                    Frame::Push(vec![
+                        Frame::Bulk(Bytes::from_static(b"message")),
+                        Frame::Bulk(Bytes::from_static(INVALIDATE_CHANNEL)),
+                        keys,
+                    ])
+                } else {
+                    return None;
+                }
+            }
+            Message::TrackingRedirBroken { redirect } => {
+                if protocol != Protocol::Resp3 {
+                    return None;
+                }
+                Frame::Push(vec![
+                    Frame::Bulk(Bytes::from_static(b"tracking-redir-broken")),
+                    Frame::Integer(redirect as i64),
+                ])
+            }
+        };
+
+        Some(frame)
+    }
+}
@@ -1,11 +1,35 @@
+use std::fmt;
+use std::future::poll_fn;
 use std::net::SocketAddr;
-use std::sync::atomic::{AtomicBool, AtomicU64};
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{
+    AtomicBool, AtomicU16, AtomicU64, AtomicUsize, Ordering,
+};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::Poll;
+use std::time::{Duration, Instant};
 
 use bytestring::ByteString;
+use futures::task::AtomicWaker;
 use futures_locks::RwLock;
 use scc::HashMap;
 
+mod pause;
+mod shutdown;
+mod stats;
+
+use self::pause::ClientPause;
+pub use self::pause::PauseMode;
+pub use self::shutdown::Shutdown;
+pub use self::stats::{ConnectionSlot, ConnectionStats};
+use super::cmd::CommandRegistry;
+use super::frame::Protocol;
+use super::wasm::WasmEngine;
+use crate::domain::acl::{AclLog, Users};
+use crate::domain::pubsub::{PubSub, Subscriber};
+use crate::domain::scripting::Scripts;
+use crate::domain::tracking::TrackingTable;
+
 /// [Supervisor] is the Applicative layer that allow you to interact with the
 /// connections currently open in roster.
 ///
@@ -28,32 +52,217 @@ pub struct Supervisor {
     // TODO(@miaxos): Think about having a Weak here, as it shouldn't matter if
     // there are no connection anymore.
     current_connections: Arc<HashMap<u64, Arc<MetadataConnection>>>,
+
+    /// The Pub/Sub channels & patterns subscribed by the connections.
+    pubsub: PubSub,
+
+    /// The keys tracked by the connections using client side caching.
+    tracking: TrackingTable,
+
+    /// The scripts loaded and the one currently running.
+    scripts: Scripts,
+
+    /// The engine running the WebAssembly functions.
+    wasm: WasmEngine,
+
+    /// The commands registered by the users of the crate.
+    commands: CommandRegistry,
+
+    /// The ACL users.
+    users: Users,
+
+    /// The commands denied by the ACL rules and the failed authentications.
+    acl_log: AclLog,
+
+    /// Where the users are loaded from and saved to by `ACL LOAD` and
+    /// `ACL SAVE`.
+    acl_file: Option<Arc<PathBuf>>,
+
+    /// The maximum number of connections open at the same time.
+    connections_limit: Arc<AtomicU16>,
+
+    /// The connections open, accepted & rejected.
+    stats: Arc<ConnectionStats>,
+
+    /// The pause of the clients set by `CLIENT PAUSE`.
+    pause: Arc<ClientPause>,
+
+    /// The graceful shutdown of the server.
+    shutdown: Shutdown,
 }
 
+/// How often a suspended command checks if the pause is over, so
+/// `CLIENT UNPAUSE` resumes the clients quickly.
+const PAUSE_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
 impl Supervisor {
-    pub fn new(init_connection: u64) -> Self {
+    pub fn new(
+        init_connection: u64,
+        scripts: Scripts,
+        wasm: WasmEngine,
+        commands: CommandRegistry,
+        users: Users,
+        acl_file: Option<PathBuf>,
+        connections_limit: Arc<AtomicU16>,
+    ) -> Self {
         Supervisor {
             current_id: Arc::new(AtomicU64::new(init_connection)),
             current_connections: Default::default(),
+            pubsub: PubSub::default(),
+            tracking: TrackingTable::default(),
+            scripts,
+            wasm,
+            commands,
+            users,
+            acl_log: AclLog::default(),
+            acl_file: acl_file.map(Arc::new),
+            connections_limit,
+            stats: Default::default(),
+            pause: Default::default(),
+            shutdown: Default::default(),
         }
     }
 
+    /// The Pub/Sub registry shared by every connection.
+    pub fn pubsub(&self) -> &PubSub {
+        &self.pubsub
+    }
+
+    /// The client side caching table shared by every connection.
+    pub fn tracking(&self) -> &TrackingTable {
+        &self.tracking
+    }
+
+    /// The scripts shared by every connection.
+    pub fn scripts(&self) -> &Scripts {
+        &self.scripts
+    }
+
+    /// The WebAssembly engine shared by every connection.
+    pub fn wasm(&self) -> &WasmEngine {
+        &self.wasm
+    }
+
+    /// The commands registered by the users of the crate.
+    pub fn commands(&self) -> &CommandRegistry {
+        &self.commands
+    }
+
+    /// The ACL users shared by every connection.
+    pub fn users(&self) -> &Users {
+        &self.users
+    }
+
+    /// The ACL log shared by every connection.
+    pub fn acl_log(&self) -> &AclLog {
+        &self.acl_log
+    }
+
+    /// The ACL file, if the server is configured with one.
+    pub fn acl_file(&self) -> Option<&Path> {
+        self.acl_file.as_deref().map(PathBuf::as_path)
+    }
+
+    /// The maximum number of connections open at the same time.
+    pub fn connections_limit(&self) -> u16 {
+        self.connections_limit.load(Ordering::Relaxed)
+    }
+
+    /// The counters of the connections.
+    pub fn stats(&self) -> &ConnectionStats {
+        &self.stats
+    }
+
+    /// Count a new connection, `None` when the `connections_limit` is
+    /// reached and the connection must be refused.
+    pub fn acquire_connection_slot(&self) -> Option<ConnectionSlot> {
+        self.stats.acquire(&self.connections_limit)
+    }
+
+    /// Suspend the commands of every client for `timeout`, only the ones
+    /// which may write with [PauseMode::Write].
+    pub fn pause_clients(&self, timeout: Duration, mode: PauseMode) {
+        self.pause.pause(timeout, mode);
+    }
+
+    /// Resume the commands suspended by [Supervisor::pause_clients].
+    pub fn unpause_clients(&self) {
+        self.pause.unpause();
+    }
+
+    /// Wait until a command of `conn` can run, `may_write` tells if it's
+    /// suspended by a [PauseMode::Write] pause. The wait ends early when
+    /// `conn` is killed, so a paused client doesn't delay a shutdown.
+    pub async fn wait_unpaused(
+        &self,
+        conn: &MetadataConnection,
+        may_write: bool,
+    ) {
+        while let Some(remaining) = self.pause.remaining(may_write) {
+            if conn.is_killed() {
+                return;
+            }
+            monoio::time::sleep(remaining.min(PAUSE_CHECK_INTERVAL)).await;
+        }
+    }
+
+    /// The graceful shutdown of the server, shared by every thread.
+    pub fn shutdown(&self) -> &Shutdown {
+        &self.shutdown
+    }
+
+    /// Get the [MetadataConnection] of an open connection.
+    pub async fn connection(&self, id: u64) -> Option<Arc<MetadataConnection>> {
+        self.current_connections
+            .read_async(&id, |_, conn| conn.clone())
+            .await
+            .filter(|conn| !conn.stopped.load(Ordering::Relaxed))
+    }
+
     /// Assign a new connection to the [Supervisor] and return a
     /// [MetadataConnection]
     pub fn assign_new_connection(
         &self,
-        addr: SocketAddr,
-        laddr: SocketAddr,
+        addr: ConnectionAddr,
+        laddr: ConnectionAddr,
         fd: i32,
     ) -> Arc<MetadataConnection> {
         let id = self
             .current_id
             .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        // Like Redis, a connection is authenticated as `default` when this
+        // user doesn't need a password.
+        let authenticated = self
+            .users
+            .get("default")
+            .is_some_and(|user| user.is_enabled() && user.is_nopass());
         let conn = Arc::new(MetadataConnection {
             id,
             kind: MetadataConnectionKind::Normal,
             stopped: AtomicBool::new(false),
+            channels: AtomicUsize::new(0),
+            patterns: AtomicUsize::new(0),
+            shard_channels: AtomicUsize::new(0),
+            messages: OnceLock::new(),
             name: RwLock::new(None),
+            user: RwLock::new(ByteString::from_static("default")),
+            authenticated: AtomicBool::new(authenticated),
+            created: Instant::now(),
+            last_interaction: AtomicU64::new(0),
+            executing: AtomicBool::new(false),
+            killed: AtomicBool::new(false),
+            kill_waker: AtomicWaker::new(),
+            no_evict: AtomicBool::new(false),
+            no_touch: AtomicBool::new(false),
+            state: Mutex::new(ConnectionState::default()),
+            last_command: RwLock::new(None),
+            query_buffer: AtomicUsize::new(0),
+            query_buffer_capacity: AtomicUsize::new(0),
+            output_buffer: AtomicUsize::new(0),
+            output_buffer_capacity: AtomicUsize::new(0),
+            writing: AtomicBool::new(false),
+            lib_name: RwLock::new(None),
+            lib_ver: RwLock::new(None),
             addr,
             laddr,
             fd,
@@ -64,6 +273,20 @@ impl Supervisor {
         conn
     }
 
+    /// Unregister a closed connection, which isn't shown by `CLIENT LIST`
+    /// anymore.
+    pub async fn unregister_connection(&self, conn: &MetadataConnection) {
+        conn.stop();
+        if self
+            .current_connections
+            .remove_async(&conn.id)
+            .await
+            .is_some()
+        {
+            self.stats.close();
+        }
+    }
+
     /// Get the list of [MetadataConnection] for normal connection.
     pub async fn get_normal_connection(&self) -> Vec<Arc<MetadataConnection>> {
         let mut result = Vec::new();
@@ -83,6 +306,33 @@ impl Supervisor {
     }
 }
 
+/// The address of a connection, shown like Redis in `CLIENT LIST`:
+/// `ip:port`, or `path:0` for a unix socket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConnectionAddr {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl ConnectionAddr {
+    /// Tell if the client is on the same host: loopback or unix socket.
+    pub fn is_local(&self) -> bool {
+        match self {
+            ConnectionAddr::Tcp(addr) => addr.ip().to_canonical().is_loopback(),
+            ConnectionAddr::Unix(_) => true,
+        }
+    }
+}
+
+impl fmt::Display for ConnectionAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectionAddr::Tcp(addr) => addr.fmt(f),
+            ConnectionAddr::Unix(path) => write!(f, "{}:0", path.display()),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum MetadataConnectionKind {
     Normal,
@@ -97,12 +347,58 @@ pub struct MetadataConnection {
     pub kind: MetadataConnectionKind,
     /// the name set by the client with CLIENT SETNAME
     name: RwLock<Option<ByteString>>,
+    /// The ACL user the connection is authenticated with
+    user: RwLock<ByteString>,
+    /// Tell if the connection is authenticated, only `AUTH`, `HELLO` and
+    /// `QUIT` are accepted until then
+    authenticated: AtomicBool,
     /// Tell if the connection is stopped
     pub stopped: AtomicBool,
+    /// Number of channels the connection is subscribed to
+    channels: AtomicUsize,
+    /// Number of patterns the connection is subscribed to
+    patterns: AtomicUsize,
+    /// Number of shard channels the connection is subscribed to
+    shard_channels: AtomicUsize,
+    /// Where the Pub/Sub & invalidation messages of the connection are sent
+    messages: OnceLock<Subscriber>,
+    /// When the connection was accepted
+    created: Instant,
+    /// When the client last sent a command or was answered, in milliseconds
+    /// since `created`
+    last_interaction: AtomicU64,
+    /// Tell if a command of the connection is running
+    executing: AtomicBool,
+    /// Tell if the connection was killed with `CLIENT KILL`
+    killed: AtomicBool,
+    /// Wake the connection up when it's killed, from any thread
+    kill_waker: AtomicWaker,
+    /// Set by `CLIENT NO-EVICT`
+    no_evict: AtomicBool,
+    /// Set by `CLIENT NO-TOUCH`
+    no_touch: AtomicBool,
+    /// The state owned by the thread of the connection
+    state: Mutex<ConnectionState>,
+    /// The name of the last command sent by the client
+    last_command: RwLock<Option<ByteString>>,
+    /// Number of bytes read from the client but not parsed yet
+    query_buffer: AtomicUsize,
+    /// Capacity of the buffer the client is read into
+    query_buffer_capacity: AtomicUsize,
+    /// Number of bytes of the replies waiting to be sent to the client
+    output_buffer: AtomicUsize,
+    /// Capacity of the buffer the replies are encoded into
+    output_buffer_capacity: AtomicUsize,
+    /// Tell if a reply is being written to the client
+    writing: AtomicBool,
+    /// The client library name set with CLIENT SETINFO
+    lib_name: RwLock<Option<ByteString>>,
+    /// The client library version set with CLIENT SETINFO
+    lib_ver: RwLock<Option<ByteString>>,
     /// Address/Port of the client
-    pub addr: SocketAddr,
+    pub addr: ConnectionAddr,
     /// address/port of local address client connected to (bind address)
-    pub laddr: SocketAddr,
+    pub laddr: ConnectionAddr,
     /// file descriptor corresponding to the socket
     pub fd: i32,
 }
@@ -114,6 +410,189 @@ impl MetadataConnection {
             .store(true, std::sync::atomic::Ordering::Relaxed);
     }
 
+    /// Ask the thread owning the connection to close it, the connection
+    /// isn't listed anymore.
+    pub fn kill(&self) {
+        self.stop();
+        self.killed.store(true, Ordering::Relaxed);
+        self.kill_waker.wake();
+    }
+
+    /// Wait until the connection is killed by [MetadataConnection::kill].
+    pub async fn killed(&self) {
+        poll_fn(|cx| {
+            self.kill_waker.register(cx.waker());
+            match self.killed.load(Ordering::Relaxed) {
+                true => Poll::Ready(()),
+                false => Poll::Pending,
+            }
+        })
+        .await
+    }
+
+    /// Tell if the connection was killed by [MetadataConnection::kill].
+    pub fn is_killed(&self) -> bool {
+        self.killed.load(Ordering::Relaxed)
+    }
+
+    /// Set if the connection is excluded from the client eviction.
+    pub fn set_no_evict(&self, no_evict: bool) {
+        self.no_evict.store(no_evict, Ordering::Relaxed);
+    }
+
+    /// Tell if the connection is excluded from the client eviction.
+    pub fn is_no_evict(&self) -> bool {
+        self.no_evict.load(Ordering::Relaxed)
+    }
+
+    /// Set if the commands of the connection leave the access time of the
+    /// keys untouched.
+    pub fn set_no_touch(&self, no_touch: bool) {
+        self.no_touch.store(no_touch, Ordering::Relaxed);
+    }
+
+    /// Tell if the commands of the connection leave the access time of the
+    /// keys untouched.
+    pub fn is_no_touch(&self) -> bool {
+        self.no_touch.load(Ordering::Relaxed)
+    }
+
+    /// How long ago the connection was accepted.
+    pub fn age(&self) -> Duration {
+        self.created.elapsed()
+    }
+
+    /// How long ago the client last sent a command or was answered.
+    pub fn idle(&self) -> Duration {
+        let last = self.last_interaction.load(Ordering::Relaxed);
+        self.age().saturating_sub(Duration::from_millis(last))
+    }
+
+    /// Record an interaction with the client, which isn't idle anymore.
+    pub fn touch(&self) {
+        let now = self.age().as_millis() as u64;
+        self.last_interaction.fetch_max(now, Ordering::Relaxed);
+    }
+
+    /// Set if a command of the connection is running.
+    pub fn set_executing(&self, executing: bool) {
+        self.executing.store(executing, Ordering::Relaxed);
+    }
+
+    /// Tell if the connection can be closed by the idle `timeout`: like
+    /// Redis, the Pub/Sub connections and the ones waiting for a command to
+    /// complete are never closed.
+    pub fn is_idle_for(&self, timeout: Duration) -> bool {
+        self.idle() >= timeout
+            && !self.is_pubsub()
+            && !self.executing.load(Ordering::Relaxed)
+    }
+
+    /// Set the number of channels, patterns & shard channels the connection
+    /// is subscribed to.
+    pub fn set_subscriptions(
+        &self,
+        channels: usize,
+        patterns: usize,
+        shard_channels: usize,
+    ) {
+        self.channels.store(channels, Ordering::Relaxed);
+        self.patterns.store(patterns, Ordering::Relaxed);
+        self.shard_channels.store(shard_channels, Ordering::Relaxed);
+    }
+
+    /// Tell if the connection is subscribed to at least one channel, pattern
+    /// or shard channel.
+    pub fn is_pubsub(&self) -> bool {
+        self.channels.load(Ordering::Relaxed) > 0
+            || self.patterns.load(Ordering::Relaxed) > 0
+            || self.shard_channels.load(Ordering::Relaxed) > 0
+    }
+
+    /// Copy the state owned by the thread of the connection, so it's shown
+    /// by `CLIENT LIST`.
+    pub fn set_state(&self, state: ConnectionState) {
+        *self.state.lock().unwrap() = state;
+    }
+
+    /// The state owned by the thread of the connection, as of the last
+    /// [MetadataConnection::set_state].
+    pub fn state(&self) -> ConnectionState {
+        *self.state.lock().unwrap()
+    }
+
+    /// Record the name of the command sent by the client.
+    pub async fn set_last_command(&self, name: &str) {
+        let mut lock = self.last_command.write().await;
+        if lock.as_deref() != Some(name) {
+            *lock = Some(ByteString::from(name));
+        }
+    }
+
+    /// Record how much of the buffer the client is read into is used.
+    pub fn set_query_buffer(&self, len: usize, capacity: usize) {
+        self.query_buffer.store(len, Ordering::Relaxed);
+        self.query_buffer_capacity
+            .store(capacity, Ordering::Relaxed);
+    }
+
+    /// Record how much of the buffer the replies are encoded into is used,
+    /// and if a reply is being written to the client.
+    pub fn set_output_buffer(
+        &self,
+        len: usize,
+        capacity: usize,
+        writing: bool,
+    ) {
+        self.output_buffer.store(len, Ordering::Relaxed);
+        self.output_buffer_capacity
+            .store(capacity, Ordering::Relaxed);
+        self.writing.store(writing, Ordering::Relaxed);
+    }
+
+    /// Set the client library name.
+    pub async fn set_lib_name(&self, name: ByteString) {
+        *self.lib_name.write().await = Some(name);
+    }
+
+    /// Set the client library version.
+    pub async fn set_lib_ver(&self, version: ByteString) {
+        *self.lib_ver.write().await = Some(version);
+    }
+
+    /// The flags shown by `CLIENT LIST`, in the same order as Redis.
+    fn flags(&self, state: &ConnectionState) -> String {
+        let mut flags = String::new();
+        let mut flag = |set: bool, flag: char| {
+            if set {
+                flags.push(flag);
+            }
+        };
+        flag(self.is_pubsub(), 'P');
+        flag(state.multi.is_some(), 'x');
+        flag(state.tracking, 't');
+        flag(state.tracking && state.bcast, 'B');
+        flag(self.is_killed(), 'A');
+        flag(matches!(self.addr, ConnectionAddr::Unix(_)), 'U');
+        flag(self.is_no_evict(), 'e');
+        flag(self.is_no_touch(), 'T');
+        if flags.is_empty() {
+            flags.push('N');
+        }
+        flags
+    }
+
+    /// Attach the sending side of the messages of the connection, so other
+    /// connections can send messages to it.
+    pub fn attach_messages(&self, sender: Subscriber) {
+        let _ = self.messages.set(sender);
+    }
+
+    /// The sending side of the messages of the connection.
+    pub fn messages(&self) -> Option<Subscriber> {
+        self.messages.get().cloned()
+    }
+
     /// Set the name of the connection
     pub async fn set_name(&self, name: ByteString) {
         let mut lock = self.name.write().await;
@@ -125,20 +604,123 @@ impl MetadataConnection {
         self.name.read().await.clone()
     }
 
+    /// The ACL user the connection is authenticated with
+    pub async fn user(&self) -> ByteString {
+        self.user.read().await.clone()
+    }
+
+    /// Tell if the connection is authenticated.
+    pub fn is_authenticated(&self) -> bool {
+        self.authenticated.load(Ordering::Relaxed)
+    }
+
+    /// Authenticate the connection with the ACL user `user`.
+    pub async fn authenticate(&self, user: ByteString) {
+        let mut lock = self.user.write().await;
+        *lock = user;
+        self.authenticated.store(true, Ordering::Relaxed);
+    }
+
+    /// The line describing the connection in `CLIENT LIST` and
+    /// `CLIENT INFO`, with the same fields as Redis.
+    ///
+    /// The replies are encoded into a single output buffer: a reply too large
+    /// for it is written to the socket directly instead of being queued to a
+    /// reply list, so `oll` and `omem` are always `0`.
     pub async fn format_conn(&self) -> ByteString {
+        let state = self.state();
+        let optional = |value: Option<ByteString>| value.unwrap_or_default();
         ByteString::from(format!(
-            "id={id} addr={addr} laddr={laddr} fd={fd} name={name}",
-            id = &self.id,
-            addr = &self.addr,
-            laddr = &self.laddr,
-            fd = &self.fd,
-            name = &self.name().await.unwrap_or(ByteString::new()),
+            "id={id} addr={addr} laddr={laddr} fd={fd} name={name} age={age} \
+             idle={idle} flags={flags} db=0 sub={sub} psub={psub} ssub={ssub} \
+             multi={multi} watch={watch} qbuf={qbuf} obl={obl} oll=0 omem=0 \
+             tot-mem={tot_mem} events={events} cmd={cmd} user={user} \
+             redir={redir} resp={resp} lib-name={lib_name} lib-ver={lib_ver}",
+            id = self.id,
+            addr = self.addr,
+            laddr = self.laddr,
+            fd = self.fd,
+            name = optional(self.name().await),
+            age = self.age().as_secs(),
+            idle = self.idle().as_secs(),
+            flags = self.flags(&state),
+            sub = self.channels.load(Ordering::Relaxed),
+            psub = self.patterns.load(Ordering::Relaxed),
+            ssub = self.shard_channels.load(Ordering::Relaxed),
+            multi = state.multi.map_or(-1, |queued| queued as i64),
+            watch = state.watch,
+            qbuf = self.query_buffer.load(Ordering::Relaxed),
+            obl = self.output_buffer.load(Ordering::Relaxed),
+            tot_mem = self.query_buffer_capacity.load(Ordering::Relaxed)
+                + self.output_buffer_capacity.load(Ordering::Relaxed),
+            events = if self.writing.load(Ordering::Relaxed) {
+                "rw"
+            } else {
+                "r"
+            },
+            cmd = self.last_command.read().await.as_deref().unwrap_or("NULL"),
+            user = self.user().await,
+            redir = state.redirect.map_or(-1, |id| id as i64),
+            resp = state.protocol.version(),
+            lib_name = optional(self.lib_name.read().await.clone()),
+            lib_ver = optional(self.lib_ver.read().await.clone()),
         ))
     }
 }
 
+/// The state of a connection owned by its thread, copied to its
+/// [MetadataConnection] so any thread can show it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionState {
+    /// The number of commands queued, `None` outside of a `MULTI`.
+    pub multi: Option<usize>,
+    /// The number of keys watched.
+    pub watch: usize,
+    /// The protocol negotiated with `HELLO`.
+    pub protocol: Protocol,
+    /// Tell if the client side caching is enabled.
+    pub tracking: bool,
+    /// Tell if the client side caching is in broadcasting mode.
+    pub bcast: bool,
+    /// Where the invalidation messages are redirected.
+    pub redirect: Option<u64>,
+}
+
 impl MetadataConnection {
     pub fn id(&self) -> u64 {
         self.id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::domain::scripting::Functions;
+
+    #[monoio::test]
+    async fn unregister_closed_connections() {
+        let supervisor = Supervisor::new(
+            0,
+            Scripts::new(Duration::from_secs(5), Functions::default()),
+            WasmEngine::new(0, 0).unwrap(),
+            Default::default(),
+            Default::default(),
+            None,
+            Arc::new(AtomicU16::new(10)),
+        );
+        let addr = ConnectionAddr::Tcp("127.0.0.1:6379".parse().unwrap());
+        let first =
+            supervisor.assign_new_connection(addr.clone(), addr.clone(), 0);
+        let second = supervisor.assign_new_connection(addr.clone(), addr, 1);
+
+        supervisor.unregister_connection(&first).await;
+        supervisor.unregister_connection(&first).await;
+        assert!(first.stopped.load(Ordering::Relaxed));
+        assert_eq!(supervisor.current_connections.len(), 1);
+        assert!(supervisor.connection(first.id()).await.is_none());
+        assert!(supervisor.connection(second.id()).await.is_some());
+        assert_eq!(supervisor.stats().closed(), 1);
+    }
+}
@@ -0,0 +1,76 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// What is suspended by `CLIENT PAUSE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PauseMode {
+    /// Only the commands which may write are suspended.
+    Write,
+    /// Every command is suspended.
+    All,
+}
+
+/// The pause of the clients set by `CLIENT PAUSE`, shared by every thread.
+#[derive(Debug, Default)]
+pub struct ClientPause {
+    pause: Mutex<Option<(Instant, PauseMode)>>,
+}
+
+impl ClientPause {
+    /// Suspend the commands for `timeout`. Like Redis, an ongoing pause is
+    /// only extended: the latest end and the most restrictive mode are
+    /// kept.
+    pub fn pause(&self, timeout: Duration, mode: PauseMode) {
+        let end = Instant::now() + timeout;
+        let mut pause = self.pause.lock().unwrap();
+        *pause = match *pause {
+            Some((current_end, current_mode))
+                if current_end > Instant::now() =>
+            {
+                Some((current_end.max(end), current_mode.max(mode)))
+            }
+            _ => Some((end, mode)),
+        };
+    }
+
+    /// End the pause.
+    pub fn unpause(&self) {
+        *self.pause.lock().unwrap() = None;
+    }
+
+    /// How long a command is still suspended, `None` when it can run.
+    pub fn remaining(&self, may_write: bool) -> Option<Duration> {
+        let (end, mode) = (*self.pause.lock().unwrap())?;
+        if mode == PauseMode::Write && !may_write {
+            return None;
+        }
+        Some(end.saturating_duration_since(Instant::now()))
+            .filter(|remaining| !remaining.is_zero())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause_is_only_extended() {
+        let pause = ClientPause::default();
+        assert!(pause.remaining(true).is_none());
+
+        pause.pause(Duration::from_secs(10), PauseMode::All);
+        pause.pause(Duration::from_secs(1), PauseMode::Write);
+        assert!(pause.remaining(false).unwrap() > Duration::from_secs(5));
+
+        pause.unpause();
+        pause.pause(Duration::from_secs(10), PauseMode::Write);
+        assert!(pause.remaining(false).is_none());
+        assert!(pause.remaining(true).is_some());
+
+        pause.pause(Duration::ZERO, PauseMode::All);
+        assert!(pause.remaining(false).is_some());
+
+        pause.unpause();
+        assert!(pause.remaining(true).is_none());
+    }
+}
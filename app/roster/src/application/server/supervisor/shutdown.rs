@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// The graceful shutdown of the server, shared by every thread.
+///
+/// Once requested, the threads stop accepting connections, close the open
+/// ones once their current command is applied and stop.
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    requested: AtomicBool,
+    /// The threads serving the connections which didn't stop yet.
+    running: AtomicUsize,
+    completed: AtomicBool,
+    /// The tasks waiting for the shutdown to be requested or completed, by
+    /// waiter, a waiter forgets its waker once dropped.
+    wakers: Mutex<HashMap<u64, Waker>>,
+    next_waiter: AtomicU64,
+}
+
+impl Shutdown {
+    /// Request the shutdown, return `false` if it was already requested.
+    pub fn request(&self) -> bool {
+        let first = !self.inner.requested.swap(true, Ordering::SeqCst);
+        self.wake();
+        first
+    }
+
+    /// Tell if the shutdown was requested.
+    pub fn is_requested(&self) -> bool {
+        self.inner.requested.load(Ordering::SeqCst)
+    }
+
+    /// Wait until the shutdown is requested.
+    pub fn requested(&self) -> impl Future<Output = ()> + '_ {
+        self.wait(&self.inner.requested)
+    }
+
+    /// Wait until every thread stopped.
+    pub fn completed(&self) -> impl Future<Output = ()> + '_ {
+        self.wait(&self.inner.completed)
+    }
+
+    /// The number of tasks waiting for the shutdown.
+    pub fn waiters(&self) -> usize {
+        self.inner.wakers.lock().unwrap().len()
+    }
+
+    /// Count a thread serving the connections, it must call
+    /// [Shutdown::thread_stopped] once it stops.
+    pub fn thread_started(&self) {
+        self.inner.running.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Count a thread which stopped, `cleanup` is called by the last one
+    /// before the shutdown is completed.
+    pub fn thread_stopped(&self, cleanup: impl FnOnce()) {
+        if self.inner.running.fetch_sub(1, Ordering::SeqCst) == 1 {
+            cleanup();
+            self.inner.completed.store(true, Ordering::SeqCst);
+            self.wake();
+        }
+    }
+
+    fn wait<'a>(&'a self, flag: &'a AtomicBool) -> Waiter<'a> {
+        Waiter {
+            inner: &self.inner,
+            flag,
+            id: self.inner.next_waiter.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    fn wake(&self) {
+        let wakers = std::mem::take(&mut *self.inner.wakers.lock().unwrap());
+        for waker in wakers.into_values() {
+            waker.wake();
+        }
+    }
+}
+
+/// A task waiting for the shutdown, its waker is kept until it's dropped.
+struct Waiter<'a> {
+    inner: &'a Inner,
+    flag: &'a AtomicBool,
+    id: u64,
+}
+
+impl Future for Waiter<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.flag.load(Ordering::SeqCst) {
+            return Poll::Ready(());
+        }
+        self.inner
+            .wakers
+            .lock()
+            .unwrap()
+            .insert(self.id, cx.waker().clone());
+        // The flag may be set before the waker is registered.
+        match self.flag.load(Ordering::SeqCst) {
+            true => Poll::Ready(()),
+            false => Poll::Pending,
+        }
+    }
+}
+
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        self.inner.wakers.lock().unwrap().remove(&self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[monoio::test]
+    async fn completed_by_the_last_thread() {
+        let shutdown = Shutdown::default();
+        shutdown.thread_started();
+        shutdown.thread_started();
+
+        let waiting = shutdown.clone();
+        let completed = monoio::spawn(async move {
+            waiting.requested().await;
+            waiting.completed().await;
+        });
+
+        assert!(shutdown.request());
+        assert!(!shutdown.request());
+
+        let mut cleaned = 0;
+        shutdown.thread_stopped(|| cleaned += 1);
+        assert_eq!(cleaned, 0);
+        shutdown.thread_stopped(|| cleaned += 1);
+        assert_eq!(cleaned, 1);
+
+        completed.await;
+    }
+
+    #[monoio::test]
+    async fn dropped_waiters_are_forgotten() {
+        let shutdown = Shutdown::default();
+
+        // Like a listener waiting for the shutdown or a new connection.
+        for _ in 0..100 {
+            let mut waiting = std::pin::pin!(shutdown.requested());
+            assert!(futures::poll!(waiting.as_mut()).is_pending());
+            assert!(futures::poll!(waiting.as_mut()).is_pending());
+            assert_eq!(shutdown.waiters(), 1);
+        }
+        assert_eq!(shutdown.waiters(), 0);
+    }
+}
@@ -0,0 +1,110 @@
+//! The counters of the connections shown by `INFO`.
+
+use std::sync::atomic::{AtomicU16, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// The connections counted across every thread.
+#[derive(Debug, Default)]
+pub struct ConnectionStats {
+    /// The connections currently open.
+    connected: AtomicUsize,
+    /// The connections accepted since the start.
+    total_received: AtomicU64,
+    /// The connections refused, by the `connections_limit` or the protected
+    /// mode.
+    rejected: AtomicU64,
+    /// The connections accepted then closed since the start.
+    closed: AtomicU64,
+}
+
+impl ConnectionStats {
+    pub fn connected(&self) -> usize {
+        self.connected.load(Ordering::Relaxed)
+    }
+
+    pub fn total_received(&self) -> u64 {
+        self.total_received.load(Ordering::Relaxed)
+    }
+
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+
+    pub fn closed(&self) -> u64 {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    /// Count a connection closed, once it's unregistered.
+    pub fn close(&self) {
+        self.closed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a connection refused before it was accepted.
+    pub fn reject(&self) {
+        self.rejected.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a new connection, unless `limit` connections are already open.
+    pub fn acquire(
+        self: &Arc<Self>,
+        limit: &AtomicU16,
+    ) -> Option<ConnectionSlot> {
+        let limit = limit.load(Ordering::Relaxed) as usize;
+        let mut connected = self.connected.load(Ordering::Relaxed);
+        let acquired = loop {
+            if connected >= limit {
+                break false;
+            }
+            match self.connected.compare_exchange_weak(
+                connected,
+                connected + 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break true,
+                Err(current) => connected = current,
+            }
+        };
+
+        if !acquired {
+            self.reject();
+            return None;
+        }
+        self.total_received.fetch_add(1, Ordering::Relaxed);
+        Some(ConnectionSlot {
+            stats: self.clone(),
+        })
+    }
+}
+
+/// A connection counted in the open ones, until it's dropped.
+#[derive(Debug)]
+pub struct ConnectionSlot {
+    stats: Arc<ConnectionStats>,
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.stats.connected.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limit_connections() {
+        let stats = Arc::new(ConnectionStats::default());
+        let limit = AtomicU16::new(2);
+
+        let first = stats.acquire(&limit).unwrap();
+        let _second = stats.acquire(&limit).unwrap();
+        assert!(stats.acquire(&limit).is_none());
+        assert_eq!((stats.connected(), stats.rejected()), (2, 1));
+
+        drop(first);
+        assert!(stats.acquire(&limit).is_some());
+        assert_eq!((stats.connected(), stats.total_received()), (1, 3));
+    }
+}
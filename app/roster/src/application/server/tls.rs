@@ -0,0 +1,333 @@
+//! TLS on top of the monoio streams.
+//!
+//! The handshake is done on the whole [TcpStream], then the stream is split
+//! like a plain one: both halves share the rustls [ServerConnection] while
+//! the encrypted records go through the io_uring read & write halves of the
+//! socket. The rustls state is never borrowed across an `.await`, so the
+//! halves can be used concurrently by the tasks of a connection.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use monoio::buf::{IoBuf, IoBufMut, IoVecBuf, IoVecBufMut, RawBuf};
+use monoio::io::{
+    AsyncReadRent, AsyncWriteRent, AsyncWriteRentExt, OwnedReadHalf,
+    OwnedWriteHalf, Splitable,
+};
+use monoio::net::TcpStream;
+use monoio::BufResult;
+use rustls::crypto::ring::default_provider;
+use rustls::server::WebPkiClientVerifier;
+use rustls::{RootCertStore, ServerConnection};
+use serde::{Deserialize, Serialize};
+
+/// The size of the buffer used to read the encrypted records.
+const READ_BUFFER_SIZE: usize = 16 * 1024;
+
+/// If the clients must authenticate with a certificate, like
+/// `tls-auth-clients` in Redis.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum TlsAuthClients {
+    /// The clients don't send a certificate.
+    No,
+    /// A client may send a certificate, which is verified.
+    Optional,
+    /// Every client must send a valid certificate.
+    #[default]
+    Yes,
+}
+
+/// The configuration of the TLS listener.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    /// The address the TLS connections are accepted on.
+    pub bind_addr: SocketAddr,
+    /// The certificate chain of the server, in PEM.
+    pub cert_file: PathBuf,
+    /// The private key of the server, in PEM.
+    pub key_file: PathBuf,
+    /// The certificates of the authorities signing the client certificates,
+    /// in PEM. Required unless `auth_clients` is [TlsAuthClients::No].
+    pub ca_cert_file: Option<PathBuf>,
+    pub auth_clients: TlsAuthClients,
+}
+
+fn invalid_data(err: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err.to_string())
+}
+
+impl TlsConfig {
+    /// Load the certificates and the key to create the rustls configuration.
+    pub fn load(&self) -> anyhow::Result<Arc<rustls::ServerConfig>> {
+        let provider = Arc::new(default_provider());
+
+        let mut reader = BufReader::new(File::open(&self.cert_file)?);
+        let certs = rustls_pemfile::certs(&mut reader)
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut reader = BufReader::new(File::open(&self.key_file)?);
+        let key =
+            rustls_pemfile::private_key(&mut reader)?.ok_or_else(|| {
+                anyhow::anyhow!("No private key in {}", self.key_file.display())
+            })?;
+
+        let builder =
+            rustls::ServerConfig::builder_with_provider(provider.clone())
+                .with_safe_default_protocol_versions()?;
+
+        let builder = match (self.auth_clients, &self.ca_cert_file) {
+            (TlsAuthClients::No, _) => builder.with_no_client_auth(),
+            (_, None) => anyhow::bail!(
+                "A CA certificate is required to authenticate the clients"
+            ),
+            (auth_clients, Some(ca_cert_file)) => {
+                let mut roots = RootCertStore::empty();
+                let mut reader = BufReader::new(File::open(ca_cert_file)?);
+                for cert in rustls_pemfile::certs(&mut reader) {
+                    roots.add(cert?)?;
+                }
+
+                let verifier = WebPkiClientVerifier::builder_with_provider(
+                    Arc::new(roots),
+                    provider,
+                );
+                let verifier = match auth_clients {
+                    TlsAuthClients::Optional => {
+                        verifier.allow_unauthenticated().build()?
+                    }
+                    _ => verifier.build()?,
+                };
+                builder.with_client_cert_verifier(verifier)
+            }
+        };
+
+        Ok(Arc::new(builder.with_single_cert(certs, key)?))
+    }
+}
+
+/// Write every pending encrypted record to `io`.
+async fn write_records(
+    tls: &RefCell<ServerConnection>,
+    io: &mut impl AsyncWriteRent,
+) -> io::Result<()> {
+    loop {
+        let records = {
+            let mut tls = tls.borrow_mut();
+            if !tls.wants_write() {
+                return Ok(());
+            }
+            let mut records = Vec::new();
+            tls.write_tls(&mut records)?;
+            records
+        };
+        io.write_all(records).await.0?;
+    }
+}
+
+/// Give the encrypted `records` read from the socket to rustls.
+fn read_records(
+    tls: &RefCell<ServerConnection>,
+    mut records: &[u8],
+) -> io::Result<()> {
+    let mut tls = tls.borrow_mut();
+    while !records.is_empty() {
+        tls.read_tls(&mut records)?;
+        tls.process_new_packets().map_err(invalid_data)?;
+    }
+    Ok(())
+}
+
+/// A TLS connection accepted by the server.
+pub struct TlsStream {
+    io: TcpStream,
+    tls: Rc<RefCell<ServerConnection>>,
+}
+
+impl TlsStream {
+    /// Do the TLS handshake on a newly accepted connection.
+    pub async fn accept(
+        config: Arc<rustls::ServerConfig>,
+        mut io: TcpStream,
+    ) -> io::Result<TlsStream> {
+        let tls =
+            RefCell::new(ServerConnection::new(config).map_err(invalid_data)?);
+        let mut buffer = Vec::with_capacity(READ_BUFFER_SIZE);
+
+        while tls.borrow().is_handshaking() {
+            write_records(&tls, &mut io).await?;
+
+            let (read, buf) = io.read(buffer).await;
+            buffer = buf;
+            if read? == 0 {
+                return Err(io::ErrorKind::UnexpectedEof.into());
+            }
+
+            let result = read_records(&tls, &buffer);
+            buffer.clear();
+            if let Err(err) = result {
+                // Try to tell the client why the handshake failed.
+                let _ = write_records(&tls, &mut io).await;
+                return Err(err);
+            }
+        }
+        // The session tickets are sent once the handshake is done.
+        write_records(&tls, &mut io).await?;
+
+        Ok(TlsStream {
+            io,
+            tls: Rc::new(RefCell::new(tls.into_inner())),
+        })
+    }
+
+    /// The common name of the certificate sent by the client, if any.
+    pub fn peer_common_name(&self) -> Option<String> {
+        let tls = self.tls.borrow();
+        let cert = tls.peer_certificates()?.first()?;
+        let (_, cert) =
+            x509_parser::parse_x509_certificate(cert.as_ref()).ok()?;
+        let name = cert.subject().iter_common_name().next()?;
+        name.as_str().ok().map(str::to_string)
+    }
+
+    pub fn tcp(&self) -> &TcpStream {
+        &self.io
+    }
+
+    pub fn into_split(self) -> (TlsReadHalf, TlsWriteHalf) {
+        let (read, write) = self.io.into_split();
+        (
+            TlsReadHalf {
+                io: read,
+                tls: self.tls.clone(),
+                buffer: Some(Vec::with_capacity(READ_BUFFER_SIZE)),
+            },
+            TlsWriteHalf {
+                io: write,
+                tls: self.tls,
+            },
+        )
+    }
+}
+
+pub struct TlsReadHalf {
+    io: OwnedReadHalf<TcpStream>,
+    tls: Rc<RefCell<ServerConnection>>,
+    /// Where the encrypted records are read, `None` while a read is pending.
+    buffer: Option<Vec<u8>>,
+}
+
+impl TlsReadHalf {
+    /// Read the decrypted data into `dst`.
+    async fn read_plaintext(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.tls.borrow_mut().reader().read(dst) {
+                Ok(read) => return Ok(read),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                Err(err) => return Err(err),
+            }
+
+            let buffer = self
+                .buffer
+                .take()
+                .unwrap_or_else(|| Vec::with_capacity(READ_BUFFER_SIZE));
+            let (read, mut buffer) = self.io.read(buffer).await;
+            let read = read?;
+            let result = read_records(&self.tls, &buffer);
+            buffer.clear();
+            self.buffer = Some(buffer);
+
+            // The client closed the connection without a `close_notify`.
+            if read == 0 {
+                return Ok(0);
+            }
+            result?;
+        }
+    }
+}
+
+impl AsyncReadRent for TlsReadHalf {
+    async fn read<T: IoBufMut>(&mut self, mut buf: T) -> BufResult<usize, T> {
+        // Safety: the buffer is valid for `bytes_total` bytes.
+        let dst = unsafe {
+            std::slice::from_raw_parts_mut(buf.write_ptr(), buf.bytes_total())
+        };
+        let result = self.read_plaintext(dst).await;
+        if let Ok(read) = result {
+            // Safety: `read` bytes were just written.
+            unsafe { buf.set_init(read) };
+        }
+        (result, buf)
+    }
+
+    async fn readv<T: IoVecBufMut>(
+        &mut self,
+        mut buf: T,
+    ) -> BufResult<usize, T> {
+        // Safety: the first iovec outlives the read.
+        let Some(raw) = (unsafe { RawBuf::new_from_iovec_mut(&mut buf) })
+        else {
+            return (Ok(0), buf);
+        };
+        let (result, _) = self.read(raw).await;
+        if let Ok(read) = result {
+            // Safety: `read` bytes were just written.
+            unsafe { buf.set_init(read) };
+        }
+        (result, buf)
+    }
+}
+
+pub struct TlsWriteHalf {
+    io: OwnedWriteHalf<TcpStream>,
+    tls: Rc<RefCell<ServerConnection>>,
+}
+
+impl TlsWriteHalf {
+    pub fn into_inner(self) -> OwnedWriteHalf<TcpStream> {
+        self.io
+    }
+}
+
+impl AsyncWriteRent for TlsWriteHalf {
+    async fn write<T: IoBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        // Safety: the buffer is valid for `bytes_init` bytes.
+        let src = unsafe {
+            std::slice::from_raw_parts(buf.read_ptr(), buf.bytes_init())
+        };
+        let written = self.tls.borrow_mut().writer().write(src);
+        let result = match written {
+            Ok(written) => write_records(&self.tls, &mut self.io)
+                .await
+                .map(|()| written),
+            Err(err) => Err(err),
+        };
+        (result, buf)
+    }
+
+    async fn writev<T: IoVecBuf>(&mut self, buf: T) -> BufResult<usize, T> {
+        // Safety: the first iovec outlives the write.
+        let Some(raw) = (unsafe { RawBuf::new_from_iovec(&buf) }) else {
+            return (Ok(0), buf);
+        };
+        let (result, _) = self.write(raw).await;
+        (result, buf)
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        write_records(&self.tls, &mut self.io).await?;
+        self.io.flush().await
+    }
+
+    async fn shutdown(&mut self) -> io::Result<()> {
+        self.tls.borrow_mut().send_close_notify();
+        write_records(&self.tls, &mut self.io).await?;
+        self.io.shutdown().await
+    }
+}
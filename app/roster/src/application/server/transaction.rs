@@ -0,0 +1,91 @@
+use bytestring::ByteString;
+
+use super::cmd::Command;
+
+/// State of the `MULTI` / `EXEC` transaction of a connection.
+///
+/// Commands received after `MULTI` are queued instead of being applied, they
+/// are applied all at once by `EXEC`.
+#[derive(Debug, Default)]
+pub struct Transaction {
+    /// `Some` when the connection is inside a `MULTI`.
+    queued: Option<Vec<Command>>,
+    /// A command couldn't be queued, `EXEC` will be refused.
+    aborted: bool,
+    /// Keys watched with their version at the time of the `WATCH`.
+    watched: Vec<(ByteString, u64)>,
+}
+
+impl Transaction {
+    /// Start a transaction, return `false` if one is already started.
+    pub fn start(&mut self) -> bool {
+        if self.queued.is_some() {
+            return false;
+        }
+
+        self.queued = Some(Vec::new());
+        self.aborted = false;
+        true
+    }
+
+    /// Is the connection inside a `MULTI`.
+    pub fn is_started(&self) -> bool {
+        self.queued.is_some()
+    }
+
+    /// The number of commands queued, `None` outside of a `MULTI`.
+    pub fn queued(&self) -> Option<usize> {
+        self.queued.as_ref().map(Vec::len)
+    }
+
+    /// The number of keys watched.
+    pub fn watched(&self) -> usize {
+        self.watched.len()
+    }
+
+    /// Queue a command to be applied on `EXEC`.
+    pub fn queue(&mut self, cmd: Command) {
+        if let Some(queued) = &mut self.queued {
+            queued.push(cmd);
+        }
+    }
+
+    /// Flag the transaction so it'll be discarded on `EXEC`.
+    pub fn abort(&mut self) {
+        if self.queued.is_some() {
+            self.aborted = true;
+        }
+    }
+
+    /// Add a key to watch with its current version.
+    pub fn watch(&mut self, key: ByteString, version: u64) {
+        if !self.watched.iter().any(|(watched, _)| watched == &key) {
+            self.watched.push((key, version));
+        }
+    }
+
+    /// Forget every watched key.
+    pub fn unwatch(&mut self) {
+        self.watched.clear();
+    }
+
+    /// End the transaction, the watched keys are forgotten too.
+    ///
+    /// Return `None` if there is no transaction started.
+    pub fn finish(&mut self) -> Option<FinishedTransaction> {
+        let commands = self.queued.take()?;
+        Some(FinishedTransaction {
+            commands,
+            aborted: std::mem::take(&mut self.aborted),
+            watched: std::mem::take(&mut self.watched),
+        })
+    }
+}
+
+/// A transaction ended by `EXEC` or `DISCARD`.
+#[derive(Debug)]
+pub struct FinishedTransaction {
+    pub commands: Vec<Command>,
+    pub aborted: bool,
+    pub watched: Vec<(ByteString, u64)>,
+}
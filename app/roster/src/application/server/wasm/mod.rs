@@ -0,0 +1,474 @@
+//! The WebAssembly engine running the functions called with `WCALL`.
+//!
+//! A module is loaded with `WASM LOAD` and identified by its SHA1, like the
+//! scripts. Any function exported by the module which takes no parameter and
+//! returns nothing, an `i32` or an `i64` can be called. The module must
+//! export its `memory` and can import the following functions from the
+//! `roster` module:
+//!
+//! ```text
+//! key_len(index: i32) -> i32            the length of a key
+//! key(index: i32, ptr: i32) -> i32      copy a key at `ptr`
+//! arg_len(index: i32) -> i32            the length of an argument
+//! arg(index: i32, ptr: i32) -> i32      copy an argument at `ptr`
+//! get(key_ptr: i32, key_len: i32) -> i32
+//!                                       read a key, return the length of
+//!                                       its value
+//! result(ptr: i32) -> i32               copy the value read by `get` at
+//!                                       `ptr`
+//! set(key_ptr: i32, key_len: i32, val_ptr: i32, val_len: i32) -> i32
+//!                                       write a key, return 0
+//! reply(ptr: i32, len: i32)             reply with a bulk string
+//! reply_error(ptr: i32, len: i32)       reply with an error
+//! ```
+//!
+//! The functions returning an `i32` return a negative [status] on failure.
+//! Without a call to `reply` or `reply_error`, the value returned by the
+//! function is sent as an integer, or a null when it returns nothing.
+//!
+//! A function can only access the keys given to `WCALL`, which are checked
+//! by the ACL rules and hash to the same slot.
+//!
+//! Like the scripts, a function runs while the connection holds an
+//! exclusive access on the storage. Each call can consume a limited amount
+//! of fuel and memory, a function running out of fuel is stopped. The
+//! function runs on a thread of its own: the host functions accessing the
+//! storage send their requests to the connection, which applies them, so the
+//! thread of the connection keeps running its other tasks.
+
+use std::fmt;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use bytestring::ByteString;
+use futures::channel::mpsc::{unbounded, UnboundedSender};
+use futures::channel::oneshot;
+use futures::StreamExt;
+use wasmtime::{
+    Caller, Config, Engine, Extern, Linker, Memory, Module, Store, StoreLimits,
+    StoreLimitsBuilder, Trap, Val, ValType,
+};
+
+use super::cmd::CommandError;
+use super::context::Context;
+use super::frame::Frame;
+use crate::domain::storage::SetOptions;
+
+/// The negative values returned by the host functions on failure.
+pub mod status {
+    /// The key, the argument or the value doesn't exist.
+    pub const NOT_FOUND: i32 = -1;
+    /// A key was written by a function called with `WCALL_RO`.
+    pub const READ_ONLY: i32 = -2;
+    /// The key isn't one of the keys given to `WCALL`.
+    pub const UNDECLARED_KEY: i32 = -3;
+    /// The key is not valid UTF-8.
+    pub const INVALID_KEY: i32 = -4;
+}
+
+/// The tables of a module can't grow past this number of elements.
+const MAX_TABLE_ELEMENTS: usize = 10_000;
+
+/// A request of a host function accessing the storage, applied by the
+/// connection.
+enum Request {
+    Get(ByteString, oneshot::Sender<Option<Bytes>>),
+    Set(ByteString, Bytes, oneshot::Sender<()>),
+}
+
+/// The state of a call, available to the host functions.
+struct Host {
+    requests: UnboundedSender<Request>,
+    keys: Vec<Bytes>,
+    args: Vec<Bytes>,
+    read_only: bool,
+    /// The value read by the last `get`.
+    result: Option<Bytes>,
+    reply: Option<Frame>,
+    limits: StoreLimits,
+}
+
+impl Host {
+    /// Check the key is one of the keys of the call, so it was checked by the
+    /// ACL rules.
+    fn check_key(&self, key: Vec<u8>) -> Result<ByteString, i32> {
+        if !self.keys.iter().any(|declared| declared[..] == key[..]) {
+            return Err(status::UNDECLARED_KEY);
+        }
+        ByteString::try_from(Bytes::from(key)).map_err(|_| status::INVALID_KEY)
+    }
+
+    /// Send a request to the connection and wait for its response, which
+    /// blocks the thread of the function.
+    fn request<T>(
+        &self,
+        request: impl FnOnce(oneshot::Sender<T>) -> Request,
+    ) -> wasmtime::Result<T> {
+        let (tx, rx) = oneshot::channel();
+        self.requests
+            .unbounded_send(request(tx))
+            .map_err(|_| wasmtime::Error::msg("the connection is gone"))?;
+        futures::executor::block_on(rx)
+            .map_err(|_| wasmtime::Error::msg("the connection is gone"))
+    }
+}
+
+/// The engine compiling and running the modules, shared across every thread.
+///
+/// **Should be cheap to clone**
+#[derive(Clone)]
+pub struct WasmEngine {
+    engine: Engine,
+    linker: Arc<Linker<Host>>,
+    /// The modules compiled, by SHA1.
+    compiled: Arc<scc::HashMap<String, Module>>,
+    /// The fuel a call can consume.
+    fuel: u64,
+    /// The memory a call can use, in bytes.
+    max_memory: usize,
+}
+
+impl fmt::Debug for WasmEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WasmEngine")
+            .field("fuel", &self.fuel)
+            .field("max_memory", &self.max_memory)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WasmEngine {
+    pub fn new(fuel: u64, max_memory: usize) -> wasmtime::Result<WasmEngine> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config)?;
+
+        let mut linker = Linker::new(&engine);
+        host_functions(&mut linker)?;
+
+        Ok(WasmEngine {
+            engine,
+            linker: Arc::new(linker),
+            compiled: Default::default(),
+            fuel,
+            max_memory,
+        })
+    }
+
+    /// Compile the module `bytes` identified by `sha`, the compiled modules
+    /// are kept until [WasmEngine::flush].
+    ///
+    /// The module can be a binary or a text module.
+    pub fn compile(
+        &self,
+        sha: &str,
+        bytes: &[u8],
+    ) -> Result<Module, CommandError> {
+        if let Some(module) =
+            self.compiled.read(sha, |_, module| module.clone())
+        {
+            return Ok(module);
+        }
+
+        let module = Module::new(&self.engine, bytes).map_err(|err| {
+            CommandError::err(format!("Error compiling module: {err}"))
+        })?;
+        let _ = self.compiled.insert(sha.to_string(), module.clone());
+        Ok(module)
+    }
+
+    /// Forget the compiled modules.
+    pub fn flush(&self) {
+        self.compiled.clear();
+    }
+
+    /// Call the function `name` exported by `module`.
+    ///
+    /// The function runs on a new thread while the requests of its host
+    /// functions are applied on the thread of the connection. The caller
+    /// must hold an exclusive access on the storage.
+    pub async fn call(
+        &self,
+        ctx: &Context,
+        module: &Module,
+        name: &str,
+        keys: &[Bytes],
+        args: &[Bytes],
+        read_only: bool,
+    ) -> Result<Frame, CommandError> {
+        let (requests, mut pending) = unbounded();
+        let host = Host {
+            requests,
+            keys: keys.to_vec(),
+            args: args.to_vec(),
+            read_only,
+            result: None,
+            reply: None,
+            limits: StoreLimitsBuilder::new()
+                .memory_size(self.max_memory)
+                .table_elements(MAX_TABLE_ELEMENTS)
+                .build(),
+        };
+
+        let (tx, rx) = oneshot::channel();
+        let engine = self.clone();
+        let module = module.clone();
+        let name = name.to_string();
+        std::thread::Builder::new()
+            .name("roster-wasm".to_string())
+            .spawn(move || {
+                let _ = tx.send(engine.run(host, &module, &name));
+            })
+            .map_err(|err| {
+                CommandError::err(format!("Error running WASM function: {err}"))
+            })?;
+
+        // The requests end when the function is done and drops its `Host`.
+        while let Some(request) = pending.next().await {
+            match request {
+                Request::Get(key, response) => {
+                    ctx.track_key(key.as_bytes()).await;
+                    let value = ctx.storage.get_async(key, ctx.now()).await;
+                    let _ = response.send(value);
+                }
+                Request::Set(key, value, response) => {
+                    let _ = ctx
+                        .storage
+                        .set_async(
+                            key.clone(),
+                            value,
+                            SetOptions { expired: None },
+                            ctx.now(),
+                        )
+                        .await;
+                    ctx.signal_modified_key(key.as_bytes()).await;
+                    let _ = response.send(());
+                }
+            }
+        }
+
+        rx.await.unwrap_or_else(|_| {
+            Err(CommandError::err("WASM function stopped unexpectedly"))
+        })
+    }
+
+    /// Run the function `name` exported by `module` on the current thread.
+    fn run(
+        &self,
+        host: Host,
+        module: &Module,
+        name: &str,
+    ) -> Result<Frame, CommandError> {
+        let mut store = Store::new(&self.engine, host);
+        store.limiter(|host| &mut host.limits);
+        store.set_fuel(self.fuel).map_err(|err| {
+            CommandError::err(format!("Error running WASM function: {err}"))
+        })?;
+
+        let instance =
+            self.linker.instantiate(&mut store, module).map_err(|err| {
+                CommandError::err(format!("Error instantiating module: {err}"))
+            })?;
+        let function = instance
+            .get_func(&mut store, name)
+            .ok_or_else(|| CommandError::err("Function not found"))?;
+
+        let ty = function.ty(&store);
+        let mut results = match ty.results().collect::<Vec<_>>()[..] {
+            _ if ty.params().len() > 0 => None,
+            [] => Some(vec![]),
+            [ValType::I32] => Some(vec![Val::I32(0)]),
+            [ValType::I64] => Some(vec![Val::I64(0)]),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            CommandError::err(
+                "WASM functions must take no parameter and return nothing, an \
+                 i32 or an i64",
+            )
+        })?;
+
+        function.call(&mut store, &[], &mut results).map_err(
+            |err| match err.downcast_ref::<Trap>() {
+                Some(Trap::OutOfFuel) => {
+                    CommandError::err("WASM function ran out of fuel")
+                }
+                Some(trap) => CommandError::err(format!(
+                    "Error running WASM function: {trap}"
+                )),
+                None => CommandError::err(format!(
+                    "Error running WASM function: {}",
+                    err.root_cause()
+                )),
+            },
+        )?;
+
+        if let Some(reply) = store.into_data().reply {
+            return Ok(reply);
+        }
+        let reply = match results.first() {
+            Some(Val::I32(val)) => Frame::Integer(*val as i64),
+            Some(Val::I64(val)) => Frame::Integer(*val),
+            _ => Frame::Null,
+        };
+        Ok(reply)
+    }
+}
+
+fn memory(caller: &mut Caller<'_, Host>) -> wasmtime::Result<Memory> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| wasmtime::Error::msg("the module must export memory"))
+}
+
+/// Read `len` bytes at `ptr` in the memory of the module.
+fn read(
+    caller: &mut Caller<'_, Host>,
+    ptr: i32,
+    len: i32,
+) -> wasmtime::Result<Vec<u8>> {
+    let memory = memory(caller)?;
+    let start = usize::try_from(ptr)?;
+    let end = start.checked_add(usize::try_from(len)?);
+    end.and_then(|end| memory.data(&caller).get(start..end))
+        .map(<[u8]>::to_vec)
+        .ok_or_else(|| wasmtime::Error::msg("out of bounds memory access"))
+}
+
+/// Write `bytes` at `ptr` in the memory of the module and return their
+/// length.
+fn write(
+    caller: &mut Caller<'_, Host>,
+    ptr: i32,
+    bytes: &[u8],
+) -> wasmtime::Result<i32> {
+    let memory = memory(caller)?;
+    memory.write(&mut *caller, usize::try_from(ptr)?, bytes)?;
+    Ok(bytes.len() as i32)
+}
+
+fn host_functions(linker: &mut Linker<Host>) -> wasmtime::Result<()> {
+    linker.func_wrap(
+        "roster",
+        "key_len",
+        |caller: Caller<'_, Host>, index: i32| -> i32 {
+            usize::try_from(index)
+                .ok()
+                .and_then(|index| caller.data().keys.get(index))
+                .map_or(status::NOT_FOUND, |key| key.len() as i32)
+        },
+    )?;
+    linker.func_wrap(
+        "roster",
+        "key",
+        |mut caller: Caller<'_, Host>, index: i32, ptr: i32| {
+            let key = usize::try_from(index)
+                .ok()
+                .and_then(|index| caller.data().keys.get(index).cloned());
+            match key {
+                Some(key) => write(&mut caller, ptr, &key),
+                None => Ok(status::NOT_FOUND),
+            }
+        },
+    )?;
+    linker.func_wrap(
+        "roster",
+        "arg_len",
+        |caller: Caller<'_, Host>, index: i32| -> i32 {
+            usize::try_from(index)
+                .ok()
+                .and_then(|index| caller.data().args.get(index))
+                .map_or(status::NOT_FOUND, |arg| arg.len() as i32)
+        },
+    )?;
+    linker.func_wrap(
+        "roster",
+        "arg",
+        |mut caller: Caller<'_, Host>, index: i32, ptr: i32| {
+            let arg = usize::try_from(index)
+                .ok()
+                .and_then(|index| caller.data().args.get(index).cloned());
+            match arg {
+                Some(arg) => write(&mut caller, ptr, &arg),
+                None => Ok(status::NOT_FOUND),
+            }
+        },
+    )?;
+    linker.func_wrap(
+        "roster",
+        "get",
+        |mut caller: Caller<'_, Host>, key_ptr: i32, key_len: i32| {
+            let key = read(&mut caller, key_ptr, key_len)?;
+            let host = caller.data();
+            let key = match host.check_key(key) {
+                Ok(key) => key,
+                Err(status) => return Ok(status),
+            };
+
+            let value = host.request(|tx| Request::Get(key, tx))?;
+            let status = value
+                .as_ref()
+                .map_or(status::NOT_FOUND, |value| value.len() as i32);
+            caller.data_mut().result = value;
+            Ok(status)
+        },
+    )?;
+    linker.func_wrap(
+        "roster",
+        "result",
+        |mut caller: Caller<'_, Host>, ptr: i32| match caller
+            .data_mut()
+            .result
+            .take()
+        {
+            Some(value) => write(&mut caller, ptr, &value),
+            None => Ok(status::NOT_FOUND),
+        },
+    )?;
+    linker.func_wrap(
+        "roster",
+        "set",
+        |mut caller: Caller<'_, Host>,
+         key_ptr: i32,
+         key_len: i32,
+         val_ptr: i32,
+         val_len: i32| {
+            let key = read(&mut caller, key_ptr, key_len)?;
+            let value = read(&mut caller, val_ptr, val_len)?;
+            let host = caller.data();
+            if host.read_only {
+                return Ok(status::READ_ONLY);
+            }
+            let key = match host.check_key(key) {
+                Ok(key) => key,
+                Err(status) => return Ok(status),
+            };
+
+            host.request(|tx| Request::Set(key, Bytes::from(value), tx))?;
+            Ok(0)
+        },
+    )?;
+    linker.func_wrap(
+        "roster",
+        "reply",
+        |mut caller: Caller<'_, Host>, ptr: i32, len: i32| {
+            let value = read(&mut caller, ptr, len)?;
+            caller.data_mut().reply = Some(Frame::Bulk(Bytes::from(value)));
+            Ok(())
+        },
+    )?;
+    linker.func_wrap(
+        "roster",
+        "reply_error",
+        |mut caller: Caller<'_, Host>, ptr: i32, len: i32| {
+            let message = read(&mut caller, ptr, len)?;
+            caller.data_mut().reply = Some(Frame::Error(ByteString::from(
+                String::from_utf8_lossy(&message).into_owned(),
+            )));
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! The log of the commands denied by the ACL rules and of the failed
+//! authentications, as shown by `ACL LOG`.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How many entries are kept, the oldest ones are dropped first.
+const MAX_LEN: usize = 128;
+
+/// Similar entries created within this delay are grouped together.
+const GROUPING_DELAY: Duration = Duration::from_secs(60);
+
+/// Why an entry was logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogReason {
+    Command,
+    Key,
+    Channel,
+    Auth,
+}
+
+impl LogReason {
+    pub fn name(self) -> &'static str {
+        match self {
+            LogReason::Command => "command",
+            LogReason::Key => "key",
+            LogReason::Channel => "channel",
+            LogReason::Auth => "auth",
+        }
+    }
+}
+
+/// Where the denied command was called from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogContext {
+    Toplevel,
+    Multi,
+    Lua,
+}
+
+impl LogContext {
+    pub fn name(self) -> &'static str {
+        match self {
+            LogContext::Toplevel => "toplevel",
+            LogContext::Multi => "multi",
+            LogContext::Lua => "lua",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    /// How many times this entry happened.
+    pub count: u64,
+    pub reason: LogReason,
+    pub context: LogContext,
+    /// The command, key or channel denied, `AUTH` for an authentication.
+    pub object: String,
+    pub username: String,
+    /// The `CLIENT INFO` of the last connection which was denied.
+    pub client_info: String,
+    pub entry_id: u64,
+    /// When the entry was created, in milliseconds since the Unix epoch.
+    pub created: u64,
+    /// When the entry last happened, in milliseconds since the Unix epoch.
+    pub updated: u64,
+}
+
+impl LogEntry {
+    /// How long ago the entry last happened.
+    pub fn age(&self) -> Duration {
+        Duration::from_millis(now().saturating_sub(self.updated))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// The most recent entries first.
+    entries: VecDeque<LogEntry>,
+    next_id: u64,
+}
+
+/// The ACL log, shared across every thread.
+///
+/// **Should be cheap to clone**
+#[derive(Debug, Clone, Default)]
+pub struct AclLog {
+    inner: Arc<Mutex<Entries>>,
+}
+
+impl AclLog {
+    /// Log a denial, grouped with a similar entry which happened recently.
+    pub fn push(
+        &self,
+        reason: LogReason,
+        context: LogContext,
+        object: String,
+        username: String,
+        client_info: String,
+    ) {
+        let now = now();
+        let mut inner = self.inner.lock().unwrap();
+
+        let similar = inner.entries.iter().position(|entry| {
+            entry.reason == reason
+                && entry.context == context
+                && entry.object == object
+                && entry.username == username
+                && now.saturating_sub(entry.updated)
+                    < GROUPING_DELAY.as_millis() as u64
+        });
+        if let Some(mut entry) =
+            similar.and_then(|index| inner.entries.remove(index))
+        {
+            entry.count += 1;
+            entry.updated = now;
+            entry.client_info = client_info;
+            inner.entries.push_front(entry);
+            return;
+        }
+
+        let entry_id = inner.next_id;
+        inner.next_id += 1;
+        inner.entries.push_front(LogEntry {
+            count: 1,
+            reason,
+            context,
+            object,
+            username,
+            client_info,
+            entry_id,
+            created: now,
+            updated: now,
+        });
+        inner.entries.truncate(MAX_LEN);
+    }
+
+    /// The `count` most recent entries, the most recent first.
+    pub fn entries(&self, count: usize) -> Vec<LogEntry> {
+        let inner = self.inner.lock().unwrap();
+        inner.entries.iter().take(count).cloned().collect()
+    }
+
+    /// Remove every entry.
+    pub fn reset(&self) {
+        self.inner.lock().unwrap().entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(log: &AclLog, object: &str) {
+        log.push(
+            LogReason::Command,
+            LogContext::Toplevel,
+            object.to_string(),
+            "alice".to_string(),
+            "id=0".to_string(),
+        );
+    }
+
+    #[test]
+    fn group_and_bound_entries() {
+        let log = AclLog::default();
+        push(&log, "get");
+        push(&log, "set");
+        push(&log, "get");
+
+        let entries = log.entries(10);
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].object.as_str(), entries[0].count), ("get", 2));
+        assert_eq!(entries[0].entry_id, 0);
+        assert_eq!((entries[1].object.as_str(), entries[1].count), ("set", 1));
+
+        for i in 0..MAX_LEN {
+            push(&log, &i.to_string());
+        }
+        assert_eq!(log.entries(usize::MAX).len(), MAX_LEN);
+        assert_eq!(log.entries(1)[0].entry_id, MAX_LEN as u64 + 1);
+
+        log.reset();
+        assert!(log.entries(10).is_empty());
+    }
+}
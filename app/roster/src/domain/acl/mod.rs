@@ -0,0 +1,350 @@
+//! Access control: the categories grouping the commands, which rules can
+//! allow or deny as a whole, e.g. `+@read -@dangerous`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, RwLock};
+
+pub mod log;
+mod user;
+pub use log::{AclLog, LogContext, LogEntry, LogReason};
+pub use user::{
+    hash_password, AclError, Denied, KeyAccess, Request, RuleError, Selector,
+    User,
+};
+
+/// A category of commands, as listed by `ACL CAT`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AclCategory {
+    Keyspace,
+    Read,
+    Write,
+    Set,
+    SortedSet,
+    List,
+    Hash,
+    String,
+    Bitmap,
+    HyperLogLog,
+    Geo,
+    Stream,
+    PubSub,
+    Admin,
+    Fast,
+    Slow,
+    Blocking,
+    Dangerous,
+    Connection,
+    Transaction,
+    Scripting,
+}
+
+impl AclCategory {
+    /// Every category, in the order listed by `ACL CAT`.
+    pub const ALL: [AclCategory; 21] = [
+        AclCategory::Keyspace,
+        AclCategory::Read,
+        AclCategory::Write,
+        AclCategory::Set,
+        AclCategory::SortedSet,
+        AclCategory::List,
+        AclCategory::Hash,
+        AclCategory::String,
+        AclCategory::Bitmap,
+        AclCategory::HyperLogLog,
+        AclCategory::Geo,
+        AclCategory::Stream,
+        AclCategory::PubSub,
+        AclCategory::Admin,
+        AclCategory::Fast,
+        AclCategory::Slow,
+        AclCategory::Blocking,
+        AclCategory::Dangerous,
+        AclCategory::Connection,
+        AclCategory::Transaction,
+        AclCategory::Scripting,
+    ];
+
+    /// The name of the category, without the `@` prefix.
+    pub fn name(self) -> &'static str {
+        match self {
+            AclCategory::Keyspace => "keyspace",
+            AclCategory::Read => "read",
+            AclCategory::Write => "write",
+            AclCategory::Set => "set",
+            AclCategory::SortedSet => "sortedset",
+            AclCategory::List => "list",
+            AclCategory::Hash => "hash",
+            AclCategory::String => "string",
+            AclCategory::Bitmap => "bitmap",
+            AclCategory::HyperLogLog => "hyperloglog",
+            AclCategory::Geo => "geo",
+            AclCategory::Stream => "stream",
+            AclCategory::PubSub => "pubsub",
+            AclCategory::Admin => "admin",
+            AclCategory::Fast => "fast",
+            AclCategory::Slow => "slow",
+            AclCategory::Blocking => "blocking",
+            AclCategory::Dangerous => "dangerous",
+            AclCategory::Connection => "connection",
+            AclCategory::Transaction => "transaction",
+            AclCategory::Scripting => "scripting",
+        }
+    }
+}
+
+impl fmt::Display for AclCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error("Unknown command category '{0}'")]
+pub struct UnknownCategory(pub String);
+
+impl FromStr for AclCategory {
+    type Err = UnknownCategory;
+
+    /// Parse the name of a category, case insensitive.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        AclCategory::ALL
+            .into_iter()
+            .find(|category| category.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| UnknownCategory(s.to_string()))
+    }
+}
+
+/// An error in an ACL file, on the line `line` starting at 1.
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error("{line}: {error}")]
+pub struct AclFileError {
+    pub line: usize,
+    pub error: AclError,
+}
+
+/// Apply the `rules` to `user`, the rules between parentheses spanning
+/// several arguments are joined into a single selector.
+fn apply_rules(
+    user: &mut User,
+    rules: &[&str],
+    is_known: &dyn Fn(&str) -> bool,
+) -> Result<(), AclError> {
+    let mut rules = rules.iter();
+    while let Some(rule) = rules.next() {
+        if !rule.starts_with('(') || rule.ends_with(')') {
+            user.apply(rule, is_known)?;
+            continue;
+        }
+
+        let mut selector = rule.to_string();
+        loop {
+            let Some(next) = rules.next() else {
+                return Err(AclError::UnmatchedParenthesis(selector));
+            };
+            selector.push(' ');
+            selector.push_str(next);
+            if next.ends_with(')') {
+                break;
+            }
+        }
+        user.apply(&selector, is_known)?;
+    }
+    Ok(())
+}
+
+/// The users, shared across every thread.
+///
+/// **Should be cheap to clone**
+#[derive(Debug, Clone)]
+pub struct Users {
+    users: Arc<RwLock<BTreeMap<String, Arc<User>>>>,
+}
+
+impl Default for Users {
+    fn default() -> Self {
+        let default = User::default_user();
+        Self {
+            users: Arc::new(RwLock::new(BTreeMap::from([(
+                default.name().to_string(),
+                Arc::new(default),
+            )]))),
+        }
+    }
+}
+
+impl Users {
+    pub fn get(&self, name: &str) -> Option<Arc<User>> {
+        self.users.read().unwrap().get(name).cloned()
+    }
+
+    /// Apply the `rules` to the user `name`, which is created when it doesn't
+    /// exist. The user is left unchanged when one of the rules is invalid.
+    pub fn set_user(
+        &self,
+        name: &str,
+        rules: &[&str],
+        is_known: &dyn Fn(&str) -> bool,
+    ) -> Result<(), AclError> {
+        let mut users = self.users.write().unwrap();
+        let mut user = match users.get(name) {
+            Some(user) => User::clone(user),
+            None => User::new(name),
+        };
+
+        apply_rules(&mut user, rules, is_known)?;
+        users.insert(name.to_string(), Arc::new(user));
+        Ok(())
+    }
+
+    /// Get the user `name` if it's enabled and `password` is valid.
+    pub fn authenticate(
+        &self,
+        name: &str,
+        password: &[u8],
+    ) -> Option<Arc<User>> {
+        self.get(name)
+            .filter(|user| user.is_enabled() && user.check_password(password))
+    }
+
+    /// Delete the users `names` and return how many existed.
+    pub fn delete(&self, names: &[&str]) -> Result<usize, AclError> {
+        if names.contains(&"default") {
+            return Err(AclError::DefaultUser);
+        }
+        let mut users = self.users.write().unwrap();
+        Ok(names
+            .iter()
+            .filter(|name| users.remove(**name).is_some())
+            .count())
+    }
+
+    /// Every user, ordered by name.
+    pub fn list(&self) -> Vec<Arc<User>> {
+        self.users.read().unwrap().values().cloned().collect()
+    }
+
+    /// Replace every user with the ones of an ACL file, made of lines like
+    /// `user alice on #<hash> ~* +@all`. The users are left unchanged when
+    /// one of the lines is invalid.
+    ///
+    /// The `default` user keeps its default rules when it's not in the file.
+    pub fn load(
+        &self,
+        contents: &str,
+        is_known: &dyn Fn(&str) -> bool,
+    ) -> Result<(), AclFileError> {
+        let mut loaded = BTreeMap::new();
+        for (index, line) in contents.lines().enumerate() {
+            let fail = |error| AclFileError {
+                line: index + 1,
+                error,
+            };
+
+            let words = line.split_whitespace().collect::<Vec<_>>();
+            let (name, rules) = match &words[..] {
+                [] => continue,
+                ["user", name, rules @ ..] => (*name, rules),
+                _ => return Err(fail(AclError::MissingUserKeyword)),
+            };
+            if loaded.contains_key(name) {
+                return Err(fail(AclError::DuplicateUser(name.to_string())));
+            }
+
+            let mut user = User::new(name);
+            apply_rules(&mut user, rules, is_known).map_err(fail)?;
+            loaded.insert(name.to_string(), Arc::new(user));
+        }
+
+        loaded
+            .entry("default".to_string())
+            .or_insert_with(|| Arc::new(User::default_user()));
+        *self.users.write().unwrap() = loaded;
+        Ok(())
+    }
+
+    /// Every user as a line of an ACL file, see [Users::load].
+    pub fn to_file(&self) -> String {
+        self.list()
+            .iter()
+            .map(|user| format!("{}\n", user.describe()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AclCategory, AclError, AclFileError, Users};
+
+    #[test]
+    fn parse_categories() {
+        for category in AclCategory::ALL {
+            assert_eq!(category.name().parse(), Ok(category));
+        }
+        assert_eq!("SortedSet".parse(), Ok(AclCategory::SortedSet));
+        assert!("nope".parse::<AclCategory>().is_err());
+    }
+
+    #[test]
+    fn set_users() {
+        let users = Users::default();
+        assert!(users.get("default").unwrap().is_unrestricted());
+
+        users
+            .set_user("alice", &["on", "(~a*", "+get)", "+@read"], &|_| true)
+            .unwrap();
+        let alice = users.get("alice").unwrap();
+        assert_eq!(alice.selectors().len(), 1);
+        assert_eq!(alice.root().describe_commands(), "-@all +@read");
+
+        assert_eq!(
+            users.set_user("alice", &["off", "(~a*"], &|_| true),
+            Err(AclError::UnmatchedParenthesis("(~a*".into()))
+        );
+        assert!(users.get("alice").unwrap().is_enabled());
+
+        assert!(users.authenticate("alice", b"secret").is_none());
+        users.set_user("alice", &[">secret"], &|_| true).unwrap();
+        assert!(users.authenticate("alice", b"secret").is_some());
+        assert!(users.authenticate("alice", b"other").is_none());
+        assert!(users.authenticate("default", b"any").is_some());
+
+        assert_eq!(users.delete(&["default"]), Err(AclError::DefaultUser));
+        assert_eq!(users.delete(&["alice", "bob"]), Ok(1));
+        assert_eq!(users.list().len(), 1);
+    }
+
+    #[test]
+    fn load_file() {
+        let users = Users::default();
+        let file = "user alice on nopass ~* (+get ~a*) -@all +set\n\nuser \
+                    default off\n";
+        users.load(file, &|_| true).unwrap();
+        assert_eq!(
+            users.to_file(),
+            "user alice on nopass ~* resetchannels -@all +set (~a* \
+             resetchannels -@all +get)\nuser default off resetchannels -@all\n"
+        );
+
+        assert_eq!(
+            users.load("user bob on\nbob on\n", &|_| true),
+            Err(AclFileError {
+                line: 2,
+                error: AclError::MissingUserKeyword
+            })
+        );
+        assert_eq!(
+            users.load("user bob on\nuser bob off", &|_| true),
+            Err(AclFileError {
+                line: 2,
+                error: AclError::DuplicateUser("bob".into())
+            })
+        );
+        assert!(users.get("bob").is_none());
+
+        users.load("", &|_| true).unwrap();
+        assert_eq!(users.list().len(), 1);
+        assert!(users.get("default").unwrap().is_unrestricted());
+    }
+}
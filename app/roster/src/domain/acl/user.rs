@@ -0,0 +1,721 @@
+//! The users and the rules describing what they can do, as given to
+//! `ACL SETUSER`:
+//!
+//! ```text
+//! on, off                 Enable or disable the user.
+//! nopass, resetpass       Allow any password, or remove every password.
+//! >password, <password    Add or remove a password.
+//! #hash, !hash            Add or remove the SHA-256 of a password.
+//! ~pattern                Allow the keys matching the pattern, %R~ and %W~
+//!                         only allow to read or to write them.
+//! allkeys, resetkeys      Allow every key, or none.
+//! &pattern                Allow the Pub/Sub channels matching the pattern.
+//! allchannels             Allow every channel, or none with resetchannels.
+//! +command, -command      Allow or deny a command, or a subcommand with
+//!                         +command|subcommand.
+//! +@category, -@category  Allow or deny the commands of a category.
+//! allcommands             Alias of +@all, nocommands of -@all.
+//! (rules ...)             Add a selector, a set of key, channel and command
+//!                         rules checked when the root rules deny a command.
+//! clearselectors          Remove every selector.
+//! reset                   Remove every password, key, channel and command
+//!                         rule, and disable the user.
+//! ```
+//!
+//! The rules of a user or of a selector are checked independently: a command
+//! is allowed when the root rules or one of the selectors allow its name, its
+//! keys and its channels.
+
+use std::fmt::Write as _;
+
+use sha2::{Digest, Sha256};
+
+use super::AclCategory;
+use crate::infrastructure::glob::glob_match;
+
+/// The SHA-256 digest of `password` as a lowercase hexadecimal string, only
+/// the digests of the passwords are stored.
+pub fn hash_password(password: &[u8]) -> String {
+    Sha256::digest(password).iter().fold(
+        String::with_capacity(64),
+        |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        },
+    )
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum RuleError {
+    #[error("Syntax error")]
+    Syntax,
+    #[error("Unknown command or category name in ACL")]
+    UnknownCommand,
+    #[error(
+        "The password hash must be exactly 64 characters and contain only \
+         lowercase hexadecimal characters"
+    )]
+    PasswordHash,
+    #[error(
+        "The password you are trying to remove from the user does not exist"
+    )]
+    NoSuchPassword,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum AclError {
+    #[error("Error in ACL SETUSER modifier '{rule}': {error}")]
+    Rule { rule: String, error: RuleError },
+    #[error("Unmatched parenthesis in acl selector starting at '{0}'.")]
+    UnmatchedParenthesis(String),
+    #[error("The 'default' user cannot be removed")]
+    DefaultUser,
+    #[error("should start with user keyword followed by the user name")]
+    MissingUserKeyword,
+    #[error("Duplicate user '{0}' found")]
+    DuplicateUser(String),
+}
+
+/// Why a command is denied, ordered by relevance: when every selector denies
+/// a command, the most relevant reason is reported.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Denied {
+    Command,
+    Key(Vec<u8>),
+    Channel(Vec<u8>),
+}
+
+/// How a command accesses a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAccess {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl KeyAccess {
+    fn reads(self) -> bool {
+        matches!(self, KeyAccess::Read | KeyAccess::ReadWrite)
+    }
+
+    fn writes(self) -> bool {
+        matches!(self, KeyAccess::Write | KeyAccess::ReadWrite)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct KeyPattern {
+    pattern: String,
+    read: bool,
+    write: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RuleTarget {
+    All,
+    Category(AclCategory),
+    /// A command, or a subcommand like `client|id`.
+    Command(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CommandRule {
+    allow: bool,
+    target: RuleTarget,
+}
+
+impl CommandRule {
+    /// Does this rule apply to the command `name`, which is `command|sub`
+    /// for a subcommand.
+    fn matches(&self, name: &str, categories: &[AclCategory]) -> bool {
+        match &self.target {
+            RuleTarget::All => true,
+            RuleTarget::Category(category) => categories.contains(category),
+            RuleTarget::Command(command) => {
+                name == command
+                    || name
+                        .strip_prefix(command.as_str())
+                        .is_some_and(|sub| sub.starts_with('|'))
+            }
+        }
+    }
+}
+
+/// A set of command, key and channel rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Selector {
+    /// Applied in order, the last rule matching a command decides.
+    commands: Vec<CommandRule>,
+    keys: Vec<KeyPattern>,
+    all_channels: bool,
+    channels: Vec<String>,
+}
+
+impl Selector {
+    /// Apply a key, channel or command rule, `is_known` tells if a command
+    /// or a subcommand exists.
+    fn apply(
+        &mut self,
+        rule: &str,
+        is_known: &dyn Fn(&str) -> bool,
+    ) -> Result<(), RuleError> {
+        match rule {
+            "allkeys" => self.apply("~*", is_known)?,
+            "resetkeys" => self.keys.clear(),
+            "allchannels" => {
+                self.all_channels = true;
+                self.channels.clear();
+            }
+            "resetchannels" => {
+                self.all_channels = false;
+                self.channels.clear();
+            }
+            "allcommands" => self.apply("+@all", is_known)?,
+            "nocommands" => self.apply("-@all", is_known)?,
+            _ if rule.starts_with('~') || rule.starts_with('%') => {
+                self.add_key_pattern(rule)?
+            }
+            _ if rule.starts_with('&') => {
+                let pattern = &rule[1..];
+                if pattern == "*" {
+                    self.apply("allchannels", is_known)?;
+                } else if !self.all_channels
+                    && !self.channels.iter().any(|known| known == pattern)
+                {
+                    self.channels.push(pattern.to_string());
+                }
+            }
+            _ if rule.starts_with('+') || rule.starts_with('-') => {
+                let allow = rule.starts_with('+');
+                let target = match rule[1..].strip_prefix('@') {
+                    Some("all") => RuleTarget::All,
+                    Some(category) => RuleTarget::Category(
+                        category
+                            .parse()
+                            .map_err(|_| RuleError::UnknownCommand)?,
+                    ),
+                    None => {
+                        let command = rule[1..].to_lowercase();
+                        if !is_known(&command) {
+                            return Err(RuleError::UnknownCommand);
+                        }
+                        RuleTarget::Command(command)
+                    }
+                };
+                self.add_command_rule(CommandRule { allow, target });
+            }
+            _ => return Err(RuleError::Syntax),
+        }
+        Ok(())
+    }
+
+    fn add_key_pattern(&mut self, rule: &str) -> Result<(), RuleError> {
+        let (flags, pattern) = match rule.strip_prefix('%') {
+            Some(rule) => rule.split_once('~').ok_or(RuleError::Syntax)?,
+            None => ("RW", &rule[1..]),
+        };
+        if flags.is_empty() || flags.chars().any(|flag| !"RW".contains(flag)) {
+            return Err(RuleError::Syntax);
+        }
+        let (read, write) = (flags.contains('R'), flags.contains('W'));
+
+        match self.keys.iter_mut().find(|key| key.pattern == pattern) {
+            Some(key) => {
+                key.read |= read;
+                key.write |= write;
+            }
+            None => self.keys.push(KeyPattern {
+                pattern: pattern.to_string(),
+                read,
+                write,
+            }),
+        }
+        Ok(())
+    }
+
+    /// Add a command rule, the rules it overrides are removed to keep the
+    /// description short.
+    fn add_command_rule(&mut self, rule: CommandRule) {
+        match &rule.target {
+            RuleTarget::All => self.commands.clear(),
+            RuleTarget::Category(_) => {
+                self.commands.retain(|known| known.target != rule.target);
+            }
+            RuleTarget::Command(command) => {
+                let sub_prefix = format!("{command}|");
+                self.commands.retain(|known| match &known.target {
+                    RuleTarget::Command(known) => {
+                        known != command && !known.starts_with(&sub_prefix)
+                    }
+                    _ => true,
+                });
+            }
+        }
+        self.commands.push(rule);
+    }
+
+    fn is_unrestricted(&self) -> bool {
+        self.all_channels
+            && self
+                .keys
+                .iter()
+                .any(|key| key.pattern == "*" && key.read && key.write)
+            && self.commands
+                == [CommandRule {
+                    allow: true,
+                    target: RuleTarget::All,
+                }]
+    }
+
+    fn can_run(&self, name: &str, categories: &[AclCategory]) -> bool {
+        self.commands
+            .iter()
+            .rev()
+            .find(|rule| rule.matches(name, categories))
+            .is_some_and(|rule| rule.allow)
+    }
+
+    fn can_access_key(&self, key: &[u8], access: KeyAccess) -> bool {
+        self.keys.iter().any(|pattern| {
+            (pattern.read || !access.reads())
+                && (pattern.write || !access.writes())
+                && glob_match(pattern.pattern.as_bytes(), key, false)
+        })
+    }
+
+    /// Check a channel, a pattern given to `PSUBSCRIBE` must be allowed as
+    /// is.
+    fn can_access_channel(&self, channel: &[u8], is_pattern: bool) -> bool {
+        self.all_channels
+            || self.channels.iter().any(|pattern| {
+                if is_pattern {
+                    pattern.as_bytes() == channel
+                } else {
+                    glob_match(pattern.as_bytes(), channel, false)
+                }
+            })
+    }
+
+    fn check(&self, request: &Request<'_>) -> Result<(), Denied> {
+        if !self.can_run(request.name, request.categories) {
+            return Err(Denied::Command);
+        }
+        for (key, access) in request.keys {
+            if !self.can_access_key(key, *access) {
+                return Err(Denied::Key(key.to_vec()));
+            }
+        }
+        for (channel, is_pattern) in request.channels {
+            if !self.can_access_channel(channel, *is_pattern) {
+                return Err(Denied::Channel(channel.to_vec()));
+            }
+        }
+        Ok(())
+    }
+
+    /// The command rules, like `+@all -client`.
+    pub fn describe_commands(&self) -> String {
+        let mut rules = Vec::with_capacity(self.commands.len() + 1);
+        if self.commands.first().map(|rule| &rule.target)
+            != Some(&RuleTarget::All)
+        {
+            rules.push("-@all".to_string());
+        }
+        for rule in &self.commands {
+            let sign = if rule.allow { '+' } else { '-' };
+            rules.push(match &rule.target {
+                RuleTarget::All => format!("{sign}@all"),
+                RuleTarget::Category(category) => format!("{sign}@{category}"),
+                RuleTarget::Command(command) => format!("{sign}{command}"),
+            });
+        }
+        rules.join(" ")
+    }
+
+    /// The key patterns, like `~* %R~cache:*`.
+    pub fn describe_keys(&self) -> String {
+        let patterns =
+            self.keys.iter().map(|key| match (key.read, key.write) {
+                (true, true) => format!("~{}", key.pattern),
+                (true, false) => format!("%R~{}", key.pattern),
+                _ => format!("%W~{}", key.pattern),
+            });
+        patterns.collect::<Vec<_>>().join(" ")
+    }
+
+    /// The channel patterns, like `&*`.
+    pub fn describe_channels(&self) -> String {
+        if self.all_channels {
+            return "&*".to_string();
+        }
+        let patterns =
+            self.channels.iter().map(|channel| format!("&{channel}"));
+        patterns.collect::<Vec<_>>().join(" ")
+    }
+
+    /// Every rule, in the format of `ACL LIST`.
+    fn describe(&self) -> String {
+        let mut rules = Vec::new();
+        let keys = self.describe_keys();
+        if !keys.is_empty() {
+            rules.push(keys);
+        }
+        if !self.all_channels {
+            rules.push("resetchannels".to_string());
+        }
+        let channels = self.describe_channels();
+        if !channels.is_empty() {
+            rules.push(channels);
+        }
+        rules.push(self.describe_commands());
+        rules.join(" ")
+    }
+}
+
+/// What a command does, checked against the rules of a user.
+#[derive(Debug, Clone, Copy)]
+pub struct Request<'a> {
+    /// The name of the command, `command|subcommand` for a subcommand.
+    pub name: &'a str,
+    pub categories: &'a [AclCategory],
+    pub keys: &'a [(&'a [u8], KeyAccess)],
+    /// The channels, and if each one is a pattern given to `PSUBSCRIBE`.
+    pub channels: &'a [(&'a [u8], bool)],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct User {
+    name: String,
+    enabled: bool,
+    /// Any password is accepted.
+    nopass: bool,
+    /// The SHA-256 of the passwords.
+    passwords: Vec<String>,
+    root: Selector,
+    selectors: Vec<Selector>,
+}
+
+impl User {
+    /// A new user, disabled and without any permission.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            enabled: false,
+            nopass: false,
+            passwords: Vec::new(),
+            root: Selector::default(),
+            selectors: Vec::new(),
+        }
+    }
+
+    /// The `default` user, used by the connections which didn't
+    /// authenticate, can run every command.
+    pub fn default_user() -> Self {
+        let mut user = User::new("default");
+        for rule in ["on", "nopass", "allkeys", "allchannels", "allcommands"] {
+            user.apply(rule, &|_| true)
+                .expect("The default rules are valid");
+        }
+        user
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn is_nopass(&self) -> bool {
+        self.nopass
+    }
+
+    /// The SHA-256 of the passwords.
+    pub fn passwords(&self) -> &[String] {
+        &self.passwords
+    }
+
+    /// Tell if `password` is one of the passwords of the user, any password
+    /// is valid for a `nopass` user.
+    pub fn check_password(&self, password: &[u8]) -> bool {
+        self.nopass || self.passwords.contains(&hash_password(password))
+    }
+
+    pub fn root(&self) -> &Selector {
+        &self.root
+    }
+
+    pub fn selectors(&self) -> &[Selector] {
+        &self.selectors
+    }
+
+    /// Apply a rule, `is_known` tells if a command or a subcommand exists.
+    ///
+    /// A rule between parentheses adds a selector.
+    pub fn apply(
+        &mut self,
+        rule: &str,
+        is_known: &dyn Fn(&str) -> bool,
+    ) -> Result<(), AclError> {
+        self.apply_rule(rule, is_known)
+            .map_err(|error| AclError::Rule {
+                rule: rule.to_string(),
+                error,
+            })
+    }
+
+    fn apply_rule(
+        &mut self,
+        rule: &str,
+        is_known: &dyn Fn(&str) -> bool,
+    ) -> Result<(), RuleError> {
+        match rule {
+            "on" => self.enabled = true,
+            "off" => self.enabled = false,
+            "nopass" => {
+                self.nopass = true;
+                self.passwords.clear();
+            }
+            "resetpass" => {
+                self.nopass = false;
+                self.passwords.clear();
+            }
+            "clearselectors" => self.selectors.clear(),
+            "reset" => {
+                for rule in [
+                    "resetpass",
+                    "resetkeys",
+                    "resetchannels",
+                    "off",
+                    "clearselectors",
+                    "-@all",
+                ] {
+                    self.apply_rule(rule, is_known)?;
+                }
+            }
+            _ if rule.starts_with('>') => {
+                self.add_password(hash_password(&rule.as_bytes()[1..]));
+            }
+            _ if rule.starts_with('<') => {
+                self.remove_password(&hash_password(&rule.as_bytes()[1..]))?;
+            }
+            _ if rule.starts_with('#') => {
+                let hash = &rule[1..];
+                if hash.len() != 64
+                    || !hash
+                        .bytes()
+                        .all(|c| matches!(c, b'0'..=b'9' | b'a'..=b'f'))
+                {
+                    return Err(RuleError::PasswordHash);
+                }
+                self.add_password(hash.to_string());
+            }
+            _ if rule.starts_with('!') => self.remove_password(&rule[1..])?,
+            _ if rule.starts_with('(') && rule.ends_with(')') => {
+                let mut selector = Selector::default();
+                for rule in rule[1..rule.len() - 1].split_ascii_whitespace() {
+                    selector.apply(rule, is_known)?;
+                }
+                self.selectors.push(selector);
+            }
+            _ => self.root.apply(rule, is_known)?,
+        }
+        Ok(())
+    }
+
+    fn add_password(&mut self, hash: String) {
+        self.nopass = false;
+        if !self.passwords.contains(&hash) {
+            self.passwords.push(hash);
+        }
+    }
+
+    fn remove_password(&mut self, hash: &str) -> Result<(), RuleError> {
+        let count = self.passwords.len();
+        self.passwords.retain(|known| known != hash);
+        if self.passwords.len() == count {
+            return Err(RuleError::NoSuchPassword);
+        }
+        Ok(())
+    }
+
+    /// Can this user run every command, on every key and channel, which
+    /// allows to skip the checks.
+    pub fn is_unrestricted(&self) -> bool {
+        self.root.is_unrestricted()
+    }
+
+    /// Check the user can run the command described by `request`.
+    pub fn check(&self, request: &Request<'_>) -> Result<(), Denied> {
+        let mut denied = match self.root.check(request) {
+            Ok(()) => return Ok(()),
+            Err(denied) => denied,
+        };
+        for selector in &self.selectors {
+            match selector.check(request) {
+                Ok(()) => return Ok(()),
+                Err(other) => denied = denied.max(other),
+            }
+        }
+        Err(denied)
+    }
+
+    /// The rules of the user, in the format of `ACL LIST`.
+    pub fn describe(&self) -> String {
+        let mut rules = vec![
+            format!("user {}", self.name),
+            if self.enabled { "on" } else { "off" }.to_string(),
+        ];
+        if self.nopass {
+            rules.push("nopass".to_string());
+        }
+        for hash in &self.passwords {
+            rules.push(format!("#{hash}"));
+        }
+        rules.push(self.root.describe());
+        for selector in &self.selectors {
+            rules.push(format!("({})", selector.describe()));
+        }
+        rules.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        hash_password, AclError, Denied, KeyAccess, Request, RuleError, User,
+    };
+    use crate::domain::acl::AclCategory;
+
+    fn user(rules: &[&str]) -> User {
+        let mut user = User::new("alice");
+        for rule in rules {
+            user.apply(rule, &|name| name == "get" || name == "client|id")
+                .unwrap();
+        }
+        user
+    }
+
+    fn request<'a>(
+        name: &'a str,
+        categories: &'a [AclCategory],
+        keys: &'a [(&'a [u8], KeyAccess)],
+    ) -> Request<'a> {
+        Request {
+            name,
+            categories,
+            keys,
+            channels: &[],
+        }
+    }
+
+    #[test]
+    fn describe_users() {
+        assert_eq!(
+            User::default_user().describe(),
+            "user default on nopass ~* &* +@all"
+        );
+        assert!(User::default_user().is_unrestricted());
+        assert_eq!(
+            User::new("alice").describe(),
+            "user alice off resetchannels -@all"
+        );
+
+        let user = user(&[
+            "on",
+            ">secret",
+            "~cache:*",
+            "%R~read:*",
+            "&news",
+            "+@all",
+            "-get",
+            "(~other +get)",
+        ]);
+        assert_eq!(
+            user.describe(),
+            format!(
+                "user alice on #{} ~cache:* %R~read:* resetchannels &news \
+                 +@all -get (~other resetchannels -@all +get)",
+                hash_password(b"secret")
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_rules() {
+        let mut user = User::new("alice");
+        assert_eq!(
+            user.apply("+unknown", &|_| false),
+            Err(AclError::Rule {
+                rule: "+unknown".into(),
+                error: RuleError::UnknownCommand
+            })
+        );
+        assert!(user.apply("+@nope", &|_| true).is_err());
+        assert!(user.apply("%X~key", &|_| true).is_err());
+        assert!(user.apply("#abc", &|_| true).is_err());
+        assert!(user.apply("<missing", &|_| true).is_err());
+        assert!(user.apply("what", &|_| true).is_err());
+    }
+
+    #[test]
+    fn check_commands() {
+        let user = user(&["+@read", "-get", "+client|id"]);
+        let read = [AclCategory::Read];
+        assert_eq!(user.check(&request("mget", &read, &[])), Ok(()));
+        assert_eq!(
+            user.check(&request("get", &read, &[])),
+            Err(Denied::Command)
+        );
+        assert_eq!(user.check(&request("client|id", &[], &[])), Ok(()));
+        assert_eq!(
+            user.check(&request("client|list", &[], &[])),
+            Err(Denied::Command)
+        );
+    }
+
+    #[test]
+    fn check_keys_and_selectors() {
+        let user = user(&["+@all", "%R~read:*", "~rw:*", "(+get ~other:*)"]);
+        let keys = [(&b"read:1"[..], KeyAccess::Read)];
+        assert_eq!(user.check(&request("set", &[], &keys)), Ok(()));
+        let keys = [(&b"read:1"[..], KeyAccess::Write)];
+        assert_eq!(
+            user.check(&request("set", &[], &keys)),
+            Err(Denied::Key(b"read:1".to_vec()))
+        );
+        let keys = [(&b"rw:1"[..], KeyAccess::ReadWrite)];
+        assert_eq!(user.check(&request("set", &[], &keys)), Ok(()));
+
+        let keys = [(&b"other:1"[..], KeyAccess::Read)];
+        assert_eq!(user.check(&request("get", &[], &keys)), Ok(()));
+        assert_eq!(
+            user.check(&request("set", &[], &keys)),
+            Err(Denied::Key(b"other:1".to_vec()))
+        );
+    }
+
+    #[test]
+    fn check_channels() {
+        let user = user(&["+@all", "&news.*"]);
+        let check = |channel: &[u8], is_pattern| {
+            user.check(&Request {
+                name: "subscribe",
+                categories: &[],
+                keys: &[],
+                channels: &[(channel, is_pattern)],
+            })
+        };
+        assert_eq!(check(b"news.sport", false), Ok(()));
+        assert_eq!(check(b"news.*", true), Ok(()));
+        assert_eq!(
+            check(b"news.s*", true),
+            Err(Denied::Channel(b"news.s*".to_vec()))
+        );
+        assert_eq!(
+            check(b"other", false),
+            Err(Denied::Channel(b"other".to_vec()))
+        );
+    }
+}
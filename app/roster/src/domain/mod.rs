@@ -1,3 +1,8 @@
+pub mod acl;
 pub mod cluster;
 pub mod dialer;
+pub mod notification;
+pub mod pubsub;
+pub mod scripting;
 pub mod storage;
+pub mod tracking;
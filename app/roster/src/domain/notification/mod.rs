@@ -0,0 +1,277 @@
+//! Keyspace notifications: events happening on keys are published through
+//! Pub/Sub on the `__keyspace@0__:<key>` and `__keyevent@0__:<event>`
+//! channels.
+//!
+//! Which events are published is configured with the `notify-keyspace-events`
+//! flags, each character enables a class of events:
+//!
+//! ```text
+//! K     Keyspace events, published with __keyspace@<db>__ prefix.
+//! E     Keyevent events, published with __keyevent@<db>__ prefix.
+//! g     Generic commands (non-type specific) like DEL, EXPIRE, RENAME, ...
+//! $     String commands
+//! l     List commands
+//! s     Set commands
+//! h     Hash commands
+//! z     Sorted set commands
+//! t     Stream commands
+//! d     Module key type events
+//! x     Expired events (events generated every time a key expires)
+//! e     Evicted events (events generated when a key is evicted for maxmemory)
+//! m     Key miss events (events generated when a key that doesn't exist is
+//!       accessed)
+//! n     New key events (Note: not included in the 'A' class)
+//! A     Alias for "g$lshztxed", so that the "AKE" string means all the
+//!       events except "m" and "n".
+//! ```
+
+use std::fmt::Display;
+use std::ops::BitOr;
+use std::str::FromStr;
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use super::pubsub::PubSub;
+use super::tracking::TrackingTable;
+
+/// The classes of keyspace events enabled, parsed from the
+/// `notify-keyspace-events` flags.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KeyspaceEvents(u16);
+
+impl KeyspaceEvents {
+    pub const KEYSPACE: KeyspaceEvents = KeyspaceEvents(1 << 0);
+    pub const KEYEVENT: KeyspaceEvents = KeyspaceEvents(1 << 1);
+    pub const GENERIC: KeyspaceEvents = KeyspaceEvents(1 << 2);
+    pub const STRING: KeyspaceEvents = KeyspaceEvents(1 << 3);
+    pub const LIST: KeyspaceEvents = KeyspaceEvents(1 << 4);
+    pub const SET: KeyspaceEvents = KeyspaceEvents(1 << 5);
+    pub const HASH: KeyspaceEvents = KeyspaceEvents(1 << 6);
+    pub const ZSET: KeyspaceEvents = KeyspaceEvents(1 << 7);
+    pub const EXPIRED: KeyspaceEvents = KeyspaceEvents(1 << 8);
+    pub const EVICTED: KeyspaceEvents = KeyspaceEvents(1 << 9);
+    pub const STREAM: KeyspaceEvents = KeyspaceEvents(1 << 10);
+    pub const KEY_MISS: KeyspaceEvents = KeyspaceEvents(1 << 11);
+    pub const MODULE: KeyspaceEvents = KeyspaceEvents(1 << 12);
+    pub const NEW: KeyspaceEvents = KeyspaceEvents(1 << 13);
+    /// Every class of events except [Self::KEY_MISS] & [Self::NEW].
+    pub const ALL: KeyspaceEvents = KeyspaceEvents(
+        Self::GENERIC.0
+            | Self::STRING.0
+            | Self::LIST.0
+            | Self::SET.0
+            | Self::HASH.0
+            | Self::ZSET.0
+            | Self::EXPIRED.0
+            | Self::EVICTED.0
+            | Self::STREAM.0
+            | Self::MODULE.0,
+    );
+
+    /// The flag character of every class, in the order they are displayed.
+    const CLASSES: [(char, KeyspaceEvents); 10] = [
+        ('g', Self::GENERIC),
+        ('$', Self::STRING),
+        ('l', Self::LIST),
+        ('s', Self::SET),
+        ('h', Self::HASH),
+        ('z', Self::ZSET),
+        ('x', Self::EXPIRED),
+        ('e', Self::EVICTED),
+        ('t', Self::STREAM),
+        ('d', Self::MODULE),
+    ];
+
+    /// Check if every class of `other` is enabled.
+    pub fn contains(self, other: KeyspaceEvents) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Check if at least one class of `other` is enabled.
+    pub fn intersects(self, other: KeyspaceEvents) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl BitOr for KeyspaceEvents {
+    type Output = KeyspaceEvents;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        KeyspaceEvents(self.0 | rhs.0)
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+#[error("Invalid event class character '{0}'")]
+pub struct InvalidKeyspaceEvents(char);
+
+impl FromStr for KeyspaceEvents {
+    type Err = InvalidKeyspaceEvents;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.chars().try_fold(KeyspaceEvents::default(), |events, c| {
+            let class = match c {
+                'A' => KeyspaceEvents::ALL,
+                'K' => KeyspaceEvents::KEYSPACE,
+                'E' => KeyspaceEvents::KEYEVENT,
+                'm' => KeyspaceEvents::KEY_MISS,
+                'n' => KeyspaceEvents::NEW,
+                c => KeyspaceEvents::CLASSES
+                    .iter()
+                    .find(|(flag, _)| *flag == c)
+                    .map(|(_, class)| *class)
+                    .ok_or(InvalidKeyspaceEvents(c))?,
+            };
+            Ok(events | class)
+        })
+    }
+}
+
+impl Display for KeyspaceEvents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.contains(KeyspaceEvents::ALL) {
+            write!(f, "A")?;
+        } else {
+            for (flag, class) in KeyspaceEvents::CLASSES {
+                if self.contains(class) {
+                    write!(f, "{flag}")?;
+                }
+            }
+        }
+
+        for (flag, class) in [
+            ('K', KeyspaceEvents::KEYSPACE),
+            ('E', KeyspaceEvents::KEYEVENT),
+            ('m', KeyspaceEvents::KEY_MISS),
+            ('n', KeyspaceEvents::NEW),
+        ] {
+            if self.contains(class) {
+                write!(f, "{flag}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Publish the keyspace events enabled by the configuration and invalidate
+/// the keys tracked by the connections using client side caching.
+///
+/// **Should be cheap to clone**
+#[derive(Debug, Clone, Default)]
+pub struct Notifier {
+    events: KeyspaceEvents,
+    pubsub: PubSub,
+    tracking: TrackingTable,
+}
+
+impl Notifier {
+    pub fn new(
+        events: KeyspaceEvents,
+        pubsub: PubSub,
+        tracking: TrackingTable,
+    ) -> Self {
+        Self {
+            events,
+            pubsub,
+            tracking,
+        }
+    }
+
+    /// Invalidate `key` for the connections tracking it, used when a key is
+    /// modified without any connection involved, like an expiration.
+    pub async fn invalidate(&self, key: &[u8]) {
+        self.tracking.invalidate(key, None).await;
+    }
+
+    /// Notify that `event` of the class `class` happened on `key`.
+    pub async fn notify(
+        &self,
+        class: KeyspaceEvents,
+        event: &'static str,
+        key: &[u8],
+    ) {
+        if !self.events.intersects(class) {
+            return;
+        }
+
+        if self.events.contains(KeyspaceEvents::KEYSPACE) {
+            let channel = channel(b"__keyspace@0__:", key);
+            self.pubsub
+                .publish(channel, Bytes::from_static(event.as_bytes()))
+                .await;
+        }
+
+        if self.events.contains(KeyspaceEvents::KEYEVENT) {
+            let channel = channel(b"__keyevent@0__:", event.as_bytes());
+            self.pubsub
+                .publish(channel, Bytes::copy_from_slice(key))
+                .await;
+        }
+    }
+}
+
+fn channel(prefix: &[u8], suffix: &[u8]) -> Bytes {
+    let mut channel = BytesMut::with_capacity(prefix.len() + suffix.len());
+    channel.put_slice(prefix);
+    channel.put_slice(suffix);
+    channel.freeze()
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::channel::mpsc::unbounded;
+
+    use super::{InvalidKeyspaceEvents, KeyspaceEvents, Notifier};
+    use crate::domain::pubsub::{Message, PubSub};
+
+    #[test]
+    fn parse_flags() {
+        let events: KeyspaceEvents = "KEA".parse().unwrap();
+        assert_eq!(events.to_string(), "AKE");
+
+        let events: KeyspaceEvents = "Ex$".parse().unwrap();
+        assert!(events.contains(KeyspaceEvents::EXPIRED));
+        assert!(!events.contains(KeyspaceEvents::KEYSPACE));
+        assert_eq!(events.to_string(), "$xE");
+
+        let events: KeyspaceEvents = "".parse().unwrap();
+        assert_eq!(events, KeyspaceEvents::default());
+
+        assert_eq!(
+            "KEw".parse::<KeyspaceEvents>(),
+            Err(InvalidKeyspaceEvents('w'))
+        );
+    }
+
+    #[monoio::test]
+    async fn notify_enabled_classes() {
+        let pubsub = PubSub::default();
+        let (tx, mut rx) = unbounded();
+        pubsub
+            .patterns()
+            .subscribe(Bytes::from_static(b"__key*__:*"), 1, tx)
+            .await;
+
+        let notifier = Notifier::new(
+            "E$".parse().unwrap(),
+            pubsub.clone(),
+            Default::default(),
+        );
+        notifier.notify(KeyspaceEvents::STRING, "set", b"key").await;
+        notifier
+            .notify(KeyspaceEvents::EXPIRED, "expired", b"key")
+            .await;
+
+        assert_eq!(
+            rx.try_next().unwrap(),
+            Some(Message::PMessage {
+                pattern: Bytes::from_static(b"__key*__:*"),
+                channel: Bytes::from_static(b"__keyevent@0__:set"),
+                payload: Bytes::from_static(b"key"),
+            })
+        );
+        assert!(rx.try_next().is_err());
+    }
+}
@@ -0,0 +1,224 @@
+//! Pub/Sub primitive: connections subscribe to channels or patterns and
+//! receive the messages published on them, whatever the thread they are
+//! running on.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::channel::mpsc::UnboundedSender;
+use scc::HashMap;
+
+use crate::infrastructure::glob::glob_match;
+
+/// A message delivered to a subscribed connection, variants are named after
+/// the kind of push sent to the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::enum_variant_names)]
+pub enum Message {
+    /// Published on a channel the connection is subscribed to.
+    Message { channel: Bytes, payload: Bytes },
+    /// Published on a channel matching a pattern the connection is subscribed
+    /// to.
+    PMessage {
+        pattern: Bytes,
+        channel: Bytes,
+        payload: Bytes,
+    },
+    /// Published on a shard channel the connection is subscribed to.
+    SMessage { channel: Bytes, payload: Bytes },
+    /// Keys tracked by the connection were modified, see
+    /// [crate::domain::tracking].
+    Invalidate { keys: Vec<Bytes> },
+    /// The connection receiving the invalidation messages is gone.
+    TrackingRedirBroken { redirect: u64 },
+}
+
+/// The sending side used to deliver [Message] to a connection.
+pub type Subscriber = UnboundedSender<Message>;
+
+/// Subscribers of a set of channels (or patterns), indexed by the connection
+/// ID.
+///
+/// **Should be cheap to clone**
+#[derive(Debug, Clone, Default)]
+pub struct ChannelRegistry {
+    channels: Arc<HashMap<Bytes, Vec<(u64, Subscriber)>>>,
+}
+
+impl ChannelRegistry {
+    /// Subscribe the connection `id` to `channel`.
+    pub async fn subscribe(&self, channel: Bytes, id: u64, sub: Subscriber) {
+        let mut entry = self.channels.entry_async(channel).await.or_default();
+        let subscribers = entry.get_mut();
+        if !subscribers.iter().any(|(sub_id, _)| *sub_id == id) {
+            subscribers.push((id, sub));
+        }
+    }
+
+    /// Unsubscribe the connection `id` from `channel`.
+    pub async fn unsubscribe(&self, channel: &Bytes, id: u64) {
+        if let scc::hash_map::Entry::Occupied(mut entry) =
+            self.channels.entry_async(channel.clone()).await
+        {
+            entry.get_mut().retain(|(sub_id, _)| *sub_id != id);
+            if entry.get().is_empty() {
+                let _ = entry.remove();
+            }
+        }
+    }
+
+    /// Send a message to every subscriber of `channel`, return the number of
+    /// connections which received it.
+    pub async fn publish(&self, channel: &Bytes, msg: Message) -> usize {
+        self.channels
+            .read_async(channel, |_, subscribers| send_all(subscribers, &msg))
+            .await
+            .unwrap_or(0)
+    }
+
+    /// Number of subscribers of `channel`.
+    pub async fn subscribers(&self, channel: &Bytes) -> usize {
+        self.channels
+            .read_async(channel, |_, subscribers| subscribers.len())
+            .await
+            .unwrap_or(0)
+    }
+
+    /// Every channel with at least one subscriber, optionally filtered by a
+    /// glob-style pattern.
+    pub async fn channels(&self, pattern: Option<&[u8]>) -> Vec<Bytes> {
+        let mut result = Vec::new();
+        self.channels
+            .scan_async(|channel, _| {
+                let matched = pattern
+                    .map(|pattern| glob_match(pattern, channel, false))
+                    .unwrap_or(true);
+                if matched {
+                    result.push(channel.clone());
+                }
+            })
+            .await;
+        result
+    }
+
+    /// Number of channels with at least one subscriber.
+    pub fn count(&self) -> usize {
+        self.channels.len()
+    }
+}
+
+/// Send `msg` to every subscriber, the subscribers which are gone are not
+/// counted.
+fn send_all(subscribers: &[(u64, Subscriber)], msg: &Message) -> usize {
+    subscribers
+        .iter()
+        .filter(|(_, sub)| sub.unbounded_send(msg.clone()).is_ok())
+        .count()
+}
+
+/// The Pub/Sub registry shared across every thread.
+///
+/// **Should be cheap to clone**
+#[derive(Debug, Clone, Default)]
+pub struct PubSub {
+    channels: ChannelRegistry,
+    patterns: ChannelRegistry,
+}
+
+impl PubSub {
+    /// Channels subscribed with `SUBSCRIBE`.
+    pub fn channels(&self) -> &ChannelRegistry {
+        &self.channels
+    }
+
+    /// Patterns subscribed with `PSUBSCRIBE`.
+    pub fn patterns(&self) -> &ChannelRegistry {
+        &self.patterns
+    }
+
+    /// Publish `payload` on `channel`, the message is delivered to the
+    /// subscribers of the channel and of every matching pattern.
+    ///
+    /// Return the number of deliveries.
+    pub async fn publish(&self, channel: Bytes, payload: Bytes) -> usize {
+        let mut receivers = self
+            .channels
+            .publish(
+                &channel,
+                Message::Message {
+                    channel: channel.clone(),
+                    payload: payload.clone(),
+                },
+            )
+            .await;
+
+        self.patterns
+            .channels
+            .scan_async(|pattern, subscribers| {
+                if glob_match(pattern, &channel, false) {
+                    receivers += send_all(
+                        subscribers,
+                        &Message::PMessage {
+                            pattern: pattern.clone(),
+                            channel: channel.clone(),
+                            payload: payload.clone(),
+                        },
+                    );
+                }
+            })
+            .await;
+
+        receivers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::channel::mpsc::unbounded;
+
+    use super::{Message, PubSub};
+
+    #[monoio::test]
+    async fn publish_to_channels_and_patterns() {
+        let pubsub = PubSub::default();
+        let (tx, mut rx) = unbounded();
+
+        let news = Bytes::from_static(b"news.tech");
+        pubsub
+            .channels()
+            .subscribe(news.clone(), 1, tx.clone())
+            .await;
+        pubsub
+            .patterns()
+            .subscribe(Bytes::from_static(b"news.*"), 1, tx)
+            .await;
+
+        let receivers = pubsub
+            .publish(news.clone(), Bytes::from_static(b"hi"))
+            .await;
+        assert_eq!(receivers, 2);
+        assert_eq!(
+            rx.try_next().unwrap(),
+            Some(Message::Message {
+                channel: news.clone(),
+                payload: Bytes::from_static(b"hi"),
+            })
+        );
+        assert_eq!(
+            rx.try_next().unwrap(),
+            Some(Message::PMessage {
+                pattern: Bytes::from_static(b"news.*"),
+                channel: news.clone(),
+                payload: Bytes::from_static(b"hi"),
+            })
+        );
+
+        pubsub.channels().unsubscribe(&news, 1).await;
+        assert_eq!(pubsub.channels().count(), 0);
+        let receivers = pubsub
+            .publish(Bytes::from_static(b"other"), Bytes::from_static(b"hi"))
+            .await;
+        assert_eq!(receivers, 0);
+    }
+}
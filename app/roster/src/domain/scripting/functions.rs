@@ -0,0 +1,408 @@
+//! The libraries of functions loaded with `FUNCTION LOAD`.
+//!
+//! A library is identified by the name given in its shebang and registers
+//! functions which can be called with `FCALL`. Only the metadata of the
+//! libraries is kept here, each interpreter runs the code of a library the
+//! first time one of its functions is called.
+//!
+//! When the server is configured with a functions file, the libraries are
+//! saved to it after every change, as a `FUNCTION DUMP` payload, and loaded
+//! from it at startup.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use super::{sha1hex, ScriptFlags};
+use crate::infrastructure::fs::write_atomic;
+
+/// The version of the payload produced by `FUNCTION DUMP`.
+const DUMP_VERSION: u16 = 1;
+
+/// The length of the checksum ending the payload produced by `FUNCTION DUMP`.
+const CHECKSUM_LEN: usize = 40;
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum FunctionError {
+    #[error("Library '{0}' already exists")]
+    LibraryExists(String),
+    #[error("Function {0} already exists")]
+    FunctionExists(String),
+    #[error("Library not found")]
+    LibraryNotFound,
+    #[error("payload version or checksum are wrong")]
+    BadPayload,
+    #[error("Error saving the functions to '{path}': {message}")]
+    Save { path: String, message: String },
+}
+
+/// A function registered by a library with `redis.register_function`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionInfo {
+    pub name: String,
+    pub description: Option<String>,
+    pub flags: ScriptFlags,
+}
+
+#[derive(Debug, Clone)]
+pub struct Library {
+    /// Identify this version of the library, a library replaced by another one
+    /// with the same name gets a new id.
+    id: u64,
+    pub name: String,
+    pub code: Bytes,
+    pub functions: Vec<FunctionInfo>,
+}
+
+impl Library {
+    pub fn new(
+        name: String,
+        code: Bytes,
+        functions: Vec<FunctionInfo>,
+    ) -> Self {
+        Self {
+            id: 0,
+            name,
+            code,
+            functions,
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// The function `name` registered by this library.
+    pub fn function(&self, name: &str) -> Option<&FunctionInfo> {
+        self.functions.iter().find(|function| function.name == name)
+    }
+}
+
+/// How `FUNCTION RESTORE` handles the libraries already loaded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RestorePolicy {
+    /// Fail when a restored library already exists.
+    #[default]
+    Append,
+    /// Replace the libraries with the same name.
+    Replace,
+    /// Delete every library before restoring.
+    Flush,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Registry {
+    libraries: BTreeMap<String, Arc<Library>>,
+    /// The library registering each function.
+    functions: HashMap<String, Arc<Library>>,
+}
+
+impl Registry {
+    fn insert(
+        &mut self,
+        library: Library,
+        replace: bool,
+    ) -> Result<(), FunctionError> {
+        if self.libraries.contains_key(&library.name) && !replace {
+            return Err(FunctionError::LibraryExists(library.name));
+        }
+        for function in &library.functions {
+            if let Some(other) = self.functions.get(&function.name) {
+                if other.name != library.name {
+                    return Err(FunctionError::FunctionExists(
+                        function.name.clone(),
+                    ));
+                }
+            }
+        }
+
+        self.remove(&library.name);
+        let library = Arc::new(library);
+        for function in &library.functions {
+            self.functions
+                .insert(function.name.clone(), library.clone());
+        }
+        self.libraries.insert(library.name.clone(), library);
+        Ok(())
+    }
+
+    fn remove(&mut self, name: &str) -> Option<Arc<Library>> {
+        let library = self.libraries.remove(name)?;
+        for function in &library.functions {
+            self.functions.remove(&function.name);
+        }
+        Some(library)
+    }
+
+    /// See [Functions::dump].
+    fn dump(&self) -> Bytes {
+        let mut payload = BytesMut::new();
+        payload.put_u16_le(DUMP_VERSION);
+        for library in self.libraries.values() {
+            payload.put_u32_le(library.code.len() as u32);
+            payload.put_slice(&library.code);
+        }
+        let checksum = sha1hex(&payload);
+        payload.put_slice(checksum.as_bytes());
+        payload.freeze()
+    }
+}
+
+/// The libraries of functions, shared across every thread.
+///
+/// **Should be cheap to clone**
+#[derive(Debug, Clone, Default)]
+pub struct Functions {
+    registry: Arc<RwLock<Registry>>,
+    next_id: Arc<AtomicU64>,
+    /// The file the libraries are saved to after every change.
+    file: Option<Arc<PathBuf>>,
+}
+
+impl Functions {
+    /// Create the libraries, saved to `file` when it's set.
+    pub fn new(file: Option<PathBuf>) -> Self {
+        Self {
+            file: file.map(Arc::new),
+            ..Default::default()
+        }
+    }
+
+    fn assign_id(&self, library: &mut Library) {
+        library.id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+    }
+
+    /// Replace `registry` by `updated` once it's saved, so the libraries are
+    /// unchanged when the functions file can't be written.
+    fn commit(
+        &self,
+        registry: &mut Registry,
+        updated: Registry,
+    ) -> Result<(), FunctionError> {
+        if let Some(file) = &self.file {
+            write_atomic(file, &updated.dump()).map_err(|err| {
+                FunctionError::Save {
+                    path: file.display().to_string(),
+                    message: err.to_string(),
+                }
+            })?;
+        }
+        *registry = updated;
+        Ok(())
+    }
+
+    /// Add a library, an existing library with the same name is only replaced
+    /// when `replace` is set.
+    pub fn load(
+        &self,
+        mut library: Library,
+        replace: bool,
+    ) -> Result<(), FunctionError> {
+        self.assign_id(&mut library);
+        let mut registry = self.registry.write().unwrap();
+        let mut updated = registry.clone();
+        updated.insert(library, replace)?;
+        self.commit(&mut registry, updated)
+    }
+
+    /// Add the libraries of a `FUNCTION DUMP` payload, nothing is changed
+    /// when one of them can't be added.
+    pub fn restore(
+        &self,
+        libraries: Vec<Library>,
+        policy: RestorePolicy,
+    ) -> Result<(), FunctionError> {
+        let mut registry = self.registry.write().unwrap();
+        let mut restored = match policy {
+            RestorePolicy::Flush => Registry::default(),
+            RestorePolicy::Append | RestorePolicy::Replace => registry.clone(),
+        };
+        for mut library in libraries {
+            self.assign_id(&mut library);
+            restored.insert(library, policy == RestorePolicy::Replace)?;
+        }
+        self.commit(&mut registry, restored)
+    }
+
+    pub fn delete(&self, name: &str) -> Result<(), FunctionError> {
+        let mut registry = self.registry.write().unwrap();
+        let mut updated = registry.clone();
+        updated.remove(name).ok_or(FunctionError::LibraryNotFound)?;
+        self.commit(&mut registry, updated)
+    }
+
+    /// Delete every library.
+    pub fn flush(&self) -> Result<(), FunctionError> {
+        let mut registry = self.registry.write().unwrap();
+        self.commit(&mut registry, Registry::default())
+    }
+
+    /// The library registering the function `name`.
+    pub fn library_of(&self, name: &str) -> Option<Arc<Library>> {
+        self.registry.read().unwrap().functions.get(name).cloned()
+    }
+
+    /// Every library, ordered by name.
+    pub fn libraries(&self) -> Vec<Arc<Library>> {
+        self.registry
+            .read()
+            .unwrap()
+            .libraries
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// The number of libraries and the number of functions.
+    pub fn count(&self) -> (usize, usize) {
+        let registry = self.registry.read().unwrap();
+        (registry.libraries.len(), registry.functions.len())
+    }
+
+    /// Serialize the code of every library, the libraries are loaded again
+    /// from their code by `FUNCTION RESTORE`.
+    ///
+    /// The payload is the version, the length and the code of each library,
+    /// then the SHA1 of everything before it.
+    pub fn dump(&self) -> Bytes {
+        self.registry.read().unwrap().dump()
+    }
+}
+
+/// Read the code of the libraries of a payload produced by [Functions::dump].
+pub fn parse_dump(payload: &[u8]) -> Result<Vec<Bytes>, FunctionError> {
+    let Some(split) = payload.len().checked_sub(CHECKSUM_LEN) else {
+        return Err(FunctionError::BadPayload);
+    };
+    let (mut content, checksum) = payload.split_at(split);
+    if sha1hex(content).as_bytes() != checksum {
+        return Err(FunctionError::BadPayload);
+    }
+
+    let Some(version) = take(&mut content, 2) else {
+        return Err(FunctionError::BadPayload);
+    };
+    if u16::from_le_bytes([version[0], version[1]]) != DUMP_VERSION {
+        return Err(FunctionError::BadPayload);
+    }
+
+    let mut codes = Vec::new();
+    while !content.is_empty() {
+        let len = take(&mut content, 4)
+            .map(|len| u32::from_le_bytes([len[0], len[1], len[2], len[3]]))
+            .ok_or(FunctionError::BadPayload)?;
+        let code = take(&mut content, len as usize)
+            .ok_or(FunctionError::BadPayload)?;
+        codes.push(Bytes::copy_from_slice(code));
+    }
+    Ok(codes)
+}
+
+fn take<'a>(content: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+    if content.len() < len {
+        return None;
+    }
+    let (taken, rest) = content.split_at(len);
+    *content = rest;
+    Some(taken)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::{
+        parse_dump, FunctionError, FunctionInfo, Functions, Library,
+        RestorePolicy,
+    };
+    use crate::domain::scripting::ScriptFlags;
+
+    fn library(name: &str, functions: &[&str]) -> Library {
+        Library::new(
+            name.to_string(),
+            Bytes::from(format!("#!lua name={name}")),
+            functions
+                .iter()
+                .map(|name| FunctionInfo {
+                    name: name.to_string(),
+                    description: None,
+                    flags: ScriptFlags::default(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn load_libraries() {
+        let functions = Functions::default();
+        functions.load(library("lib", &["a", "b"]), false).unwrap();
+
+        assert_eq!(
+            functions.load(library("lib", &["c"]), false),
+            Err(FunctionError::LibraryExists("lib".into()))
+        );
+        assert_eq!(
+            functions.load(library("other", &["a"]), false),
+            Err(FunctionError::FunctionExists("a".into()))
+        );
+
+        let first = functions.library_of("a").unwrap().id();
+        functions.load(library("lib", &["c"]), true).unwrap();
+        assert!(functions.library_of("a").is_none());
+        assert_ne!(functions.library_of("c").unwrap().id(), first);
+        assert_eq!(functions.count(), (1, 1));
+
+        assert_eq!(functions.delete("lib"), Ok(()));
+        assert_eq!(
+            functions.delete("lib"),
+            Err(FunctionError::LibraryNotFound)
+        );
+    }
+
+    #[test]
+    fn dump_and_restore() {
+        let functions = Functions::default();
+        functions.load(library("one", &["a"]), false).unwrap();
+        functions.load(library("two", &["b"]), false).unwrap();
+
+        let payload = functions.dump();
+        let codes = parse_dump(&payload).unwrap();
+        assert_eq!(
+            codes,
+            [Bytes::from("#!lua name=one"), Bytes::from("#!lua name=two")]
+        );
+
+        let mut corrupted = payload.to_vec();
+        corrupted[4] ^= 1;
+        assert_eq!(parse_dump(&corrupted), Err(FunctionError::BadPayload));
+
+        assert_eq!(
+            functions.restore(
+                vec![library("three", &["c"]), library("one", &["a"])],
+                RestorePolicy::Append
+            ),
+            Err(FunctionError::LibraryExists("one".into()))
+        );
+        assert!(functions.library_of("c").is_none());
+
+        functions
+            .restore(vec![library("three", &["c"])], RestorePolicy::Flush)
+            .unwrap();
+        assert_eq!(functions.count(), (1, 1));
+    }
+
+    #[test]
+    fn unchanged_when_not_saved() {
+        let functions =
+            Functions::new(Some("/nonexistent/roster/functions".into()));
+
+        assert!(matches!(
+            functions.load(library("lib", &["a"]), false),
+            Err(FunctionError::Save { .. })
+        ));
+        assert_eq!(functions.count(), (0, 0));
+    }
+}
@@ -0,0 +1,376 @@
+//! Scripting primitives which don't depend on the engine running the scripts:
+//! the caches of the scripts and of the WebAssembly modules loaded, identified
+//! by the SHA1 of their content, the libraries of functions, the shebang
+//! describing a script and the state of the script currently running.
+//!
+//! Only one script can run at a time as a script holds an exclusive access on
+//! the storage, the state is shared by every thread so a script can be killed
+//! from another connection.
+
+use std::ops::BitOr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use bytes::Bytes;
+use scc::HashMap;
+
+mod functions;
+pub use functions::{
+    parse_dump, FunctionError, FunctionInfo, Functions, Library, RestorePolicy,
+};
+
+/// The SHA1 digest of `body` as a lowercase hexadecimal string, which
+/// identifies a script.
+pub fn sha1hex(body: &[u8]) -> String {
+    sha1_smol::Sha1::from(body).digest().to_string()
+}
+
+/// Flags declared by a script in its shebang.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScriptFlags(u8);
+
+impl ScriptFlags {
+    /// The script doesn't modify the dataset.
+    pub const NO_WRITES: ScriptFlags = ScriptFlags(1 << 0);
+    /// The script can run when the memory is over the limit.
+    pub const ALLOW_OOM: ScriptFlags = ScriptFlags(1 << 1);
+    /// The script can run on a stale replica.
+    pub const ALLOW_STALE: ScriptFlags = ScriptFlags(1 << 2);
+    /// The script can't run in cluster mode.
+    pub const NO_CLUSTER: ScriptFlags = ScriptFlags(1 << 3);
+    /// The script can access keys from several slots.
+    pub const ALLOW_CROSS_SLOT_KEYS: ScriptFlags = ScriptFlags(1 << 4);
+
+    const NAMES: [(&'static str, ScriptFlags); 5] = [
+        ("no-writes", Self::NO_WRITES),
+        ("allow-oom", Self::ALLOW_OOM),
+        ("allow-stale", Self::ALLOW_STALE),
+        ("no-cluster", Self::NO_CLUSTER),
+        ("allow-cross-slot-keys", Self::ALLOW_CROSS_SLOT_KEYS),
+    ];
+
+    /// The names of the flags set.
+    pub fn names(self) -> impl Iterator<Item = &'static str> {
+        Self::NAMES
+            .into_iter()
+            .filter(move |(_, flag)| self.contains(*flag))
+            .map(|(name, _)| name)
+    }
+
+    /// Check if every flag of `other` is set.
+    pub fn contains(self, other: ScriptFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for ScriptFlags {
+    type Output = ScriptFlags;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        ScriptFlags(self.0 | rhs.0)
+    }
+}
+
+impl FromStr for ScriptFlags {
+    type Err = ShebangError;
+
+    /// Parse a comma separated list of flags.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',').filter(|name| !name.is_empty()).try_fold(
+            ScriptFlags::default(),
+            |flags, name| {
+                let flag = ScriptFlags::NAMES
+                    .iter()
+                    .find(|(known, _)| *known == name)
+                    .map(|(_, flag)| *flag)
+                    .ok_or_else(|| ShebangError::Flag(name.into()))?;
+                Ok(flags | flag)
+            },
+        )
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum ShebangError {
+    #[error("Unexpected engine in script shebang: {0}")]
+    Engine(String),
+    #[error("Unknown lua shebang option: {0}")]
+    Option(String),
+    #[error("Unexpected flag in script shebang: {0}")]
+    Flag(String),
+}
+
+/// The first line of a script starting with `#!`, like
+/// `#!lua name=mylib flags=no-writes`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Shebang {
+    /// The engine running the script, e.g. `lua`.
+    pub engine: String,
+    /// The name given with `name=`, used by libraries of functions.
+    pub name: Option<String>,
+    pub flags: ScriptFlags,
+}
+
+impl Shebang {
+    /// Parse the shebang of `body`, `None` when the script doesn't start with
+    /// one.
+    pub fn parse(body: &[u8]) -> Result<Option<Shebang>, ShebangError> {
+        let Some(rest) = body.strip_prefix(b"#!") else {
+            return Ok(None);
+        };
+        let line = rest.split(|c| *c == b'\n').next().unwrap_or_default();
+        let line = String::from_utf8_lossy(line);
+
+        let mut parts = line.split_ascii_whitespace();
+        let mut shebang = Shebang {
+            engine: parts.next().unwrap_or_default().to_string(),
+            ..Default::default()
+        };
+        if shebang.engine != "lua" {
+            return Err(ShebangError::Engine(shebang.engine));
+        }
+
+        for part in parts {
+            match part.split_once('=') {
+                Some(("name", name)) => shebang.name = Some(name.to_string()),
+                Some(("flags", flags)) => shebang.flags = flags.parse()?,
+                _ => return Err(ShebangError::Option(part.to_string())),
+            }
+        }
+
+        Ok(Some(shebang))
+    }
+}
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum KillError {
+    #[error("no script is running")]
+    NotBusy,
+    /// A script which modified the dataset must run until its end, or the
+    /// dataset could be left half modified.
+    #[error("the script already executed write commands")]
+    Unkillable,
+}
+
+/// The function called by the script currently running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunningFunction {
+    pub name: String,
+    /// The command calling the function, with its arguments.
+    pub command: Vec<Bytes>,
+}
+
+/// The state of the script currently running.
+#[derive(Debug, Default)]
+struct RunningScript {
+    started: Mutex<Option<Instant>>,
+    function: Mutex<Option<RunningFunction>>,
+    wrote: AtomicBool,
+    killed: AtomicBool,
+}
+
+/// The scripts loaded by the connections and the one currently running,
+/// shared across every thread.
+///
+/// **Should be cheap to clone**
+#[derive(Debug, Clone)]
+pub struct Scripts {
+    bodies: Arc<HashMap<String, Bytes>>,
+    /// The WebAssembly modules, by SHA1.
+    modules: Arc<HashMap<String, Bytes>>,
+    functions: Functions,
+    running: Arc<RunningScript>,
+    /// How long a script can run before the other connections are answered
+    /// with a `BUSY` error.
+    busy_threshold: Duration,
+}
+
+impl Default for Scripts {
+    fn default() -> Self {
+        Scripts::new(Duration::from_secs(5), Functions::default())
+    }
+}
+
+impl Scripts {
+    pub fn new(busy_threshold: Duration, functions: Functions) -> Self {
+        Self {
+            bodies: Default::default(),
+            modules: Default::default(),
+            functions,
+            running: Default::default(),
+            busy_threshold,
+        }
+    }
+
+    pub fn functions(&self) -> &Functions {
+        &self.functions
+    }
+
+    /// Add a script to the cache and return its SHA1.
+    pub async fn load(&self, body: Bytes) -> String {
+        let sha = sha1hex(&body);
+        let _ = self.bodies.insert_async(sha.clone(), body).await;
+        sha
+    }
+
+    /// The body of the script identified by `sha`.
+    pub async fn get(&self, sha: &str) -> Option<Bytes> {
+        self.bodies.read_async(sha, |_, body| body.clone()).await
+    }
+
+    /// Check if the script identified by `sha` is in the cache.
+    pub async fn contains(&self, sha: &str) -> bool {
+        self.bodies.contains_async(sha).await
+    }
+
+    /// Remove every script from the cache.
+    pub async fn flush(&self) {
+        self.bodies.clear_async().await;
+    }
+
+    /// Add a WebAssembly module to the cache and return its SHA1.
+    pub async fn load_module(&self, module: Bytes) -> String {
+        let sha = sha1hex(&module);
+        let _ = self.modules.insert_async(sha.clone(), module).await;
+        sha
+    }
+
+    /// The WebAssembly module identified by `sha`.
+    pub async fn module(&self, sha: &str) -> Option<Bytes> {
+        self.modules
+            .read_async(sha, |_, module| module.clone())
+            .await
+    }
+
+    /// Check if the WebAssembly module identified by `sha` is in the cache.
+    pub async fn contains_module(&self, sha: &str) -> bool {
+        self.modules.contains_async(sha).await
+    }
+
+    /// Remove every WebAssembly module from the cache.
+    pub async fn flush_modules(&self) {
+        self.modules.clear_async().await;
+    }
+
+    /// Mark a script as running until the returned guard is dropped,
+    /// `function` is set when the script is a function called with `FCALL`.
+    pub fn start(&self, function: Option<RunningFunction>) -> RunningGuard {
+        *self.running.started.lock().unwrap() = Some(Instant::now());
+        *self.running.function.lock().unwrap() = function;
+        self.running.wrote.store(false, Ordering::Relaxed);
+        self.running.killed.store(false, Ordering::Relaxed);
+        RunningGuard {
+            running: self.running.clone(),
+        }
+    }
+
+    /// The running script modified the dataset, it can't be killed anymore.
+    pub fn mark_write(&self) {
+        self.running.wrote.store(true, Ordering::Relaxed);
+    }
+
+    /// Check if the running script must stop.
+    pub fn is_killed(&self) -> bool {
+        self.running.killed.load(Ordering::Relaxed)
+    }
+
+    /// Ask the running script to stop.
+    pub fn kill(&self) -> Result<(), KillError> {
+        if self.running.started.lock().unwrap().is_none() {
+            return Err(KillError::NotBusy);
+        }
+        if self.running.wrote.load(Ordering::Relaxed) {
+            return Err(KillError::Unkillable);
+        }
+        self.running.killed.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// The function currently running and for how long it runs.
+    pub fn running_function(&self) -> Option<(RunningFunction, Duration)> {
+        let started = (*self.running.started.lock().unwrap())?;
+        let function = self.running.function.lock().unwrap().clone()?;
+        Some((function, started.elapsed()))
+    }
+
+    /// Check if a script is running for longer than the busy threshold.
+    pub fn is_busy(&self) -> bool {
+        self.running
+            .started
+            .lock()
+            .unwrap()
+            .is_some_and(|started| started.elapsed() >= self.busy_threshold)
+    }
+}
+
+/// Returned by [Scripts::start], the script is done once dropped.
+#[derive(Debug)]
+pub struct RunningGuard {
+    running: Arc<RunningScript>,
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        *self.running.started.lock().unwrap() = None;
+        *self.running.function.lock().unwrap() = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{sha1hex, Functions, KillError, ScriptFlags, Scripts, Shebang};
+
+    #[test]
+    fn script_sha() {
+        assert_eq!(
+            sha1hex(b"return 1"),
+            "e0e1f9fabfc9d4800c877a703b823ac0578ff8db"
+        );
+    }
+
+    #[test]
+    fn parse_shebang() {
+        assert_eq!(Shebang::parse(b"return 1"), Ok(None));
+
+        let shebang =
+            Shebang::parse(b"#!lua name=mylib flags=no-writes,allow-oom\n")
+                .unwrap()
+                .unwrap();
+        assert_eq!(shebang.engine, "lua");
+        assert_eq!(shebang.name.as_deref(), Some("mylib"));
+        assert_eq!(
+            shebang.flags,
+            ScriptFlags::NO_WRITES | ScriptFlags::ALLOW_OOM
+        );
+        assert_eq!(
+            shebang.flags.names().collect::<Vec<_>>(),
+            ["no-writes", "allow-oom"]
+        );
+
+        assert!(Shebang::parse(b"#!lua flags=nope\n").is_err());
+        assert!(Shebang::parse(b"#!lua other\n").is_err());
+        assert!(Shebang::parse(b"#!python\n").is_err());
+    }
+
+    #[test]
+    fn kill_running_script() {
+        let scripts = Scripts::new(Duration::ZERO, Functions::default());
+        assert_eq!(scripts.kill(), Err(KillError::NotBusy));
+        assert!(!scripts.is_busy());
+
+        let guard = scripts.start(None);
+        assert!(scripts.is_busy());
+        assert_eq!(scripts.kill(), Ok(()));
+        assert!(scripts.is_killed());
+        drop(guard);
+
+        let _guard = scripts.start(None);
+        assert!(!scripts.is_killed());
+        scripts.mark_write();
+        assert_eq!(scripts.kill(), Err(KillError::Unkillable));
+    }
+}
@@ -1,7 +1,7 @@
 //! Storage primitive which is used to interact with Keys
 
 use std::hash::BuildHasherDefault;
-use std::sync::atomic::AtomicU32;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
 
 use bytes::Bytes;
@@ -9,14 +9,20 @@ use bytestring::ByteString;
 use coarsetime::Instant;
 use rustc_hash::FxHasher;
 use scc::HashMap;
+use tokio::sync::{OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
 
 use super::dialer::Slot;
-use crate::infrastructure::hash::HASH_SLOT_MAX;
+use super::notification::{KeyspaceEvents, Notifier};
+use super::pubsub::ChannelRegistry;
+use crate::infrastructure::hash::{crc_hash, HASH_SLOT_MAX};
 
 #[derive(Debug)]
 pub struct StorageValue {
     pub expired: Option<Instant>,
     pub val: Vec<u8>,
+    /// Version of the key, changed each time the key is written. Used by
+    /// `WATCH` to know if a key was modified.
+    pub version: u64,
 }
 
 /// A [StorageSegment] is shared across multiple threads and owns a part of the
@@ -27,6 +33,18 @@ pub struct StorageSegment {
     #[allow(dead_code)]
     slot: Slot,
     count: Arc<AtomicU32>,
+    /// Source of the keys versions, a missing key has the version `0`.
+    version: Arc<AtomicU64>,
+    /// Commands are applied while holding a shared access, a transaction is
+    /// applied with an exclusive access so it can't be interleaved with other
+    /// commands. The lock is fair: a waiting transaction isn't starved by the
+    /// commands coming after it.
+    exec_lock: Arc<RwLock<()>>,
+    /// Shard channels are owned by the segment handling their hash slot, like
+    /// keys.
+    shard_channels: ChannelRegistry,
+    /// Keyspace notifications emitted when keys are modified.
+    notifier: Notifier,
 }
 
 #[derive(Default)]
@@ -35,8 +53,9 @@ pub struct SetOptions {
 }
 
 impl StorageSegment {
-    /// Create a new [StorageSegment] by specifying the hash slot it handles.
-    pub fn new(slot: Slot) -> Self {
+    /// Create a new [StorageSegment] by specifying the hash slot it handles and
+    /// where the keyspace notifications are sent.
+    pub fn new(slot: Slot, notifier: Notifier) -> Self {
         let h = HashMap::with_capacity_and_hasher(
             2usize.pow(20),
             Default::default(),
@@ -50,20 +69,45 @@ impl StorageSegment {
             db: Arc::new(h),
             slot,
             count: Arc::new(AtomicU32::new(0)),
+            version: Arc::new(AtomicU64::new(1)),
+            exec_lock: Arc::new(RwLock::new(())),
+            shard_channels: ChannelRegistry::default(),
+            notifier,
         }
     }
 
+    /// The shard channels subscribed with `SSUBSCRIBE` whose hash slot is
+    /// owned by this segment.
+    pub fn shard_channels(&self) -> &ChannelRegistry {
+        &self.shard_channels
+    }
+
+    /// Acquire a shared access on the storage, needed to apply a command.
+    pub async fn shared(&self) -> OwnedRwLockReadGuard<()> {
+        self.exec_lock.clone().read_owned().await
+    }
+
+    /// Acquire an exclusive access on the storage, needed to apply a
+    /// transaction atomically.
+    pub async fn exclusive(&self) -> OwnedRwLockWriteGuard<()> {
+        self.exec_lock.clone().write_owned().await
+    }
+
     #[allow(dead_code)]
     pub fn is_in_slot(&self, i: u16) -> bool {
         self.slot.contains(&i)
     }
 
     /// Set a key into the storage
-    pub fn set_async(
+    ///
+    /// Return the previous value, an expired one is missed like in
+    /// [StorageSegment::get_async].
+    pub async fn set_async(
         &self,
         key: ByteString,
         val: Bytes,
         opt: SetOptions,
+        now: Instant,
     ) -> Result<Option<StorageValue>, (String, StorageValue)> {
         let mut val = val.to_vec();
         val.shrink_to_fit();
@@ -71,10 +115,12 @@ impl StorageSegment {
         let val = StorageValue {
             expired: opt.expired,
             val,
+            version: self.version.fetch_add(1, Ordering::Relaxed),
         };
 
         let mut key = key.into_bytes().to_vec();
         key.shrink_to_fit();
+        let notified_key = key.clone();
 
         let old = self
             .count
@@ -84,12 +130,32 @@ impl StorageSegment {
             dbg!(old);
         }
 
-        if let Err((key, val)) = self.db.insert(key, val) {
-            let old = self.db.update(&key, |_, _| val);
-            Ok(old)
+        let old = if let Err((key, val)) = self.db.insert(key, val) {
+            self.db.update(&key, |_, old| std::mem::replace(old, val))
         } else {
-            Ok(None)
+            None
+        };
+
+        let old = match old {
+            Some(old) if old.expired.is_some_and(|expired| now > expired) => {
+                self.notifier
+                    .notify(KeyspaceEvents::EXPIRED, "expired", &notified_key)
+                    .await;
+                None
+            }
+            old => old,
+        };
+
+        if old.is_none() {
+            self.notifier
+                .notify(KeyspaceEvents::NEW, "new", &notified_key)
+                .await;
         }
+        self.notifier
+            .notify(KeyspaceEvents::STRING, "set", &notified_key)
+            .await;
+
+        Ok(old)
     }
 
     /// Get a key
@@ -103,22 +169,52 @@ impl StorageSegment {
         let mut key = key.into_bytes().to_vec();
         key.shrink_to_fit();
 
-        match self.db.entry_async(key).await {
+        let key = match self.db.entry_async(key).await {
             scc::hash_map::Entry::Occupied(oqp) => {
                 let val = oqp.get();
                 let is_expired =
                     val.expired.map(|expired| now > expired).unwrap_or(false);
 
+                if !is_expired {
+                    return Some(Bytes::from(val.val.clone()));
+                }
+
                 // TODO: Better handle expiration
+                let (key, _) = oqp.remove_entry();
+                self.notifier
+                    .notify(KeyspaceEvents::EXPIRED, "expired", &key)
+                    .await;
+                self.notifier.invalidate(&key).await;
+                key
+            }
+            scc::hash_map::Entry::Vacant(vacant) => vacant.into_key(),
+        };
+
+        // An expired key is missed too.
+        self.notifier
+            .notify(KeyspaceEvents::KEY_MISS, "keymiss", &key)
+            .await;
+        None
+    }
+
+    /// Get the version of a key.
+    ///
+    /// Return `0` if it doesn't exist or if it's expired.
+    pub async fn version_async(&self, key: ByteString, now: Instant) -> u64 {
+        let key = key.into_bytes();
+
+        self.db
+            .read_async(&key[..], |_, val| {
+                let is_expired =
+                    val.expired.map(|expired| now > expired).unwrap_or(false);
                 if is_expired {
-                    let _ = oqp.remove();
-                    None
+                    0
                 } else {
-                    Some(Bytes::from(val.val.clone()))
+                    val.version
                 }
-            }
-            scc::hash_map::Entry::Vacant(_) => None,
-        }
+            })
+            .await
+            .unwrap_or(0)
     }
 }
 
@@ -130,9 +226,10 @@ pub struct Storage {
 }
 
 impl Storage {
-    /// Create a new [Storage] by specifying the number of slot wanted and the
-    /// whole [Slot] this [Storage] should handle.
-    pub fn new(nb_slot: u16, slot: Slot) -> Self {
+    /// Create a new [Storage] by specifying the number of slot wanted, the
+    /// whole [Slot] this [Storage] should handle and where the keyspace
+    /// notifications are sent.
+    pub fn new(nb_slot: u16, slot: Slot, notifier: Notifier) -> Self {
         assert!(nb_slot != 0);
         assert!(nb_slot <= HASH_SLOT_MAX);
 
@@ -152,7 +249,7 @@ impl Storage {
             };
 
             let slot = Slot::from(start..end);
-            let store = StorageSegment::new(slot.clone());
+            let store = StorageSegment::new(slot.clone(), notifier.clone());
             slots.push((slot, store));
         }
 
@@ -184,4 +281,61 @@ impl Storage {
             .cloned()
             .expect("WTF")
     }
+
+    /// Every [StorageSegment] of the [Storage].
+    pub fn segments(&self) -> impl Iterator<Item = &StorageSegment> {
+        self.internal_vec.iter().map(|(_, segment)| segment)
+    }
+
+    /// The [StorageSegment] owning the hash slot `slot`.
+    pub fn segment(&self, slot: u16) -> &StorageSegment {
+        self.internal_vec
+            .iter()
+            .find(|(owned, _)| owned.contains(&slot))
+            .map(|(_, segment)| segment)
+            .expect("Every hash slot is owned by a segment")
+    }
+
+    /// The shard channels of the segment owning the hash slot of `channel`.
+    pub fn shard_channels(&self, channel: &[u8]) -> &ChannelRegistry {
+        self.segment(crc_hash(channel)).shard_channels()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::channel::mpsc::unbounded;
+
+    use super::Storage;
+    use crate::domain::dialer::Slot;
+    use crate::domain::notification::Notifier;
+    use crate::infrastructure::hash::{crc_hash, HASH_SLOT_MAX};
+
+    #[monoio::test]
+    async fn shard_channels_live_on_their_segment() {
+        let storage =
+            Storage::new(2, Slot::from(0..HASH_SLOT_MAX), Notifier::default());
+        let (first, second) = (storage.part(0).1, storage.part(1).1);
+
+        // `bar` hashes to the first half of the slots, `foo` to the second
+        // one.
+        let low = Bytes::from_static(b"bar");
+        let high = Bytes::from_static(b"foo");
+        assert!(first.is_in_slot(crc_hash(&low)));
+        assert!(second.is_in_slot(crc_hash(&high)));
+
+        let (tx, _rx) = unbounded();
+        storage
+            .shard_channels(&low)
+            .subscribe(low.clone(), 1, tx.clone())
+            .await;
+        storage
+            .shard_channels(&high)
+            .subscribe(high.clone(), 1, tx)
+            .await;
+
+        assert_eq!(first.shard_channels().channels(None).await, vec![low]);
+        assert_eq!(second.shard_channels().channels(None).await, vec![high]);
+    }
 }
@@ -0,0 +1,239 @@
+//! Server-assisted client side caching: the keys read by a tracking
+//! connection are remembered so an invalidation message can be sent to it
+//! when they are modified.
+//!
+//! Two modes are available:
+//!
+//! - In the default mode, the keys read by the connection are remembered.
+//! - In the broadcasting mode (`BCAST`), the connection is notified of every
+//!   modified key matching one of its prefixes, whatever it read.
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use scc::HashMap;
+
+use super::pubsub::{Message, Subscriber};
+
+/// The options given to `CLIENT TRACKING ON`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackingOptions {
+    /// Send the invalidation messages to another connection.
+    pub redirect: Option<u64>,
+    /// Broadcasting mode.
+    pub bcast: bool,
+    /// Prefixes of the keys to broadcast, every key when empty.
+    pub prefixes: Vec<Bytes>,
+    /// Only track the keys read after `CLIENT CACHING YES`.
+    pub optin: bool,
+    /// Don't track the keys read after `CLIENT CACHING NO`.
+    pub optout: bool,
+    /// Don't send invalidation messages for keys modified by this connection.
+    pub noloop: bool,
+}
+
+#[derive(Debug)]
+struct TrackedClient {
+    options: TrackingOptions,
+    /// The messages of the tracking connection itself.
+    own: Subscriber,
+    /// Where the invalidation messages are sent, either the tracking
+    /// connection or the one it redirects to.
+    target: Subscriber,
+}
+
+/// The table of the tracking connections and of the keys they track, shared
+/// across every thread.
+///
+/// **Should be cheap to clone**
+#[derive(Debug, Clone, Default)]
+pub struct TrackingTable {
+    clients: Arc<HashMap<u64, TrackedClient>>,
+    /// Keys read by connections in default mode.
+    keys: Arc<HashMap<Bytes, Vec<u64>>>,
+    /// Prefixes registered by connections in broadcasting mode.
+    prefixes: Arc<HashMap<Bytes, Vec<u64>>>,
+}
+
+impl TrackingTable {
+    /// Enable the tracking of the connection `id`, its messages are sent
+    /// through `own` or `target` when it redirects them.
+    ///
+    /// When the tracking is already enabled, the new prefixes are added to the
+    /// existing ones.
+    pub async fn enable(
+        &self,
+        id: u64,
+        mut options: TrackingOptions,
+        own: Subscriber,
+        target: Subscriber,
+    ) {
+        if options.bcast && options.prefixes.is_empty() {
+            options.prefixes.push(Bytes::new());
+        }
+
+        for prefix in &options.prefixes {
+            let mut entry =
+                self.prefixes.entry_async(prefix.clone()).await.or_default();
+            if !entry.get().contains(&id) {
+                entry.get_mut().push(id);
+            }
+        }
+
+        match self.clients.entry_async(id).await {
+            scc::hash_map::Entry::Occupied(mut entry) => {
+                let client = entry.get_mut();
+                let mut prefixes = std::mem::take(&mut client.options.prefixes);
+                for prefix in options.prefixes.drain(..) {
+                    if !prefixes.contains(&prefix) {
+                        prefixes.push(prefix);
+                    }
+                }
+                options.prefixes = prefixes;
+                client.options = options;
+                client.target = target;
+            }
+            scc::hash_map::Entry::Vacant(entry) => {
+                entry.insert_entry(TrackedClient {
+                    options,
+                    own,
+                    target,
+                });
+            }
+        }
+    }
+
+    /// Disable the tracking of the connection `id`.
+    ///
+    /// The keys it read are forgotten lazily, when they are invalidated.
+    pub async fn disable(&self, id: u64) {
+        let Some((_, client)) = self.clients.remove_async(&id).await else {
+            return;
+        };
+
+        for prefix in client.options.prefixes {
+            if let scc::hash_map::Entry::Occupied(mut entry) =
+                self.prefixes.entry_async(prefix).await
+            {
+                entry.get_mut().retain(|client_id| *client_id != id);
+                if entry.get().is_empty() {
+                    let _ = entry.remove();
+                }
+            }
+        }
+    }
+
+    /// The tracking options of the connection `id`, `None` when the tracking
+    /// is disabled.
+    pub async fn options(&self, id: u64) -> Option<TrackingOptions> {
+        self.clients
+            .read_async(&id, |_, client| client.options.clone())
+            .await
+    }
+
+    /// Remember that the connection `id` read `key`.
+    pub async fn remember(&self, id: u64, key: Bytes) {
+        let mut entry = self.keys.entry_async(key).await.or_default();
+        if !entry.get().contains(&id) {
+            entry.get_mut().push(id);
+        }
+    }
+
+    /// `key` was modified by the connection `writer`, send an invalidation
+    /// message to every connection tracking it.
+    pub async fn invalidate(&self, key: &[u8], writer: Option<u64>) {
+        let mut ids = self
+            .keys
+            .remove_async(key)
+            .await
+            .map(|(_, ids)| ids)
+            .unwrap_or_default();
+
+        if !self.prefixes.is_empty() {
+            self.prefixes
+                .scan_async(|prefix, prefix_ids| {
+                    if key.starts_with(prefix) {
+                        for id in prefix_ids {
+                            if !ids.contains(id) {
+                                ids.push(*id);
+                            }
+                        }
+                    }
+                })
+                .await;
+        }
+
+        for id in ids {
+            self.clients
+                .read_async(&id, |_, client| {
+                    if client.options.noloop && writer == Some(id) {
+                        return;
+                    }
+
+                    let msg = Message::Invalidate {
+                        keys: vec![Bytes::copy_from_slice(key)],
+                    };
+                    if client.target.unbounded_send(msg).is_err() {
+                        if let Some(redirect) = client.options.redirect {
+                            let _ = client.own.unbounded_send(
+                                Message::TrackingRedirBroken { redirect },
+                            );
+                        }
+                    }
+                })
+                .await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use futures::channel::mpsc::unbounded;
+
+    use super::{TrackingOptions, TrackingTable};
+    use crate::domain::pubsub::Message;
+
+    #[monoio::test]
+    async fn invalidate_default_and_bcast() {
+        let table = TrackingTable::default();
+        let (tx, mut rx) = unbounded();
+        let (bcast_tx, mut bcast_rx) = unbounded();
+
+        table
+            .enable(1, TrackingOptions::default(), tx.clone(), tx)
+            .await;
+        table
+            .enable(
+                2,
+                TrackingOptions {
+                    bcast: true,
+                    prefixes: vec![Bytes::from_static(b"user:")],
+                    noloop: true,
+                    ..Default::default()
+                },
+                bcast_tx.clone(),
+                bcast_tx.clone(),
+            )
+            .await;
+
+        table.remember(1, Bytes::from_static(b"user:1")).await;
+        table.invalidate(b"user:1", Some(2)).await;
+
+        let invalidation = Some(Message::Invalidate {
+            keys: vec![Bytes::from_static(b"user:1")],
+        });
+        assert_eq!(rx.try_next().unwrap(), invalidation);
+        // The key was modified by the connection itself.
+        assert!(bcast_rx.try_next().is_err());
+
+        // The key is forgotten once invalidated.
+        table.invalidate(b"user:1", None).await;
+        assert!(rx.try_next().is_err());
+        assert_eq!(bcast_rx.try_next().unwrap(), invalidation);
+
+        table.disable(2).await;
+        table.invalidate(b"user:1", None).await;
+        assert!(bcast_rx.try_next().is_err());
+    }
+}
@@ -1,22 +1,130 @@
-use std::net::SocketAddr;
+use std::path::PathBuf;
 
 use anyhow::Context;
 use config::Config;
 use serde::{Deserialize, Serialize};
 
+use crate::application::server::{BindAddr, TlsAuthClients};
+
 /// Configuration file for the application.
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Cfg {
-    /// The bind address we are going to listen to.
-    pub bind_addr: SocketAddr,
+    /// The bind addresses we are going to listen to, separated by spaces.
+    ///
+    /// Like `bind` in Redis, an address prefixed by `-` is skipped when it
+    /// isn't available, e.g. `10.0.0.1:3456 -[::1]:3456`.
+    pub bind_addr: String,
+    /// Only accept the loopback & unix socket clients while the `default`
+    /// user has no password, like `protected-mode` in Redis.
+    #[serde(default = "default_protected_mode")]
+    pub protected_mode: bool,
     /// Maximum number of concurrent connections the redis server will accept.
     ///
-    /// When this limit is reached, the server will stop accepting connections
-    /// until an active connection terminates.
+    /// When this limit is reached, the new connections are answered with an
+    /// error and closed until an active connection terminates.
     pub max_connection: u16,
+    /// The classes of keyspace events published through Pub/Sub, using the
+    /// `notify-keyspace-events` flags of Redis, e.g. `KEA`.
+    ///
+    /// Notifications are disabled by default.
+    #[serde(default)]
+    pub notify_keyspace_events: String,
+    /// How long a script can run, in milliseconds, before the other
+    /// connections are answered with a `BUSY` error and the script can be
+    /// killed with `SCRIPT KILL`.
+    #[serde(default = "default_busy_reply_threshold")]
+    pub busy_reply_threshold: u64,
+    /// The fuel a WebAssembly function called with `WCALL` can consume
+    /// before it's stopped.
+    #[serde(default = "default_wasm_fuel")]
+    pub wasm_fuel: u64,
+    /// The memory a WebAssembly function called with `WCALL` can use, in
+    /// bytes.
+    #[serde(default = "default_wasm_max_memory")]
+    pub wasm_max_memory: usize,
+    /// Close the connections once their client is idle for this many
+    /// seconds, like `timeout` in Redis. Disabled when `0`, the default.
+    #[serde(default)]
+    pub timeout: u64,
+    /// Send TCP keepalive probes to the clients idle for this many seconds,
+    /// like `tcp-keepalive` in Redis. Disabled when `0`.
+    #[serde(default = "default_tcp_keepalive")]
+    pub tcp_keepalive: u64,
+    /// The password of the `default` user, like `requirepass` in Redis.
+    ///
+    /// When it's set, every connection must authenticate with `AUTH` before
+    /// running a command.
+    #[serde(default)]
+    pub requirepass: Option<String>,
+    /// The ACL file, like `aclfile` in Redis: the users are loaded from it at
+    /// startup and by `ACL LOAD`, and saved to it by `ACL SAVE`.
+    #[serde(default)]
+    pub aclfile: Option<PathBuf>,
+    /// The file the libraries of functions are loaded from at startup and
+    /// saved to by `FUNCTION LOAD`, `DELETE`, `FLUSH` & `RESTORE`.
+    #[serde(default)]
+    pub functions_file: Option<PathBuf>,
+    /// The port TLS connections are accepted on, on the IP of the first
+    /// address of `bind_addr`.
+    ///
+    /// TLS is disabled unless it's set, with `tls_cert_file` & `tls_key_file`.
+    #[serde(default)]
+    pub tls_port: Option<u16>,
+    /// The certificate of the server, in PEM.
+    #[serde(default)]
+    pub tls_cert_file: Option<PathBuf>,
+    /// The private key of the server, in PEM.
+    #[serde(default)]
+    pub tls_key_file: Option<PathBuf>,
+    /// The CA certificates used to verify the client certificates, in PEM.
+    #[serde(default)]
+    pub tls_ca_cert_file: Option<PathBuf>,
+    /// If the clients must send a certificate: `yes`, `optional` or `no`.
+    ///
+    /// The common name of a client certificate authenticates the connection
+    /// as the ACL user of the same name.
+    #[serde(default)]
+    pub tls_auth_clients: TlsAuthClients,
+    /// The unix socket connections are also accepted on, like `unixsocket`
+    /// in Redis.
+    #[serde(default)]
+    pub unixsocket: Option<PathBuf>,
+    /// The permissions of the unix socket in octal, e.g. `700`.
+    #[serde(default)]
+    pub unixsocketperm: Option<String>,
+}
+
+fn default_protected_mode() -> bool {
+    true
+}
+
+fn default_tcp_keepalive() -> u64 {
+    300
+}
+
+fn default_busy_reply_threshold() -> u64 {
+    5_000
+}
+
+fn default_wasm_fuel() -> u64 {
+    10_000_000
+}
+
+fn default_wasm_max_memory() -> usize {
+    64 * 1024 * 1024
 }
 
 impl Cfg {
+    /// The addresses of `bind_addr`.
+    pub fn bind_addrs(&self) -> anyhow::Result<Vec<BindAddr>> {
+        let binds =
+            BindAddr::parse_list(&self.bind_addr).with_context(|| {
+                format!("Invalid `bind_addr`: {}", self.bind_addr)
+            })?;
+        anyhow::ensure!(!binds.is_empty(), "`bind_addr` must be set.");
+        Ok(binds)
+    }
+
     /// Read the associated configuration env
     pub fn from_env() -> anyhow::Result<Cfg> {
         let file_location = dotenv::var("CONFIG_FILE_LOCATION")
@@ -0,0 +1,165 @@
+//! Glob-style pattern matching, used by the commands taking a pattern like
+//! `PSUBSCRIBE` or `KEYS`.
+//!
+//! It follows the rules of `stringmatchlen` from Redis:
+//!
+//! - `?` matches any single character.
+//! - `*` matches any sequence of characters, even an empty one.
+//! - `[abc]` matches one of the characters, `[^abc]` matches any other
+//!   character and `[a-z]` matches a range.
+//! - `\` escapes the next character.
+
+/// Check if `string` matches the glob-style `pattern`.
+pub fn glob_match(pattern: &[u8], string: &[u8], nocase: bool) -> bool {
+    let mut pattern = pattern;
+    let mut string = string;
+
+    while let Some(&p) = pattern.first() {
+        match p {
+            b'*' => {
+                // Consecutive stars are the same as a single one.
+                while let [b'*', rest @ ..] = pattern {
+                    pattern = rest;
+                }
+                if pattern.is_empty() {
+                    return true;
+                }
+                for start in 0..string.len() {
+                    if glob_match(pattern, &string[start..], nocase) {
+                        return true;
+                    }
+                }
+                return false;
+            }
+            b'?' => {
+                if string.is_empty() {
+                    return false;
+                }
+                string = &string[1..];
+                pattern = &pattern[1..];
+            }
+            b'[' => {
+                let Some(&c) = string.first() else {
+                    return false;
+                };
+                pattern = &pattern[1..];
+
+                let not = pattern.first() == Some(&b'^');
+                if not {
+                    pattern = &pattern[1..];
+                }
+
+                let mut matched = false;
+                loop {
+                    match pattern {
+                        // An unterminated class is closed at the end of the
+                        // pattern.
+                        [] => break,
+                        [b'\\', escaped, rest @ ..] => {
+                            if eq(*escaped, c, nocase) {
+                                matched = true;
+                            }
+                            pattern = rest;
+                        }
+                        [b']', rest @ ..] => {
+                            pattern = rest;
+                            break;
+                        }
+                        [start, b'-', end, rest @ ..] if *end != b']' => {
+                            let (mut start, mut end, mut c) = (*start, *end, c);
+                            if start > end {
+                                std::mem::swap(&mut start, &mut end);
+                            }
+                            if nocase {
+                                start = start.to_ascii_lowercase();
+                                end = end.to_ascii_lowercase();
+                                c = c.to_ascii_lowercase();
+                            }
+                            if (start..=end).contains(&c) {
+                                matched = true;
+                            }
+                            pattern = rest;
+                        }
+                        [other, rest @ ..] => {
+                            if eq(*other, c, nocase) {
+                                matched = true;
+                            }
+                            pattern = rest;
+                        }
+                    }
+                }
+
+                if matched == not {
+                    return false;
+                }
+                string = &string[1..];
+            }
+            _ => {
+                let expected = match pattern {
+                    [b'\\', escaped, ..] => {
+                        pattern = &pattern[1..];
+                        *escaped
+                    }
+                    _ => p,
+                };
+                match string.first() {
+                    Some(&c) if eq(expected, c, nocase) => {
+                        string = &string[1..];
+                        pattern = &pattern[1..];
+                    }
+                    _ => return false,
+                }
+            }
+        }
+    }
+
+    string.is_empty()
+}
+
+#[inline]
+fn eq(a: u8, b: u8, nocase: bool) -> bool {
+    if nocase {
+        a.eq_ignore_ascii_case(&b)
+    } else {
+        a == b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::glob_match;
+
+    #[test]
+    fn glob_simple() {
+        assert!(glob_match(b"news", b"news", false));
+        assert!(!glob_match(b"news", b"newsx", false));
+        assert!(glob_match(b"news.*", b"news.tech", false));
+        assert!(glob_match(b"news.*", b"news.", false));
+        assert!(!glob_match(b"news.*", b"new", false));
+        assert!(glob_match(b"*", b"", false));
+        assert!(glob_match(b"h?llo", b"hello", false));
+        assert!(!glob_match(b"h?llo", b"hllo", false));
+        assert!(glob_match(b"*a*b", b"xxaxxb", false));
+    }
+
+    #[test]
+    fn glob_class() {
+        assert!(glob_match(b"h[ae]llo", b"hallo", false));
+        assert!(!glob_match(b"h[ae]llo", b"hillo", false));
+        assert!(glob_match(b"h[^e]llo", b"hallo", false));
+        assert!(!glob_match(b"h[^e]llo", b"hello", false));
+        assert!(glob_match(b"h[a-b]llo", b"hbllo", false));
+        assert!(glob_match(b"h[b-a]llo", b"hbllo", false));
+        assert!(!glob_match(b"h[a-b]llo", b"hcllo", false));
+        assert!(glob_match(b"[A-Z]", b"c", true));
+    }
+
+    #[test]
+    fn glob_escape() {
+        assert!(glob_match(b"h\\*llo", b"h*llo", false));
+        assert!(!glob_match(b"h\\*llo", b"hello", false));
+        assert!(glob_match(b"[\\]]", b"]", false));
+        assert!(glob_match(b"HELLO", b"hello", true));
+        assert!(!glob_match(b"HELLO", b"hello", false));
+    }
+}
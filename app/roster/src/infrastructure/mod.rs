@@ -1,3 +1,5 @@
 pub mod config;
+pub mod fs;
+pub mod glob;
 pub mod hash;
 pub mod instruments;
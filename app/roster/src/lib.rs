@@ -1,9 +1,11 @@
-#![feature(negative_impls)]
 pub mod application;
 pub mod domain;
 pub mod infrastructure;
 
-pub use application::server::ServerConfigBuilder;
+pub use application::server::{
+    BindAddr, CommandRegistry, ServerConfigBuilder, ServerHandle,
+    TlsAuthClients, TlsConfig,
+};
 
 #[cfg(debug_assertions)]
 pub const VERSION: &str =
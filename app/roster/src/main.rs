@@ -1,21 +1,12 @@
 #![allow(clippy::print_literal)]
-#![feature(negative_impls)]
-mod application;
-mod domain;
-mod infrastructure;
-
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
-use application::server::ServerConfigBuilder;
-use infrastructure::config::Cfg;
-// use infrastructure::instruments::Instruments;
-
-#[cfg(debug_assertions)]
-pub const VERSION: &str =
-    concat!("(dev) ", env!("CARGO_PKG_VERSION"), "-", env!("GIT_HASH"),);
-
-#[cfg(not(debug_assertions))]
-pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+use roster::domain::notification::KeyspaceEvents;
+use roster::infrastructure::config::Cfg;
+use roster::{ServerConfigBuilder, TlsConfig, VERSION};
+// use roster::infrastructure::instruments::Instruments;
 
 fn main() -> anyhow::Result<()> {
     // Initialize config
@@ -33,9 +24,53 @@ fn main() -> anyhow::Result<()> {
     //
 
     // Initialize server with Redis Protocol to accept connections;
-    let server = ServerConfigBuilder::default()
+    let binds = config.bind_addrs()?;
+    let mut server = ServerConfigBuilder::default()
         .connections_limit(Arc::new(config.max_connection.into()))
-        .bind_addr(config.bind_addr)
+        .protected_mode(config.protected_mode)
+        .notify_keyspace_events(
+            config.notify_keyspace_events.parse::<KeyspaceEvents>()?,
+        )
+        .busy_reply_threshold(Duration::from_millis(
+            config.busy_reply_threshold,
+        ))
+        .wasm_fuel(config.wasm_fuel)
+        .wasm_max_memory(config.wasm_max_memory)
+        .timeout(Duration::from_secs(config.timeout))
+        .tcp_keepalive(Duration::from_secs(config.tcp_keepalive));
+    for bind in &binds {
+        server = server.bind_addr(*bind);
+    }
+    if let Some(password) = config.requirepass {
+        server = server.requirepass(password);
+    }
+    if let Some(aclfile) = config.aclfile {
+        server = server.aclfile(aclfile);
+    }
+    if let Some(functions_file) = config.functions_file {
+        server = server.functions_file(functions_file);
+    }
+    if let Some(unixsocket) = config.unixsocket {
+        server = server.unixsocket(unixsocket);
+    }
+    if let Some(perm) = config.unixsocketperm {
+        server = server.unixsocketperm(u32::from_str_radix(&perm, 8)?);
+    }
+    if let Some(port) = config.tls_port {
+        let (Some(cert_file), Some(key_file)) =
+            (config.tls_cert_file, config.tls_key_file)
+        else {
+            anyhow::bail!("`tls_cert_file` and `tls_key_file` must be set.");
+        };
+        server = server.tls(TlsConfig {
+            bind_addr: SocketAddr::new(binds[0].addr.ip(), port),
+            cert_file,
+            key_file,
+            ca_cert_file: config.tls_ca_cert_file,
+            auth_clients: config.tls_auth_clients,
+        });
+    }
+    let server = server
         .build()
         .expect("Couldn't create the config")
         .initialize();
@@ -67,6 +102,7 @@ fn main() -> anyhow::Result<()> {
         pid = std::process::id()
     );
 
+    server.shutdown_on_signals()?;
     server.join();
 
     Ok(())
@@ -0,0 +1,51 @@
+mod utils;
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+/// Send `PING` and read the reply.
+fn ping(stream: &mut TcpStream) -> std::io::Result<String> {
+    stream.set_read_timeout(Some(Duration::from_millis(500)))?;
+    stream.write_all(b"PING\r\n")?;
+    let mut buf = [0; 64];
+    let read = stream.read(&mut buf)?;
+    Ok(String::from_utf8_lossy(&buf[..read]).into_owned())
+}
+
+#[test]
+fn keep_accepting_when_out_of_file_descriptors() {
+    let addr = utils::start_simple_server();
+    std::thread::sleep(Duration::from_millis(1_000));
+    let mut client = TcpStream::connect(addr).unwrap();
+    assert_eq!(ping(&mut client).unwrap(), "+PONG\r\n");
+
+    // The clients and the server share the file descriptors of the process:
+    // once the clients used them, the server fails to accept.
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    assert_eq!(
+        unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) },
+        0
+    );
+    let opened = std::fs::read_dir("/proc/self/fd").unwrap().count();
+    let lowered = libc::rlimit {
+        rlim_cur: opened as libc::rlim_t + 4,
+        rlim_max: limit.rlim_max,
+    };
+    assert_eq!(unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &lowered) }, 0);
+
+    let clients = (0..8)
+        .map_while(|_| TcpStream::connect(addr).ok())
+        .collect::<Vec<_>>();
+    std::thread::sleep(Duration::from_millis(200));
+
+    drop(clients);
+    assert_eq!(unsafe { libc::setrlimit(libc::RLIMIT_NOFILE, &limit) }, 0);
+    std::thread::sleep(Duration::from_millis(500));
+
+    let mut client = TcpStream::connect(addr).unwrap();
+    assert_eq!(ping(&mut client).unwrap(), "+PONG\r\n");
+}
@@ -0,0 +1,319 @@
+mod utils;
+
+#[tokio::test]
+pub async fn acl_cat() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"ACL CAT\r\n").await;
+    assert!(res.starts_with("*21\r\n$8\r\nkeyspace\r\n"));
+
+    let res = utils::send_raw(&mut stream, b"ACL CAT scripting\r\n").await;
+    assert!(res.contains("$4\r\neval\r\n"));
+    assert!(!res.contains("$3\r\nget\r\n"));
+
+    let res = utils::send_raw(&mut stream, b"ACL CAT unknown\r\n").await;
+    assert_eq!(res, "-ERR Unknown category 'unknown'\r\n");
+}
+
+#[tokio::test]
+pub async fn manage_users() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"ACL WHOAMI\r\n").await;
+    assert_eq!(res, "$7\r\ndefault\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"ACL SETUSER alice on >secret ~cache:* +get +@pubsub\r\n",
+    )
+    .await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut stream, b"ACL USERS\r\n").await;
+    assert_eq!(res, "*2\r\n$5\r\nalice\r\n$7\r\ndefault\r\n");
+
+    let res = utils::send_raw(&mut stream, b"ACL LIST\r\n").await;
+    assert_eq!(
+        res,
+        concat!(
+            "*2\r\n",
+            "$122\r\nuser alice on ",
+            "#2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b",
+            " ~cache:* resetchannels -@all +get +@pubsub\r\n",
+            "$34\r\nuser default on nopass ~* &* +@all\r\n"
+        )
+    );
+
+    let res = utils::send_raw(&mut stream, b"ACL GETUSER alice\r\n").await;
+    assert_eq!(
+        res,
+        concat!(
+            "*12\r\n",
+            "$5\r\nflags\r\n*1\r\n$2\r\non\r\n",
+            "$9\r\npasswords\r\n*1\r\n$64\r\n",
+            "2bb80d537b1da3e38bd30361aa855686bde0eacd7162fef6a25fe97bf527a25b",
+            "\r\n",
+            "$8\r\ncommands\r\n$19\r\n-@all +get +@pubsub\r\n",
+            "$4\r\nkeys\r\n$8\r\n~cache:*\r\n",
+            "$8\r\nchannels\r\n$0\r\n\r\n",
+            "$9\r\nselectors\r\n*0\r\n"
+        )
+    );
+
+    let res =
+        utils::send_raw(&mut stream, b"ACL SETUSER alice +unknown\r\n").await;
+    assert_eq!(
+        res,
+        "-ERR Error in ACL SETUSER modifier '+unknown': Unknown command or \
+         category name in ACL\r\n"
+    );
+
+    let res = utils::send_raw(&mut stream, b"ACL DELUSER alice bob\r\n").await;
+    assert_eq!(res, ":1\r\n");
+
+    let res = utils::send_raw(&mut stream, b"ACL GETUSER alice\r\n").await;
+    assert_eq!(res, "$-1\r\n");
+
+    let res = utils::send_raw(&mut stream, b"ACL DELUSER default\r\n").await;
+    assert_eq!(res, "-ERR The 'default' user cannot be removed\r\n");
+}
+
+#[tokio::test]
+pub async fn gen_pass() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"ACL GENPASS\r\n").await;
+    assert_eq!(res.len(), "$64\r\n\r\n".len() + 64);
+
+    let res = utils::send_raw(&mut stream, b"ACL GENPASS 5\r\n").await;
+    assert_eq!(res.len(), "$2\r\n\r\n".len() + 2);
+
+    let res = utils::send_raw(&mut stream, b"ACL GENPASS 0\r\n").await;
+    assert_eq!(
+        res,
+        "-ERR ACL GENPASS argument must be the number of bits for the output \
+         password, a positive number up to 4096\r\n"
+    );
+}
+
+#[tokio::test]
+pub async fn dry_run() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(
+        &mut stream,
+        b"ACL SETUSER alice on nopass %R~read:* ~all:* &news:* +get +set \
+          +subscribe\r\n",
+    )
+    .await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res =
+        utils::send_raw(&mut stream, b"ACL DRYRUN alice GET read:1\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res =
+        utils::send_raw(&mut stream, b"ACL DRYRUN alice SET read:1 v\r\n")
+            .await;
+    assert_eq!(
+        res,
+        "$55\r\nThis user has no permissions to access the 'read:1' key\r\n"
+    );
+
+    let res =
+        utils::send_raw(&mut stream, b"ACL DRYRUN alice SET all:1 v\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut stream, b"ACL DRYRUN alice PING\r\n").await;
+    assert_eq!(
+        res,
+        "$55\r\nUser alice has no permissions to run the 'ping' command\r\n"
+    );
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"ACL DRYRUN alice SUBSCRIBE news:1 sport\r\n",
+    )
+    .await;
+    assert_eq!(
+        res,
+        "$58\r\nThis user has no permissions to access the 'sport' channel\r\n"
+    );
+
+    let res = utils::send_raw(&mut stream, b"ACL DRYRUN bob PING\r\n").await;
+    assert_eq!(res, "-ERR User 'bob' not found\r\n");
+
+    let res =
+        utils::send_raw(&mut stream, b"ACL DRYRUN alice UNKNOWN\r\n").await;
+    assert_eq!(res, "-ERR Command 'unknown' not found\r\n");
+}
+
+#[tokio::test]
+pub async fn enforce_permissions() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(
+        &mut stream,
+        b"ACL SETUSER default resetkeys ~allowed:* -get\r\n",
+    )
+    .await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut stream, b"GET allowed:1\r\n").await;
+    assert_eq!(
+        res,
+        "-NOPERM User default has no permissions to run the 'get' command\r\n"
+    );
+
+    let res = utils::send_raw(&mut stream, b"SET denied v\r\n").await;
+    assert_eq!(res, "-NOPERM No permissions to access a key\r\n");
+
+    let res = utils::send_raw(&mut stream, b"SET allowed:1 v\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    // A denied command is refused on `EXEC`.
+    let res = utils::send_raw(&mut stream, b"MULTI\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+    let res = utils::send_raw(&mut stream, b"GET allowed:1\r\n").await;
+    assert_eq!(
+        res,
+        "-NOPERM User default has no permissions to run the 'get' command\r\n"
+    );
+    let res = utils::send_raw(&mut stream, b"EXEC\r\n").await;
+    assert_eq!(
+        res,
+        "-EXECABORT Transaction discarded because of previous errors.\r\n"
+    );
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"return redis.pcall('GET', KEYS[1])\" 1 allowed:1\r\n",
+    )
+    .await;
+    assert_eq!(
+        res,
+        "-NOPERM User default has no permissions to run the 'get' command\r\n"
+    );
+}
+
+#[tokio::test]
+pub async fn load_and_save() {
+    let path = std::env::temp_dir()
+        .join(format!("roster-{}-users.acl", std::process::id()));
+    std::fs::write(&path, "user alice on nopass ~* +get\n").unwrap();
+
+    let aclfile = path.clone();
+    let addr = utils::start_server_with(|builder| builder.aclfile(aclfile));
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"ACL USERS\r\n").await;
+    assert_eq!(res, "*2\r\n$5\r\nalice\r\n$7\r\ndefault\r\n");
+
+    let res =
+        utils::send_raw(&mut stream, b"ACL SETUSER bob on nopass +set\r\n")
+            .await;
+    assert_eq!(res, "+OK\r\n");
+    let res = utils::send_raw(&mut stream, b"ACL SAVE\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        concat!(
+            "user alice on nopass ~* resetchannels -@all +get\n",
+            "user bob on nopass resetchannels -@all +set\n",
+            "user default on nopass ~* &* +@all\n",
+        )
+    );
+
+    std::fs::write(&path, "user carol on\nuser dave +unknown\n").unwrap();
+    let res = utils::send_raw(&mut stream, b"ACL LOAD\r\n").await;
+    assert_eq!(
+        res,
+        format!(
+            "-ERR {}:2: Error in ACL SETUSER modifier '+unknown': Unknown \
+             command or category name in ACL. WARNING: ACL errors detected, \
+             no change to the previously active ACL rules was performed\r\n",
+            path.display()
+        )
+    );
+
+    std::fs::write(&path, "user carol on\n").unwrap();
+    let res = utils::send_raw(&mut stream, b"ACL LOAD\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+    let res = utils::send_raw(&mut stream, b"ACL USERS\r\n").await;
+    assert_eq!(res, "*2\r\n$5\r\ncarol\r\n$7\r\ndefault\r\n");
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[tokio::test]
+pub async fn load_without_file() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"ACL LOAD\r\n").await;
+    assert!(res.starts_with(
+        "-ERR This Roster instance is not configured to use an ACL file."
+    ));
+}
+
+#[tokio::test]
+pub async fn log() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(
+        &mut stream,
+        b"ACL SETUSER alice on >pass ~allowed:* +get\r\n",
+    )
+    .await;
+    assert_eq!(res, "+OK\r\n");
+
+    let mut alice = utils::connect_raw(addr).await;
+    let res = utils::send_raw(
+        &mut alice,
+        b"AUTH alice wrong\r\nAUTH alice pass\r\nSET key value\r\nGET \
+          denied\r\nGET denied\r\n",
+    )
+    .await;
+    assert!(res.ends_with("-NOPERM No permissions to access a key\r\n"));
+
+    let res = utils::send_raw(&mut stream, b"ACL LOG\r\n").await;
+    let entries = res.split("$5\r\ncount\r\n").skip(1).collect::<Vec<_>>();
+    assert_eq!(entries.len(), 3);
+    assert!(entries[0].starts_with(concat!(
+        ":2\r\n",
+        "$6\r\nreason\r\n$3\r\nkey\r\n",
+        "$7\r\ncontext\r\n$8\r\ntoplevel\r\n",
+        "$6\r\nobject\r\n$6\r\ndenied\r\n",
+        "$8\r\nusername\r\n$5\r\nalice\r\n",
+    )));
+    assert!(entries[0].contains(" user=alice "));
+    assert!(entries[1].starts_with(concat!(
+        ":1\r\n",
+        "$6\r\nreason\r\n$7\r\ncommand\r\n",
+        "$7\r\ncontext\r\n$8\r\ntoplevel\r\n",
+        "$6\r\nobject\r\n$3\r\nset\r\n",
+    )));
+    assert!(entries[2].starts_with(concat!(
+        ":1\r\n",
+        "$6\r\nreason\r\n$4\r\nauth\r\n",
+        "$7\r\ncontext\r\n$8\r\ntoplevel\r\n",
+        "$6\r\nobject\r\n$4\r\nAUTH\r\n",
+    )));
+
+    let res = utils::send_raw(&mut stream, b"ACL LOG 1\r\n").await;
+    assert!(res.starts_with("*1\r\n"));
+
+    let res = utils::send_raw(&mut stream, b"ACL LOG -1\r\n").await;
+    assert_eq!(res, "-ERR value is out of range, must be positive\r\n");
+
+    let res = utils::send_raw(&mut stream, b"ACL LOG RESET\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+    let res = utils::send_raw(&mut stream, b"ACL LOG\r\n").await;
+    assert_eq!(res, "*0\r\n");
+}
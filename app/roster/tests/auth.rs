@@ -0,0 +1,92 @@
+mod utils;
+
+#[tokio::test]
+pub async fn auth_without_requirepass() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"AUTH secret\r\n").await;
+    assert_eq!(
+        res,
+        "-ERR AUTH <password> called without any password configured for the \
+         default user. Are you sure your client is configured correctly?\r\n"
+    );
+
+    let res = utils::send_raw(&mut stream, b"AUTH default any\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut stream, b"AUTH a b c\r\n").await;
+    assert_eq!(res, "-ERR syntax error\r\n");
+}
+
+#[tokio::test]
+pub async fn requirepass() {
+    let addr =
+        utils::start_server_with(|builder| builder.requirepass("secret"));
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"GET key\r\n").await;
+    assert_eq!(res, "-NOAUTH Authentication required.\r\n");
+
+    let res = utils::send_raw(&mut stream, b"HELLO 3\r\n").await;
+    assert!(res.starts_with("-NOAUTH HELLO must be called"));
+
+    let res = utils::send_raw(&mut stream, b"AUTH wrong\r\n").await;
+    assert_eq!(
+        res,
+        "-WRONGPASS invalid username-password pair or user is disabled.\r\n"
+    );
+
+    let res = utils::send_raw(&mut stream, b"AUTH secret\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut stream, b"GET key\r\n").await;
+    assert_eq!(res, "$-1\r\n");
+}
+
+#[tokio::test]
+pub async fn auth_as_user() {
+    let addr =
+        utils::start_server_with(|builder| builder.requirepass("secret"));
+
+    let mut admin = utils::connect_raw(addr).await;
+    let res = utils::send_raw(
+        &mut admin,
+        b"HELLO 2 AUTH default secret\r\nACL SETUSER alice on >pass ~* +get \
+          +client|info\r\n",
+    )
+    .await;
+    assert!(res.ends_with("+OK\r\n"));
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"AUTH alice wrong\r\n").await;
+    assert_eq!(
+        res,
+        "-WRONGPASS invalid username-password pair or user is disabled.\r\n"
+    );
+
+    let res = utils::send_raw(&mut stream, b"AUTH alice pass\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut stream, b"GET key\r\n").await;
+    assert_eq!(res, "$-1\r\n");
+
+    let res = utils::send_raw(&mut stream, b"SET key value\r\n").await;
+    assert_eq!(
+        res,
+        "-NOPERM User alice has no permissions to run the 'set' command\r\n"
+    );
+
+    let res = utils::send_raw(&mut stream, b"CLIENT INFO\r\n").await;
+    assert!(res.contains(" user=alice "));
+}
+
+#[tokio::test]
+pub async fn quit() {
+    let addr =
+        utils::start_server_with(|builder| builder.requirepass("secret"));
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"QUIT\r\nPING\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+}
@@ -0,0 +1,82 @@
+mod utils;
+
+use std::net::{IpAddr, SocketAddr, UdpSocket};
+
+use roster::BindAddr;
+
+/// A non-loopback address of this host, the one used to reach the network.
+fn host_ip() -> IpAddr {
+    let socket = UdpSocket::bind("0.0.0.0:0").unwrap();
+    socket.connect("203.0.113.1:9").unwrap();
+    let ip = socket.local_addr().unwrap().ip();
+    assert!(!ip.is_loopback());
+    ip
+}
+
+fn bind(addr: &str) -> BindAddr {
+    addr.parse().unwrap()
+}
+
+#[tokio::test]
+pub async fn multiple_bind_addresses() {
+    let port = utils::port_picker::pick_unused_port().unwrap();
+    let addr = utils::start_server_with(|builder| {
+        builder
+            .bind_addr(bind(&format!("-[::1]:{port}")))
+            // Not available on the host, so skipped.
+            .bind_addr(bind(&format!("-203.0.113.1:{port}")))
+    });
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+
+    let ipv6 = SocketAddr::from(([0, 0, 0, 0, 0, 0, 0, 1], port));
+    let mut stream = utils::connect_raw(ipv6).await;
+    let res = utils::send_raw(&mut stream, b"CLIENT INFO\r\n").await;
+    assert!(res.contains(&format!(" laddr=[::1]:{port} ")), "{res}");
+}
+
+#[tokio::test]
+pub async fn protected_mode() {
+    let public = SocketAddr::new(
+        host_ip(),
+        utils::port_picker::pick_unused_port().unwrap(),
+    );
+    let any = SocketAddr::new([0, 0, 0, 0].into(), public.port());
+    let addr = utils::start_server_with(|builder| builder.bind_addr(any));
+
+    let mut stream = utils::connect_raw(public).await;
+    let res = utils::send_raw(&mut stream, b"PING\r\n").await;
+    assert!(res.starts_with("-DENIED Roster is running in protected mode"));
+
+    // The loopback clients are still accepted.
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+
+    // Once the default user has a password, every client can authenticate.
+    let res =
+        utils::send_raw(&mut stream, b"ACL SETUSER default >secret\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let mut stream = utils::connect_raw(public).await;
+    let res = utils::send_raw(&mut stream, b"AUTH secret\r\nPING\r\n").await;
+    assert_eq!(res, "+OK\r\n+PONG\r\n");
+}
+
+#[tokio::test]
+pub async fn protected_mode_disabled() {
+    let public = SocketAddr::new(
+        host_ip(),
+        utils::port_picker::pick_unused_port().unwrap(),
+    );
+    let any = SocketAddr::new([0, 0, 0, 0].into(), public.port());
+    utils::start_server_with(|builder| {
+        builder.bind_addr(any).protected_mode(false)
+    });
+
+    let mut stream = utils::connect_raw(public).await;
+    let res = utils::send_raw(&mut stream, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+}
@@ -31,23 +31,110 @@ pub async fn test_simple_client_help() {
 
     insta::assert_display_snapshot!(joined, @r###"
     CLIENT <subcommand> [<arg> [value] [opt] ...]. subcommands are:
+    CACHING (YES|NO)
+        Enable/disable tracking of the keys for next command in OPTIN/OPTOUT modes.
+    GETREDIR
+        Return the client ID we are redirecting to when client tracking is enabled.
     GETNAME
         Return the name of the current connection.
     ID
         Return the ID of the current connection.
     INFO
         Return information about the current client connection.
+    KILL <ip:port>
+        Kill connection made from <ip:port>.
+    KILL <option> <value> [<option> <value> [...]]
+        Kill connections. Options are:
+        * ADDR (<ip:port>|<unixsocket>:0)
+          Kill connections made from the specified address
+        * LADDR (<ip:port>|<unixsocket>:0)
+          Kill connections made to specified local address
+        * ID <client-id>
+          Kill connections by client id.
+        * USER <username>
+          Kill connections authenticated by <username>.
+        * SKIPME (YES|NO)
+          Skip killing current connection (default: yes).
+        * MAXAGE <maxage>
+          Kill connections older than the specified age.
     LIST [options ...]
         Return information about client connections. Options:
         * TYPE (NORMAL|MASTER|REPLICA|PUBSUB)
           Return clients of specified type.
+        * USER <username>
+          Return clients authenticated by <username>.
+        * ADDR <ip:port> / LADDR <ip:port>
+          Return clients connected from / to the specified address.
+        * SKIPME (YES|NO)
+          Skip the current connection (default: no).
+        * ID <client-id> [<client-id> ...]
+          Return clients with the specified IDs.
+    NO-EVICT (ON|OFF)
+        Protect current client connection from eviction.
+    NO-TOUCH (ON|OFF)
+        Will not touch LRU/LFU stats when this mode is on.
+    PAUSE <timeout> [WRITE|ALL]
+        Suspend all, or just write, clients for <timeout> milliseconds.
+    REPLY (ON|OFF|SKIP)
+        Control the replies sent to the current connection.
     SETNAME <name>
         Assign the name <name> to the current connection.
     SETINFO <option> <value>
         Set client meta attr. Options are:
         * LIB-NAME: the client lib name.
         * LIB-VER: the client lib version.
+    TRACKING (ON|OFF) [REDIRECT <id>] [BCAST] [PREFIX <prefix> [...]]
+             [OPTIN] [OPTOUT] [NOLOOP]
+        Control server assisted client side caching.
+    TRACKINGINFO
+        Report tracking status for the current connection.
+    UNPAUSE
+        Stop the current client pause, resuming traffic.
     HELP
         Print this help.
     "###);
 }
+
+#[tokio::test]
+pub async fn test_client_no_evict_no_touch() {
+    let addr = utils::start_simple_server();
+
+    let connection = utils::connect_without_auth(addr).await;
+
+    for cmd in ["NO-EVICT", "NO-TOUCH"] {
+        let res: String = connection
+            .send(resp_array!["CLIENT", cmd, "ON"])
+            .await
+            .unwrap();
+        assert_eq!(res, "OK");
+        let res: String = connection
+            .send(resp_array!["CLIENT", cmd, "off"])
+            .await
+            .unwrap();
+        assert_eq!(res, "OK");
+        let res = connection
+            .send::<String>(resp_array!["CLIENT", cmd, "maybe"])
+            .await;
+        assert!(res.is_err());
+    }
+}
+
+#[tokio::test]
+pub async fn test_client_setinfo_errors() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res =
+        utils::send_raw(&mut stream, b"CLIENT SETINFO LIB-NAME \"a b\"\r\n")
+            .await;
+    assert_eq!(
+        res,
+        "-ERR LIB-NAME cannot contain spaces, newlines or special \
+         characters.\r\n"
+    );
+
+    let res =
+        utils::send_raw(&mut stream, b"CLIENT SETINFO LIB-FOO bar\r\n").await;
+    assert_eq!(res, "-ERR Unrecognized option 'LIB-FOO'\r\n");
+}
@@ -4,8 +4,10 @@ use regex::Regex;
 
 #[tokio::test]
 pub async fn client_info() {
-    let test_re: Regex =
-        Regex::new(r"^id=0 addr=.*? laddr=.*? fd=.*? name=$").unwrap();
+    let test_re: Regex = Regex::new(
+        r"^id=0 addr=.*? laddr=.*? fd=.*? name= age=\d+ idle=\d+ flags=N db=0 sub=0 psub=0 ssub=0 multi=-1 watch=0 qbuf=\d+ obl=0 oll=0 omem=0 tot-mem=\d+ events=r cmd=client\|info user=default redir=-1 resp=2 lib-name= lib-ver=$",
+    )
+    .unwrap();
     let addr = utils::start_simple_server();
 
     let connection = utils::connect_without_auth(addr).await;
@@ -0,0 +1,105 @@
+mod utils;
+
+use std::time::Duration;
+
+use regex::Regex;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+
+/// Tell if the server closed the connection.
+async fn is_closed(stream: &mut TcpStream) -> bool {
+    let mut buf = [0; 16];
+    let read =
+        tokio::time::timeout(Duration::from_secs(1), stream.read(&mut buf))
+            .await
+            .unwrap();
+    matches!(read, Ok(0) | Err(_))
+}
+
+#[tokio::test]
+pub async fn client_kill_id() {
+    let addr = utils::start_simple_server();
+
+    let mut victim = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut victim, b"CLIENT ID\r\n").await;
+    let id = res.trim_start_matches(':').trim_end().to_owned();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let cmd = format!("CLIENT KILL ID {id}\r\n");
+    let res = utils::send_raw(&mut stream, cmd.as_bytes()).await;
+    assert_eq!(res, ":1\r\n");
+    assert!(is_closed(&mut victim).await);
+
+    let res = utils::send_raw(&mut stream, cmd.as_bytes()).await;
+    assert_eq!(res, ":0\r\n");
+
+    let res = utils::send_raw(&mut stream, b"CLIENT LIST\r\n").await;
+    assert!(!res.contains(&format!("id={id} ")), "{res}");
+}
+
+#[tokio::test]
+pub async fn client_kill_addr() {
+    let addr = utils::start_simple_server();
+
+    let mut victim = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut victim, b"CLIENT INFO\r\n").await;
+    let re = Regex::new(r" addr=([^ ]+) laddr=([^ ]+) ").unwrap();
+    let caps = re.captures(&res).expect(&res);
+    let (victim_addr, laddr) = (caps[1].to_owned(), caps[2].to_owned());
+
+    let mut stream = utils::connect_raw(addr).await;
+    let cmd = format!("CLIENT KILL ADDR 127.0.0.1:1 LADDR {laddr}\r\n");
+    let res = utils::send_raw(&mut stream, cmd.as_bytes()).await;
+    assert_eq!(res, ":0\r\n");
+
+    let cmd = format!("CLIENT KILL {victim_addr}\r\n");
+    let res = utils::send_raw(&mut stream, cmd.as_bytes()).await;
+    assert_eq!(res, "+OK\r\n");
+    assert!(is_closed(&mut victim).await);
+
+    let res = utils::send_raw(&mut stream, cmd.as_bytes()).await;
+    assert_eq!(res, "-ERR No such client\r\n");
+}
+
+#[tokio::test]
+pub async fn client_kill_user_and_skipme() {
+    let addr = utils::start_simple_server();
+
+    let mut first = utils::connect_raw(addr).await;
+    let mut second = utils::connect_raw(addr).await;
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res =
+        utils::send_raw(&mut stream, b"CLIENT KILL USER nobody\r\n").await;
+    assert_eq!(res, "-ERR No such user 'nobody'\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"CLIENT KILL USER default MAXAGE 100\r\n",
+    )
+    .await;
+    assert_eq!(res, ":0\r\n");
+
+    let res =
+        utils::send_raw(&mut stream, b"CLIENT KILL USER default\r\n").await;
+    assert_eq!(res, ":2\r\n");
+    assert!(is_closed(&mut first).await);
+    assert!(is_closed(&mut second).await);
+
+    // The calling client is killed too with `SKIPME NO`, once answered.
+    let res =
+        utils::send_raw(&mut stream, b"CLIENT KILL USER default SKIPME no\r\n")
+            .await;
+    assert_eq!(res, ":1\r\n");
+    assert!(is_closed(&mut stream).await);
+}
+
+#[tokio::test]
+pub async fn client_kill_syntax_error() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res =
+        utils::send_raw(&mut stream, b"CLIENT KILL ID 1 NAME foo\r\n").await;
+    assert_eq!(res, "-ERR syntax error\r\n");
+}
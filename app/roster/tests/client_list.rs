@@ -4,8 +4,10 @@ use regex::Regex;
 
 #[tokio::test]
 pub async fn client_list() {
-    let test_re: Regex =
-        Regex::new(r"^id=0 addr=.*? laddr=.*? fd=.*? name=$").unwrap();
+    let test_re: Regex = Regex::new(
+        r"^id=0 addr=.*? laddr=.*? fd=.*? name= age=\d+ idle=\d+ flags=N db=0 sub=0 psub=0 ssub=0 multi=-1 watch=0 qbuf=\d+ obl=0 oll=0 omem=0 tot-mem=\d+ events=r cmd=client\|list user=default redir=-1 resp=2 lib-name= lib-ver=$",
+    )
+    .unwrap();
     let addr = utils::start_simple_server();
 
     let connection = utils::connect_without_auth(addr).await;
@@ -19,3 +21,126 @@ pub async fn client_list() {
     let first_value = res_f.pop().unwrap();
     assert!(test_re.is_match(&first_value));
 }
+
+#[tokio::test]
+pub async fn client_list_fields() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(
+        &mut stream,
+        b"CLIENT SETINFO lib-name roster\r\nCLIENT SETINFO LIB-VER 1.0\r\n\
+          HELLO 3\r\nCLIENT NO-EVICT on\r\nWATCH key\r\nMULTI\r\nGET key\r\n",
+    )
+    .await;
+    assert!(res.ends_with("+OK\r\n+QUEUED\r\n"), "{res}");
+
+    let mut other = utils::connect_raw(addr).await;
+    let res = utils::send_raw(
+        &mut other,
+        b"CLIENT TRACKING on BCAST\r\nSUBSCRIBE a b\r\nPSUBSCRIBE c\r\n",
+    )
+    .await;
+    assert!(res.starts_with("+OK\r\n"), "{res}");
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"CLIENT LIST\r\n").await;
+    let lines: Vec<_> = res.lines().collect();
+    assert_eq!(lines.len(), 4, "{res}");
+    assert!(
+        lines[1].contains(
+            " flags=xe db=0 sub=0 psub=0 ssub=0 multi=1 watch=1 qbuf=0 obl=0 \
+             oll=0 omem=0 "
+        ),
+        "{res}"
+    );
+    assert!(
+        lines[1].ends_with(
+            " events=r cmd=get user=default redir=-1 resp=3 lib-name=roster \
+             lib-ver=1.0"
+        ),
+        "{res}"
+    );
+    assert!(
+        lines[2].contains(" flags=PtB db=0 sub=2 psub=1 ssub=0 multi=-1 "),
+        "{res}"
+    );
+    assert!(lines[2].contains(" cmd=psubscribe "), "{res}");
+    assert!(lines[3].contains(" cmd=client|list "), "{res}");
+}
+
+#[tokio::test]
+pub async fn client_list_output_buffer() {
+    use tokio::io::AsyncWriteExt;
+    use tokio::time::Duration;
+
+    let addr = utils::start_simple_server();
+
+    let mut slow = utils::connect_raw(addr).await;
+    let value = "x".repeat(2 * 1024);
+    let set = format!(
+        "*3\r\n$3\r\nSET\r\n$3\r\nbig\r\n${}\r\n{value}\r\n",
+        value.len()
+    );
+    let res = utils::send_raw(&mut slow, set.as_bytes()).await;
+    assert_eq!(res, "+OK\r\n");
+
+    // The replies fill the socket buffers as they are never read, the last
+    // one waits in the output buffer.
+    slow.write_all(&b"GET big\r\n".repeat(16 * 1024))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"CLIENT LIST\r\n").await;
+    let lines: Vec<_> = res.lines().collect();
+    assert_eq!(lines.len(), 3, "{res}");
+    let re =
+        Regex::new(r" obl=2057 oll=0 omem=0 tot-mem=\d+ events=rw cmd=get ")
+            .unwrap();
+    assert!(re.is_match(lines[1]), "{res}");
+    assert!(lines[2].contains(" obl=0 oll=0 omem=0 "), "{res}");
+    assert!(lines[2].contains(" events=r cmd=client|list "), "{res}");
+}
+
+#[tokio::test]
+pub async fn client_list_filters() {
+    let addr = utils::start_simple_server();
+
+    let mut first = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut first, b"CLIENT INFO\r\n").await;
+    let re = Regex::new(r"id=(\d+) addr=([^ ]+) laddr=([^ ]+) ").unwrap();
+    let caps = re.captures(&res).expect(&res);
+    let (id, first_addr, laddr) =
+        (caps[1].to_owned(), caps[2].to_owned(), caps[3].to_owned());
+
+    let mut stream = utils::connect_raw(addr).await;
+    let list = |filters: String| {
+        let cmd = format!("CLIENT LIST {filters}\r\n");
+        async move {
+            let mut stream = utils::connect_raw(addr).await;
+            utils::send_raw(&mut stream, cmd.as_bytes()).await
+        }
+    };
+
+    let res = list(format!("ADDR {first_addr}")).await;
+    assert_eq!(res.lines().count(), 2, "{res}");
+    assert!(res.contains(&format!("id={id} ")), "{res}");
+
+    let res = list(format!("LADDR {laddr} USER default SKIPME yes")).await;
+    assert_eq!(res.lines().count(), 3, "{res}");
+
+    let res = list(format!("TYPE normal ID {id} 1000")).await;
+    assert_eq!(res.lines().count(), 2, "{res}");
+
+    let res = list("TYPE pubsub".to_owned()).await;
+    assert_eq!(res, "*0\r\n");
+
+    let res =
+        utils::send_raw(&mut stream, b"CLIENT LIST USER nobody\r\n").await;
+    assert_eq!(res, "-ERR No such user 'nobody'\r\n");
+
+    let res = utils::send_raw(&mut stream, b"CLIENT LIST NAME foo\r\n").await;
+    assert_eq!(res, "-ERR syntax error\r\n");
+}
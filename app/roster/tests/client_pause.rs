@@ -0,0 +1,88 @@
+mod utils;
+
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+pub async fn client_pause_write() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let mut other = utils::connect_raw(addr).await;
+
+    let res =
+        utils::send_raw(&mut stream, b"CLIENT PAUSE 1500 WRITE\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    // The reads are still served.
+    let start = Instant::now();
+    let res = utils::send_raw(&mut other, b"GET key\r\n").await;
+    assert_eq!(res, "$-1\r\n");
+    assert!(start.elapsed() < Duration::from_millis(1_000));
+
+    // The writes wait for the end of the pause.
+    let start = Instant::now();
+    let mut set = utils::send_raw(&mut other, b"SET key value\r\n").await;
+    while set.is_empty() && start.elapsed() < Duration::from_secs(5) {
+        set = utils::send_raw(&mut other, b"").await;
+    }
+    assert_eq!(set, "+OK\r\n");
+    assert!(start.elapsed() >= Duration::from_millis(1_000));
+}
+
+#[tokio::test]
+pub async fn client_pause_write_function_load() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let mut other = utils::connect_raw(addr).await;
+
+    let res =
+        utils::send_raw(&mut stream, b"CLIENT PAUSE 1500 WRITE\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    // Loading a library modifies the functions, like a write.
+    let start = Instant::now();
+    let mut load = utils::send_raw(
+        &mut other,
+        b"FUNCTION LOAD \"#!lua name=lib\\n\
+          redis.register_function('f', function() return 1 end)\"\r\n",
+    )
+    .await;
+    while load.is_empty() && start.elapsed() < Duration::from_secs(5) {
+        load = utils::send_raw(&mut other, b"").await;
+    }
+    assert_eq!(load, "$3\r\nlib\r\n");
+    assert!(start.elapsed() >= Duration::from_millis(1_000));
+}
+
+#[tokio::test]
+pub async fn client_unpause() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let mut other = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, b"CLIENT PAUSE 100000\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    // Every command is suspended.
+    let res = utils::send_raw(&mut other, b"PING\r\n").await;
+    assert_eq!(res, "");
+
+    let res = utils::send_raw(&mut stream, b"CLIENT UNPAUSE\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+    let res = utils::send_raw(&mut other, b"").await;
+    assert_eq!(res, "+PONG\r\n");
+}
+
+#[tokio::test]
+pub async fn client_pause_invalid() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"CLIENT PAUSE foo\r\n").await;
+    assert_eq!(res, "-ERR timeout is not an integer or out of range\r\n");
+
+    let res = utils::send_raw(&mut stream, b"CLIENT PAUSE 10 READ\r\n").await;
+    assert_eq!(res, "-ERR syntax error\r\n");
+}
@@ -0,0 +1,50 @@
+mod utils;
+
+#[tokio::test]
+pub async fn client_reply_off_and_skip() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, b"CLIENT REPLY OFF\r\n").await;
+    assert_eq!(res, "");
+    let res =
+        utils::send_raw(&mut stream, b"SET key value\r\nGET key\r\nFOO\r\n")
+            .await;
+    assert_eq!(res, "");
+
+    let res = utils::send_raw(&mut stream, b"CLIENT REPLY ON\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"CLIENT REPLY SKIP\r\nGET key\r\nGET key\r\n",
+    )
+    .await;
+    assert_eq!(res, "$5\r\nvalue\r\n");
+}
+
+#[tokio::test]
+pub async fn client_reply_off_receives_messages() {
+    let addr = utils::start_simple_server();
+
+    let mut subscriber = utils::connect_raw(addr).await;
+    let mut publisher = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(
+        &mut subscriber,
+        b"HELLO 3\r\nCLIENT REPLY OFF\r\nSUBSCRIBE channel\r\n",
+    )
+    .await;
+    assert!(!res.contains("subscribe"), "{res}");
+
+    let res =
+        utils::send_raw(&mut publisher, b"PUBLISH channel message\r\n").await;
+    assert_eq!(res, ":1\r\n");
+
+    let res = utils::send_raw(&mut subscriber, b"").await;
+    assert_eq!(
+        res,
+        ">3\r\n$7\r\nmessage\r\n$7\r\nchannel\r\n$7\r\nmessage\r\n"
+    );
+}
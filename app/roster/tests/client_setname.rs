@@ -4,8 +4,10 @@ use regex::Regex;
 
 #[tokio::test]
 pub async fn client_setname() {
-    let test_re: Regex =
-        Regex::new(r"^id=0 addr=.*? laddr=.*? fd=.*? name=$").unwrap();
+    let test_re: Regex = Regex::new(
+        r"^id=0 addr=.*? laddr=.*? fd=.*? name= age=\d+ idle=\d+ flags=N db=0 sub=0 psub=0 ssub=0 multi=-1 watch=0 qbuf=\d+ obl=0 oll=0 omem=0 tot-mem=\d+ events=r cmd=client\|list user=default redir=-1 resp=2 lib-name= lib-ver=$",
+    )
+    .unwrap();
     let addr = utils::start_simple_server();
 
     let connection = utils::connect_without_auth(addr).await;
@@ -31,8 +33,10 @@ pub async fn client_setname() {
         .await
         .unwrap();
 
-    let test_re: Regex =
-        Regex::new(r"^id=0 addr=.*? laddr=.*? fd=.*? name=newname$").unwrap();
+    let test_re: Regex = Regex::new(
+        r"^id=0 addr=.*? laddr=.*? fd=.*? name=newname age=\d+ idle=\d+ flags=N db=0 sub=0 psub=0 ssub=0 multi=-1 watch=0 qbuf=\d+ obl=0 oll=0 omem=0 tot-mem=\d+ events=r cmd=client\|list user=default redir=-1 resp=2 lib-name= lib-ver=$",
+    )
+    .unwrap();
     assert_eq!(res_f.len(), 1);
     let first_value = res_f.pop().unwrap();
     assert!(test_re.is_match(&first_value));
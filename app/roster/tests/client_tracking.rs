@@ -0,0 +1,177 @@
+mod utils;
+
+#[tokio::test]
+pub async fn tracking_default_mode() {
+    let addr = utils::start_simple_server();
+
+    let mut tracking = utils::connect_raw(addr).await;
+    let mut writer = utils::connect_raw(addr).await;
+
+    let _ = utils::send_raw(&mut tracking, b"HELLO 3\r\n").await;
+    let res = utils::send_raw(&mut tracking, b"CLIENT TRACKING on\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut tracking, b"GET key\r\n").await;
+    assert_eq!(res, "_\r\n");
+
+    let res = utils::send_raw(&mut writer, b"SET key value\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut tracking, b"").await;
+    assert_eq!(res, ">2\r\n$10\r\ninvalidate\r\n*1\r\n$3\r\nkey\r\n");
+
+    // The key is not tracked anymore until it's read again.
+    let _ = utils::send_raw(&mut writer, b"SET key other\r\n").await;
+    let res = utils::send_raw(&mut tracking, b"").await;
+    assert_eq!(res, "");
+
+    let res = utils::send_raw(&mut tracking, b"CLIENT TRACKING off\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let _ = utils::send_raw(&mut tracking, b"GET key\r\n").await;
+    let _ = utils::send_raw(&mut writer, b"SET key value\r\n").await;
+    let res = utils::send_raw(&mut tracking, b"").await;
+    assert_eq!(res, "");
+}
+
+#[tokio::test]
+pub async fn tracking_redirect_resp2() {
+    let addr = utils::start_simple_server();
+
+    let mut receiver = utils::connect_raw(addr).await;
+    let mut tracking = utils::connect_raw(addr).await;
+    let mut writer = utils::connect_raw(addr).await;
+
+    let id = utils::send_raw(&mut receiver, b"CLIENT ID\r\n").await;
+    let id = id.trim_start_matches(':').trim_end();
+    let _ =
+        utils::send_raw(&mut receiver, b"SUBSCRIBE __redis__:invalidate\r\n")
+            .await;
+
+    let res = utils::send_raw(
+        &mut tracking,
+        format!("CLIENT TRACKING on REDIRECT {id}\r\n").as_bytes(),
+    )
+    .await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut tracking, b"CLIENT GETREDIR\r\n").await;
+    assert_eq!(res, format!(":{id}\r\n"));
+
+    let _ = utils::send_raw(&mut tracking, b"GET key\r\n").await;
+    let _ = utils::send_raw(&mut writer, b"SET key value\r\n").await;
+
+    let res = utils::send_raw(&mut receiver, b"").await;
+    assert_eq!(
+        res,
+        concat!(
+            "*3\r\n$7\r\nmessage\r\n$20\r\n__redis__:invalidate\r\n",
+            "*1\r\n$3\r\nkey\r\n",
+        )
+    );
+
+    let res = utils::send_raw(
+        &mut tracking,
+        b"CLIENT TRACKING on REDIRECT 123456\r\n",
+    )
+    .await;
+    assert_eq!(
+        res,
+        "-ERR The client ID you want redirect to does not exist\r\n"
+    );
+}
+
+#[tokio::test]
+pub async fn tracking_bcast_noloop() {
+    let addr = utils::start_simple_server();
+
+    let mut tracking = utils::connect_raw(addr).await;
+    let mut writer = utils::connect_raw(addr).await;
+
+    let _ = utils::send_raw(&mut tracking, b"HELLO 3\r\n").await;
+    let res = utils::send_raw(
+        &mut tracking,
+        b"CLIENT TRACKING on BCAST PREFIX user: NOLOOP\r\n",
+    )
+    .await;
+    assert_eq!(res, "+OK\r\n");
+
+    // Keys modified by the connection itself are not invalidated.
+    let res = utils::send_raw(&mut tracking, b"SET user:1 value\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let _ = utils::send_raw(&mut writer, b"SET other value\r\n").await;
+    let _ = utils::send_raw(&mut writer, b"SET user:2 value\r\n").await;
+
+    let res = utils::send_raw(&mut tracking, b"").await;
+    assert_eq!(res, ">2\r\n$10\r\ninvalidate\r\n*1\r\n$6\r\nuser:2\r\n");
+
+    let res = utils::send_raw(
+        &mut tracking,
+        b"CLIENT TRACKING on BCAST PREFIX user:admin\r\n",
+    )
+    .await;
+    assert_eq!(
+        res,
+        "-ERR Prefix 'user:admin' overlaps with an existing prefix 'user:'. \
+         Prefixes for a single client must not overlap.\r\n"
+    );
+
+    let res = utils::send_raw(&mut tracking, b"CLIENT TRACKINGINFO\r\n").await;
+    assert_eq!(
+        res,
+        concat!(
+            "%3\r\n",
+            "$5\r\nflags\r\n~3\r\n$2\r\non\r\n$5\r\nbcast\r\n$6\r\nnoloop\r\n",
+            "$8\r\nredirect\r\n:0\r\n",
+            "$8\r\nprefixes\r\n*1\r\n$5\r\nuser:\r\n",
+        )
+    );
+}
+
+#[tokio::test]
+pub async fn tracking_optin() {
+    let addr = utils::start_simple_server();
+
+    let mut tracking = utils::connect_raw(addr).await;
+    let mut writer = utils::connect_raw(addr).await;
+
+    let _ = utils::send_raw(&mut tracking, b"HELLO 3\r\n").await;
+
+    let res = utils::send_raw(&mut tracking, b"CLIENT CACHING yes\r\n").await;
+    assert_eq!(
+        res,
+        "-ERR CLIENT CACHING can be called only when the client is in \
+         tracking mode with OPTIN or OPTOUT mode enabled\r\n"
+    );
+
+    let res =
+        utils::send_raw(&mut tracking, b"CLIENT TRACKING on OPTIN\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    // Only the command following `CLIENT CACHING yes` is tracked.
+    let _ = utils::send_raw(&mut tracking, b"GET ignored\r\n").await;
+    let res = utils::send_raw(
+        &mut tracking,
+        b"CLIENT CACHING yes\r\nGET cached\r\nGET ignored2\r\n",
+    )
+    .await;
+    assert_eq!(res, "+OK\r\n_\r\n_\r\n");
+
+    let _ = utils::send_raw(&mut writer, b"SET ignored value\r\n").await;
+    let _ = utils::send_raw(&mut writer, b"SET ignored2 value\r\n").await;
+    let _ = utils::send_raw(&mut writer, b"SET cached value\r\n").await;
+
+    let res = utils::send_raw(&mut tracking, b"").await;
+    assert_eq!(res, ">2\r\n$10\r\ninvalidate\r\n*1\r\n$6\r\ncached\r\n");
+
+    let res = utils::send_raw(&mut tracking, b"CLIENT CACHING no\r\n").await;
+    assert_eq!(
+        res,
+        "-ERR CLIENT CACHING NO is only valid when tracking is enabled in \
+         OPTOUT mode.\r\n"
+    );
+
+    let res = utils::send_raw(&mut writer, b"CLIENT GETREDIR\r\n").await;
+    assert_eq!(res, ":-1\r\n");
+}
@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use bytes::Bytes;
+use bytestring::ByteString;
+use roster::application::server::frame::Frame;
+use roster::application::server::{
+    CommandError, CommandExecution, Context, CustomCommand, KeyPositions,
+    Parse, WriteConnection,
+};
+use roster::domain::acl::AclCategory;
+use roster::domain::storage::SetOptions;
+use roster::CommandRegistry;
+
+mod utils;
+
+/// `APPENDX key value`: append `value` to the string stored at `key` and
+/// return its new length.
+#[derive(Debug)]
+struct AppendX {
+    key: ByteString,
+    value: Bytes,
+}
+
+impl CustomCommand for AppendX {
+    fn parse_frames(parse: &mut Parse) -> Result<Self, CommandError> {
+        Ok(AppendX {
+            key: parse.next_string()?,
+            value: parse.next_bytes()?,
+        })
+    }
+}
+
+impl CommandExecution for AppendX {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        ctx: Context,
+    ) -> anyhow::Result<()> {
+        let current = ctx
+            .storage
+            .get_async(self.key.clone(), ctx.now())
+            .await
+            .unwrap_or_default();
+        let value = [&current[..], &self.value[..]].concat();
+        let len = value.len() as i64;
+        let _ = ctx
+            .storage
+            .set_async(
+                self.key,
+                Bytes::from(value),
+                SetOptions::default(),
+                ctx.now(),
+            )
+            .await;
+
+        dst.write_frame(&Frame::Integer(len)).await?;
+        Ok(())
+    }
+}
+
+/// `WAITX`: reply after a timer of the runtime fired.
+#[derive(Debug)]
+struct WaitX;
+
+impl CustomCommand for WaitX {
+    fn parse_frames(_parse: &mut Parse) -> Result<Self, CommandError> {
+        Ok(WaitX)
+    }
+}
+
+impl CommandExecution for WaitX {
+    async fn apply(
+        self,
+        dst: &mut WriteConnection,
+        _ctx: Context,
+    ) -> anyhow::Result<()> {
+        monoio::time::sleep(Duration::from_millis(10)).await;
+        dst.write_frame(&Frame::Simple("OK".into())).await?;
+        Ok(())
+    }
+}
+
+fn start_server() -> std::net::SocketAddr {
+    utils::start_server_with(|builder| {
+        builder.commands(
+            CommandRegistry::new()
+                .register::<AppendX>(
+                    "appendx",
+                    KeyPositions::single(1),
+                    &[AclCategory::Write, AclCategory::String],
+                )
+                .register::<WaitX>(
+                    "waitx",
+                    KeyPositions::none(),
+                    &[AclCategory::Fast],
+                ),
+        )
+    })
+}
+
+#[tokio::test]
+pub async fn call_registered_command() {
+    let addr = start_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, b"APPENDX key hello\r\n").await;
+    assert_eq!(res, ":5\r\n");
+
+    let res = utils::send_raw(&mut stream, b"appendx key world\r\n").await;
+    assert_eq!(res, ":10\r\n");
+
+    let res = utils::send_raw(&mut stream, b"GET key\r\n").await;
+    assert_eq!(res, "$10\r\nhelloworld\r\n");
+
+    let res = utils::send_raw(&mut stream, b"APPENDX key\r\n").await;
+    assert_eq!(
+        res,
+        "-ERR wrong number of arguments for 'appendx' command\r\n"
+    );
+
+    let res =
+        utils::send_raw(&mut stream, b"APPENDX key value other\r\n").await;
+    assert_eq!(
+        res,
+        "-ERR wrong number of arguments for 'appendx' command\r\n"
+    );
+
+    let res = utils::send_raw(&mut stream, b"UNKNOWNX key\r\n").await;
+    assert_eq!(res, "-ERR unknown command 'unknownx'\r\n");
+}
+
+#[tokio::test]
+pub async fn call_registered_command_from_script() {
+    let addr = start_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"return redis.call('APPENDX', KEYS[1], 'abc')\" 1 key\r\n",
+    )
+    .await;
+    assert_eq!(res, ":3\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL_RO \"return redis.call('APPENDX', KEYS[1], 'abc')\" 1 key\r\n",
+    )
+    .await;
+    assert_eq!(
+        res,
+        "-ERR Write commands are not allowed from read-only scripts.\r\n"
+    );
+}
+
+#[tokio::test]
+pub async fn script_waiting_on_command() {
+    let addr = start_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"return redis.call('WAITX')\" 0\r\n",
+    )
+    .await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"local ok, reply = pcall(redis.call, 'WAITX') return {ok and \
+          1 or 0, reply}\" 0\r\n",
+    )
+    .await;
+    assert_eq!(res, "*2\r\n:1\r\n+OK\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"local ok, err = pcall(error, 'boom', 0) return err\" 0\r\n",
+    )
+    .await;
+    assert_eq!(res, "$4\r\nboom\r\n");
+}
@@ -0,0 +1,54 @@
+mod utils;
+
+#[tokio::test]
+pub async fn wrong_arity_keeps_the_connection() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, b"*1\r\n$3\r\nGET\r\n").await;
+    assert_eq!(res, "-ERR wrong number of arguments for 'get' command\r\n");
+
+    let res = utils::send_raw(&mut stream, b"CLIENT GETNAME blbl\r\n").await;
+    assert_eq!(
+        res,
+        "-ERR wrong number of arguments for 'client|getname' command\r\n"
+    );
+
+    let res = utils::send_raw(&mut stream, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+}
+
+#[tokio::test]
+pub async fn invalid_arguments() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, b"CLIENT BLBL\r\n").await;
+    assert_eq!(res, "-ERR unknown subcommand 'BLBL'. Try CLIENT HELP.\r\n");
+
+    let res = utils::send_raw(&mut stream, b"SET key value EX ten\r\n").await;
+    assert_eq!(res, "-ERR value is not an integer or out of range\r\n");
+
+    let res = utils::send_raw(&mut stream, b"SET key value EX 0\r\n").await;
+    assert_eq!(res, "-ERR invalid expire time in 'set' command\r\n");
+
+    let res = utils::send_raw(&mut stream, b"SET key value BLBL\r\n").await;
+    assert_eq!(res, "-ERR syntax error\r\n");
+}
+
+#[tokio::test]
+pub async fn protocol_error_closes_the_connection() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, b"GET \"key\r\n").await;
+    assert_eq!(res, "-ERR Protocol error: unbalanced quotes in request\r\n");
+
+    // The server is still serving new connections.
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+}
@@ -0,0 +1,228 @@
+mod utils;
+
+const LIBRARY: &[u8] = b"FUNCTION LOAD \"#!lua name=mylib\\n\
+    redis.register_function('set_get', function(keys, args) \
+        redis.call('SET', keys[1], args[1]) \
+        return redis.call('GET', keys[1]) \
+    end)\\n\
+    redis.register_function{function_name='get', \
+        callback=function(keys) return redis.call('GET', keys[1]) end, \
+        flags={'no-writes'}, description='Get a key'}\"\r\n";
+
+#[tokio::test]
+pub async fn load_and_call() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, LIBRARY).await;
+    assert_eq!(res, "$5\r\nmylib\r\n");
+
+    let res = utils::send_raw(&mut stream, LIBRARY).await;
+    assert_eq!(res, "-ERR Library 'mylib' already exists\r\n");
+
+    let res =
+        utils::send_raw(&mut stream, b"FCALL set_get 1 key value\r\n").await;
+    assert_eq!(res, "$5\r\nvalue\r\n");
+
+    let res = utils::send_raw(&mut stream, b"FCALL_RO get 1 key\r\n").await;
+    assert_eq!(res, "$5\r\nvalue\r\n");
+
+    let res =
+        utils::send_raw(&mut stream, b"FCALL_RO set_get 1 key other\r\n").await;
+    assert_eq!(
+        res,
+        "-ERR Can not execute a script with write flag using *_ro command.\r\n"
+    );
+
+    let res = utils::send_raw(&mut stream, b"FCALL missing 0\r\n").await;
+    assert_eq!(res, "-ERR Function not found\r\n");
+
+    let res = utils::send_raw(&mut stream, b"FUNCTION DELETE mylib\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut stream, b"FCALL get 1 key\r\n").await;
+    assert_eq!(res, "-ERR Function not found\r\n");
+
+    let res = utils::send_raw(&mut stream, b"FUNCTION DELETE mylib\r\n").await;
+    assert_eq!(res, "-ERR Library not found\r\n");
+}
+
+#[tokio::test]
+pub async fn load_errors() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res =
+        utils::send_raw(&mut stream, b"FUNCTION LOAD \"return 1\"\r\n").await;
+    assert_eq!(res, "-ERR Missing library metadata\r\n");
+
+    let res =
+        utils::send_raw(&mut stream, b"FUNCTION LOAD \"#!lua\\nreturn 1\"\r\n")
+            .await;
+    assert_eq!(res, "-ERR Library name was not given\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"FUNCTION LOAD \"#!lua name=lib\\nreturn 1\"\r\n",
+    )
+    .await;
+    assert_eq!(res, "-ERR No functions registered\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"FUNCTION LOAD \"#!lua name=lib\\n\
+          redis.register_function('f', function() end, 1)\"\r\n",
+    )
+    .await;
+    assert_eq!(
+        res,
+        "-ERR Error registering functions: wrong number of arguments to \
+         redis.register_function\r\n"
+    );
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"FUNCTION LOAD \"#!lua name=lib\\nredis.call('GET', 'a')\"\r\n",
+    )
+    .await;
+    assert!(
+        res.starts_with("-ERR Error registering functions: "),
+        "{res}"
+    );
+
+    let res = utils::send_raw(&mut stream, b"FUNCTION LIST\r\n").await;
+    assert_eq!(res, "*0\r\n");
+}
+
+#[tokio::test]
+pub async fn list_and_stats() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, LIBRARY).await;
+    assert_eq!(res, "$5\r\nmylib\r\n");
+
+    let res =
+        utils::send_raw(&mut stream, b"FUNCTION LIST LIBRARYNAME my*\r\n")
+            .await;
+    assert_eq!(
+        res,
+        concat!(
+            "*1\r\n*6\r\n",
+            "$12\r\nlibrary_name\r\n$5\r\nmylib\r\n",
+            "$6\r\nengine\r\n$3\r\nLUA\r\n",
+            "$9\r\nfunctions\r\n*2\r\n",
+            "*6\r\n$4\r\nname\r\n$7\r\nset_get\r\n",
+            "$11\r\ndescription\r\n$-1\r\n$5\r\nflags\r\n*0\r\n",
+            "*6\r\n$4\r\nname\r\n$3\r\nget\r\n",
+            "$11\r\ndescription\r\n$9\r\nGet a key\r\n",
+            "$5\r\nflags\r\n*1\r\n$9\r\nno-writes\r\n",
+        )
+    );
+
+    let res =
+        utils::send_raw(&mut stream, b"FUNCTION LIST LIBRARYNAME other\r\n")
+            .await;
+    assert_eq!(res, "*0\r\n");
+
+    let res = utils::send_raw(&mut stream, b"FUNCTION STATS\r\n").await;
+    assert_eq!(
+        res,
+        concat!(
+            "*4\r\n$14\r\nrunning_script\r\n$-1\r\n",
+            "$7\r\nengines\r\n*2\r\n$3\r\nLUA\r\n",
+            "*4\r\n$15\r\nlibraries_count\r\n:1\r\n",
+            "$15\r\nfunctions_count\r\n:2\r\n",
+        )
+    );
+}
+
+#[tokio::test]
+pub async fn dump_and_restore() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, LIBRARY).await;
+    assert_eq!(res, "$5\r\nmylib\r\n");
+
+    let connection =
+        redis_async::client::paired_connect(addr.ip().to_string(), addr.port())
+            .await
+            .unwrap();
+    let payload: Vec<u8> = connection
+        .send(redis_async::resp_array!["FUNCTION", "DUMP"])
+        .await
+        .unwrap();
+
+    let res = connection
+        .send::<String>(redis_async::resp_array![
+            "FUNCTION",
+            "RESTORE",
+            payload.clone()
+        ])
+        .await
+        .map_err(|err| err.to_string())
+        .unwrap_err();
+    assert!(res.contains("Library 'mylib' already exists"), "{res}");
+
+    let res: String = connection
+        .send(redis_async::resp_array!["FUNCTION", "FLUSH"])
+        .await
+        .unwrap();
+    assert_eq!(res, "OK");
+
+    let res: String = connection
+        .send(redis_async::resp_array!["FUNCTION", "RESTORE", payload])
+        .await
+        .unwrap();
+    assert_eq!(res, "OK");
+
+    let res =
+        utils::send_raw(&mut stream, b"FCALL set_get 1 key value\r\n").await;
+    assert_eq!(res, "$5\r\nvalue\r\n");
+
+    let res = utils::send_raw(&mut stream, b"FUNCTION RESTORE nope\r\n").await;
+    assert_eq!(res, "-ERR payload version or checksum are wrong\r\n");
+}
+
+#[tokio::test]
+pub async fn persist_libraries() {
+    let path = std::env::temp_dir()
+        .join(format!("roster-{}-functions.dump", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let functions_file = path.clone();
+    let addr = utils::start_server_with(|builder| {
+        builder.functions_file(functions_file)
+    });
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, LIBRARY).await;
+    assert_eq!(res, "$5\r\nmylib\r\n");
+
+    // A restarted server loads the libraries saved to the file.
+    let functions_file = path.clone();
+    let addr = utils::start_server_with(|builder| {
+        builder.functions_file(functions_file)
+    });
+    let mut stream = utils::connect_raw(addr).await;
+    let res =
+        utils::send_raw(&mut stream, b"FCALL set_get 1 key value\r\n").await;
+    assert_eq!(res, "$5\r\nvalue\r\n");
+
+    let res = utils::send_raw(&mut stream, b"FUNCTION DELETE mylib\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let functions_file = path.clone();
+    let addr = utils::start_server_with(|builder| {
+        builder.functions_file(functions_file)
+    });
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"FUNCTION LIST\r\n").await;
+    assert_eq!(res, "*0\r\n");
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -6,6 +6,8 @@ use redis_async::resp_array;
 
 #[tokio::test]
 #[ignore = "redis-async doesn't support map from resp 3 properly"]
+// Kept as it was written, the assertion is a placeholder.
+#[allow(clippy::assertions_on_constants)]
 pub async fn hello() {
     let addr = utils::start_simple_server();
 
@@ -16,3 +18,68 @@ pub async fn hello() {
 
     assert!(false);
 }
+
+#[tokio::test]
+pub async fn hello_resp2() {
+    let addr = utils::start_simple_server();
+
+    let connection = utils::connect_without_auth(addr).await;
+
+    let res_f: HashMap<String, RespValue> =
+        connection.send(resp_array!["HELLO"]).await.unwrap();
+
+    assert_eq!(
+        res_f.get("server"),
+        Some(&RespValue::BulkString(b"roster".to_vec()))
+    );
+    assert_eq!(res_f.get("proto"), Some(&RespValue::Integer(2)));
+}
+
+#[tokio::test]
+pub async fn hello_resp3() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"*4\r\n$5\r\nHELLO\r\n$1\r\n3\r\n$7\r\nSETNAME\r\n$4\r\nname\r\n",
+    )
+    .await;
+    assert!(res.starts_with("%7\r\n$6\r\nserver\r\n$6\r\nroster\r\n"));
+    assert!(res.contains("$5\r\nproto\r\n:3\r\n"));
+
+    // Null are now sent with the RESP3 encoding.
+    let res =
+        utils::send_raw(&mut stream, b"*2\r\n$3\r\nGET\r\n$4\r\nnone\r\n")
+            .await;
+    assert_eq!(res, "_\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"*2\r\n$6\r\nCLIENT\r\n$7\r\nGETNAME\r\n",
+    )
+    .await;
+    assert_eq!(res, "$4\r\nname\r\n");
+
+    // And we can go back to RESP2.
+    let res =
+        utils::send_raw(&mut stream, b"*2\r\n$5\r\nHELLO\r\n$1\r\n2\r\n").await;
+    assert!(res.starts_with("*14\r\n"));
+
+    let res =
+        utils::send_raw(&mut stream, b"*2\r\n$3\r\nGET\r\n$4\r\nnone\r\n")
+            .await;
+    assert_eq!(res, "$-1\r\n");
+}
+
+#[tokio::test]
+pub async fn hello_noproto() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res =
+        utils::send_raw(&mut stream, b"*2\r\n$5\r\nHELLO\r\n$1\r\n4\r\n").await;
+    assert_eq!(res, "-NOPROTO unsupported protocol version\r\n");
+}
@@ -0,0 +1,34 @@
+mod utils;
+
+#[tokio::test]
+pub async fn inline_ping() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+
+    // Netcat without `-C` only sends `\n`.
+    let res = utils::send_raw(&mut stream, b"ping hello\n").await;
+    assert_eq!(res, "$5\r\nhello\r\n");
+}
+
+#[tokio::test]
+pub async fn inline_quoted_arguments() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res =
+        utils::send_raw(&mut stream, b"SET \"my key\" 'it\\'s'\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    // Inline & multibulk commands can be mixed.
+    let res = utils::send_raw(
+        &mut stream,
+        b"\r\n*2\r\n$3\r\nGET\r\n$6\r\nmy key\r\nGET \"my\\x20key\"\r\n",
+    )
+    .await;
+    assert_eq!(res, "$4\r\nit's\r\n$4\r\nit's\r\n");
+}
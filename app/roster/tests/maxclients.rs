@@ -0,0 +1,53 @@
+mod utils;
+
+use std::sync::Arc;
+
+use tokio::time::Duration;
+
+#[tokio::test]
+pub async fn connections_limit() {
+    let addr = utils::start_server_with(|builder| {
+        builder.connections_limit(Arc::new(2.into()))
+    });
+
+    let mut first = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut first, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+    let mut second = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut second, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+
+    let mut refused = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut refused, b"PING\r\n").await;
+    assert_eq!(res, "-ERR max number of clients reached\r\n");
+
+    let res = utils::send_raw(&mut first, b"INFO clients stats\r\n").await;
+    assert_eq!(
+        res,
+        concat!(
+            "$139\r\n",
+            "# Clients\r\nconnected_clients:2\r\nmaxclients:2\r\n\r\n",
+            "# Stats\r\ntotal_connections_received:2\r\n",
+            "total_connections_closed:0\r\n",
+            "rejected_connections:1\r\n\r\n"
+        )
+    );
+
+    // Once a client leaves, a new one can connect.
+    drop(second);
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    let mut third = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut third, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+
+    let res = utils::send_raw(&mut third, b"INFO stats\r\n").await;
+    assert_eq!(
+        res,
+        concat!(
+            "$91\r\n",
+            "# Stats\r\ntotal_connections_received:3\r\n",
+            "total_connections_closed:1\r\n",
+            "rejected_connections:1\r\n\r\n"
+        )
+    );
+}
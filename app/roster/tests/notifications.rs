@@ -0,0 +1,118 @@
+use roster::domain::notification::KeyspaceEvents;
+
+mod utils;
+
+#[tokio::test]
+pub async fn keyspace_and_keyevent_notifications() {
+    let addr = utils::start_server_with(|builder| {
+        builder.notify_keyspace_events("KEA".parse::<KeyspaceEvents>().unwrap())
+    });
+
+    let mut subscriber = utils::connect_raw(addr).await;
+    let mut client = utils::connect_raw(addr).await;
+
+    let _ =
+        utils::send_raw(&mut subscriber, b"PSUBSCRIBE __keyspace@0__:*\r\n")
+            .await;
+    let _ = utils::send_raw(
+        &mut subscriber,
+        b"SUBSCRIBE __keyevent@0__:expired\r\n",
+    )
+    .await;
+
+    let res = utils::send_raw(&mut client, b"SET key value PX 100\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    let res = utils::send_raw(&mut client, b"GET key\r\n").await;
+    assert_eq!(res, "$-1\r\n");
+
+    let res = utils::send_raw(&mut subscriber, b"").await;
+    assert_eq!(
+        res,
+        concat!(
+            "*4\r\n$8\r\npmessage\r\n$16\r\n__keyspace@0__:*\r\n",
+            "$18\r\n__keyspace@0__:key\r\n$3\r\nset\r\n",
+            "*4\r\n$8\r\npmessage\r\n$16\r\n__keyspace@0__:*\r\n",
+            "$18\r\n__keyspace@0__:key\r\n$7\r\nexpired\r\n",
+            "*3\r\n$7\r\nmessage\r\n$22\r\n__keyevent@0__:expired\r\n",
+            "$3\r\nkey\r\n",
+        )
+    );
+}
+
+#[tokio::test]
+pub async fn new_and_keymiss_notifications() {
+    let addr = utils::start_server_with(|builder| {
+        builder.notify_keyspace_events("Enm".parse::<KeyspaceEvents>().unwrap())
+    });
+
+    let mut subscriber = utils::connect_raw(addr).await;
+    let mut client = utils::connect_raw(addr).await;
+
+    let _ =
+        utils::send_raw(&mut subscriber, b"PSUBSCRIBE __keyevent@0__:*\r\n")
+            .await;
+
+    let _ = utils::send_raw(&mut client, b"SET key value\r\n").await;
+    // Overwriting a key doesn't create it.
+    let _ = utils::send_raw(&mut client, b"SET key other\r\n").await;
+    let _ = utils::send_raw(&mut client, b"GET missing\r\n").await;
+
+    let res = utils::send_raw(&mut subscriber, b"").await;
+    assert_eq!(
+        res,
+        concat!(
+            "*4\r\n$8\r\npmessage\r\n$16\r\n__keyevent@0__:*\r\n",
+            "$18\r\n__keyevent@0__:new\r\n$3\r\nkey\r\n",
+            "*4\r\n$8\r\npmessage\r\n$16\r\n__keyevent@0__:*\r\n",
+            "$22\r\n__keyevent@0__:keymiss\r\n$7\r\nmissing\r\n",
+        )
+    );
+}
+
+#[tokio::test]
+pub async fn notifications_disabled_by_default() {
+    let addr = utils::start_simple_server();
+
+    let mut subscriber = utils::connect_raw(addr).await;
+    let mut client = utils::connect_raw(addr).await;
+
+    let _ = utils::send_raw(&mut subscriber, b"PSUBSCRIBE __key*\r\n").await;
+    let _ = utils::send_raw(&mut client, b"SET key value\r\n").await;
+
+    let res = utils::send_raw(&mut subscriber, b"").await;
+    assert_eq!(res, "");
+}
+
+#[tokio::test]
+pub async fn overwrite_expired_key_notifications() {
+    let addr = utils::start_server_with(|builder| {
+        builder.notify_keyspace_events("Exn".parse::<KeyspaceEvents>().unwrap())
+    });
+
+    let mut subscriber = utils::connect_raw(addr).await;
+    let mut client = utils::connect_raw(addr).await;
+
+    let _ =
+        utils::send_raw(&mut subscriber, b"PSUBSCRIBE __keyevent@0__:*\r\n")
+            .await;
+
+    let _ = utils::send_raw(&mut client, b"SET key value PX 100\r\n").await;
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    // The expired key is gone, so it's created again.
+    let _ = utils::send_raw(&mut client, b"SET key other\r\n").await;
+
+    let res = utils::send_raw(&mut subscriber, b"").await;
+    assert_eq!(
+        res,
+        concat!(
+            "*4\r\n$8\r\npmessage\r\n$16\r\n__keyevent@0__:*\r\n",
+            "$18\r\n__keyevent@0__:new\r\n$3\r\nkey\r\n",
+            "*4\r\n$8\r\npmessage\r\n$16\r\n__keyevent@0__:*\r\n",
+            "$22\r\n__keyevent@0__:expired\r\n$3\r\nkey\r\n",
+            "*4\r\n$8\r\npmessage\r\n$16\r\n__keyevent@0__:*\r\n",
+            "$18\r\n__keyevent@0__:new\r\n$3\r\nkey\r\n",
+        )
+    );
+}
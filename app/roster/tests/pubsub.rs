@@ -0,0 +1,178 @@
+mod utils;
+
+#[tokio::test]
+pub async fn subscribe_and_publish() {
+    let addr = utils::start_simple_server();
+
+    let mut subscriber = utils::connect_raw(addr).await;
+    let mut publisher = utils::connect_raw(addr).await;
+
+    let res =
+        utils::send_raw(&mut subscriber, b"SUBSCRIBE news other\r\n").await;
+    assert_eq!(
+        res,
+        concat!(
+            "*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n",
+            "*3\r\n$9\r\nsubscribe\r\n$5\r\nother\r\n:2\r\n",
+        )
+    );
+
+    let res =
+        utils::send_raw(&mut subscriber, b"PSUBSCRIBE n[aeiou]ws\r\n").await;
+    assert_eq!(
+        res,
+        "*3\r\n$10\r\npsubscribe\r\n$10\r\nn[aeiou]ws\r\n:3\r\n"
+    );
+
+    let res = utils::send_raw(&mut publisher, b"PUBLISH news hello\r\n").await;
+    assert_eq!(res, ":2\r\n");
+
+    let res = utils::send_raw(&mut subscriber, b"").await;
+    assert_eq!(
+        res,
+        concat!(
+            "*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n",
+            "*4\r\n$8\r\npmessage\r\n$10\r\nn[aeiou]ws\r\n",
+            "$4\r\nnews\r\n$5\r\nhello\r\n",
+        )
+    );
+
+    let res =
+        utils::send_raw(&mut publisher, b"PUBLISH nobody hello\r\n").await;
+    assert_eq!(res, ":0\r\n");
+}
+
+#[tokio::test]
+pub async fn subscribed_mode_resp2() {
+    let addr = utils::start_simple_server();
+
+    let mut subscriber = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut subscriber, b"SUBSCRIBE news\r\n").await;
+    assert_eq!(res, "*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n");
+
+    let res = utils::send_raw(&mut subscriber, b"GET key\r\n").await;
+    assert_eq!(
+        res,
+        "-ERR Can't execute 'get': only (P|S)SUBSCRIBE / (P|S)UNSUBSCRIBE / \
+         PING / QUIT / RESET are allowed in this context\r\n"
+    );
+
+    let res = utils::send_raw(&mut subscriber, b"PING\r\n").await;
+    assert_eq!(res, "*2\r\n$4\r\npong\r\n$0\r\n\r\n");
+
+    let res = utils::send_raw(&mut subscriber, b"UNSUBSCRIBE\r\n").await;
+    assert_eq!(res, "*3\r\n$11\r\nunsubscribe\r\n$4\r\nnews\r\n:0\r\n");
+
+    let res = utils::send_raw(&mut subscriber, b"UNSUBSCRIBE\r\n").await;
+    assert_eq!(res, "*3\r\n$11\r\nunsubscribe\r\n$-1\r\n:0\r\n");
+
+    let res = utils::send_raw(&mut subscriber, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+}
+
+#[tokio::test]
+pub async fn subscribed_mode_resp3() {
+    let addr = utils::start_simple_server();
+
+    let mut subscriber = utils::connect_raw(addr).await;
+    let mut publisher = utils::connect_raw(addr).await;
+
+    let _ = utils::send_raw(&mut subscriber, b"HELLO 3\r\n").await;
+
+    let res = utils::send_raw(&mut subscriber, b"SUBSCRIBE news\r\n").await;
+    assert_eq!(res, ">3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n");
+
+    // Every command is available with RESP3.
+    let res = utils::send_raw(&mut subscriber, b"GET key\r\n").await;
+    assert_eq!(res, "_\r\n");
+
+    let res = utils::send_raw(&mut publisher, b"PUBLISH news hello\r\n").await;
+    assert_eq!(res, ":1\r\n");
+
+    let res = utils::send_raw(&mut subscriber, b"").await;
+    assert_eq!(res, ">3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n");
+}
+
+#[tokio::test]
+pub async fn pubsub_introspection() {
+    let addr = utils::start_simple_server();
+
+    let mut subscriber = utils::connect_raw(addr).await;
+    let mut other = utils::connect_raw(addr).await;
+
+    let _ = utils::send_raw(&mut subscriber, b"SUBSCRIBE news.tech\r\n").await;
+    let _ = utils::send_raw(&mut subscriber, b"PSUBSCRIBE news.*\r\n").await;
+
+    let res = utils::send_raw(&mut other, b"PUBSUB CHANNELS news.*\r\n").await;
+    assert_eq!(res, "*1\r\n$9\r\nnews.tech\r\n");
+
+    let res = utils::send_raw(&mut other, b"PUBSUB CHANNELS other\r\n").await;
+    assert_eq!(res, "*0\r\n");
+
+    let res =
+        utils::send_raw(&mut other, b"PUBSUB NUMSUB news.tech other\r\n").await;
+    assert_eq!(res, "*4\r\n$9\r\nnews.tech\r\n:1\r\n$5\r\nother\r\n:0\r\n");
+
+    let res = utils::send_raw(&mut other, b"PUBSUB NUMPAT\r\n").await;
+    assert_eq!(res, ":1\r\n");
+
+    let res = utils::send_raw(&mut other, b"CLIENT LIST TYPE pubsub\r\n").await;
+    assert_eq!(res.matches("id=").count(), 1);
+
+    let res = utils::send_raw(&mut other, b"CLIENT LIST TYPE normal\r\n").await;
+    assert_eq!(res.matches("id=").count(), 1);
+
+    // The subscriptions are removed when the connection is closed.
+    drop(subscriber);
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    let res = utils::send_raw(&mut other, b"PUBSUB NUMPAT\r\n").await;
+    assert_eq!(res, ":0\r\n");
+
+    let res = utils::send_raw(&mut other, b"PUBSUB CHANNELS\r\n").await;
+    assert_eq!(res, "*0\r\n");
+}
+
+#[tokio::test]
+pub async fn sharded_pubsub() {
+    let addr = utils::start_simple_server();
+
+    let mut subscriber = utils::connect_raw(addr).await;
+    let mut publisher = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut subscriber, b"SSUBSCRIBE news\r\n").await;
+    assert_eq!(res, "*3\r\n$10\r\nssubscribe\r\n$4\r\nnews\r\n:1\r\n");
+
+    // Shard channels are counted apart from channels & patterns.
+    let res = utils::send_raw(&mut subscriber, b"SUBSCRIBE news\r\n").await;
+    assert_eq!(res, "*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n");
+
+    let res =
+        utils::send_raw(&mut subscriber, b"SSUBSCRIBE news other\r\n").await;
+    assert_eq!(
+        res,
+        "-CROSSSLOT Keys in request don't hash to the same slot\r\n"
+    );
+
+    let res = utils::send_raw(&mut publisher, b"SPUBLISH news hello\r\n").await;
+    assert_eq!(res, ":1\r\n");
+
+    let res = utils::send_raw(&mut subscriber, b"").await;
+    assert_eq!(res, "*3\r\n$8\r\nsmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n");
+
+    let res =
+        utils::send_raw(&mut publisher, b"PUBSUB SHARDCHANNELS n*\r\n").await;
+    assert_eq!(res, "*1\r\n$4\r\nnews\r\n");
+
+    let res =
+        utils::send_raw(&mut publisher, b"PUBSUB SHARDNUMSUB news other\r\n")
+            .await;
+    assert_eq!(res, "*4\r\n$4\r\nnews\r\n:1\r\n$5\r\nother\r\n:0\r\n");
+
+    let res = utils::send_raw(&mut subscriber, b"SUNSUBSCRIBE\r\n").await;
+    assert_eq!(res, "*3\r\n$12\r\nsunsubscribe\r\n$4\r\nnews\r\n:0\r\n");
+
+    let res = utils::send_raw(&mut publisher, b"SPUBLISH news hello\r\n").await;
+    assert_eq!(res, ":0\r\n");
+}
@@ -0,0 +1,185 @@
+mod utils;
+
+#[tokio::test]
+pub async fn eval_keys_and_args() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"return {KEYS[1], ARGV[1], 3}\" 1 key arg\r\n",
+    )
+    .await;
+    assert_eq!(res, "*3\r\n$3\r\nkey\r\n$3\r\narg\r\n:3\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"redis.call('SET', KEYS[1], ARGV[1]) \
+          return redis.call('GET', KEYS[1])\" 1 key value\r\n",
+    )
+    .await;
+    assert_eq!(res, "$5\r\nvalue\r\n");
+
+    let res = utils::send_raw(&mut stream, b"GET key\r\n").await;
+    assert_eq!(res, "$5\r\nvalue\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"return redis.status_reply('FINE')\" 0\r\n",
+    )
+    .await;
+    assert_eq!(res, "+FINE\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"return redis.call('NOPE')\" 0\r\n",
+    )
+    .await;
+    assert_eq!(res, "-ERR Unknown Redis command called from script\r\n");
+}
+
+#[tokio::test]
+pub async fn eval_errors() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, b"EVAL \"return (\" 0\r\n").await;
+    assert!(
+        res.starts_with("-ERR Error compiling script (new function): "),
+        "{res}"
+    );
+
+    let res = utils::send_raw(&mut stream, b"EVAL \"return 1\" -1\r\n").await;
+    assert_eq!(res, "-ERR Number of keys can't be negative\r\n");
+
+    let res =
+        utils::send_raw(&mut stream, b"EVAL \"return 1\" 2 a b\r\n").await;
+    assert_eq!(
+        res,
+        "-CROSSSLOT Keys in request don't hash to the same slot\r\n"
+    );
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL_RO \"return redis.call('SET', KEYS[1], 1)\" 1 key\r\n",
+    )
+    .await;
+    assert_eq!(
+        res,
+        "-ERR Write commands are not allowed from read-only scripts.\r\n"
+    );
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"#!lua flags=no-writes\\nreturn redis.call('SET', KEYS[1], \
+          1)\" 1 key\r\n",
+    )
+    .await;
+    assert_eq!(
+        res,
+        "-ERR Write commands are not allowed from read-only scripts.\r\n"
+    );
+
+    let res = utils::send_raw(&mut stream, b"SCRIPT KILL\r\n").await;
+    assert_eq!(res, "-NOTBUSY No scripts in execution right now.\r\n");
+}
+
+#[tokio::test]
+pub async fn script_cache() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVALSHA e0e1f9fabfc9d4800c877a703b823ac0578ff8db 0\r\n",
+    )
+    .await;
+    assert_eq!(res, "-NOSCRIPT No matching script. Please use EVAL.\r\n");
+
+    let res =
+        utils::send_raw(&mut stream, b"SCRIPT LOAD \"return 1\"\r\n").await;
+    assert_eq!(res, "$40\r\ne0e1f9fabfc9d4800c877a703b823ac0578ff8db\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVALSHA E0E1F9FABFC9D4800C877A703B823AC0578FF8DB 0\r\n",
+    )
+    .await;
+    assert_eq!(res, ":1\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"SCRIPT EXISTS e0e1f9fabfc9d4800c877a703b823ac0578ff8db nope\r\n",
+    )
+    .await;
+    assert_eq!(res, "*2\r\n:1\r\n:0\r\n");
+
+    let res = utils::send_raw(&mut stream, b"SCRIPT FLUSH\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVALSHA_RO e0e1f9fabfc9d4800c877a703b823ac0578ff8db 0\r\n",
+    )
+    .await;
+    assert_eq!(res, "-NOSCRIPT No matching script. Please use EVAL.\r\n");
+}
+
+#[tokio::test]
+pub async fn script_libraries() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"return cjson.encode({cjson.decode(ARGV[1]).a, \
+          bit.band(7, 3)})\" 0 '{\"a\":\"b\"}'\r\n",
+    )
+    .await;
+    assert_eq!(res, "$7\r\n[\"b\",3]\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"return cmsgpack.unpack(cmsgpack.pack({1, 2}))[2]\" 0\r\n",
+    )
+    .await;
+    assert_eq!(res, ":2\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"return redis.sha1hex('return 1')\" 0\r\n",
+    )
+    .await;
+    assert_eq!(res, "$40\r\ne0e1f9fabfc9d4800c877a703b823ac0578ff8db\r\n");
+}
+
+#[tokio::test]
+pub async fn script_resp3() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"return redis.call('HELLO')\" 0\r\n",
+    )
+    .await;
+    assert_eq!(
+        res,
+        "-ERR This Redis command is not allowed from script\r\n"
+    );
+
+    let _ = utils::send_raw(&mut stream, b"HELLO 3\r\n").await;
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"EVAL \"redis.setresp(3) return {redis.call('GET', 'missing') \
+          == nil, {double = 1.5}}\" 0\r\n",
+    )
+    .await;
+    assert_eq!(res, "*2\r\n#t\r\n,1.5\r\n");
+}
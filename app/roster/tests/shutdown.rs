@@ -0,0 +1,122 @@
+mod utils;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::{timeout, Duration};
+
+/// Wait until the server closes the connection.
+async fn assert_closed(stream: &mut tokio::net::TcpStream) {
+    let mut buf = [0u8; 64];
+    let read = timeout(Duration::from_secs(5), stream.read(&mut buf)).await;
+    assert!(matches!(read, Ok(Ok(0))), "{read:?}");
+}
+
+#[tokio::test]
+pub async fn shutdown_closes_connections() {
+    let path = std::env::temp_dir()
+        .join(format!("roster-shutdown-{}.sock", std::process::id()));
+    let handle = utils::start_server_handle_with(|builder| {
+        builder.unixsocket(path.clone())
+    });
+    let addr = handle.bind;
+
+    let mut stream = utils::connect_raw(addr).await;
+    let mut other = utils::connect_raw(addr).await;
+    assert!(path.exists());
+
+    // Nothing is replied, the caller is closed like every other client.
+    let res = utils::send_raw(&mut stream, b"SHUTDOWN NOSAVE NOW\r\n").await;
+    assert_eq!(res, "");
+    assert_closed(&mut stream).await;
+    assert_closed(&mut other).await;
+
+    timeout(Duration::from_secs(5), handle.shutdown())
+        .await
+        .unwrap();
+    assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+    assert!(!path.exists());
+    handle.join();
+}
+
+#[tokio::test]
+pub async fn shutdown_handle() {
+    let handle = utils::start_server_handle_with(|builder| builder);
+    let addr = handle.bind;
+
+    let mut stream = utils::connect_raw(addr).await;
+    let mut other = utils::connect_raw(addr).await;
+
+    // A paused client doesn't delay the shutdown.
+    let res = utils::send_raw(&mut stream, b"CLIENT PAUSE 100000\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+    let res = utils::send_raw(&mut other, b"PING\r\n").await;
+    assert_eq!(res, "");
+
+    timeout(Duration::from_secs(5), handle.shutdown())
+        .await
+        .unwrap();
+    assert_closed(&mut stream).await;
+    assert_closed(&mut other).await;
+    assert!(tokio::net::TcpStream::connect(addr).await.is_err());
+    handle.join();
+}
+
+#[tokio::test]
+pub async fn shutdown_errors() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"SHUTDOWN ABORT\r\n").await;
+    assert_eq!(res, "-ERR No shutdown in progress.\r\n");
+
+    let res = utils::send_raw(&mut stream, b"SHUTDOWN SAVE NOSAVE\r\n").await;
+    assert_eq!(res, "-ERR syntax error\r\n");
+
+    let res = utils::send_raw(&mut stream, b"SHUTDOWN ABORT FORCE\r\n").await;
+    assert_eq!(res, "-ERR syntax error\r\n");
+
+    // Not allowed in a transaction, which is aborted.
+    let res =
+        utils::send_raw(&mut stream, b"MULTI\r\nSHUTDOWN\r\nEXEC\r\n").await;
+    assert_eq!(
+        res,
+        "+OK\r\n-ERR Command not allowed inside a transaction\r\n-EXECABORT \
+         Transaction discarded because of previous errors.\r\n"
+    );
+
+    let res = utils::send_raw(&mut stream, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+}
+
+#[tokio::test]
+pub async fn shutdown_waiters_bounded() {
+    let handle = utils::start_server_handle_with(|builder| builder);
+    let addr = handle.bind;
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+    drop(stream);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let waiters = handle.shutdown_waiters();
+
+    // Every accept waits for the shutdown, the closed connections must not
+    // leave their wakers behind.
+    for _ in 0..20 {
+        for _ in 0..10 {
+            let mut stream =
+                tokio::net::TcpStream::connect(addr).await.unwrap();
+            stream.write_all(b"PING\r\n").await.unwrap();
+            let mut buf = [0u8; 7];
+            stream.read_exact(&mut buf).await.unwrap();
+            assert_eq!(&buf, b"+PONG\r\n");
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(handle.shutdown_waiters(), waiters);
+
+    timeout(Duration::from_secs(5), handle.shutdown())
+        .await
+        .unwrap();
+    handle.join();
+}
@@ -0,0 +1,56 @@
+mod utils;
+
+use std::time::Duration;
+
+use regex::Regex;
+use tokio::io::AsyncReadExt;
+
+#[tokio::test]
+pub async fn idle_clients_are_closed() {
+    let addr = utils::start_server_with(|builder| {
+        builder.timeout(Duration::from_secs(1))
+    });
+
+    let mut idle = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut idle, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+
+    let mut subscriber = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut subscriber, b"SUBSCRIBE channel\r\n").await;
+    assert_eq!(res, "*3\r\n$9\r\nsubscribe\r\n$7\r\nchannel\r\n:1\r\n");
+
+    tokio::time::sleep(Duration::from_millis(2_500)).await;
+
+    // The server closed the idle connection.
+    let mut buf = [0; 16];
+    let read =
+        tokio::time::timeout(Duration::from_secs(1), idle.read(&mut buf))
+            .await
+            .unwrap();
+    assert!(matches!(read, Ok(0) | Err(_)));
+
+    // The Pub/Sub connections are never closed.
+    let res = utils::send_raw(&mut subscriber, b"PING\r\n").await;
+    assert_eq!(res, "*2\r\n$4\r\npong\r\n$0\r\n\r\n");
+}
+
+#[tokio::test]
+pub async fn client_list_age_and_idle() {
+    let addr = utils::start_simple_server();
+
+    let mut idle = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut idle, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+    tokio::time::sleep(Duration::from_millis(2_000)).await;
+
+    let mut stream = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut stream, b"CLIENT LIST\r\n").await;
+    let re = Regex::new(r"(?m)^\+id=0 .* age=([0-9]+) idle=([0-9]+) ").unwrap();
+    let caps = re.captures(&res).expect(&res);
+    let age: u64 = caps[1].parse().unwrap();
+    let idle: u64 = caps[2].parse().unwrap();
+    assert!(age >= 3 && idle >= 2 && idle <= age, "{res}");
+
+    let res = utils::send_raw(&mut stream, b"CLIENT INFO\r\n").await;
+    assert!(res.contains(" idle=0 "), "{res}");
+}
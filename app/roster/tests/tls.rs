@@ -0,0 +1,218 @@
+mod utils;
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rcgen::{
+    BasicConstraints, Certificate, CertificateParams, DnType,
+    ExtendedKeyUsagePurpose, IsCa, KeyPair,
+};
+use roster::{TlsAuthClients, TlsConfig};
+use rustls::crypto::ring::default_provider;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+
+/// The certificates generated for a test, the client one is for `alice`.
+struct Certificates {
+    ca: Certificate,
+    client: Certificate,
+    client_key: KeyPair,
+    dir: PathBuf,
+}
+
+fn generate_certificates(name: &str) -> Certificates {
+    let dir = std::env::temp_dir()
+        .join(format!("roster-tls-{name}-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    params
+        .distinguished_name
+        .push(DnType::CommonName, "roster ca");
+    let ca_key = KeyPair::generate().unwrap();
+    let ca = params.self_signed(&ca_key).unwrap();
+
+    let mut params =
+        CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+    params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ServerAuth];
+    let server_key = KeyPair::generate().unwrap();
+    let server = params.signed_by(&server_key, &ca, &ca_key).unwrap();
+
+    let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+    params.distinguished_name.push(DnType::CommonName, "alice");
+    params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+    let client_key = KeyPair::generate().unwrap();
+    let client = params.signed_by(&client_key, &ca, &ca_key).unwrap();
+
+    std::fs::write(dir.join("ca.crt"), ca.pem()).unwrap();
+    std::fs::write(dir.join("server.crt"), server.pem()).unwrap();
+    std::fs::write(dir.join("server.key"), server_key.serialize_pem()).unwrap();
+
+    Certificates {
+        ca,
+        client,
+        client_key,
+        dir,
+    }
+}
+
+/// Start a server with a TLS listener, returns the TLS address.
+fn start_tls_server(
+    certs: &Certificates,
+    auth_clients: TlsAuthClients,
+    configure: impl FnOnce(
+        roster::ServerConfigBuilder,
+    ) -> roster::ServerConfigBuilder,
+) -> SocketAddr {
+    let tls_addr = SocketAddr::new(
+        IpAddr::V4(Ipv4Addr::LOCALHOST),
+        utils::port_picker::pick_unused_port().unwrap(),
+    );
+    let tls = TlsConfig {
+        bind_addr: tls_addr,
+        cert_file: certs.dir.join("server.crt"),
+        key_file: certs.dir.join("server.key"),
+        ca_cert_file: Some(certs.dir.join("ca.crt")),
+        auth_clients,
+    };
+    utils::start_server_with(|builder| {
+        configure(builder.requirepass("secret").tls(tls))
+    });
+    std::thread::sleep(Duration::from_millis(1_000));
+    tls_addr
+}
+
+fn connect_tls(
+    addr: SocketAddr,
+    certs: &Certificates,
+    with_certificate: bool,
+) -> StreamOwned<ClientConnection, TcpStream> {
+    let mut roots = RootCertStore::empty();
+    roots.add(certs.ca.der().clone()).unwrap();
+    let config =
+        ClientConfig::builder_with_provider(Arc::new(default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_root_certificates(roots);
+    let config = if with_certificate {
+        let key = PrivateKeyDer::Pkcs8(certs.client_key.serialize_der().into());
+        let chain: Vec<CertificateDer> = vec![certs.client.der().clone()];
+        config.with_client_auth_cert(chain, key).unwrap()
+    } else {
+        config.with_no_client_auth()
+    };
+
+    let conn = ClientConnection::new(
+        Arc::new(config),
+        "localhost".try_into().unwrap(),
+    )
+    .unwrap();
+    let stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_millis(500)))
+        .unwrap();
+    StreamOwned::new(conn, stream)
+}
+
+/// Send raw bytes and collect everything the server answers until it stops
+/// sending data for a little while.
+fn send(
+    stream: &mut StreamOwned<ClientConnection, TcpStream>,
+    data: &[u8],
+) -> std::io::Result<String> {
+    stream.write_all(data)?;
+    let mut res = Vec::new();
+    let mut buf = vec![0; 4096];
+    loop {
+        match stream.read(&mut buf) {
+            Ok(0) => break,
+            Ok(read) => res.extend_from_slice(&buf[..read]),
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    ErrorKind::WouldBlock | ErrorKind::TimedOut
+                ) =>
+            {
+                break
+            }
+            Err(err) if res.is_empty() => return Err(err),
+            Err(_) => break,
+        }
+    }
+    Ok(String::from_utf8_lossy(&res).into_owned())
+}
+
+#[test]
+fn client_certificate_authenticates_user() {
+    let certs = generate_certificates("auth");
+    let addr = start_tls_server(&certs, TlsAuthClients::Yes, |builder| builder);
+
+    // Without an ACL user named like the certificate, the connection must
+    // still authenticate with a password.
+    let mut stream = connect_tls(addr, &certs, true);
+    let res = send(&mut stream, b"PING\r\n").unwrap();
+    assert_eq!(res, "-NOAUTH Authentication required.\r\n");
+
+    let res = send(
+        &mut stream,
+        b"AUTH secret\r\nACL SETUSER alice on nopass +@all ~*\r\n",
+    )
+    .unwrap();
+    assert_eq!(res, "+OK\r\n+OK\r\n");
+
+    let mut stream = connect_tls(addr, &certs, true);
+    let res = send(&mut stream, b"ACL WHOAMI\r\n").unwrap();
+    assert_eq!(res, "$5\r\nalice\r\n");
+
+    let res = send(&mut stream, b"SET key value\r\nGET key\r\n").unwrap();
+    assert_eq!(res, "+OK\r\n$5\r\nvalue\r\n");
+}
+
+#[test]
+fn client_certificate_required() {
+    let certs = generate_certificates("required");
+    let addr = start_tls_server(&certs, TlsAuthClients::Yes, |builder| builder);
+
+    // The handshake is rejected by the server once the client sent its
+    // (missing) certificate.
+    let mut stream = connect_tls(addr, &certs, false);
+    let res = send(&mut stream, b"PING\r\n");
+    assert!(!matches!(res, Ok(res) if !res.is_empty()));
+}
+
+#[test]
+fn client_certificate_optional() {
+    let certs = generate_certificates("optional");
+    let addr =
+        start_tls_server(&certs, TlsAuthClients::Optional, |builder| builder);
+
+    let mut stream = connect_tls(addr, &certs, false);
+    let res = send(&mut stream, b"AUTH secret\r\nPING\r\n").unwrap();
+    assert_eq!(res, "+OK\r\n+PONG\r\n");
+}
+
+#[test]
+fn handshake_counts_against_connections_limit() {
+    let certs = generate_certificates("limit");
+    let addr = start_tls_server(&certs, TlsAuthClients::Optional, |builder| {
+        builder.connections_limit(Arc::new(1.into()))
+    });
+
+    // A client which never does its handshake still holds a connection.
+    let idle = TcpStream::connect(addr).unwrap();
+    std::thread::sleep(Duration::from_millis(200));
+
+    let mut stream = connect_tls(addr, &certs, false);
+    let res = send(&mut stream, b"AUTH secret\r\nPING\r\n");
+    assert!(!matches!(res, Ok(res) if !res.is_empty()));
+
+    drop(idle);
+    std::thread::sleep(Duration::from_millis(200));
+    let mut stream = connect_tls(addr, &certs, false);
+    let res = send(&mut stream, b"AUTH secret\r\nPING\r\n").unwrap();
+    assert_eq!(res, "+OK\r\n+PONG\r\n");
+}
@@ -0,0 +1,159 @@
+mod utils;
+
+#[tokio::test]
+pub async fn multi_exec() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, b"MULTI\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut stream, b"SET key value\r\n").await;
+    assert_eq!(res, "+QUEUED\r\n");
+
+    let res = utils::send_raw(&mut stream, b"GET key\r\n").await;
+    assert_eq!(res, "+QUEUED\r\n");
+
+    let res = utils::send_raw(&mut stream, b"EXEC\r\n").await;
+    assert_eq!(res, "*2\r\n+OK\r\n$5\r\nvalue\r\n");
+
+    let res = utils::send_raw(&mut stream, b"EXEC\r\n").await;
+    assert_eq!(res, "-ERR EXEC without MULTI\r\n");
+}
+
+#[tokio::test]
+pub async fn multi_discard() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, b"MULTI\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut stream, b"MULTI\r\n").await;
+    assert_eq!(res, "-ERR MULTI calls can not be nested\r\n");
+
+    let res = utils::send_raw(&mut stream, b"SET key value\r\n").await;
+    assert_eq!(res, "+QUEUED\r\n");
+
+    let res = utils::send_raw(&mut stream, b"DISCARD\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut stream, b"GET key\r\n").await;
+    assert_eq!(res, "$-1\r\n");
+}
+
+#[tokio::test]
+pub async fn multi_execabort() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, b"MULTI\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut stream, b"GET\r\n").await;
+    assert_eq!(res, "-ERR wrong number of arguments for 'get' command\r\n");
+
+    let res = utils::send_raw(&mut stream, b"SET key value\r\n").await;
+    assert_eq!(res, "+QUEUED\r\n");
+
+    let res = utils::send_raw(&mut stream, b"EXEC\r\n").await;
+    assert_eq!(
+        res,
+        "-EXECABORT Transaction discarded because of previous errors.\r\n"
+    );
+
+    let res = utils::send_raw(&mut stream, b"GET key\r\n").await;
+    assert_eq!(res, "$-1\r\n");
+}
+
+#[tokio::test]
+pub async fn watch_modified_key() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let mut other = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, b"WATCH key\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut other, b"SET key other\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut stream, b"MULTI\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(&mut stream, b"WATCH key\r\n").await;
+    assert_eq!(res, "-ERR WATCH inside MULTI is not allowed\r\n");
+
+    let res = utils::send_raw(&mut stream, b"SET key value\r\n").await;
+    assert_eq!(res, "+QUEUED\r\n");
+
+    let res = utils::send_raw(&mut stream, b"EXEC\r\n").await;
+    assert_eq!(res, "*-1\r\n");
+
+    let res = utils::send_raw(&mut stream, b"GET key\r\n").await;
+    assert_eq!(res, "$5\r\nother\r\n");
+}
+
+#[tokio::test]
+pub async fn watch_untouched_key() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+
+    let res = utils::send_raw(&mut stream, b"SET key value\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"WATCH key missing\r\nMULTI\r\nSET key new\r\nEXEC\r\n",
+    )
+    .await;
+    assert_eq!(res, "+OK\r\n+OK\r\n+QUEUED\r\n*1\r\n+OK\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"WATCH key\r\nUNWATCH\r\nSET key again\r\nMULTI\r\nGET key\r\nEXEC\r\n",
+    )
+    .await;
+    assert_eq!(
+        res,
+        "+OK\r\n+OK\r\n+OK\r\n+OK\r\n+QUEUED\r\n*1\r\n$5\r\nagain\r\n"
+    );
+}
+
+#[tokio::test]
+pub async fn exec_not_blocked_by_slow_reader() {
+    use tokio::io::AsyncWriteExt;
+    use tokio::time::{timeout, Duration};
+
+    let addr = utils::start_simple_server();
+
+    let mut slow = utils::connect_raw(addr).await;
+    let mut stream = utils::connect_raw(addr).await;
+
+    let value = "x".repeat(2 * 1024);
+    let set = format!(
+        "*3\r\n$3\r\nSET\r\n$3\r\nbig\r\n${}\r\n{value}\r\n",
+        value.len()
+    );
+    let res = utils::send_raw(&mut slow, set.as_bytes()).await;
+    assert_eq!(res, "+OK\r\n");
+
+    // The replies fill the socket buffers as they are never read.
+    slow.write_all(&b"GET big\r\n".repeat(16 * 1024))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let res = timeout(
+        Duration::from_secs(5),
+        utils::send_raw(&mut stream, b"MULTI\r\nSET key value\r\nEXEC\r\n"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(res, "+OK\r\n+QUEUED\r\n*1\r\n+OK\r\n");
+}
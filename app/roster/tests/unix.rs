@@ -0,0 +1,32 @@
+mod utils;
+
+use std::os::unix::fs::PermissionsExt;
+
+use tokio::time::Duration;
+
+#[tokio::test]
+pub async fn unixsocket() {
+    let path = std::env::temp_dir()
+        .join(format!("roster-unix-{}.sock", std::process::id()));
+    let addr = utils::start_server_with(|builder| {
+        builder.unixsocket(path.clone()).unixsocketperm(0o700u32)
+    });
+
+    tokio::time::sleep(Duration::from_millis(1_000)).await;
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o700);
+
+    let mut stream = tokio::net::UnixStream::connect(&path).await.unwrap();
+    let res = utils::send_raw(&mut stream, b"PING\r\n").await;
+    assert_eq!(res, "+PONG\r\n");
+
+    let res = utils::send_raw(&mut stream, b"CLIENT INFO\r\n").await;
+    let expected =
+        format!("addr={path}:0 laddr={path}:0 ", path = path.display());
+    assert!(res.contains(&expected), "{res}");
+
+    // The TCP listener is still there.
+    let mut tcp = utils::connect_raw(addr).await;
+    let res = utils::send_raw(&mut tcp, b"CLIENT LIST\r\n").await;
+    assert!(res.contains(&expected), "{res}");
+}
@@ -1,9 +1,21 @@
+#![allow(dead_code)]
+
 use std::net::SocketAddr;
 
-mod port_picker;
+pub mod port_picker;
 
 /// Start a simple Roster server
 pub fn start_simple_server() -> SocketAddr {
+    start_server_with(|builder| builder)
+}
+
+/// Start a Roster server, the configuration can be customized before the
+/// server is started.
+pub fn start_server_with(
+    configure: impl FnOnce(
+        roster::ServerConfigBuilder,
+    ) -> roster::ServerConfigBuilder,
+) -> SocketAddr {
     use std::net::{IpAddr, Ipv4Addr};
     use std::sync::Arc;
 
@@ -15,11 +27,13 @@ pub fn start_simple_server() -> SocketAddr {
         IpAddr::V4(Ipv4Addr::LOCALHOST),
         pick_unused_port().unwrap(),
     );
-    let server_config = ServerConfigBuilder::default()
-        .connections_limit(Arc::new(20.into()))
-        .bind_addr(addr)
-        .build()
-        .unwrap();
+    let server_config = configure(
+        ServerConfigBuilder::default()
+            .connections_limit(Arc::new(20.into()))
+            .bind_addr(addr),
+    )
+    .build()
+    .unwrap();
     let _handle = std::thread::spawn(move || {
         server_config.initialize();
     });
@@ -28,6 +42,33 @@ pub fn start_simple_server() -> SocketAddr {
     // debug_server()
 }
 
+/// Start a Roster server and keep its handle, so the test can stop it.
+pub fn start_server_handle_with(
+    configure: impl FnOnce(
+        roster::ServerConfigBuilder,
+    ) -> roster::ServerConfigBuilder,
+) -> roster::ServerHandle {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::sync::Arc;
+
+    use roster::ServerConfigBuilder;
+
+    use crate::utils::port_picker::pick_unused_port;
+
+    let addr = SocketAddr::new(
+        IpAddr::V4(Ipv4Addr::LOCALHOST),
+        pick_unused_port().unwrap(),
+    );
+    configure(
+        ServerConfigBuilder::default()
+            .connections_limit(Arc::new(20.into()))
+            .bind_addr(addr),
+    )
+    .build()
+    .unwrap()
+    .initialize()
+}
+
 /*
 pub fn debug_server() -> SocketAddr {
     SocketAddr::from_str("192.168.64.6:3456").unwrap()
@@ -44,3 +85,37 @@ pub async fn connect_without_auth(
         .await
         .unwrap()
 }
+
+/// Connect to roster with a raw TCP connection, useful to check the exact
+/// bytes sent by the server.
+pub async fn connect_raw(addr: SocketAddr) -> tokio::net::TcpStream {
+    use tokio::time::Duration;
+
+    tokio::time::sleep(Duration::from_millis(1_000)).await;
+    tokio::net::TcpStream::connect(addr).await.unwrap()
+}
+
+/// Send raw bytes and collect everything the server answers until it stops
+/// sending data for a little while.
+pub async fn send_raw(
+    stream: &mut (impl tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin),
+    data: &[u8],
+) -> String {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::time::{timeout, Duration};
+
+    stream.write_all(data).await.unwrap();
+
+    let mut result = Vec::new();
+    let mut buf = [0u8; 4096];
+    while let Ok(Ok(n)) =
+        timeout(Duration::from_millis(200), stream.read(&mut buf)).await
+    {
+        if n == 0 {
+            break;
+        }
+        result.extend_from_slice(&buf[..n]);
+    }
+
+    String::from_utf8(result).unwrap()
+}
@@ -0,0 +1,182 @@
+mod utils;
+
+const MODULE: &[u8] = b"WASM LOAD '(module \
+    (import \"roster\" \"key\" (func $key (param i32 i32) (result i32))) \
+    (import \"roster\" \"arg\" (func $arg (param i32 i32) (result i32))) \
+    (import \"roster\" \"get\" (func $get (param i32 i32) (result i32))) \
+    (import \"roster\" \"result\" (func $result (param i32) (result i32))) \
+    (import \"roster\" \"set\" \
+        (func $set (param i32 i32 i32 i32) (result i32))) \
+    (import \"roster\" \"reply\" (func $reply (param i32 i32))) \
+    (memory (export \"memory\") 1) \
+    (func (export \"answer\") (result i32) i32.const 42) \
+    (func (export \"set\") (result i32) \
+        (call $set (i32.const 0) (call $key (i32.const 0) (i32.const 0)) \
+            (i32.const 256) (call $arg (i32.const 0) (i32.const 256)))) \
+    (func (export \"get\") (result i32) (local $len i32) \
+        (local.set $len \
+            (call $get (i32.const 0) (call $key (i32.const 0) (i32.const 0)))) \
+        (if (i32.lt_s (local.get $len) (i32.const 0)) \
+            (then (return (local.get $len)))) \
+        (drop (call $result (i32.const 256))) \
+        (call $reply (i32.const 256) (local.get $len)) \
+        (i32.const 0)) \
+    (func (export \"get_arg\") (result i32) \
+        (call $get (i32.const 0) (call $arg (i32.const 0) (i32.const 0)))) \
+    (func (export \"grow\") (result i32) (memory.grow (i32.const 2048))) \
+    (func (export \"spin\") (loop $forever (br $forever))) \
+    (func (export \"params\") (param i32)))'\r\n";
+
+/// Load the test module and return its SHA1.
+async fn load(stream: &mut tokio::net::TcpStream) -> String {
+    let res = utils::send_raw(stream, MODULE).await;
+    let sha = res.lines().nth(1).unwrap().to_string();
+    assert_eq!(res, format!("$40\r\n{sha}\r\n"));
+    sha
+}
+
+#[tokio::test]
+pub async fn load_and_call() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let sha = load(&mut stream).await;
+
+    let res = utils::send_raw(
+        &mut stream,
+        format!("WCALL {sha} answer 0\r\n").as_bytes(),
+    )
+    .await;
+    assert_eq!(res, ":42\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        format!("WCALL {sha} get 1 key\r\n").as_bytes(),
+    )
+    .await;
+    assert_eq!(res, ":-1\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        format!("WCALL {sha} set 1 key value\r\n").as_bytes(),
+    )
+    .await;
+    assert_eq!(res, ":0\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        format!("WCALL_RO {} get 1 key\r\n", sha.to_uppercase()).as_bytes(),
+    )
+    .await;
+    assert_eq!(res, "$5\r\nvalue\r\n");
+
+    let res = utils::send_raw(&mut stream, b"GET key\r\n").await;
+    assert_eq!(res, "$5\r\nvalue\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        format!("WCALL_RO {sha} set 1 key other\r\n").as_bytes(),
+    )
+    .await;
+    assert_eq!(res, ":-2\r\n");
+}
+
+#[tokio::test]
+pub async fn call_errors() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let sha = load(&mut stream).await;
+
+    let res = utils::send_raw(
+        &mut stream,
+        format!("WCALL {sha} spin 0\r\n").as_bytes(),
+    )
+    .await;
+    assert_eq!(res, "-ERR WASM function ran out of fuel\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        format!("WCALL {sha} missing 0\r\n").as_bytes(),
+    )
+    .await;
+    assert_eq!(res, "-ERR Function not found\r\n");
+
+    // Only the keys given to WCALL can be accessed.
+    let res = utils::send_raw(
+        &mut stream,
+        format!("WCALL {sha} get_arg 1 key other\r\n").as_bytes(),
+    )
+    .await;
+    assert_eq!(res, ":-3\r\n");
+
+    // The memory can't grow past `wasm_max_memory`.
+    let res = utils::send_raw(
+        &mut stream,
+        format!("WCALL {sha} grow 0\r\n").as_bytes(),
+    )
+    .await;
+    assert_eq!(res, ":-1\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        format!("WCALL {sha} params 0\r\n").as_bytes(),
+    )
+    .await;
+    assert_eq!(
+        res,
+        concat!(
+            "-ERR WASM functions must take no parameter and return nothing, ",
+            "an i32 or an i64\r\n"
+        )
+    );
+
+    let res = utils::send_raw(
+        &mut stream,
+        b"WCALL ffffffffffffffffffffffffffffffffffffffff answer 0\r\n",
+    )
+    .await;
+    assert_eq!(
+        res,
+        "-NOSCRIPT No matching module. Please use WASM LOAD.\r\n"
+    );
+
+    let res =
+        utils::send_raw(&mut stream, b"WASM LOAD '(module (func'\r\n").await;
+    assert!(res.starts_with("-ERR Error compiling module: "));
+}
+
+#[tokio::test]
+pub async fn exists_and_flush() {
+    let addr = utils::start_simple_server();
+
+    let mut stream = utils::connect_raw(addr).await;
+    let sha = load(&mut stream).await;
+
+    let res = utils::send_raw(
+        &mut stream,
+        format!("WASM EXISTS {sha} {}\r\n", "f".repeat(40)).as_bytes(),
+    )
+    .await;
+    assert_eq!(res, "*2\r\n:1\r\n:0\r\n");
+
+    let res = utils::send_raw(&mut stream, b"WASM FLUSH SYNC\r\n").await;
+    assert_eq!(res, "+OK\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        format!("WASM EXISTS {sha}\r\n").as_bytes(),
+    )
+    .await;
+    assert_eq!(res, "*1\r\n:0\r\n");
+
+    let res = utils::send_raw(
+        &mut stream,
+        format!("WCALL {sha} answer 0\r\n").as_bytes(),
+    )
+    .await;
+    assert_eq!(
+        res,
+        "-NOSCRIPT No matching module. Please use WASM LOAD.\r\n"
+    );
+}